@@ -0,0 +1,128 @@
+//! Extension points for the format detection done by `AutoDecoder`.
+//!
+//! By default `AutoDecoder` only recognises the formats of the enabled algorithms, additional
+//! formats can be added by registering them in a [`Formats`] and passing it to
+//! `AutoDecoder::with_formats`.
+//!
+//! ```
+//! use async_compression::auto::{FormatDecoder, Formats};
+//!
+//! /// A format that stores its payload as-is after a 4 byte magic number.
+//! struct Stored {
+//!     header: usize,
+//! }
+//!
+//! impl FormatDecoder for Stored {
+//!     fn decode(&mut self, input: &[u8], output: &mut [u8]) -> std::io::Result<(usize, usize, bool)> {
+//!         let skip = input.len().min(4 - self.header);
+//!         self.header += skip;
+//!         let len = (input.len() - skip).min(output.len());
+//!         output[..len].copy_from_slice(&input[skip..skip + len]);
+//!         Ok((skip + len, len, false))
+//!     }
+//! }
+//!
+//! let mut formats = Formats::new();
+//! formats.register_magic(b"STOR", || Box::new(Stored { header: 0 }));
+//! ```
+
+use std::{fmt, io::Result, sync::Arc};
+
+/// A decoder for a custom format, see [`Formats::register`].
+pub trait FormatDecoder: Send + Sync {
+    /// Decodes data from `input` into `output`, returning how many bytes of `input` were consumed,
+    /// how many bytes were written to `output` and whether the end of the stream has been reached.
+    ///
+    /// The start of the stream that was used to detect the format is included in `input`.
+    fn decode(&mut self, input: &[u8], output: &mut [u8]) -> Result<(usize, usize, bool)>;
+
+    /// Called once the end of the input has been reached, returning how many bytes were written to
+    /// `output` and whether all remaining data has been written. This will be called again if
+    /// `output` was filled before all data was written.
+    fn finish(&mut self, output: &mut [u8]) -> Result<(usize, bool)> {
+        let _ = output;
+        Ok((0, true))
+    }
+}
+
+type Matcher = dyn Fn(&[u8]) -> Option<bool> + Send + Sync;
+type Factory = dyn Fn() -> Box<dyn FormatDecoder> + Send + Sync;
+
+/// A set of custom formats that `AutoDecoder` should recognise in addition to the built-in ones.
+///
+/// Custom formats are checked in the order they were registered, before any of the built-in
+/// formats.
+#[derive(Clone, Default)]
+pub struct Formats {
+    formats: Vec<(Arc<Matcher>, Arc<Factory>)>,
+}
+
+impl fmt::Debug for Formats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Formats")
+            .field("len", &self.formats.len())
+            .finish()
+    }
+}
+
+impl Formats {
+    /// Creates an empty set of formats.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a format that is detected by `matcher` and decoded by decoders created by
+    /// `factory`.
+    ///
+    /// The `matcher` is given the start of the stream, and should return `Some(true)` if it is in
+    /// this format, `Some(false)` if it isn't, or `None` if it needs to see more data to decide.
+    /// At the end of the input `None` is treated the same as `Some(false)`.
+    pub fn register(
+        &mut self,
+        matcher: impl Fn(&[u8]) -> Option<bool> + Send + Sync + 'static,
+        factory: impl Fn() -> Box<dyn FormatDecoder> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.formats.push((Arc::new(matcher), Arc::new(factory)));
+        self
+    }
+
+    /// Registers a format that starts with the magic number `magic` and is decoded by decoders
+    /// created by `factory`.
+    pub fn register_magic(
+        &mut self,
+        magic: &'static [u8],
+        factory: impl Fn() -> Box<dyn FormatDecoder> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.register(move |prefix| self::magic(prefix, magic), factory)
+    }
+
+    /// Returns a decoder for the first format that matches `prefix`, or `None` if more input is
+    /// needed to decide.
+    pub(crate) fn detect(
+        &self,
+        prefix: &[u8],
+        eof: bool,
+    ) -> Option<Option<Box<dyn FormatDecoder>>> {
+        for (matcher, factory) in &self.formats {
+            match matcher(prefix) {
+                Some(true) => return Some(Some(factory())),
+                Some(false) => {}
+                None if eof => {}
+                None => return None,
+            }
+        }
+
+        Some(None)
+    }
+}
+
+/// Returns whether `prefix` starts with `magic`, or `None` if more input is needed to tell.
+pub(crate) fn magic(prefix: &[u8], magic: &[u8]) -> Option<bool> {
+    if prefix.len() >= magic.len() {
+        Some(prefix.starts_with(magic))
+    } else if magic.starts_with(prefix) {
+        None
+    } else {
+        Some(false)
+    }
+}
@@ -0,0 +1,44 @@
+//! Types specific to the bzip2 format.
+//!
+//! bzip2 works on blocks of 100 to 900 KB of uncompressed data, picked by the compression level,
+//! and needs a working set of several times the block size both to compress and decompress them.
+//! The functions here give the expected memory usage of the encoders and decoders, so it can be
+//! checked against the memory available, such as on a router or a memory-constrained lambda.
+//!
+//! Decoders created with `BzDecoder::with_small_memory` use bzip2's small decompression mode,
+//! which needs a bit over half the memory of the default mode at about half the speed.
+//!
+//! ```
+//! use async_compression::{bzip2, Level};
+//!
+//! assert_eq!(bzip2::decoder_memory_usage(Level::Best, false), 3_700_000);
+//! assert_eq!(bzip2::decoder_memory_usage(Level::Best, true), 2_350_000);
+//! ```
+
+use crate::Level;
+
+/// The block size for each step of the compression level.
+const BLOCK_SIZE: usize = 100_000;
+
+fn block_size(level: Level) -> usize {
+    level.into_bzip2().level() as usize * BLOCK_SIZE
+}
+
+/// Returns the memory an encoder compressing with `level` uses, in bytes.
+pub fn encoder_memory_usage(level: Level) -> usize {
+    400_000 + 8 * block_size(level)
+}
+
+/// Returns the memory a decoder uses to decompress data compressed with `level`, in bytes, with
+/// or without the `small` decompression mode.
+///
+/// This depends on the block size the data was compressed with, so it is the most a decoder
+/// uses for any data compressed with `level` or lower.
+pub fn decoder_memory_usage(level: Level, small: bool) -> usize {
+    let block_size = block_size(level);
+    if small {
+        100_000 + block_size * 5 / 2
+    } else {
+        100_000 + 4 * block_size
+    }
+}
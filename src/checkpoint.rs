@@ -0,0 +1,108 @@
+//! Checkpoints for resuming a stream that was interrupted partway through.
+//!
+//! The `write` encoders can end the current member/frame at any point with their `checkpoint`
+//! method, which returns a [`Checkpoint`] recording how much uncompressed data went in and how
+//! much compressed data came out up to that boundary. The codec holds no state across a
+//! member/frame boundary, so the checkpoint is all that is needed to carry on: if the stream is
+//! interrupted later, a new encoder resumed with `with_checkpoint` continues from the checkpoint,
+//! fed the input from [`total_in`](Checkpoint::total_in) and writing its output after the first
+//! [`total_out`](Checkpoint::total_out) bytes of what was written before.
+//!
+//! The output is then made of several concatenated members/frames, which only decodes to the
+//! original data for formats where concatenated streams decode to the concatenation of their
+//! contents: `bzip2`, `gzip`, `lz4`, `snappy`, `xz` and `zstd`, and only with multi-member/frame
+//! decoding enabled.
+//!
+//! Likewise the `bufread` decoders return a checkpoint from their `checkpoint` method while they
+//! are between members/frames, from which a new decoder resumed with `with_checkpoint` continues
+//! when fed the compressed input from [`total_in`](Checkpoint::total_in).
+//!
+//! Checkpoints can be stored with [`to_bytes`](Checkpoint::to_bytes), such as alongside the
+//! state of a multipart upload, and restored with [`from_bytes`](Checkpoint::from_bytes).
+//!
+//! ```
+//! # #[cfg(all(feature = "futures-io", feature = "gzip"))]
+//! # futures::executor::block_on(async {
+//! use async_compression::{checkpoint::Checkpoint, futures::write::GzipEncoder};
+//! use futures::io::AsyncWriteExt;
+//!
+//! let mut encoder = GzipEncoder::new(Vec::new());
+//! encoder.write_all(b"hello ").await?;
+//! let token = encoder.checkpoint().await?.to_bytes();
+//! let mut output = encoder.into_inner();
+//!
+//! // ... later, after losing anything written past the checkpoint
+//! let checkpoint = Checkpoint::from_bytes(&token)?;
+//! output.truncate(checkpoint.total_out() as usize);
+//! let mut encoder = GzipEncoder::new(output).with_checkpoint(&checkpoint);
+//! encoder.write_all(&b"hello world"[checkpoint.total_in() as usize..]).await?;
+//! encoder.close().await?;
+//! # Ok::<_, std::io::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::{
+    convert::TryInto,
+    io::{Error, ErrorKind, Result},
+};
+
+/// The first byte of a serialized checkpoint, changed whenever the layout changes.
+const VERSION: u8 = 1;
+
+/// The length of a serialized checkpoint.
+const LEN: usize = 17;
+
+/// A point at a member/frame boundary of a stream from which encoding or decoding can resume, see
+/// the [module level documentation](self).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    total_in: u64,
+    total_out: u64,
+}
+
+impl Checkpoint {
+    pub(crate) fn new(total_in: u64, total_out: u64) -> Self {
+        Self {
+            total_in,
+            total_out,
+        }
+    }
+
+    /// The number of bytes consumed up to this checkpoint, uncompressed bytes for an encoder and
+    /// compressed bytes for a decoder. The input of a resumed encoder or decoder starts here.
+    pub fn total_in(&self) -> u64 {
+        self.total_in
+    }
+
+    /// The number of bytes produced up to this checkpoint, compressed bytes for an encoder and
+    /// uncompressed bytes for a decoder. The output of a resumed encoder or decoder follows this
+    /// many bytes of the earlier output.
+    pub fn total_out(&self) -> u64 {
+        self.total_out
+    }
+
+    /// Serializes this checkpoint to a fixed-size token.
+    pub fn to_bytes(&self) -> [u8; LEN] {
+        let mut bytes = [0; LEN];
+        bytes[0] = VERSION;
+        bytes[1..9].copy_from_slice(&self.total_in.to_le_bytes());
+        bytes[9..].copy_from_slice(&self.total_out.to_le_bytes());
+        bytes
+    }
+
+    /// Deserializes a checkpoint from a token created by [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns an error of kind [`InvalidData`](std::io::ErrorKind::InvalidData) if `bytes` is
+    /// not a valid token.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != LEN || bytes[0] != VERSION {
+            return Err(Error::new(ErrorKind::InvalidData, "invalid checkpoint"));
+        }
+
+        let (total_in, total_out) = bytes[1..].split_at(8);
+        Ok(Self {
+            total_in: u64::from_le_bytes(total_in.try_into().unwrap()),
+            total_out: u64::from_le_bytes(total_out.try_into().unwrap()),
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use crate::Algorithm;
+
+/// The content-codings that can be chosen, in order of preference when the client accepts several
+/// equally.
+const CODINGS: &[(&str, Algorithm)] = &[
+    #[cfg(feature = "brotli")]
+    ("br", Algorithm::Brotli),
+    #[cfg(feature = "zstd")]
+    ("zstd", Algorithm::Zstd),
+    #[cfg(feature = "gzip")]
+    ("gzip", Algorithm::Gzip),
+    #[cfg(feature = "zlib")]
+    ("deflate", Algorithm::Zlib),
+];
+
+/// Parses a qvalue into thousandths, returns `None` if it is malformed.
+fn parse_qvalue(value: &str) -> Option<u16> {
+    let (int, frac) = match value.find('.') {
+        Some(dot) => (&value[..dot], &value[dot + 1..]),
+        None => (value, ""),
+    };
+
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let frac = frac
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |acc, b| acc * 10 + u16::from(b - b'0'));
+
+    match int {
+        "0" => Some(frac),
+        "1" if frac == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+/// Parses the content-codings of an `Accept-Encoding` header along with their qvalues,
+/// skipping any that are malformed.
+fn parse(accept_encoding: &str) -> impl Iterator<Item = (&str, u16)> {
+    accept_encoding.split(',').filter_map(|item| {
+        let mut params = item.split(';');
+        let coding = params.next()?.trim();
+        if coding.is_empty() {
+            return None;
+        }
+
+        let mut q = 1000;
+        for param in params {
+            let (name, value) = param.split_at(param.find('=')?);
+            if name.trim().eq_ignore_ascii_case("q") {
+                q = parse_qvalue(value[1..].trim())?;
+            }
+        }
+
+        Some((coding, q))
+    })
+}
+
+/// Chooses the content-coding to respond with for an `Accept-Encoding` header, returning its
+/// token and algorithm, which is `None` for `identity`. Returns `None` if no content-coding is
+/// acceptable.
+pub(crate) fn negotiate(accept_encoding: &str) -> Option<(&'static str, Option<Algorithm>)> {
+    let qvalue = |token: &str| {
+        let mut exact = None;
+        let mut wildcard = None;
+        for (coding, q) in parse(accept_encoding) {
+            if coding.eq_ignore_ascii_case(token)
+                || (token == "gzip" && coding.eq_ignore_ascii_case("x-gzip"))
+            {
+                exact = Some(exact.map_or(q, |exact: u16| exact.max(q)));
+            } else if coding == "*" {
+                wildcard = Some(q);
+            }
+        }
+        exact.or(wildcard)
+    };
+
+    let mut best = None;
+    let mut best_q = 0;
+    for &(token, algorithm) in CODINGS {
+        let q = qvalue(token).unwrap_or(0);
+        if q > best_q {
+            best = Some((token, Some(algorithm)));
+            best_q = q;
+        }
+    }
+
+    // Identity is acceptable unless excluded, but is only chosen over a compressed coding if the
+    // client explicitly prefers it.
+    match qvalue("identity") {
+        Some(q) if q > best_q => Some(("identity", None)),
+        None if best.is_none() => Some(("identity", None)),
+        _ => best,
+    }
+}
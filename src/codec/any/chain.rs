@@ -0,0 +1,265 @@
+use super::AnyDecoder;
+use crate::{codec::Decode, util::PartialBuffer};
+use std::io::Result;
+
+/// The size of the buffers holding the output of one codec until the next consumes it.
+const PIPE_SIZE: usize = 8 * 1024;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum State {
+    Decoding,
+    Finishing,
+    Done,
+}
+
+/// A buffer holding the output of one codec until the next consumes it.
+#[derive(Debug)]
+pub(super) struct Pipe {
+    buf: Box<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl Pipe {
+    pub(super) fn new() -> Self {
+        Self {
+            buf: vec![0; PIPE_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.start = 0;
+        self.end = 0;
+    }
+
+    /// Runs `f` with the pending data, removing whatever it consumes.
+    pub(super) fn read<T>(&mut self, f: impl FnOnce(&mut PartialBuffer<&[u8]>) -> T) -> T {
+        let mut src = PartialBuffer::new(&self.buf[self.start..self.end]);
+        let result = f(&mut src);
+        self.start += src.written().len();
+        if self.is_empty() {
+            self.clear();
+        }
+        result
+    }
+
+    /// Runs `f` with the free space, keeping whatever it writes.
+    pub(super) fn write<T>(&mut self, f: impl FnOnce(&mut PartialBuffer<&mut [u8]>) -> T) -> T {
+        if self.end == self.buf.len() && self.start > 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+
+        let mut dst = PartialBuffer::new(&mut self.buf[self.end..]);
+        let result = f(&mut dst);
+        self.end += dst.written().len();
+        result
+    }
+}
+
+#[derive(Debug)]
+struct Stage {
+    decoder: AnyDecoder,
+    state: State,
+    // The output of this stage, `None` for the last stage which writes to the final output
+    pipe: Option<Pipe>,
+}
+
+impl Stage {
+    /// Advances this stage, writing to its pipe or to `output` if it is the last stage.
+    fn run(
+        &mut self,
+        src: &mut PartialBuffer<impl AsRef<[u8]>>,
+        upstream_done: bool,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match self.pipe.take() {
+            Some(mut pipe) => {
+                let result = pipe.write(|dst| self.step(src, upstream_done, dst));
+                self.pipe = Some(pipe);
+                result
+            }
+            None => self.step(src, upstream_done, output),
+        }
+    }
+
+    /// Flushes the decoder, writing to its pipe or to `output` if it is the last stage.
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match self.pipe.take() {
+            Some(mut pipe) => {
+                let result = pipe.write(|dst| self.decoder.flush(dst));
+                self.pipe = Some(pipe);
+                result
+            }
+            None => self.decoder.flush(output),
+        }
+    }
+
+    /// Advances this stage by one step, returns whether any progress was made.
+    fn step(
+        &mut self,
+        src: &mut PartialBuffer<impl AsRef<[u8]>>,
+        upstream_done: bool,
+        dst: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if dst.unwritten().is_empty() {
+            return Ok(false);
+        }
+
+        let (state, read, written) = (self.state, src.written().len(), dst.written().len());
+
+        match self.state {
+            State::Decoding if !src.unwritten().is_empty() => {
+                if self.decoder.decode(src, dst)? {
+                    self.state = State::Finishing;
+                }
+            }
+            State::Decoding if upstream_done => self.state = State::Finishing,
+            State::Decoding | State::Done => {}
+            State::Finishing => {
+                if self.decoder.finish(dst)? {
+                    self.state = State::Done;
+                }
+            }
+        }
+
+        Ok(state != self.state || read != src.written().len() || written != dst.written().len())
+    }
+}
+
+/// Decodes data that had multiple encodings applied, passing the output of each decoder to the
+/// next.
+#[derive(Debug)]
+pub struct ChainDecoder {
+    // In the order the decoders are applied, the reverse of the order the encodings were
+    stages: Vec<Stage>,
+}
+
+impl ChainDecoder {
+    pub(crate) fn new(decoders: Vec<AnyDecoder>) -> Self {
+        let len = decoders.len();
+        let stages = decoders
+            .into_iter()
+            .enumerate()
+            .map(|(i, decoder)| Stage {
+                decoder,
+                state: State::Decoding,
+                pipe: if i + 1 < len { Some(Pipe::new()) } else { None },
+            })
+            .collect();
+
+        Self { stages }
+    }
+
+    /// Moves data through the stages until no more progress can be made.
+    fn pump(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        eof: bool,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        loop {
+            let mut progress = false;
+
+            for i in 0..self.stages.len() {
+                let (upstream, rest) = self.stages.split_at_mut(i);
+                let stage = &mut rest[0];
+
+                progress |= match upstream.last_mut() {
+                    Some(prev) => {
+                        let pipe = prev.pipe.as_mut().expect("only the last stage has no pipe");
+                        if stage.state == State::Done && !pipe.is_empty() {
+                            // Anything following the end of an inner stream is ignored
+                            pipe.clear();
+                            progress = true;
+                        }
+                        let upstream_done = prev.state == State::Done && pipe.is_empty();
+                        pipe.read(|src| stage.run(src, upstream_done, output))?
+                    }
+                    None => stage.run(input, eof, output)?,
+                };
+            }
+
+            if !progress {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns whether every stage has reached `state` or beyond, the outer encodings end after
+    /// the inner ones so the whole stream has only ended once they all have.
+    fn all(&self, state: State) -> bool {
+        self.stages.iter().all(|stage| stage.state >= state)
+    }
+}
+
+impl Decode for ChainDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        for stage in &mut self.stages {
+            stage.decoder.reinit()?;
+            stage.state = State::Decoding;
+            if let Some(pipe) = &mut stage.pipe {
+                pipe.clear();
+            }
+        }
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.pump(input, false, output)?;
+        Ok(self.all(State::Finishing))
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let mut input = PartialBuffer::new(&[][..]);
+
+        for i in 0..self.stages.len() {
+            self.pump(&mut input, false, output)?;
+
+            let stage = &mut self.stages[i];
+            if stage.state == State::Decoding && !stage.flush(output)? {
+                return Ok(false);
+            }
+        }
+
+        self.pump(&mut input, false, output)?;
+
+        Ok(self
+            .stages
+            .iter()
+            .filter_map(|stage| stage.pipe.as_ref())
+            .all(Pipe::is_empty))
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.pump(&mut PartialBuffer::new(&[][..]), true, output)?;
+        Ok(self.all(State::Done))
+    }
+
+    fn in_header(&self) -> bool {
+        // Only the outermost encoding reads from the input
+        self.stages
+            .first()
+            .is_some_and(|stage| stage.decoder.in_header())
+    }
+}
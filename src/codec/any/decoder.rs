@@ -0,0 +1,181 @@
+use super::chain::ChainDecoder;
+#[cfg(any(feature = "deflate", feature = "zlib"))]
+use super::zlib_or_deflate::ZlibOrDeflateDecoder;
+use crate::{codec::Decode, util::PartialBuffer, Algorithm};
+use std::io::Result;
+
+// The codecs are boxed as their sizes vary wildly, brotli's state is multiple kilobytes.
+#[derive(Debug)]
+pub enum AnyDecoder {
+    #[cfg(feature = "brotli")]
+    Brotli(Box<crate::codec::BrotliDecoder>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(Box<crate::codec::BzDecoder>),
+    #[cfg(feature = "deflate")]
+    Deflate(Box<crate::codec::DeflateDecoder>),
+    #[cfg(feature = "gzip")]
+    Gzip(Box<crate::codec::GzipDecoder>),
+    #[cfg(feature = "lz4")]
+    Lz4(Box<crate::codec::Lz4Decoder>),
+    #[cfg(feature = "lzma")]
+    Lzma(Box<crate::codec::LzmaDecoder>),
+    #[cfg(feature = "snappy")]
+    Snappy(Box<crate::codec::SnappyDecoder>),
+    #[cfg(feature = "xz")]
+    Xz(Box<crate::codec::XzDecoder>),
+    #[cfg(feature = "zlib")]
+    Zlib(Box<crate::codec::ZlibDecoder>),
+    #[cfg(feature = "zstd")]
+    Zstd(Box<crate::codec::ZstdDecoder>),
+    Identity(crate::codec::IdentityDecoder),
+    #[cfg(any(feature = "deflate", feature = "zlib"))]
+    ZlibOrDeflate(Box<ZlibOrDeflateDecoder>),
+    Chain(Box<ChainDecoder>),
+}
+
+impl AnyDecoder {
+    pub(crate) fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            #[cfg(feature = "brotli")]
+            Algorithm::Brotli => Self::Brotli(Box::new(crate::codec::BrotliDecoder::new())),
+            #[cfg(feature = "bzip2")]
+            Algorithm::Bzip2 => Self::Bzip2(Box::new(crate::codec::BzDecoder::new())),
+            #[cfg(feature = "deflate")]
+            Algorithm::Deflate => Self::Deflate(Box::new(crate::codec::DeflateDecoder::new())),
+            #[cfg(feature = "gzip")]
+            Algorithm::Gzip => Self::Gzip(Box::new(crate::codec::GzipDecoder::new())),
+            #[cfg(feature = "lz4")]
+            Algorithm::Lz4 => Self::Lz4(Box::new(crate::codec::Lz4Decoder::new())),
+            #[cfg(feature = "lzma")]
+            Algorithm::Lzma => Self::Lzma(Box::new(crate::codec::LzmaDecoder::new())),
+            #[cfg(feature = "snappy")]
+            Algorithm::Snappy => Self::Snappy(Box::new(crate::codec::SnappyDecoder::new())),
+            #[cfg(feature = "xz")]
+            Algorithm::Xz => Self::Xz(Box::new(crate::codec::XzDecoder::new())),
+            #[cfg(feature = "zlib")]
+            Algorithm::Zlib => Self::Zlib(Box::new(crate::codec::ZlibDecoder::new())),
+            #[cfg(feature = "zstd")]
+            Algorithm::Zstd => Self::Zstd(Box::new(crate::codec::ZstdDecoder::new())),
+        }
+    }
+
+    /// Creates a decoder for data that had the decoders' encodings applied in reverse order.
+    pub(crate) fn chain(mut decoders: Vec<Self>) -> Self {
+        match decoders.len() {
+            0 => Self::Identity(crate::codec::IdentityDecoder::new()),
+            1 => decoders.pop().unwrap(),
+            _ => Self::Chain(Box::new(ChainDecoder::new(decoders))),
+        }
+    }
+
+    /// Chooses the decoder for an HTTP `Content-Encoding` value, which lists the content-codings
+    /// in the order they were applied. Returns `None` if any of them is not supported.
+    pub(crate) fn from_content_encoding(encoding: &str) -> Option<Self> {
+        let mut decoders = Vec::new();
+        for coding in encoding.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            match Self::from_content_coding(coding)? {
+                Self::Identity(_) => {}
+                decoder => decoders.push(decoder),
+            }
+        }
+
+        if decoders.is_empty() && encoding.trim().is_empty() {
+            return None;
+        }
+
+        decoders.reverse();
+        Some(Self::chain(decoders))
+    }
+
+    /// Chooses the decoder for a single HTTP content-coding, returns `None` if it is not
+    /// supported.
+    fn from_content_coding(coding: &str) -> Option<Self> {
+        let is = |name: &str| coding.eq_ignore_ascii_case(name);
+
+        if is("identity") {
+            return Some(Self::Identity(crate::codec::IdentityDecoder::new()));
+        }
+
+        #[cfg(feature = "brotli")]
+        if is("br") {
+            return Some(Self::new(Algorithm::Brotli));
+        }
+
+        #[cfg(any(feature = "deflate", feature = "zlib"))]
+        if is("deflate") {
+            return Some(Self::ZlibOrDeflate(Box::new(ZlibOrDeflateDecoder::new())));
+        }
+
+        #[cfg(feature = "gzip")]
+        if is("gzip") || is("x-gzip") {
+            return Some(Self::new(Algorithm::Gzip));
+        }
+
+        #[cfg(feature = "zstd")]
+        if is("zstd") {
+            return Some(Self::new(Algorithm::Zstd));
+        }
+
+        None
+    }
+}
+
+impl Decode for AnyDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        dispatch!(self, decoder => decoder.reinit(),
+            #[cfg(any(feature = "deflate", feature = "zlib"))]
+            Self::ZlibOrDeflate(decoder) => decoder.reinit(),
+            Self::Chain(decoder) => decoder.reinit(),
+        )
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        dispatch!(self, decoder => decoder.decode(input, output),
+            #[cfg(any(feature = "deflate", feature = "zlib"))]
+            Self::ZlibOrDeflate(decoder) => decoder.decode(input, output),
+            Self::Chain(decoder) => decoder.decode(input, output),
+        )
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        dispatch!(self, decoder => decoder.flush(output),
+            #[cfg(any(feature = "deflate", feature = "zlib"))]
+            Self::ZlibOrDeflate(decoder) => decoder.flush(output),
+            Self::Chain(decoder) => decoder.flush(output),
+        )
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        dispatch!(self, decoder => decoder.finish(output),
+            #[cfg(any(feature = "deflate", feature = "zlib"))]
+            Self::ZlibOrDeflate(decoder) => decoder.finish(output),
+            Self::Chain(decoder) => decoder.finish(output),
+        )
+    }
+
+    fn in_header(&self) -> bool {
+        dispatch!(self, decoder => decoder.in_header(),
+            #[cfg(any(feature = "deflate", feature = "zlib"))]
+            Self::ZlibOrDeflate(decoder) => decoder.in_header(),
+            Self::Chain(decoder) => decoder.in_header(),
+        )
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        dispatch!(self, decoder => decoder.checksum(),
+            #[cfg(any(feature = "deflate", feature = "zlib"))]
+            Self::ZlibOrDeflate(decoder) => decoder.checksum(),
+            Self::Chain(decoder) => decoder.checksum(),
+        )
+    }
+}
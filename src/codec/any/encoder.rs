@@ -0,0 +1,131 @@
+use super::accept_encoding::negotiate;
+use crate::{codec::Encode, util::PartialBuffer, Algorithm, Level};
+use std::io::Result;
+
+// The codecs are boxed as their sizes vary wildly, brotli's state is multiple kilobytes.
+#[derive(Debug)]
+pub enum AnyEncoder {
+    #[cfg(feature = "brotli")]
+    Brotli(Box<crate::codec::BrotliEncoder>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(Box<crate::codec::BzEncoder>),
+    #[cfg(feature = "deflate")]
+    Deflate(Box<crate::codec::DeflateEncoder>),
+    #[cfg(feature = "gzip")]
+    Gzip(Box<crate::codec::GzipEncoder>),
+    #[cfg(feature = "lz4")]
+    Lz4(Box<crate::codec::Lz4Encoder>),
+    #[cfg(feature = "lzma")]
+    Lzma(Box<crate::codec::LzmaEncoder>),
+    #[cfg(feature = "snappy")]
+    Snappy(Box<crate::codec::SnappyEncoder>),
+    #[cfg(feature = "xz")]
+    Xz(Box<crate::codec::XzEncoder>),
+    #[cfg(feature = "zlib")]
+    Zlib(Box<crate::codec::ZlibEncoder>),
+    #[cfg(feature = "zstd")]
+    Zstd(Box<crate::codec::ZstdEncoder>),
+    Identity(crate::codec::IdentityEncoder),
+}
+
+impl AnyEncoder {
+    pub(crate) fn new(algorithm: Algorithm, level: Level) -> Self {
+        match algorithm {
+            #[cfg(feature = "brotli")]
+            Algorithm::Brotli => Self::Brotli(Box::new(crate::codec::BrotliEncoder::new(
+                level.into_brotli(Default::default()),
+            ))),
+            #[cfg(feature = "bzip2")]
+            Algorithm::Bzip2 => Self::Bzip2(Box::new(crate::codec::BzEncoder::new(
+                level.into_bzip2(),
+                0,
+            ))),
+            #[cfg(feature = "deflate")]
+            Algorithm::Deflate => Self::Deflate(Box::new(crate::codec::DeflateEncoder::new(
+                level.into_flate2(),
+            ))),
+            #[cfg(feature = "gzip")]
+            Algorithm::Gzip => Self::Gzip(Box::new(crate::codec::GzipEncoder::new(
+                level.into_flate2(),
+            ))),
+            #[cfg(feature = "lz4")]
+            Algorithm::Lz4 => Self::Lz4(Box::new(crate::codec::Lz4Encoder::new(level.into_lz4()))),
+            #[cfg(feature = "lzma")]
+            Algorithm::Lzma => {
+                Self::Lzma(Box::new(crate::codec::LzmaEncoder::new(level.into_xz2())))
+            }
+            #[cfg(feature = "snappy")]
+            Algorithm::Snappy => Self::Snappy(Box::new(crate::codec::SnappyEncoder::new())),
+            #[cfg(feature = "xz")]
+            Algorithm::Xz => Self::Xz(Box::new(crate::codec::XzEncoder::new(level.into_xz2()))),
+            #[cfg(feature = "zlib")]
+            Algorithm::Zlib => Self::Zlib(Box::new(crate::codec::ZlibEncoder::new(
+                level.into_flate2(),
+            ))),
+            #[cfg(feature = "zstd")]
+            Algorithm::Zstd => {
+                Self::Zstd(Box::new(crate::codec::ZstdEncoder::new(level.into_zstd())))
+            }
+        }
+    }
+
+    /// Chooses the encoder for the response to a request with the given `Accept-Encoding` header,
+    /// returns the content-coding token along with the encoder, or `None` if nothing supported
+    /// is acceptable.
+    pub(crate) fn from_accept_encoding(
+        accept_encoding: &str,
+        level: Level,
+    ) -> Option<(&'static str, Self)> {
+        let (token, algorithm) = negotiate(accept_encoding)?;
+        let encoder = match algorithm {
+            Some(algorithm) => Self::new(algorithm, level),
+            None => Self::Identity(crate::codec::IdentityEncoder::new()),
+        };
+        Some((token, encoder))
+    }
+
+    /// Records the modification time and name of the compressed file in the header, for the
+    /// formats which have a place for them (only gzip), other formats ignore them.
+    pub(crate) fn set_file_info(&mut self, mtime: u32, filename: Option<&[u8]>) {
+        #[cfg(feature = "gzip")]
+        if let Self::Gzip(encoder) = self {
+            encoder.set_file_info(mtime, filename);
+        }
+    }
+}
+
+impl Encode for AnyEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        dispatch!(self, encoder => encoder.encode(input, output))
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        dispatch!(self, encoder => encoder.flush(output))
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        dispatch!(self, encoder => encoder.finish(output))
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        dispatch!(self, encoder => encoder.reset())
+    }
+
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        dispatch!(self, encoder => encoder.set_level(level))
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        dispatch!(self, encoder => encoder.checksum())
+    }
+}
@@ -0,0 +1,46 @@
+/// Forwards a call to the codec held by an `AnyEncoder` or `AnyDecoder`, any extra arms handle
+/// the variants that aren't an `Algorithm`.
+macro_rules! dispatch {
+    (
+        $this:expr, $codec:ident => $call:expr
+        $(, $(#[$attr:meta])* $pat:pat => $arm:expr)* $(,)?
+    ) => {
+        match $this {
+            #[cfg(feature = "brotli")]
+            Self::Brotli($codec) => $call,
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2($codec) => $call,
+            #[cfg(feature = "deflate")]
+            Self::Deflate($codec) => $call,
+            #[cfg(feature = "gzip")]
+            Self::Gzip($codec) => $call,
+            #[cfg(feature = "lz4")]
+            Self::Lz4($codec) => $call,
+            #[cfg(feature = "lzma")]
+            Self::Lzma($codec) => $call,
+            #[cfg(feature = "snappy")]
+            Self::Snappy($codec) => $call,
+            #[cfg(feature = "xz")]
+            Self::Xz($codec) => $call,
+            #[cfg(feature = "zlib")]
+            Self::Zlib($codec) => $call,
+            #[cfg(feature = "zstd")]
+            Self::Zstd($codec) => $call,
+            Self::Identity($codec) => $call,
+            $($(#[$attr])* $pat => $arm,)*
+        }
+    };
+}
+
+mod accept_encoding;
+mod chain;
+mod decoder;
+mod encoder;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+mod transcoder;
+#[cfg(any(feature = "deflate", feature = "zlib"))]
+mod zlib_or_deflate;
+
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) use self::transcoder::Transcoder;
+pub(crate) use self::{decoder::AnyDecoder, encoder::AnyEncoder};
@@ -0,0 +1,145 @@
+use super::{chain::Pipe, AnyDecoder, AnyEncoder};
+use crate::{
+    codec::{Decode, Encode},
+    util::PartialBuffer,
+    Algorithm, Level,
+};
+use std::io::Result;
+
+/// Decodes one format and re-encodes the decoded data in another, passing it from the decoder to
+/// the encoder through a single buffer.
+///
+/// Each member/frame of the input is re-encoded as a member/frame of its own, so the encoder is
+/// reset along with the decoder when decoding multiple members.
+#[derive(Debug)]
+pub struct Transcoder {
+    decoder: AnyDecoder,
+    encoder: AnyEncoder,
+    // The decoded data waiting to be encoded
+    pipe: Pipe,
+    // Whether the decoder side of the ongoing flush or finish is done, the pipe may still hold
+    // some of its output
+    drained: bool,
+}
+
+impl Transcoder {
+    pub(crate) fn new(from: Algorithm, to: Algorithm, level: Level) -> Self {
+        Self {
+            decoder: AnyDecoder::new(from),
+            encoder: AnyEncoder::new(to, level),
+            pipe: Pipe::new(),
+            drained: false,
+        }
+    }
+
+    /// Encodes the data waiting in the pipe, returns whether it has all been encoded.
+    fn drain(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // Encoders may start a new frame when given nothing once they've finished
+        if self.pipe.is_empty() {
+            return Ok(true);
+        }
+
+        let encoder = &mut self.encoder;
+        self.pipe.read(|src| encoder.encode(src, output))?;
+        Ok(self.pipe.is_empty())
+    }
+
+    /// Runs `f` on the decoder into the pipe and then the encoder until the decoder is done,
+    /// returns whether everything it wrote has been encoded.
+    fn drain_with(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+        mut f: impl FnMut(&mut AnyDecoder, &mut PartialBuffer<&mut [u8]>) -> Result<bool>,
+    ) -> Result<bool> {
+        loop {
+            if !self.drain(output)? {
+                return Ok(false);
+            }
+
+            if self.drained {
+                return Ok(true);
+            }
+
+            let decoder = &mut self.decoder;
+            self.drained = self.pipe.write(|dst| f(decoder, dst))?;
+
+            if !self.drained && self.pipe.is_empty() {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+impl Decode for Transcoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.decoder.reinit()?;
+        self.encoder.reset()?;
+        self.pipe.clear();
+        self.drained = false;
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.decoder.reset()?;
+        self.encoder.reset()?;
+        self.pipe.clear();
+        self.drained = false;
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            if !self.drain(output)? || input.unwritten().is_empty() {
+                return Ok(false);
+            }
+
+            let read = input.written().len();
+            let decoder = &mut self.decoder;
+            if self.pipe.write(|dst| decoder.decode(input, dst))? {
+                // What is left in the pipe is encoded by `finish`
+                return Ok(true);
+            }
+
+            if self.pipe.is_empty() && input.written().len() == read {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if !self.drain_with(output, |decoder, dst| decoder.flush(dst))? {
+            return Ok(false);
+        }
+
+        let done = self.encoder.flush(output)?;
+        if done {
+            self.drained = false;
+        }
+        Ok(done)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if !self.drain_with(output, |decoder, dst| decoder.finish(dst))? {
+            return Ok(false);
+        }
+
+        self.encoder.finish(output)
+    }
+
+    fn in_header(&self) -> bool {
+        self.decoder.in_header()
+    }
+}
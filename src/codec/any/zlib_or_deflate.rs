@@ -0,0 +1,126 @@
+use crate::{
+    codec::{is_zlib, Decode, FlateDecoder},
+    util::PartialBuffer,
+};
+use std::io::Result;
+
+/// The length of the zlib header, enough to tell zlib data from raw deflate data.
+const HEADER_LEN: usize = 2;
+
+/// A decoder for the HTTP `deflate` content-encoding.
+///
+/// The encoding is specified as zlib data, but some servers send raw deflate data instead, so the
+/// first two bytes are checked for a zlib header before choosing how to decode the stream.
+#[derive(Debug)]
+pub struct ZlibOrDeflateDecoder {
+    // The start of the stream, buffered until the header can be checked
+    prefix: PartialBuffer<Vec<u8>>,
+    inner: Option<FlateDecoder>,
+}
+
+impl ZlibOrDeflateDecoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            prefix: PartialBuffer::default(),
+            inner: None,
+        }
+    }
+
+    /// Takes input until the header can be checked, returns the decoder once it has been chosen.
+    fn detect(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        eof: bool,
+    ) -> Option<&mut FlateDecoder> {
+        if self.inner.is_none() {
+            let prefix = self.prefix.get_mut();
+            let len = input.unwritten().len().min(HEADER_LEN - prefix.len());
+            prefix.extend_from_slice(&input.unwritten()[..len]);
+            input.advance(len);
+
+            if prefix.len() == HEADER_LEN || eof {
+                self.inner = Some(FlateDecoder::new(is_zlib(prefix) == Some(true)));
+            }
+        }
+
+        self.inner.as_mut()
+    }
+
+    /// Feeds the buffered header to the decoder, returns whether the end of the stream was
+    /// reached.
+    fn replay_prefix(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match &mut self.inner {
+            Some(inner) if !self.prefix.unwritten().is_empty() => {
+                inner.decode(&mut self.prefix, output)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+impl Decode for ZlibOrDeflateDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.prefix = PartialBuffer::default();
+        self.inner = None;
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if self.detect(input, false).is_none() {
+            return Ok(false);
+        }
+
+        if self.replay_prefix(output)? {
+            return Ok(true);
+        }
+
+        match &mut self.inner {
+            Some(inner) if self.prefix.unwritten().is_empty() && !input.unwritten().is_empty() => {
+                inner.decode(input, output)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match &mut self.inner {
+            Some(inner) => inner.flush(output),
+            None => Ok(true),
+        }
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.detect(&mut PartialBuffer::new(&[][..]), true);
+
+        if self.replay_prefix(output)? {
+            return Ok(true);
+        }
+
+        if !self.prefix.unwritten().is_empty() {
+            return Ok(false);
+        }
+
+        match &mut self.inner {
+            Some(inner) => inner.finish(output),
+            None => unreachable!("the decoder is always chosen at EOF"),
+        }
+    }
+
+    fn in_header(&self) -> bool {
+        // The buffered header hasn't been decoded yet
+        !self.prefix.unwritten().is_empty() || self.inner.as_ref().is_some_and(Decode::in_header)
+    }
+}
@@ -0,0 +1,369 @@
+use crate::{
+    auto::{magic, FormatDecoder, Formats},
+    codec::{AnyDecoder, Decode, IdentityDecoder},
+    util::PartialBuffer,
+    Algorithm,
+};
+use std::{fmt, io::Result};
+
+/// The most input that will be buffered while trying to detect the format, enough to cover the
+/// longest magic number along with some extra to check for brotli.
+const SNIFF_LEN: usize = 64;
+
+#[derive(Debug)]
+enum Inner {
+    Custom(Custom),
+    Any(AnyDecoder),
+}
+
+impl Inner {
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match self {
+            Inner::Custom(decoder) => decoder.decode(input, output),
+            Inner::Any(decoder) => decoder.decode(input, output),
+        }
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match self {
+            Inner::Custom(_) => Ok(true),
+            Inner::Any(decoder) => decoder.flush(output),
+        }
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match self {
+            Inner::Custom(decoder) => decoder.finish(output),
+            Inner::Any(decoder) => decoder.finish(output),
+        }
+    }
+
+    fn in_header(&self) -> bool {
+        match self {
+            Inner::Custom(_) => false,
+            Inner::Any(decoder) => decoder.in_header(),
+        }
+    }
+    fn checksum(&self) -> Option<u32> {
+        match self {
+            Inner::Custom(_) => None,
+            Inner::Any(decoder) => decoder.checksum(),
+        }
+    }
+}
+
+/// A decoder for a format registered by the user.
+struct Custom(Box<dyn FormatDecoder>);
+
+impl fmt::Debug for Custom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Custom").finish()
+    }
+}
+
+impl Custom {
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let (read, written, done) = self.0.decode(input.unwritten(), output.unwritten_mut())?;
+        input.advance(read);
+        output.advance(written);
+        Ok(done)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let (written, done) = self.0.finish(output.unwritten_mut())?;
+        output.advance(written);
+        Ok(done)
+    }
+}
+
+/// Brotli streams have no header to match against, so instead check whether the prefix decodes
+/// without error. When at EOF the prefix is the whole stream, so it must decode completely.
+#[cfg(feature = "brotli")]
+fn is_brotli(prefix: &[u8], eof: bool) -> bool {
+    let mut decoder = crate::codec::BrotliDecoder::new();
+    let mut input = PartialBuffer::new(prefix);
+    let mut scratch = [0; 1024];
+
+    // Bound the amount of output, a small prefix is allowed to decompress to a lot of data.
+    for _ in 0..4 {
+        match decoder.decode(&mut input, &mut PartialBuffer::new(&mut scratch[..])) {
+            Ok(true) => return true,
+            Ok(false) if input.unwritten().is_empty() => return !eof,
+            Ok(false) => {}
+            Err(_) => return false,
+        }
+    }
+
+    !eof
+}
+
+/// Detects which of the built-in formats the data starting with `prefix` is in, returning
+/// `Some(None)` if it isn't in any of them or `None` if more input is needed to decide.
+fn detect_algorithm(prefix: &[u8], eof: bool) -> Option<Option<Algorithm>> {
+    let mut pending = false;
+    #[allow(unused)]
+    let mut check = |result: Option<bool>| {
+        pending |= result.is_none();
+        result.unwrap_or(false)
+    };
+
+    #[cfg(feature = "gzip")]
+    if check(magic(prefix, &[0x1f, 0x8b])) {
+        return Some(Some(Algorithm::Gzip));
+    }
+
+    #[cfg(feature = "zstd")]
+    if check(magic(prefix, &[0x28, 0xb5, 0x2f, 0xfd])) {
+        return Some(Some(Algorithm::Zstd));
+    }
+
+    #[cfg(feature = "xz")]
+    if check(magic(prefix, &[0xfd, b'7', b'z', b'X', b'Z', 0x00])) {
+        return Some(Some(Algorithm::Xz));
+    }
+
+    #[cfg(feature = "bzip2")]
+    if check(magic(prefix, b"BZh")) && check(prefix.get(3).map(|b| matches!(b, b'1'..=b'9'))) {
+        return Some(Some(Algorithm::Bzip2));
+    }
+
+    #[cfg(feature = "lz4")]
+    if check(magic(prefix, &[0x04, 0x22, 0x4d, 0x18])) {
+        return Some(Some(Algorithm::Lz4));
+    }
+
+    #[cfg(feature = "snappy")]
+    if check(magic(prefix, b"\xff\x06\x00\x00sNaPpY")) {
+        return Some(Some(Algorithm::Snappy));
+    }
+
+    #[cfg(feature = "zlib")]
+    if check(crate::codec::is_zlib(prefix)) {
+        return Some(Some(Algorithm::Zlib));
+    }
+
+    if pending && !eof {
+        return None;
+    }
+
+    #[cfg(feature = "brotli")]
+    {
+        if prefix.len() < SNIFF_LEN && !eof {
+            return None;
+        }
+
+        if is_brotli(prefix, eof) {
+            return Some(Some(Algorithm::Brotli));
+        }
+    }
+
+    Some(None)
+}
+
+/// Detects the format of the data starting with `prefix`, returning `None` if more input is
+/// needed to decide.
+fn detect(formats: &Formats, prefix: &[u8], eof: bool) -> Option<Inner> {
+    if let Some(decoder) = formats.detect(prefix, eof)? {
+        return Some(Inner::Custom(Custom(decoder)));
+    }
+
+    Some(Inner::Any(match detect_algorithm(prefix, eof)? {
+        Some(algorithm) => AnyDecoder::new(algorithm),
+        None => AnyDecoder::Identity(IdentityDecoder::new()),
+    }))
+}
+
+/// Detects which of the built-in formats the data starting with `prefix` is in, without needing
+/// more input: if `prefix` is too short to decide it is judged as if it were the whole stream.
+pub(crate) fn probe(prefix: &[u8]) -> Option<Algorithm> {
+    if prefix.is_empty() {
+        return None;
+    }
+
+    detect_algorithm(prefix, false)
+        .or_else(|| detect_algorithm(prefix, true))
+        .flatten()
+}
+
+#[derive(Debug)]
+enum State {
+    Detecting(Vec<u8>),
+    Decoding {
+        inner: Inner,
+        // The input that was buffered during detection, fed to the decoder before any more input
+        prefix: PartialBuffer<Vec<u8>>,
+        done: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct AutoDecoder {
+    formats: Formats,
+    state: State,
+}
+
+impl AutoDecoder {
+    pub(crate) fn with_formats(formats: Formats) -> Self {
+        Self {
+            formats,
+            state: State::Detecting(Vec::new()),
+        }
+    }
+
+    fn detect(&mut self, eof: bool) {
+        if let State::Detecting(prefix) = &mut self.state {
+            if let Some(inner) = detect(&self.formats, prefix, eof) {
+                self.state = State::Decoding {
+                    inner,
+                    prefix: std::mem::take(prefix).into(),
+                    done: false,
+                };
+            }
+        }
+    }
+
+    /// Feeds any remaining detection prefix to the decoder, returns whether it was all consumed.
+    fn replay_prefix(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if let State::Decoding {
+            inner,
+            prefix,
+            done,
+        } = &mut self.state
+        {
+            if !*done && !prefix.unwritten().is_empty() {
+                *done = inner.decode(prefix, output)?;
+                return Ok(!*done && prefix.unwritten().is_empty());
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl Decode for AutoDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        // Anything left over from the detection prefix belongs to the next member, which may be
+        // in a different format
+        let leftover = match &self.state {
+            State::Detecting(_) => Vec::new(),
+            State::Decoding { prefix, .. } => prefix.unwritten().to_vec(),
+        };
+        self.state = State::Detecting(leftover);
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.state = State::Detecting(Vec::new());
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.detect(false);
+
+        // Only take as much input as needed to detect the format, that way for formats with a
+        // magic number the prefix is always part of the first member.
+        while let State::Detecting(prefix) = &mut self.state {
+            match input.unwritten().first() {
+                Some(&byte) => {
+                    prefix.push(byte);
+                    input.advance(1);
+                }
+                None => return Ok(false),
+            }
+
+            self.detect(false);
+        }
+
+        if !self.replay_prefix(output)? {
+            return Ok(matches!(self.state, State::Decoding { done: true, .. }));
+        }
+
+        match &mut self.state {
+            State::Detecting(_) => unreachable!(),
+            State::Decoding { inner, .. } => {
+                if input.unwritten().is_empty() {
+                    Ok(false)
+                } else {
+                    inner.decode(input, output)
+                }
+            }
+        }
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match &mut self.state {
+            State::Detecting(_) => Ok(true),
+            State::Decoding { inner, .. } => inner.flush(output),
+        }
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.detect(true);
+
+        if !self.replay_prefix(output)? && matches!(self.state, State::Decoding { done: false, .. })
+        {
+            return Ok(false);
+        }
+
+        match &mut self.state {
+            State::Detecting(_) => unreachable!("detection always completes at EOF"),
+            State::Decoding { inner, .. } => inner.finish(output),
+        }
+    }
+
+    fn in_header(&self) -> bool {
+        // The detection prefix is the start of the header for any format with a magic number
+        match &self.state {
+            State::Detecting(prefix) => !prefix.is_empty(),
+            State::Decoding {
+                inner,
+                prefix,
+                done,
+            } => !*done && (!prefix.unwritten().is_empty() || inner.in_header()),
+        }
+    }
+
+    fn unconsumed(&self) -> &[u8] {
+        match &self.state {
+            State::Detecting(prefix) => prefix,
+            State::Decoding { prefix, .. } => prefix.unwritten(),
+        }
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        match &self.state {
+            State::Detecting(_) => None,
+            State::Decoding { inner, .. } => inner.checksum(),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+mod decoder;
+
+pub(crate) use self::decoder::{probe, AutoDecoder};
@@ -1,4 +1,4 @@
-use crate::{codec::Decode, util::PartialBuffer};
+use crate::{codec::Decode, error, util::PartialBuffer};
 use std::{
     fmt,
     io::{Error, ErrorKind, Result},
@@ -42,9 +42,7 @@ impl BrotliDecoder {
             &mut 0,
             &mut self.state,
         ) {
-            BrotliResult::ResultFailure => {
-                return Err(Error::new(ErrorKind::Other, "brotli error"))
-            }
+            BrotliResult::ResultFailure => return Err(error::Error::corrupt("brotli error")),
             status => status,
         };
 
@@ -97,6 +97,11 @@ impl Encode for BrotliEncoder {
 
         Ok(BrotliEncoderIsFinished(&self.state) == 1)
     }
+
+    fn reset(&mut self) -> Result<()> {
+        *self = Self::new(self.state.params.clone());
+        Ok(())
+    }
 }
 
 impl fmt::Debug for BrotliEncoder {
@@ -1,4 +1,4 @@
-use crate::{codec::Decode, util::PartialBuffer};
+use crate::{codec::Decode, error, util::PartialBuffer};
 use std::fmt;
 use std::io::{Error, ErrorKind, Result};
 
@@ -6,6 +6,8 @@ use bzip2::{Decompress, Status};
 
 pub struct BzDecoder {
     decompress: Decompress,
+    small: bool,
+    done: bool,
 }
 
 impl fmt::Debug for BzDecoder {
@@ -21,8 +23,15 @@ impl fmt::Debug for BzDecoder {
 
 impl BzDecoder {
     pub(crate) fn new() -> Self {
+        Self::with_small_memory(false)
+    }
+
+    /// `small` selects bzip2's small decompression mode, which uses less memory but is slower.
+    pub(crate) fn with_small_memory(small: bool) -> Self {
         Self {
-            decompress: Decompress::new(false),
+            decompress: Decompress::new(small),
+            small,
+            done: false,
         }
     }
 
@@ -37,7 +46,10 @@ impl BzDecoder {
         let status = self
             .decompress
             .decompress(input.unwritten(), output.unwritten_mut())
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            .map_err(|e| match e {
+                bzip2::Error::Data | bzip2::Error::DataMagic => error::Error::corrupt(e),
+                e => Error::new(ErrorKind::Other, e),
+            })?;
 
         input.advance((self.decompress.total_in() - prior_in) as usize);
         output.advance((self.decompress.total_out() - prior_out) as usize);
@@ -48,7 +60,8 @@ impl BzDecoder {
 
 impl Decode for BzDecoder {
     fn reinit(&mut self) -> Result<()> {
-        self.decompress = Decompress::new(false);
+        self.decompress = Decompress::new(self.small);
+        self.done = false;
         Ok(())
     }
 
@@ -71,7 +84,10 @@ impl Decode for BzDecoder {
             Status::FinishOk => unreachable!(),
 
             // The stream's end has been met, meaning that no more data can be input.
-            Status::StreamEnd => Ok(true),
+            Status::StreamEnd => {
+                self.done = true;
+                Ok(true)
+            }
 
             // There was insufficient memory in the input or output buffer to complete
             // the request, but otherwise everything went normally.
@@ -98,8 +114,22 @@ impl Decode for BzDecoder {
 
     fn finish(
         &mut self,
-        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
-        Ok(true)
+        if self.done {
+            return Ok(true);
+        }
+
+        if let Status::StreamEnd = self.decode(&mut PartialBuffer::new(&[][..]), output)? {
+            self.done = true;
+            Ok(true)
+        } else if output.unwritten().is_empty() {
+            Ok(false)
+        } else {
+            Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
+            ))
+        }
     }
 }
@@ -6,6 +6,8 @@ use bzip2::{Action, Compress, Compression, Status};
 
 pub struct BzEncoder {
     compress: Compress,
+    level: Compression,
+    work_factor: u32,
 }
 
 impl fmt::Debug for BzEncoder {
@@ -43,6 +45,8 @@ impl BzEncoder {
     pub(crate) fn new(level: Compression, work_factor: u32) -> Self {
         Self {
             compress: Compress::new(level, work_factor),
+            level,
+            work_factor,
         }
     }
 
@@ -146,4 +150,10 @@ impl Encode for BzEncoder {
             Status::MemNeeded => Err(Error::new(ErrorKind::Other, "out of memory")),
         }
     }
+
+    fn reset(&mut self) -> Result<()> {
+        // There is no way to reset a bzip2 stream in place
+        self.compress = Compress::new(self.level, self.work_factor);
+        Ok(())
+    }
 }
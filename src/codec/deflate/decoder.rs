@@ -12,6 +12,14 @@ impl DeflateDecoder {
             inner: crate::codec::FlateDecoder::new(false),
         }
     }
+
+    pub(crate) fn backend(&self) -> &flate2::Decompress {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut flate2::Decompress {
+        self.inner.backend_mut()
+    }
 }
 
 impl crate::codec::Decode for DeflateDecoder {
@@ -14,6 +14,14 @@ impl DeflateEncoder {
             inner: crate::codec::FlateEncoder::new(level, false),
         }
     }
+
+    pub(crate) fn backend(&self) -> &flate2::Compress {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut flate2::Compress {
+        self.inner.backend_mut()
+    }
 }
 
 impl Encode for DeflateEncoder {
@@ -38,4 +46,12 @@ impl Encode for DeflateEncoder {
     ) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn reset(&mut self) -> Result<()> {
+        self.inner.reset()
+    }
+
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.inner.set_level(level)
+    }
 }
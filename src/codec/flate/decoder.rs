@@ -1,12 +1,21 @@
-use crate::{codec::Decode, util::PartialBuffer};
-use std::io::{Error, ErrorKind, Result};
+use crate::{codec::Decode, error, util::PartialBuffer};
+use std::{
+    cmp::{max, min},
+    io::{Error, ErrorKind, Result},
+};
 
-use flate2::{Decompress, FlushDecompress, Status};
+use flate2::{Decompress, DecompressError, FlushDecompress, Status};
+
+// The zlib header flag for a preset dictionary, whose Adler-32 checksum follows the header
+const FDICT: u8 = 0x20;
 
 #[derive(Debug)]
 pub struct FlateDecoder {
     zlib_header: bool,
     decompress: Decompress,
+    // The first bytes of the stream, enough to read a zlib header and dictionary checksum
+    header: [u8; 6],
+    header_len: usize,
 }
 
 impl FlateDecoder {
@@ -14,6 +23,33 @@ impl FlateDecoder {
         Self {
             zlib_header,
             decompress: Decompress::new(zlib_header),
+            header: [0; 6],
+            header_len: 0,
+        }
+    }
+
+    fn record_header(&mut self, input: &[u8]) {
+        let start = self.decompress.total_in() as usize;
+        if self.zlib_header && start < self.header.len() {
+            let len = min(self.header.len() - start, input.len());
+            self.header[start..start + len].copy_from_slice(&input[..len]);
+            self.header_len = max(self.header_len, start + len);
+        }
+    }
+
+    /// Not every flate2 backend reports when a preset dictionary is needed, so this checks the
+    /// zlib header for it instead.
+    fn decode_error(&self, err: DecompressError) -> Error {
+        if self.header_len >= 2 && self.header[1] & FDICT != 0 {
+            let id = if self.header_len == self.header.len() {
+                let [_, _, id @ ..] = self.header;
+                Some(u32::from_be_bytes(id))
+            } else {
+                None
+            };
+            error::Error::DictionaryRequired { id }.into()
+        } else {
+            error::Error::corrupt(err)
         }
     }
 
@@ -23,23 +59,35 @@ impl FlateDecoder {
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
         flush: FlushDecompress,
     ) -> Result<Status> {
+        self.record_header(input.unwritten());
+
         let prior_in = self.decompress.total_in();
         let prior_out = self.decompress.total_out();
 
-        let status =
-            self.decompress
-                .decompress(input.unwritten(), output.unwritten_mut(), flush)?;
+        let status = self
+            .decompress
+            .decompress(input.unwritten(), output.unwritten_mut(), flush)
+            .map_err(|err| self.decode_error(err))?;
 
         input.advance((self.decompress.total_in() - prior_in) as usize);
         output.advance((self.decompress.total_out() - prior_out) as usize);
 
         Ok(status)
     }
+
+    pub(crate) fn backend(&self) -> &Decompress {
+        &self.decompress
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut Decompress {
+        &mut self.decompress
+    }
 }
 
 impl Decode for FlateDecoder {
     fn reinit(&mut self) -> Result<()> {
         self.decompress.reset(self.zlib_header);
+        self.header_len = 0;
         Ok(())
     }
 
@@ -84,14 +132,30 @@ impl Decode for FlateDecoder {
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
+        // `FlushDecompress::Finish` makes a truncated stream fail as corrupt, instead keep
+        // draining until the end of the stream and report if it can't be reached
+        let old_len = output.written().len();
         match self.decode(
             &mut PartialBuffer::new(&[][..]),
             output,
-            FlushDecompress::Finish,
+            FlushDecompress::None,
         )? {
-            Status::Ok => Ok(false),
             Status::StreamEnd => Ok(true),
-            Status::BufError => Err(Error::new(ErrorKind::Other, "unexpected BufError")),
+            _ if output.written().len() > old_len || output.unwritten().is_empty() => Ok(false),
+            _ => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
+            )),
         }
     }
+
+    fn in_header(&self) -> bool {
+        // The zlib header is 2 bytes, followed by the dictionary checksum if it needs one
+        let len = if self.header_len >= 2 && self.header[1] & FDICT != 0 {
+            self.header.len()
+        } else {
+            2
+        };
+        self.zlib_header && (1..len as u64).contains(&self.decompress.total_in())
+    }
 }
@@ -7,6 +7,9 @@ use flate2::{Compress, Compression, FlushCompress, Status};
 pub struct FlateEncoder {
     compress: Compress,
     flushed: bool,
+    zlib_header: bool,
+    // A level to switch to before encoding more input
+    next_level: Option<Compression>,
 }
 
 impl FlateEncoder {
@@ -14,9 +17,51 @@ impl FlateEncoder {
         Self {
             compress: Compress::new(level, zlib_header),
             flushed: true,
+            zlib_header,
+            next_level: None,
         }
     }
 
+    /// Flushes the output of the current compressor and replaces it with one using the next level,
+    /// the raw output of the new compressor continues on from the byte aligned sync flush.
+    ///
+    /// Returns whether the level has been switched
+    fn switch_level(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+        level: Compression,
+    ) -> Result<bool> {
+        // Without any space the sync flush would not be started
+        if output.unwritten().is_empty() {
+            return Ok(false);
+        }
+
+        if !self.flushed {
+            Encode::flush(self, output)?;
+        }
+
+        // The sync flush may have been cut short by the output filling up, leaving some of it
+        // still in the compressor
+        loop {
+            let old_len = output.written().len();
+            self.encode(
+                &mut PartialBuffer::new(&[][..]),
+                output,
+                FlushCompress::None,
+            )?;
+            if output.unwritten().is_empty() {
+                return Ok(false);
+            }
+            if output.written().len() == old_len {
+                break;
+            }
+        }
+
+        self.compress = Compress::new(level, false);
+        self.next_level = None;
+        Ok(true)
+    }
+
     fn encode(
         &mut self,
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
@@ -35,6 +80,14 @@ impl FlateEncoder {
 
         Ok(status)
     }
+
+    pub(crate) fn backend(&self) -> &Compress {
+        &self.compress
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut Compress {
+        &mut self.compress
+    }
 }
 
 impl Encode for FlateEncoder {
@@ -43,6 +96,12 @@ impl Encode for FlateEncoder {
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<()> {
+        if let Some(level) = self.next_level {
+            if !self.switch_level(output, level)? {
+                return Ok(());
+            }
+        }
+
         self.flushed = false;
         match self.encode(input, output, FlushCompress::None)? {
             Status::Ok => Ok(()),
@@ -98,4 +157,26 @@ impl Encode for FlateEncoder {
             Status::BufError => Err(Error::new(ErrorKind::Other, "unexpected BufError")),
         }
     }
+
+    fn reset(&mut self) -> Result<()> {
+        match self.next_level.take() {
+            Some(level) => self.compress = Compress::new(level, self.zlib_header),
+            None => self.compress.reset(),
+        }
+        self.flushed = true;
+        Ok(())
+    }
+
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        // The zlib trailer checksums the whole stream, which a new compressor would not carry on
+        if self.zlib_header {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "changing the level of a zlib stream is not supported",
+            ));
+        }
+
+        self.next_level = Some(level.into_flate2());
+        Ok(())
+    }
 }
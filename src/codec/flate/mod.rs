@@ -2,3 +2,17 @@ mod decoder;
 mod encoder;
 
 pub(crate) use self::{decoder::FlateDecoder, encoder::FlateEncoder};
+
+/// The zlib header has no magic number, but the compression method and a check value over the
+/// first two bytes make accidental matches unlikely. Returns `None` until two bytes are available.
+pub(crate) fn is_zlib(prefix: &[u8]) -> Option<bool> {
+    match *prefix {
+        [cmf, flg, ..] => Some(
+            cmf & 0x0f == 8
+                && cmf >> 4 <= 7
+                && flg & 0x20 == 0
+                && (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0,
+        ),
+        _ => None,
+    }
+}
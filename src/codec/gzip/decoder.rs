@@ -3,6 +3,7 @@ use crate::{
         gzip::header::{self, Header},
         Decode,
     },
+    error,
     util::PartialBuffer,
 };
 use std::io::{Error, ErrorKind, Result};
@@ -27,27 +28,15 @@ pub struct GzipDecoder {
 
 fn check_footer(crc: &Crc, input: &[u8]) -> Result<()> {
     if input.len() < 8 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Invalid gzip footer length",
-        ));
+        return Err(error::Error::corrupt("Invalid gzip footer length"));
     }
 
     let crc_sum = crc.sum().to_le_bytes();
     let bytes_read = crc.amount().to_le_bytes();
 
-    if crc_sum != input[0..4] {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "CRC computed does not match",
-        ));
-    }
-
-    if bytes_read != input[4..8] {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "amount of bytes read does not match",
-        ));
+    // Both the CRC and the amount of bytes read check the integrity of the decompressed data
+    if crc_sum != input[0..4] || bytes_read != input[4..8] {
+        return Err(error::Error::ChecksumMismatch.into());
     }
 
     Ok(())
@@ -63,6 +52,14 @@ impl GzipDecoder {
         }
     }
 
+    /// Returns the header of the current member, once it has been parsed.
+    pub(crate) fn header(&self) -> Option<&crate::gzip::Header> {
+        match self.state {
+            State::Header(_) => None,
+            _ => Some(&self.header.info),
+        }
+    }
+
     fn process<I: AsRef<[u8]>, O: AsRef<[u8]> + AsMut<[u8]>>(
         &mut self,
         input: &mut PartialBuffer<I>,
@@ -108,6 +105,14 @@ impl GzipDecoder {
             }
         }
     }
+
+    pub(crate) fn backend(&self) -> &flate2::Decompress {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut flate2::Decompress {
+        self.inner.backend_mut()
+    }
 }
 
 impl Decode for GzipDecoder {
@@ -167,4 +172,12 @@ impl Decode for GzipDecoder {
             ))
         }
     }
+
+    fn in_header(&self) -> bool {
+        matches!(&self.state, State::Header(parser) if parser.is_started())
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        Some(self.crc.sum())
+    }
 }
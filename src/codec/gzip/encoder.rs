@@ -16,9 +16,12 @@ pub struct GzipEncoder {
     inner: crate::codec::FlateEncoder,
     crc: Crc,
     state: State,
+    level: Compression,
+    mtime: u32,
+    filename: Option<Vec<u8>>,
 }
 
-fn header(level: Compression) -> Vec<u8> {
+fn header(level: Compression, mtime: u32, filename: Option<&[u8]>) -> Vec<u8> {
     let level_byte = if level.level() >= Compression::best().level() {
         0x02
     } else if level.level() <= Compression::fast().level() {
@@ -27,7 +30,17 @@ fn header(level: Compression) -> Vec<u8> {
         0x00
     };
 
-    vec![0x1f, 0x8b, 0x08, 0, 0, 0, 0, 0, level_byte, 0xff]
+    let flags = if filename.is_some() { 0b0000_1000 } else { 0 };
+
+    let mut header = vec![0x1f, 0x8b, 0x08, flags];
+    header.extend(&mtime.to_le_bytes());
+    header.extend(&[level_byte, 0xff]);
+    if let Some(filename) = filename {
+        header.extend(filename);
+        header.push(0);
+    }
+
+    header
 }
 
 impl GzipEncoder {
@@ -35,10 +48,27 @@ impl GzipEncoder {
         Self {
             inner: crate::codec::FlateEncoder::new(level, false),
             crc: Crc::new(),
-            state: State::Header(header(level).into()),
+            state: State::Header(header(level, 0, None).into()),
+            level,
+            mtime: 0,
+            filename: None,
         }
     }
 
+    /// Records the modification time (in seconds since the Unix epoch, 0 if unknown) and name of
+    /// the compressed file in the header, the name must not contain any nul bytes.
+    pub(crate) fn set_file_info(&mut self, mtime: u32, filename: Option<&[u8]>) {
+        self.mtime = mtime;
+        self.filename = filename.map(Vec::from);
+        if let State::Header(_) = self.state {
+            self.state = State::Header(self.header().into());
+        }
+    }
+
+    fn header(&self) -> Vec<u8> {
+        header(self.level, self.mtime, self.filename.as_deref())
+    }
+
     fn footer(&mut self) -> Vec<u8> {
         let mut output = Vec::with_capacity(8);
 
@@ -47,6 +77,14 @@ impl GzipEncoder {
 
         output
     }
+
+    pub(crate) fn backend(&self) -> &flate2::Compress {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut flate2::Compress {
+        self.inner.backend_mut()
+    }
 }
 
 impl Encode for GzipEncoder {
@@ -161,4 +199,21 @@ impl Encode for GzipEncoder {
             }
         }
     }
+
+    fn reset(&mut self) -> Result<()> {
+        self.inner.reset()?;
+        self.crc = Crc::new();
+        self.state = State::Header(self.header().into());
+        Ok(())
+    }
+
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.inner.set_level(level)?;
+        self.level = level.into_flate2();
+        Ok(())
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        Some(self.crc.sum())
+    }
 }
@@ -1,5 +1,5 @@
-use crate::util::PartialBuffer;
-use std::io::{Error, ErrorKind, Result};
+use crate::{error, util::PartialBuffer};
+use std::io::Result;
 
 #[derive(Debug, Default)]
 struct Flags {
@@ -13,6 +13,7 @@ struct Flags {
 #[derive(Debug, Default)]
 pub(super) struct Header {
     flags: Flags,
+    pub(super) info: crate::gzip::Header,
 }
 
 #[derive(Debug)]
@@ -41,7 +42,7 @@ pub(super) struct Parser {
 impl Header {
     fn parse(input: &[u8; 10]) -> Result<Self> {
         if input[0..3] != [0x1f, 0x8b, 0x08] {
-            return Err(Error::new(ErrorKind::InvalidData, "Invalid gzip header"));
+            return Err(error::Error::corrupt("Invalid gzip header"));
         }
 
         let flag = input[3];
@@ -54,11 +55,27 @@ impl Header {
             comment: (flag & 0b0001_0000) != 0,
         };
 
-        Ok(Header { flags })
+        let info = crate::gzip::Header {
+            text: flags.ascii,
+            mtime: u32::from_le_bytes([input[4], input[5], input[6], input[7]]),
+            extra_flags: input[8],
+            operating_system: input[9],
+            ..Default::default()
+        };
+
+        Ok(Header { flags, info })
     }
 }
 
 impl Parser {
+    /// Returns whether any of the header has been parsed yet.
+    pub(super) fn is_started(&self) -> bool {
+        match &self.state {
+            State::Fixed(data) => !data.written().is_empty(),
+            _ => true,
+        }
+    }
+
     pub(super) fn input(
         &mut self,
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
@@ -85,7 +102,7 @@ impl Parser {
                     data.copy_unwritten_from(input);
 
                     if data.unwritten().is_empty() {
-                        let len = u16::from_be_bytes(data.take().into_inner());
+                        let len = u16::from_le_bytes(data.take().into_inner());
                         self.state = State::Extra(vec![0; usize::from(len)].into());
                     } else {
                         return Ok(None);
@@ -96,6 +113,7 @@ impl Parser {
                     data.copy_unwritten_from(input);
 
                     if data.unwritten().is_empty() {
+                        self.header.info.extra = Some(data.take().into_inner());
                         self.state = State::Filename(<_>::default());
                     } else {
                         return Ok(None);
@@ -111,6 +129,7 @@ impl Parser {
                     if let Some(len) = memchr::memchr(0, input.unwritten()) {
                         data.extend_from_slice(&input.unwritten()[..len]);
                         input.advance(len + 1);
+                        self.header.info.filename = Some(std::mem::take(data));
                         self.state = State::Comment(<_>::default());
                     } else {
                         data.extend_from_slice(input.unwritten());
@@ -128,6 +147,7 @@ impl Parser {
                     if let Some(len) = memchr::memchr(0, input.unwritten()) {
                         data.extend_from_slice(&input.unwritten()[..len]);
                         input.advance(len + 1);
+                        self.header.info.comment = Some(std::mem::take(data));
                         self.state = State::Crc(<_>::default());
                     } else {
                         data.extend_from_slice(input.unwritten());
@@ -0,0 +1,41 @@
+use crate::{codec::Decode, util::PartialBuffer};
+use std::io::Result;
+
+#[derive(Debug)]
+pub struct IdentityDecoder;
+
+impl IdentityDecoder {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+}
+
+impl Decode for IdentityDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // There is no end marker, the stream only ends at EOF
+        output.copy_unwritten_from(input);
+        Ok(false)
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+}
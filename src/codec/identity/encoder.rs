@@ -0,0 +1,40 @@
+use crate::{codec::Encode, util::PartialBuffer};
+use std::io::Result;
+
+#[derive(Debug)]
+pub struct IdentityEncoder;
+
+impl IdentityEncoder {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+}
+
+impl Encode for IdentityEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        output.copy_unwritten_from(input);
+        Ok(())
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{codec::Decode, error, util::PartialBuffer};
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    mem::{self, MaybeUninit},
+};
+
+use isal::isal_sys::igzip_lib as sys;
+
+/// The decompressor state, boxed as it holds the whole history window.
+struct State(Box<sys::inflate_state>);
+
+// Safety: The pointers in the state are only to its own buffers and the caller's buffers for the
+// duration of a call, and are only used through `&mut self`
+unsafe impl Send for State {}
+unsafe impl Sync for State {}
+
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("State")
+            .field("block_state", &self.0.block_state)
+            .field("total_out", &self.0.total_out)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct IsalDecoder {
+    state: State,
+    format: u32,
+    // Input consumed by an earlier call but read ahead past the end of the stream, replayed into
+    // the next member/frame
+    leftover: PartialBuffer<Vec<u8>>,
+}
+
+impl IsalDecoder {
+    /// `format` is one of the `ISAL_*` formats.
+    pub(crate) fn new(format: u32) -> Self {
+        let mut state = Box::new(MaybeUninit::<sys::inflate_state>::uninit());
+        // Safety: The state is fully initialized by `isal_inflate_init`
+        let mut state = unsafe {
+            sys::isal_inflate_init(state.as_mut_ptr());
+            Box::from_raw(Box::into_raw(state).cast::<sys::inflate_state>())
+        };
+        state.crc_flag = format;
+
+        Self {
+            state: State(state),
+            format,
+            leftover: PartialBuffer::new(Vec::new()),
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.state.0.block_state == sys::isal_block_state_ISAL_BLOCK_FINISH
+    }
+
+    fn inflate(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if self.is_finished() {
+            return Ok(true);
+        }
+
+        let state = &mut *self.state.0;
+
+        // The lengths are limited to what fits in the state's counters, the rest is left for the
+        // next call
+        let input_len = input.unwritten().len().min(u32::MAX as usize);
+        let output_len = output.unwritten().len().min(u32::MAX as usize);
+
+        state.next_in = input.unwritten().as_ptr() as *mut u8;
+        state.avail_in = input_len as u32;
+        state.next_out = output.unwritten_mut().as_mut_ptr();
+        state.avail_out = output_len as u32;
+
+        // Safety: The state points to the buffers with their lengths set above
+        let code = unsafe { sys::isal_inflate(state) };
+
+        let consumed = input_len - state.avail_in as usize;
+        let written = output_len - state.avail_out as usize;
+        state.next_in = std::ptr::null_mut();
+        state.avail_in = 0;
+        state.next_out = std::ptr::null_mut();
+        state.avail_out = 0;
+
+        match code {
+            code if code == sys::ISAL_DECOMP_OK as i32 => {}
+            code if code == sys::ISAL_INCORRECT_CHECKSUM => {
+                return Err(error::Error::ChecksumMismatch.into())
+            }
+            code => {
+                return Err(error::Error::corrupt(format!(
+                    "igzip decompression failed with code {}",
+                    code
+                )))
+            }
+        }
+
+        output.advance(written);
+
+        if !self.is_finished() {
+            input.advance(consumed);
+            return Ok(false);
+        }
+
+        // Whole bytes can be left in the bit buffer after the end of the stream, they belong to
+        // whatever follows it. They were the last bytes consumed, so are given back to the input
+        // as far as they came from it and kept to be replayed otherwise.
+        let state = &mut *self.state.0;
+        let padding = state.read_in_length % 8;
+        let bytes = (state.read_in >> padding).to_le_bytes();
+        let len = (state.read_in_length / 8) as usize;
+        state.read_in = 0;
+        state.read_in_length = 0;
+
+        input.advance(consumed.saturating_sub(len));
+        self.leftover = PartialBuffer::new(bytes[..len.saturating_sub(consumed)].to_vec());
+
+        Ok(true)
+    }
+}
+
+impl Decode for IsalDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        // Safety: The state was initialized by `isal_inflate_init`
+        unsafe { sys::isal_inflate_reset(&mut *self.state.0) };
+        self.state.0.crc_flag = self.format;
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.reinit()?;
+        self.leftover = PartialBuffer::new(Vec::new());
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // Replay anything read ahead past the end of the previous member/frame first
+        if !self.leftover.unwritten().is_empty() {
+            let mut leftover = mem::replace(&mut self.leftover, PartialBuffer::new(Vec::new()));
+            let done = self.inflate(&mut leftover, output)?;
+
+            // Reaching the end again reads ahead from the replayed input
+            let mut rest = self.leftover.unwritten().to_vec();
+            rest.extend_from_slice(leftover.unwritten());
+            self.leftover = PartialBuffer::new(rest);
+
+            if done || !self.leftover.unwritten().is_empty() {
+                return Ok(done);
+            }
+        }
+
+        let old_len = (input.written().len(), output.written().len());
+        let done = self.inflate(input, output)?;
+
+        // Some invalid headers are neither consumed nor reported as an error
+        if !done
+            && !input.unwritten().is_empty()
+            && !output.unwritten().is_empty()
+            && (input.written().len(), output.written().len()) == old_len
+        {
+            return Err(error::Error::corrupt(
+                "igzip made no progress decoding the input",
+            ));
+        }
+
+        Ok(done)
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.inflate(&mut PartialBuffer::new(&[][..]), output)?;
+        Ok(!output.unwritten().is_empty())
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let old_len = output.written().len();
+        if self.inflate(&mut PartialBuffer::new(&[][..]), output)? {
+            Ok(true)
+        } else if output.written().len() > old_len || output.unwritten().is_empty() {
+            Ok(false)
+        } else {
+            Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
+            ))
+        }
+    }
+
+    fn unconsumed(&self) -> &[u8] {
+        self.leftover.unwritten()
+    }
+}
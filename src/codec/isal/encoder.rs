@@ -0,0 +1,174 @@
+use crate::{codec::Encode, util::PartialBuffer};
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    mem::MaybeUninit,
+};
+
+use isal::isal_sys::igzip_lib as sys;
+
+/// The compressor state, boxed as it is large and points into its own level buffer.
+struct Stream {
+    stream: Box<sys::isal_zstream>,
+    // Working memory for the levels above 0, only used through the stream
+    _level_buf: Vec<u8>,
+}
+
+// Safety: The pointers in the stream are only to memory owned by it or to static tables, and
+// are only used through `&mut self`
+unsafe impl Send for Stream {}
+unsafe impl Sync for Stream {}
+
+impl fmt::Debug for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stream")
+            .field("level", &self.stream.level)
+            .field("total_in", &self.stream.total_in)
+            .field("total_out", &self.stream.total_out)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct IsalEncoder {
+    stream: Stream,
+    format: u32,
+    flushed: bool,
+}
+
+impl IsalEncoder {
+    /// `format` is one of the `IGZIP_*` formats, and `level` is from 0 to 3.
+    pub(crate) fn new(level: u32, format: u32) -> Self {
+        let mut stream = Box::new(MaybeUninit::<sys::isal_zstream>::uninit());
+        // Safety: The stream is fully initialized by `isal_deflate_init`
+        let mut stream = unsafe {
+            sys::isal_deflate_init(stream.as_mut_ptr());
+            Box::from_raw(Box::into_raw(stream).cast::<sys::isal_zstream>())
+        };
+
+        let level_buf_size = match level {
+            0 => sys::ISAL_DEF_LVL0_DEFAULT,
+            1 => sys::ISAL_DEF_LVL1_DEFAULT,
+            2 => sys::ISAL_DEF_LVL2_DEFAULT,
+            _ => sys::ISAL_DEF_LVL3_DEFAULT,
+        };
+        let mut level_buf = vec![0; level_buf_size as usize];
+        stream.level = level;
+        stream.level_buf = level_buf.as_mut_ptr();
+        stream.level_buf_size = level_buf.len() as u32;
+        stream.gzip_flag = format as u16;
+
+        Self {
+            stream: Stream {
+                stream,
+                _level_buf: level_buf,
+            },
+            format,
+            flushed: true,
+        }
+    }
+
+    fn deflate(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+        flush: u32,
+        end_of_stream: bool,
+    ) -> Result<()> {
+        let stream = &mut *self.stream.stream;
+
+        // The lengths are limited to what fits in the stream's counters, the rest is left for
+        // the next call
+        let input_len = input.unwritten().len().min(u32::MAX as usize);
+        let output_len = output.unwritten().len().min(u32::MAX as usize);
+
+        stream.next_in = input.unwritten().as_ptr() as *mut u8;
+        stream.avail_in = input_len as u32;
+        stream.next_out = output.unwritten_mut().as_mut_ptr();
+        stream.avail_out = output_len as u32;
+        stream.flush = flush as u16;
+        stream.end_of_stream = end_of_stream as u16;
+
+        // Safety: The stream points to the buffers with their lengths set above
+        let code = unsafe { sys::isal_deflate(stream) };
+
+        let consumed = input_len - stream.avail_in as usize;
+        let written = output_len - stream.avail_out as usize;
+        stream.next_in = std::ptr::null_mut();
+        stream.avail_in = 0;
+        stream.next_out = std::ptr::null_mut();
+        stream.avail_out = 0;
+
+        if code != sys::COMP_OK as i32 {
+            return Err(Error::new(
+                ErrorKind::Other,
+                format!("igzip compression failed with code {}", code),
+            ));
+        }
+
+        input.advance(consumed);
+        output.advance(written);
+        Ok(())
+    }
+}
+
+impl Encode for IsalEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        self.flushed = false;
+        self.deflate(input, output, sys::NO_FLUSH, false)
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // Each sync flush writes an empty stored block, so only flush once after any input
+        if self.flushed {
+            return Ok(true);
+        }
+
+        // Without any space the flush would not be started
+        if output.unwritten().is_empty() {
+            return Ok(false);
+        }
+
+        self.deflate(
+            &mut PartialBuffer::new(&[][..]),
+            output,
+            sys::SYNC_FLUSH,
+            false,
+        )?;
+
+        // The flush has completed if it didn't fill the output
+        self.flushed = !output.unwritten().is_empty();
+        Ok(self.flushed)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.flushed = false;
+        self.deflate(
+            &mut PartialBuffer::new(&[][..]),
+            output,
+            sys::NO_FLUSH,
+            true,
+        )?;
+
+        Ok(self.stream.stream.internal_state.state == sys::isal_zstate_state_ZSTATE_END)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        // Safety: The stream was initialized by `isal_deflate_init`, the reset keeps its level
+        unsafe { sys::isal_deflate_reset(&mut *self.stream.stream) };
+        // Once the gzip header is written the format is switched to skip writing it again
+        self.stream.stream.gzip_flag = self.format as u16;
+        self.flushed = true;
+        Ok(())
+    }
+}
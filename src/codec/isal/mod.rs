@@ -0,0 +1,112 @@
+mod decoder;
+mod encoder;
+
+use self::{decoder::IsalDecoder, encoder::IsalEncoder};
+use crate::{
+    codec::{Decode, Encode},
+    util::PartialBuffer,
+};
+use std::io::Result;
+
+use isal::isal_sys::igzip_lib as sys;
+
+macro_rules! formats {
+    ($($decoder:ident($decoder_format:path) $encoder:ident($encoder_format:path);)*) => {
+        $(
+            #[derive(Debug)]
+            pub struct $decoder {
+                inner: IsalDecoder,
+            }
+
+            impl $decoder {
+                pub(crate) fn new() -> Self {
+                    Self {
+                        inner: IsalDecoder::new($decoder_format),
+                    }
+                }
+            }
+
+            impl Decode for $decoder {
+                fn reinit(&mut self) -> Result<()> {
+                    self.inner.reinit()
+                }
+
+                fn reset(&mut self) -> Result<()> {
+                    self.inner.reset()
+                }
+
+                fn decode(
+                    &mut self,
+                    input: &mut PartialBuffer<impl AsRef<[u8]>>,
+                    output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+                ) -> Result<bool> {
+                    self.inner.decode(input, output)
+                }
+
+                fn flush(
+                    &mut self,
+                    output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+                ) -> Result<bool> {
+                    self.inner.flush(output)
+                }
+
+                fn finish(
+                    &mut self,
+                    output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+                ) -> Result<bool> {
+                    self.inner.finish(output)
+                }
+
+                fn unconsumed(&self) -> &[u8] {
+                    self.inner.unconsumed()
+                }
+            }
+
+            #[derive(Debug)]
+            pub struct $encoder {
+                inner: IsalEncoder,
+            }
+
+            impl $encoder {
+                pub(crate) fn new(level: u32) -> Self {
+                    Self {
+                        inner: IsalEncoder::new(level, $encoder_format),
+                    }
+                }
+            }
+
+            impl Encode for $encoder {
+                fn encode(
+                    &mut self,
+                    input: &mut PartialBuffer<impl AsRef<[u8]>>,
+                    output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+                ) -> Result<()> {
+                    self.inner.encode(input, output)
+                }
+
+                fn flush(
+                    &mut self,
+                    output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+                ) -> Result<bool> {
+                    self.inner.flush(output)
+                }
+
+                fn finish(
+                    &mut self,
+                    output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+                ) -> Result<bool> {
+                    self.inner.finish(output)
+                }
+
+                fn reset(&mut self) -> Result<()> {
+                    self.inner.reset()
+                }
+            }
+        )*
+    };
+}
+
+formats! {
+    IsalDeflateDecoder(sys::ISAL_DEFLATE) IsalDeflateEncoder(sys::IGZIP_DEFLATE);
+    IsalGzipDecoder(sys::ISAL_GZIP) IsalGzipEncoder(sys::IGZIP_GZIP);
+}
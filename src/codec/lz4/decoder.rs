@@ -0,0 +1,141 @@
+use crate::{codec::Decode, error, unshared::Unshared, util::PartialBuffer};
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    ptr,
+};
+
+use liblz4::liblz4::{
+    check_error, LZ4FDecompressionContext, LZ4F_createDecompressionContext, LZ4F_decompress,
+    LZ4F_freeDecompressionContext, LZ4F_resetDecompressionContext, LZ4F_VERSION,
+};
+
+struct DecoderContext {
+    ctx: LZ4FDecompressionContext,
+}
+
+impl DecoderContext {
+    fn new() -> Result<Self> {
+        let mut ctx = LZ4FDecompressionContext(ptr::null_mut());
+        check_error(unsafe { LZ4F_createDecompressionContext(&mut ctx, LZ4F_VERSION) })?;
+        Ok(Self { ctx })
+    }
+}
+
+impl Drop for DecoderContext {
+    fn drop(&mut self) {
+        unsafe { LZ4F_freeDecompressionContext(self.ctx) };
+    }
+}
+
+pub struct Lz4Decoder {
+    ctx: Unshared<DecoderContext>,
+    done: bool,
+}
+
+impl fmt::Debug for Lz4Decoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lz4Decoder")
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl Lz4Decoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            ctx: Unshared::new(DecoderContext::new().unwrap()),
+            done: false,
+        }
+    }
+
+    /// Returns whether the end of the frame has been reached
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let src = input.unwritten();
+        let dst = output.unwritten_mut();
+
+        let mut src_len = src.len();
+        let mut dst_len = dst.len();
+
+        let remaining = check_error(unsafe {
+            LZ4F_decompress(
+                self.ctx.get_mut().ctx,
+                dst.as_mut_ptr(),
+                &mut dst_len,
+                src.as_ptr(),
+                &mut src_len,
+                ptr::null(),
+            )
+        })
+        .map_err(|err| {
+            // liblz4 only reports the name of the error code in the message
+            if err.to_string().contains("Checksum_invalid") {
+                error::Error::ChecksumMismatch.into()
+            } else {
+                error::Error::corrupt(err)
+            }
+        })?;
+
+        input.advance(src_len);
+        output.advance(dst_len);
+
+        self.done = remaining == 0;
+
+        Ok(self.done)
+    }
+}
+
+impl Decode for Lz4Decoder {
+    fn reinit(&mut self) -> Result<()> {
+        unsafe { LZ4F_resetDecompressionContext(self.ctx.get_mut().ctx) };
+        self.done = false;
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.decode(input, output)
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if self.done {
+            return Ok(true);
+        }
+
+        self.decode(&mut PartialBuffer::new(&[][..]), output)?;
+
+        Ok(!output.unwritten().is_empty())
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if self.done {
+            return Ok(true);
+        }
+
+        self.decode(&mut PartialBuffer::new(&[][..]), output)?;
+
+        if self.done {
+            Ok(true)
+        } else if output.unwritten().is_empty() {
+            Ok(false)
+        } else {
+            Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
+            ))
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{codec::Encode, unshared::Unshared, util::PartialBuffer};
+use std::{fmt, io::Result, ptr};
+
+use liblz4::liblz4::{
+    check_error, BlockChecksum, BlockMode, BlockSize, ContentChecksum, FrameType,
+    LZ4FCompressionContext, LZ4FFrameInfo, LZ4FPreferences, LZ4F_compressBegin, LZ4F_compressBound,
+    LZ4F_compressEnd, LZ4F_compressUpdate, LZ4F_createCompressionContext, LZ4F_flush,
+    LZ4F_freeCompressionContext, LZ4F_VERSION,
+};
+
+// The maximum size of an LZ4 frame header, `LZ4F_HEADER_SIZE_MAX` in `lz4frame.h`.
+const HEADER_SIZE_MAX: usize = 19;
+
+struct EncoderContext {
+    ctx: LZ4FCompressionContext,
+}
+
+impl EncoderContext {
+    fn new() -> Result<Self> {
+        let mut ctx = LZ4FCompressionContext(ptr::null_mut());
+        check_error(unsafe { LZ4F_createCompressionContext(&mut ctx, LZ4F_VERSION) })?;
+        Ok(Self { ctx })
+    }
+}
+
+impl Drop for EncoderContext {
+    fn drop(&mut self) {
+        unsafe { LZ4F_freeCompressionContext(self.ctx) };
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Header,
+    Encoding,
+    Footer,
+    Done,
+}
+
+pub struct Lz4Encoder {
+    ctx: Unshared<EncoderContext>,
+    preferences: LZ4FPreferences,
+    state: State,
+    // Output from the last call into lz4 that did not fit in the caller's output buffer
+    buffer: PartialBuffer<Vec<u8>>,
+    block_size: usize,
+}
+
+impl fmt::Debug for Lz4Encoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lz4Encoder")
+            .field("preferences", &self.preferences)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl Lz4Encoder {
+    pub(crate) fn new(level: u32) -> Self {
+        let preferences = LZ4FPreferences {
+            frame_info: LZ4FFrameInfo {
+                block_size_id: BlockSize::Default,
+                block_mode: BlockMode::Linked,
+                content_checksum_flag: ContentChecksum::ChecksumEnabled,
+                frame_type: FrameType::Frame,
+                content_size: 0,
+                dict_id: 0,
+                block_checksum_flag: BlockChecksum::NoBlockChecksum,
+            },
+            compression_level: level,
+            auto_flush: 0,
+            favor_dec_speed: 0,
+            reserved: [0; 3],
+        };
+        let block_size = preferences.frame_info.block_size_id.get_size();
+
+        Self {
+            ctx: Unshared::new(EncoderContext::new().unwrap()),
+            preferences,
+            state: State::Header,
+            buffer: PartialBuffer::default(),
+            block_size,
+        }
+    }
+
+    /// Copies any pending output into `output`, returns whether there is nothing left pending.
+    fn drain(&mut self, output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>) -> bool {
+        output.copy_unwritten_from(&mut self.buffer);
+        self.buffer.unwritten().is_empty()
+    }
+
+    /// Runs `f` with a destination buffer of at least `bound` bytes, writing directly into
+    /// `output` if it has enough space, otherwise staging the result in the internal buffer.
+    fn write(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+        bound: usize,
+        f: impl FnOnce(LZ4FCompressionContext, *mut u8, usize) -> usize,
+    ) -> Result<()> {
+        let ctx = self.ctx.get_mut().ctx;
+
+        if output.unwritten().len() >= bound {
+            let dst = output.unwritten_mut();
+            let len = check_error(f(ctx, dst.as_mut_ptr(), dst.len()))?;
+            output.advance(len);
+        } else {
+            let mut buffer = self.buffer.take().into_inner();
+            buffer.clear();
+            buffer.resize(bound, 0);
+            let len = check_error(f(ctx, buffer.as_mut_ptr(), buffer.len()))?;
+            buffer.truncate(len);
+            self.buffer = buffer.into();
+            output.copy_unwritten_from(&mut self.buffer);
+        }
+
+        Ok(())
+    }
+
+    fn write_header(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        let preferences = &self.preferences as *const _;
+        self.write(output, HEADER_SIZE_MAX, |ctx, dst, len| unsafe {
+            LZ4F_compressBegin(ctx, dst, len, preferences)
+        })
+    }
+
+    fn bound(&self, len: usize) -> usize {
+        unsafe { LZ4F_compressBound(len, &self.preferences) }
+    }
+}
+
+impl Encode for Lz4Encoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        loop {
+            if !self.drain(output) {
+                return Ok(());
+            }
+
+            match self.state {
+                State::Header => {
+                    self.write_header(output)?;
+                    self.state = State::Encoding;
+                }
+
+                State::Encoding => {
+                    let src = &input.unwritten()[..input.unwritten().len().min(self.block_size)];
+                    let bound = self.bound(src.len());
+                    self.write(output, bound, |ctx, dst, len| unsafe {
+                        LZ4F_compressUpdate(ctx, dst, len, src.as_ptr(), src.len(), ptr::null())
+                    })?;
+                    let consumed = src.len();
+                    input.advance(consumed);
+                }
+
+                State::Footer | State::Done => panic!("encode after complete"),
+            }
+
+            if input.unwritten().is_empty() || output.unwritten().is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            if !self.drain(output) {
+                return Ok(false);
+            }
+
+            match self.state {
+                State::Header => {
+                    self.write_header(output)?;
+                    self.state = State::Encoding;
+                }
+
+                State::Encoding => {
+                    let bound = self.bound(0);
+                    self.write(output, bound, |ctx, dst, len| unsafe {
+                        LZ4F_flush(ctx, dst, len, ptr::null())
+                    })?;
+                    return Ok(self.buffer.unwritten().is_empty());
+                }
+
+                State::Footer => self.state = State::Done,
+
+                State::Done => return Ok(true),
+            }
+        }
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            if !self.drain(output) {
+                return Ok(false);
+            }
+
+            match self.state {
+                State::Header => {
+                    self.write_header(output)?;
+                    self.state = State::Encoding;
+                }
+
+                State::Encoding => {
+                    let bound = self.bound(0);
+                    self.write(output, bound, |ctx, dst, len| unsafe {
+                        LZ4F_compressEnd(ctx, dst, len, ptr::null())
+                    })?;
+                    self.state = State::Footer;
+                }
+
+                State::Footer => self.state = State::Done,
+
+                State::Done => return Ok(true),
+            }
+        }
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        // The context is reset when the next frame is started
+        self.state = State::Header;
+        self.buffer = PartialBuffer::default();
+        Ok(())
+    }
+}
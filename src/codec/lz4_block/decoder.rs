@@ -0,0 +1,110 @@
+use crate::{codec::Decode, error, util::PartialBuffer};
+use std::{
+    convert::TryFrom,
+    io::{Error, ErrorKind, Result},
+};
+
+#[derive(Debug)]
+enum State {
+    Buffering(Vec<u8>),
+    Writing(PartialBuffer<Vec<u8>>),
+    Done,
+}
+
+#[derive(Debug)]
+pub struct Lz4BlockDecoder {
+    max_block_size: usize,
+    state: State,
+}
+
+impl Lz4BlockDecoder {
+    pub(crate) fn new() -> Self {
+        Self::new_with_block_size(super::DEFAULT_MAX_BLOCK_SIZE)
+    }
+
+    pub(crate) fn new_with_block_size(max_block_size: usize) -> Self {
+        Self {
+            max_block_size,
+            state: State::Buffering(Vec::new()),
+        }
+    }
+}
+
+impl Decode for Lz4BlockDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.state = State::Buffering(Vec::new());
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match &mut self.state {
+            State::Buffering(block) => {
+                if block.len() + input.unwritten().len()
+                    > liblz4::block::compress_bound(self.max_block_size)?
+                {
+                    return Err(error::Error::corrupt(
+                        "compressed input exceeds the maximum lz4 block size",
+                    ));
+                }
+
+                block.extend_from_slice(input.unwritten());
+                input.advance(input.unwritten().len());
+
+                // There is no end marker in a raw block, the end of the input is the only
+                // indication that the block is complete.
+                Ok(false)
+            }
+
+            State::Writing(_) | State::Done => Ok(true),
+        }
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // Nothing can be decompressed until the whole block has been received.
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            match &mut self.state {
+                State::Buffering(block) => {
+                    let max_block_size = i32::try_from(self.max_block_size)
+                        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+                    let mut decompressed = vec![0; self.max_block_size];
+                    let len = liblz4::block::decompress_to_buffer(
+                        block,
+                        Some(max_block_size),
+                        &mut decompressed,
+                    )
+                    .map_err(error::Error::corrupt)?;
+                    decompressed.truncate(len);
+                    self.state = State::Writing(decompressed.into());
+                }
+
+                State::Writing(decompressed) => {
+                    output.copy_unwritten_from(decompressed);
+
+                    if decompressed.unwritten().is_empty() {
+                        self.state = State::Done;
+                    }
+                }
+
+                State::Done => return Ok(true),
+            }
+
+            if output.unwritten().is_empty() {
+                return Ok(false);
+            }
+        }
+    }
+}
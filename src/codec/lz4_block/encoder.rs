@@ -0,0 +1,108 @@
+use crate::{codec::Encode, util::PartialBuffer};
+use std::io::{Error, ErrorKind, Result};
+
+use liblz4::block::CompressionMode;
+
+#[derive(Debug)]
+enum State {
+    Buffering(Vec<u8>),
+    Writing(PartialBuffer<Vec<u8>>),
+    Done,
+}
+
+#[derive(Debug)]
+pub struct Lz4BlockEncoder {
+    mode: CompressionMode,
+    max_block_size: usize,
+    state: State,
+}
+
+impl Lz4BlockEncoder {
+    pub(crate) fn new(level: u32) -> Self {
+        Self::new_with_block_size(level, super::DEFAULT_MAX_BLOCK_SIZE)
+    }
+
+    pub(crate) fn new_with_block_size(level: u32, max_block_size: usize) -> Self {
+        // Levels below 3 are all handled by the fast compressor, only the high compression mode
+        // actually makes use of the level.
+        let mode = if level < 3 {
+            CompressionMode::DEFAULT
+        } else {
+            CompressionMode::HIGHCOMPRESSION(level as i32)
+        };
+
+        Self {
+            mode,
+            max_block_size,
+            state: State::Buffering(Vec::new()),
+        }
+    }
+}
+
+impl Encode for Lz4BlockEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        match &mut self.state {
+            State::Buffering(block) => {
+                if block.len() + input.unwritten().len() > self.max_block_size {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "input exceeds the maximum lz4 block size",
+                    ));
+                }
+
+                block.extend_from_slice(input.unwritten());
+                input.advance(input.unwritten().len());
+
+                Ok(())
+            }
+
+            State::Writing(_) | State::Done => panic!("encode after complete"),
+        }
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // A raw block can only be produced once all of its input is known, so there is nothing
+        // that can be written out early.
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            match &mut self.state {
+                State::Buffering(block) => {
+                    let compressed = liblz4::block::compress(block, Some(self.mode), false)?;
+                    self.state = State::Writing(compressed.into());
+                }
+
+                State::Writing(compressed) => {
+                    output.copy_unwritten_from(compressed);
+
+                    if compressed.unwritten().is_empty() {
+                        self.state = State::Done;
+                    }
+                }
+
+                State::Done => return Ok(true),
+            }
+
+            if output.unwritten().is_empty() {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.state = State::Buffering(Vec::new());
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+mod decoder;
+mod encoder;
+
+/// The largest uncompressed block size used when one isn't given, matches the default block size
+/// of the LZ4 frame format.
+const DEFAULT_MAX_BLOCK_SIZE: usize = 64 * 1024;
+
+pub(crate) use self::{decoder::Lz4BlockDecoder, encoder::Lz4BlockEncoder};
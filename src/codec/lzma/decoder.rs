@@ -13,6 +13,14 @@ impl LzmaDecoder {
             inner: crate::codec::Xz2Decoder::new(),
         }
     }
+
+    pub(crate) fn backend(&self) -> &xz2::stream::Stream {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut xz2::stream::Stream {
+        self.inner.backend_mut()
+    }
 }
 
 impl Decode for LzmaDecoder {
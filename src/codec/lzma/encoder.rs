@@ -13,6 +13,14 @@ impl LzmaEncoder {
             inner: crate::codec::Xz2Encoder::new(crate::codec::Xz2FileFormat::Lzma, level),
         }
     }
+
+    pub(crate) fn backend(&self) -> &xz2::stream::Stream {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut xz2::stream::Stream {
+        self.inner.backend_mut()
+    }
 }
 
 impl Encode for LzmaEncoder {
@@ -38,4 +46,8 @@ impl Encode for LzmaEncoder {
     ) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn reset(&mut self) -> Result<()> {
+        self.inner.reset()
+    }
 }
@@ -1,6 +1,79 @@
-use crate::util::PartialBuffer;
+//! Traits for implementing custom compression formats.
+//!
+//! The adapters for each IO type are built from a generic state machine which drives an [`Encode`]
+//! or [`Decode`] implementation, and which can be used with custom implementations through the
+//! `Encoder` and `Decoder` types in each module, such as `futures::bufread::Encoder`.
+//!
+//! Both traits work on a [`PartialBuffer`] of input and of output, consuming input by advancing
+//! past it and producing output by writing into the unwritten part of the output and advancing
+//! past that. A call may make as much progress as fits in the buffers it is given, it will be
+//! called again with more input or space for more output.
+//!
+//! ```
+//! use async_compression::codec::{Encode, PartialBuffer};
+//! use std::io::Result;
+//!
+//! /// Stores each byte as the difference from the previous byte.
+//! #[derive(Debug, Default)]
+//! struct DeltaEncoder {
+//!     last: u8,
+//! }
+//!
+//! impl Encode for DeltaEncoder {
+//!     fn encode(
+//!         &mut self,
+//!         input: &mut PartialBuffer<impl AsRef<[u8]>>,
+//!         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+//!     ) -> Result<()> {
+//!         let len = input.unwritten().len().min(output.unwritten().len());
+//!         for (src, dst) in input.unwritten()[..len].iter().zip(output.unwritten_mut()) {
+//!             *dst = src.wrapping_sub(self.last);
+//!             self.last = *src;
+//!         }
+//!         input.advance(len);
+//!         output.advance(len);
+//!         Ok(())
+//!     }
+//!
+//!     fn flush(
+//!         &mut self,
+//!         _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+//!     ) -> Result<bool> {
+//!         Ok(true)
+//!     }
+//!
+//!     fn finish(
+//!         &mut self,
+//!         _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+//!     ) -> Result<bool> {
+//!         Ok(true)
+//!     }
+//! }
+//!
+//! # fn main() -> Result<()> {
+//! let mut encoder = DeltaEncoder::default();
+//! let mut input = PartialBuffer::new(&[1, 2, 4, 7][..]);
+//! let mut output = PartialBuffer::new([0; 4]);
+//! encoder.encode(&mut input, &mut output)?;
+//! assert_eq!(output.written(), [1, 1, 2, 3]);
+//! # Ok(())
+//! # }
+//! ```
+
 use std::io::Result;
 
+pub use crate::util::PartialBuffer;
+
+#[cfg(any(
+    feature = "futures-io",
+    feature = "http-body",
+    feature = "owned-buf",
+    feature = "std-io",
+    feature = "tokio"
+))]
+mod any;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+mod auto;
 #[cfg(feature = "brotli")]
 mod brotli;
 #[cfg(feature = "bzip2")]
@@ -11,8 +84,19 @@ mod deflate;
 mod flate;
 #[cfg(feature = "gzip")]
 mod gzip;
+mod identity;
+#[cfg(feature = "isal")]
+mod isal;
+#[cfg(feature = "lz4")]
+mod lz4;
+#[cfg(feature = "lz4")]
+mod lz4_block;
 #[cfg(feature = "lzma")]
 mod lzma;
+#[cfg(feature = "snappy")]
+mod snappy;
+#[cfg(feature = "snappy")]
+mod snappy_block;
 #[cfg(feature = "xz")]
 mod xz;
 #[cfg(feature = "xz2")]
@@ -22,6 +106,18 @@ mod zlib;
 #[cfg(feature = "zstd")]
 mod zstd;
 
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) use self::any::Transcoder;
+#[cfg(any(
+    feature = "futures-io",
+    feature = "http-body",
+    feature = "owned-buf",
+    feature = "std-io",
+    feature = "tokio"
+))]
+pub(crate) use self::any::{AnyDecoder, AnyEncoder};
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) use self::auto::{probe, AutoDecoder};
 #[cfg(feature = "brotli")]
 pub(crate) use self::brotli::{BrotliDecoder, BrotliEncoder};
 #[cfg(feature = "bzip2")]
@@ -29,11 +125,24 @@ pub(crate) use self::bzip2::{BzDecoder, BzEncoder};
 #[cfg(feature = "deflate")]
 pub(crate) use self::deflate::{DeflateDecoder, DeflateEncoder};
 #[cfg(feature = "flate2")]
-pub(crate) use self::flate::{FlateDecoder, FlateEncoder};
+pub(crate) use self::flate::{is_zlib, FlateDecoder, FlateEncoder};
 #[cfg(feature = "gzip")]
 pub(crate) use self::gzip::{GzipDecoder, GzipEncoder};
+pub(crate) use self::identity::{IdentityDecoder, IdentityEncoder};
+#[cfg(feature = "isal")]
+pub(crate) use self::isal::{
+    IsalDeflateDecoder, IsalDeflateEncoder, IsalGzipDecoder, IsalGzipEncoder,
+};
+#[cfg(feature = "lz4")]
+pub(crate) use self::lz4::{Lz4Decoder, Lz4Encoder};
+#[cfg(feature = "lz4")]
+pub(crate) use self::lz4_block::{Lz4BlockDecoder, Lz4BlockEncoder};
 #[cfg(feature = "lzma")]
 pub(crate) use self::lzma::{LzmaDecoder, LzmaEncoder};
+#[cfg(feature = "snappy")]
+pub(crate) use self::snappy::{SnappyDecoder, SnappyEncoder};
+#[cfg(feature = "snappy")]
+pub(crate) use self::snappy_block::{SnappyBlockDecoder, SnappyBlockEncoder};
 #[cfg(feature = "xz")]
 pub(crate) use self::xz::{XzDecoder, XzEncoder};
 #[cfg(feature = "xz2")]
@@ -43,28 +152,84 @@ pub(crate) use self::zlib::{ZlibDecoder, ZlibEncoder};
 #[cfg(feature = "zstd")]
 pub(crate) use self::zstd::{ZstdDecoder, ZstdEncoder};
 
+/// A compression format, which turns uncompressed input into compressed output.
 pub trait Encode {
+    /// Compresses data from `input` into `output`, advancing each past the data consumed and
+    /// written.
     fn encode(
         &mut self,
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<()>;
 
+    /// Writes out any data buffered internally, so that everything encoded so far can be
+    /// decoded from the output, called repeatedly until it returns `true`.
+    ///
     /// Returns whether the internal buffers are flushed
     fn flush(&mut self, output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>)
         -> Result<bool>;
 
+    /// Writes out any data buffered internally and the end of the stream, called repeatedly
+    /// until it returns `true`, after which `encode` will not be called again.
+    ///
     /// Returns whether the internal buffers are flushed and the end of the stream is written
     fn finish(
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool>;
+
+    /// Resets this encoder to its initial state ready to encode a new stream with the same
+    /// configuration, discarding anything it has buffered.
+    ///
+    /// The default implementation returns an error of kind
+    /// [`Unsupported`](std::io::ErrorKind::Unsupported).
+    fn reset(&mut self) -> Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "resetting is not supported by this encoder",
+        ))
+    }
+
+    /// Changes the compression level used for the rest of the stream, the data already encoded
+    /// is unaffected.
+    ///
+    /// The default implementation returns an error of kind
+    /// [`Unsupported`](std::io::ErrorKind::Unsupported).
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        let _ = level;
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "changing the level is not supported by this encoder",
+        ))
+    }
+
+    /// Returns the checksum of the uncompressed data encoded into the current member/frame so
+    /// far, for formats which record one in the stream. Encoders without one return `None`,
+    /// which is what the default implementation returns.
+    fn checksum(&self) -> Option<u32> {
+        None
+    }
 }
 
+/// A decompression format, which turns compressed input into uncompressed output.
 pub trait Decode {
     /// Reinitializes this decoder ready to decode a new member/frame of data.
+    ///
+    /// This is called after the end of a stream when decoding multiple members is enabled.
     fn reinit(&mut self) -> Result<()>;
 
+    /// Resets this decoder to its initial state ready to decode a new stream, discarding anything
+    /// it has buffered.
+    ///
+    /// The default implementation calls [`reinit`](Self::reinit), which is enough for decoders
+    /// that don't carry anything over between members/frames.
+    fn reset(&mut self) -> Result<()> {
+        self.reinit()
+    }
+
+    /// Decompresses data from `input` into `output`, advancing each past the data consumed and
+    /// written.
+    ///
     /// Returns whether the end of the stream has been read
     fn decode(
         &mut self,
@@ -72,13 +237,42 @@ pub trait Decode {
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool>;
 
+    /// Writes out any data decoded so far that is buffered internally, called repeatedly until
+    /// it returns `true`.
+    ///
     /// Returns whether the internal buffers are flushed
     fn flush(&mut self, output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>)
         -> Result<bool>;
 
+    /// Writes out any data buffered internally once the input has ended or the end of the
+    /// stream has been read, called repeatedly until it returns `true`. This should return an
+    /// error of kind [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the stream is
+    /// incomplete.
+    ///
     /// Returns whether the internal buffers are flushed
     fn finish(
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool>;
+
+    /// Returns any input this decoder has consumed but not decoded, such as data read ahead
+    /// past the end of the stream. This should be empty for decoders that only ever consume
+    /// input as they decode it, which is what the default implementation returns.
+    fn unconsumed(&self) -> &[u8] {
+        &[]
+    }
+
+    /// Returns whether this decoder is partway through the header of a member/frame, having
+    /// consumed some of it but not all. Decoders that don't track this return `false`, which is
+    /// what the default implementation returns.
+    fn in_header(&self) -> bool {
+        false
+    }
+
+    /// Returns the checksum of the uncompressed data decoded from the current member/frame so
+    /// far, for formats which record one in the stream. Decoders without one return `None`,
+    /// which is what the default implementation returns.
+    fn checksum(&self) -> Option<u32> {
+        None
+    }
 }
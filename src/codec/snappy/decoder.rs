@@ -0,0 +1,220 @@
+use crate::{codec::Decode, error, util::PartialBuffer};
+use std::io::{Error, ErrorKind, Result};
+
+use snap::raw::{decompress_len, max_compress_len, Decoder};
+
+use super::{
+    checksum, CHECKSUM_SIZE, CHUNK_HEADER_SIZE, CHUNK_TYPE_COMPRESSED,
+    CHUNK_TYPE_STREAM_IDENTIFIER, CHUNK_TYPE_UNCOMPRESSED, MAX_BLOCK_SIZE, STREAM_IDENTIFIER,
+};
+
+#[derive(Debug)]
+enum State {
+    Header(PartialBuffer<[u8; CHUNK_HEADER_SIZE]>),
+    Body(u8, PartialBuffer<Vec<u8>>),
+    Writing(PartialBuffer<Vec<u8>>),
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State::Header(<_>::default())
+    }
+}
+
+pub struct SnappyDecoder {
+    decoder: Decoder,
+    seen_identifier: bool,
+    state: State,
+}
+
+impl std::fmt::Debug for SnappyDecoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnappyDecoder")
+            .field("seen_identifier", &self.seen_identifier)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+fn invalid_data(message: &'static str) -> Error {
+    error::Error::corrupt(message)
+}
+
+fn check_checksum(expected: &[u8], data: &[u8]) -> Result<()> {
+    let mut bytes = [0; CHECKSUM_SIZE];
+    bytes.copy_from_slice(expected);
+
+    if u32::from_le_bytes(bytes) != checksum(data) {
+        return Err(error::Error::ChecksumMismatch.into());
+    }
+
+    Ok(())
+}
+
+impl SnappyDecoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            decoder: Decoder::new(),
+            seen_identifier: false,
+            state: State::default(),
+        }
+    }
+
+    fn parse_header(&self, header: &[u8; CHUNK_HEADER_SIZE]) -> Result<State> {
+        let chunk_type = header[0];
+        let len = u32::from_le_bytes([header[1], header[2], header[3], 0]) as usize;
+
+        if !self.seen_identifier && chunk_type != CHUNK_TYPE_STREAM_IDENTIFIER {
+            return Err(invalid_data(
+                "snappy stream does not begin with a stream identifier",
+            ));
+        }
+
+        let max_len = match chunk_type {
+            CHUNK_TYPE_STREAM_IDENTIFIER => STREAM_IDENTIFIER.len(),
+            CHUNK_TYPE_COMPRESSED => CHECKSUM_SIZE + max_compress_len(MAX_BLOCK_SIZE),
+            CHUNK_TYPE_UNCOMPRESSED => CHECKSUM_SIZE + MAX_BLOCK_SIZE,
+            0x02..=0x7f => return Err(invalid_data("unsupported snappy chunk type")),
+            // Padding and reserved skippable chunks, the contents are ignored
+            _ => len,
+        };
+
+        if len > max_len {
+            return Err(invalid_data("snappy chunk is too large"));
+        }
+
+        Ok(State::Body(chunk_type, vec![0; len].into()))
+    }
+
+    /// Processes a complete chunk, returning the uncompressed data it contained.
+    fn parse_body(&mut self, chunk_type: u8, body: &[u8]) -> Result<Option<Vec<u8>>> {
+        match chunk_type {
+            CHUNK_TYPE_STREAM_IDENTIFIER => {
+                if body != STREAM_IDENTIFIER {
+                    return Err(invalid_data("invalid snappy stream identifier"));
+                }
+                self.seen_identifier = true;
+                Ok(None)
+            }
+
+            CHUNK_TYPE_COMPRESSED | CHUNK_TYPE_UNCOMPRESSED => {
+                if body.len() < CHECKSUM_SIZE {
+                    return Err(invalid_data("snappy chunk is too short"));
+                }
+                let (expected, data) = body.split_at(CHECKSUM_SIZE);
+
+                let data = if chunk_type == CHUNK_TYPE_COMPRESSED {
+                    if decompress_len(data).map_err(error::Error::corrupt)? > MAX_BLOCK_SIZE {
+                        return Err(invalid_data("snappy chunk is too large"));
+                    }
+                    self.decoder
+                        .decompress_vec(data)
+                        .map_err(error::Error::corrupt)?
+                } else {
+                    data.to_vec()
+                };
+
+                check_checksum(expected, &data)?;
+
+                Ok(Some(data))
+            }
+
+            // Padding and reserved skippable chunks
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Decode for SnappyDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.seen_identifier = false;
+        self.state = State::default();
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            self.state = match std::mem::take(&mut self.state) {
+                State::Header(mut header) => {
+                    header.copy_unwritten_from(input);
+
+                    if header.unwritten().is_empty() {
+                        self.parse_header(header.get_mut())?
+                    } else {
+                        State::Header(header)
+                    }
+                }
+
+                State::Body(chunk_type, mut body) => {
+                    body.copy_unwritten_from(input);
+
+                    if body.unwritten().is_empty() {
+                        match self.parse_body(chunk_type, body.get_mut())? {
+                            Some(data) => State::Writing(data.into()),
+                            None => State::default(),
+                        }
+                    } else {
+                        State::Body(chunk_type, body)
+                    }
+                }
+
+                State::Writing(mut data) => {
+                    output.copy_unwritten_from(&mut data);
+
+                    if data.unwritten().is_empty() {
+                        State::default()
+                    } else {
+                        State::Writing(data)
+                    }
+                }
+            };
+
+            match self.state {
+                State::Writing(_) if output.unwritten().is_empty() => break,
+                State::Header(_) | State::Body(..) if input.unwritten().is_empty() => break,
+                _ => {}
+            }
+        }
+
+        // The framing format has no end of stream marker, the stream only ends when the input does.
+        Ok(false)
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if let State::Writing(data) = &mut self.state {
+            output.copy_unwritten_from(data);
+
+            if !data.unwritten().is_empty() {
+                return Ok(false);
+            }
+
+            self.state = State::default();
+        }
+
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        if !self.flush(output)? {
+            return Ok(false);
+        }
+
+        match &self.state {
+            State::Header(header) if header.written().is_empty() => Ok(true),
+            _ => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
+            )),
+        }
+    }
+}
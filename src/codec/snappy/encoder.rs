@@ -0,0 +1,157 @@
+use crate::{codec::Encode, util::PartialBuffer};
+use std::io::Result;
+
+use snap::raw::{max_compress_len, Encoder};
+
+use super::{
+    checksum, CHECKSUM_SIZE, CHUNK_HEADER_SIZE, CHUNK_TYPE_COMPRESSED,
+    CHUNK_TYPE_STREAM_IDENTIFIER, CHUNK_TYPE_UNCOMPRESSED, MAX_BLOCK_SIZE, STREAM_IDENTIFIER,
+};
+
+pub struct SnappyEncoder {
+    encoder: Encoder,
+    wrote_identifier: bool,
+    // Uncompressed data waiting to be written as the next chunk
+    block: Vec<u8>,
+    // Encoded chunks that have not yet been copied to the caller's output buffer
+    buffer: PartialBuffer<Vec<u8>>,
+}
+
+impl std::fmt::Debug for SnappyEncoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnappyEncoder")
+            .field("wrote_identifier", &self.wrote_identifier)
+            .field("block", &self.block.len())
+            .field("buffer", &self.buffer.unwritten().len())
+            .finish()
+    }
+}
+
+fn write_chunk_header(buffer: &mut Vec<u8>, chunk_type: u8, len: usize) {
+    buffer.push(chunk_type);
+    buffer.extend_from_slice(&(len as u32).to_le_bytes()[..3]);
+}
+
+impl SnappyEncoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            encoder: Encoder::new(),
+            wrote_identifier: false,
+            block: Vec::with_capacity(MAX_BLOCK_SIZE),
+            buffer: PartialBuffer::default(),
+        }
+    }
+
+    /// Moves the pending block (and the stream identifier, if it hasn't been written yet) into the
+    /// output buffer as encoded chunks.
+    fn write_block(&mut self) -> Result<()> {
+        let mut buffer = self.buffer.take().into_inner();
+        buffer.clear();
+
+        if !self.wrote_identifier {
+            write_chunk_header(
+                &mut buffer,
+                CHUNK_TYPE_STREAM_IDENTIFIER,
+                STREAM_IDENTIFIER.len(),
+            );
+            buffer.extend_from_slice(STREAM_IDENTIFIER);
+            self.wrote_identifier = true;
+        }
+
+        if !self.block.is_empty() {
+            let start = buffer.len();
+            let data_start = start + CHUNK_HEADER_SIZE + CHECKSUM_SIZE;
+            buffer.resize(data_start + max_compress_len(self.block.len()), 0);
+
+            let len = self
+                .encoder
+                .compress(&self.block, &mut buffer[data_start..])?;
+
+            // Only keep the compressed data if it saves at least 12.5%, otherwise store the block
+            // as-is to make decompression cheaper.
+            let (chunk_type, data_len) = if len < self.block.len() - self.block.len() / 8 {
+                buffer.truncate(data_start + len);
+                (CHUNK_TYPE_COMPRESSED, len)
+            } else {
+                buffer.truncate(data_start);
+                buffer.extend_from_slice(&self.block);
+                (CHUNK_TYPE_UNCOMPRESSED, self.block.len())
+            };
+
+            let header = &mut buffer[start..data_start];
+            header[0] = chunk_type;
+            header[1..4].copy_from_slice(&((CHECKSUM_SIZE + data_len) as u32).to_le_bytes()[..3]);
+            header[4..].copy_from_slice(&checksum(&self.block).to_le_bytes());
+
+            self.block.clear();
+        }
+
+        self.buffer = buffer.into();
+
+        Ok(())
+    }
+}
+
+impl Encode for SnappyEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        loop {
+            output.copy_unwritten_from(&mut self.buffer);
+            if !self.buffer.unwritten().is_empty() {
+                return Ok(());
+            }
+
+            if self.block.len() == MAX_BLOCK_SIZE {
+                self.write_block()?;
+                continue;
+            }
+
+            let len = input
+                .unwritten()
+                .len()
+                .min(MAX_BLOCK_SIZE - self.block.len());
+            self.block.extend_from_slice(&input.unwritten()[..len]);
+            input.advance(len);
+
+            if input.unwritten().is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        output.copy_unwritten_from(&mut self.buffer);
+        if !self.buffer.unwritten().is_empty() {
+            return Ok(false);
+        }
+
+        if !self.block.is_empty() || !self.wrote_identifier {
+            self.write_block()?;
+            output.copy_unwritten_from(&mut self.buffer);
+        }
+
+        Ok(self.buffer.unwritten().is_empty())
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // There is no end of stream marker in the framing format, so finishing is the same as
+        // flushing out the last block.
+        self.flush(output)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.wrote_identifier = false;
+        self.block.clear();
+        self.buffer = PartialBuffer::default();
+        Ok(())
+    }
+}
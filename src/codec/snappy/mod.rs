@@ -0,0 +1,29 @@
+mod decoder;
+mod encoder;
+
+pub(crate) use self::{decoder::SnappyDecoder, encoder::SnappyEncoder};
+
+use crc::{Crc, CRC_32_ISCSI};
+
+const STREAM_IDENTIFIER: &[u8] = b"sNaPpY";
+
+const CHUNK_TYPE_COMPRESSED: u8 = 0x00;
+const CHUNK_TYPE_UNCOMPRESSED: u8 = 0x01;
+const CHUNK_TYPE_STREAM_IDENTIFIER: u8 = 0xff;
+
+/// The size of a chunk header, a 1 byte chunk type followed by a 3 byte little endian length.
+const CHUNK_HEADER_SIZE: usize = 4;
+
+/// The size of the checksum at the start of data chunks.
+const CHECKSUM_SIZE: usize = 4;
+
+/// The maximum amount of uncompressed data a single chunk may contain.
+const MAX_BLOCK_SIZE: usize = 65_536;
+
+const CASTAGNOLI: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// The masked CRC-32C of `data` as stored in compressed and uncompressed chunks.
+fn checksum(data: &[u8]) -> u32 {
+    let crc = CASTAGNOLI.checksum(data);
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
@@ -0,0 +1,108 @@
+use crate::{codec::Decode, error, util::PartialBuffer};
+use std::io::Result;
+
+use snap::raw::{decompress_len, max_compress_len, Decoder};
+
+#[derive(Debug)]
+enum State {
+    Buffering(Vec<u8>),
+    Writing(PartialBuffer<Vec<u8>>),
+    Done,
+}
+
+pub struct SnappyBlockDecoder {
+    decoder: Decoder,
+    state: State,
+}
+
+impl std::fmt::Debug for SnappyBlockDecoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnappyBlockDecoder")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl SnappyBlockDecoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            decoder: Decoder::new(),
+            state: State::Buffering(Vec::new()),
+        }
+    }
+}
+
+impl Decode for SnappyBlockDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.state = State::Buffering(Vec::new());
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        match &mut self.state {
+            State::Buffering(block) => {
+                block.extend_from_slice(input.unwritten());
+                input.advance(input.unwritten().len());
+
+                // Once the length prefix is available it bounds how much compressed data can
+                // belong to the block, so reject anything larger instead of buffering it.
+                if let Ok(len) = decompress_len(block) {
+                    if block.len() > max_compress_len(len) {
+                        return Err(error::Error::corrupt(
+                            "snappy block is larger than its length prefix allows",
+                        ));
+                    }
+                }
+
+                // The block does not mark where the compressed data ends, the end of the input is
+                // the only indication that the block is complete.
+                Ok(false)
+            }
+
+            State::Writing(_) | State::Done => Ok(true),
+        }
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // Nothing can be decompressed until the whole block has been received.
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            match &mut self.state {
+                State::Buffering(block) => {
+                    let decompressed = self
+                        .decoder
+                        .decompress_vec(block)
+                        .map_err(error::Error::corrupt)?;
+                    self.state = State::Writing(decompressed.into());
+                }
+
+                State::Writing(decompressed) => {
+                    output.copy_unwritten_from(decompressed);
+
+                    if decompressed.unwritten().is_empty() {
+                        self.state = State::Done;
+                    }
+                }
+
+                State::Done => return Ok(true),
+            }
+
+            if output.unwritten().is_empty() {
+                return Ok(false);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{codec::Encode, util::PartialBuffer};
+use std::io::Result;
+
+use snap::raw::Encoder;
+
+#[derive(Debug)]
+enum State {
+    Buffering(Vec<u8>),
+    Writing(PartialBuffer<Vec<u8>>),
+    Done,
+}
+
+pub struct SnappyBlockEncoder {
+    encoder: Encoder,
+    state: State,
+}
+
+impl std::fmt::Debug for SnappyBlockEncoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnappyBlockEncoder")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl SnappyBlockEncoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            encoder: Encoder::new(),
+            state: State::Buffering(Vec::new()),
+        }
+    }
+}
+
+impl Encode for SnappyBlockEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        match &mut self.state {
+            State::Buffering(block) => {
+                block.extend_from_slice(input.unwritten());
+                input.advance(input.unwritten().len());
+
+                Ok(())
+            }
+
+            State::Writing(_) | State::Done => panic!("encode after complete"),
+        }
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        // The length prefix can only be written once all of the input is known, so there is
+        // nothing that can be written out early.
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        loop {
+            match &mut self.state {
+                State::Buffering(block) => {
+                    let compressed = self.encoder.compress_vec(block)?;
+                    self.state = State::Writing(compressed.into());
+                }
+
+                State::Writing(compressed) => {
+                    output.copy_unwritten_from(compressed);
+
+                    if compressed.unwritten().is_empty() {
+                        self.state = State::Done;
+                    }
+                }
+
+                State::Done => return Ok(true),
+            }
+
+            if output.unwritten().is_empty() {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.state = State::Buffering(Vec::new());
+        Ok(())
+    }
+}
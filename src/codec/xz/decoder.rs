@@ -1,6 +1,6 @@
-use crate::{codec::Decode, util::PartialBuffer};
+use crate::{codec::Decode, error, util::PartialBuffer};
 
-use std::io::{Error, ErrorKind, Result};
+use std::io::Result;
 
 #[derive(Debug)]
 pub struct XzDecoder {
@@ -15,6 +15,14 @@ impl XzDecoder {
             skip_padding: None,
         }
     }
+
+    pub(crate) fn backend(&self) -> &xz2::stream::Stream {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut xz2::stream::Stream {
+        self.inner.backend_mut()
+    }
 }
 
 impl Decode for XzDecoder {
@@ -42,8 +50,7 @@ impl Decode for XzDecoder {
             // If this is non-padding then it cannot start with null bytes, so it must be invalid
             // padding
             if *count != 4 {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
+                return Err(error::Error::corrupt(
                     "stream padding was not a multiple of 4 bytes",
                 ));
             }
@@ -13,6 +13,14 @@ impl XzEncoder {
             inner: crate::codec::Xz2Encoder::new(crate::codec::Xz2FileFormat::Xz, level),
         }
     }
+
+    pub(crate) fn backend(&self) -> &xz2::stream::Stream {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut xz2::stream::Stream {
+        self.inner.backend_mut()
+    }
 }
 
 impl Encode for XzEncoder {
@@ -37,4 +45,8 @@ impl Encode for XzEncoder {
     ) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn reset(&mut self) -> Result<()> {
+        self.inner.reset()
+    }
 }
@@ -1,8 +1,8 @@
-use crate::{codec::Decode, util::PartialBuffer};
+use crate::{codec::Decode, error, util::PartialBuffer};
 
 use std::fmt::{Debug, Formatter, Result as FmtResult};
 use std::io::Result;
-use xz2::stream::{Action, Status, Stream};
+use xz2::stream::{Action, Error, Status, Stream};
 
 pub struct Xz2Decoder {
     stream: Stream,
@@ -20,6 +20,14 @@ impl Xz2Decoder {
             stream: Stream::new_auto_decoder(u64::max_value(), 0).unwrap(),
         }
     }
+
+    pub(crate) fn backend(&self) -> &Stream {
+        &self.stream
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut Stream {
+        &mut self.stream
+    }
 }
 
 impl Decode for Xz2Decoder {
@@ -38,7 +46,11 @@ impl Decode for Xz2Decoder {
 
         let status = self
             .stream
-            .process(input.unwritten(), output.unwritten_mut(), Action::Run)?;
+            .process(input.unwritten(), output.unwritten_mut(), Action::Run)
+            .map_err(|err| match err {
+                Error::Data | Error::Format => error::Error::corrupt(err),
+                err => err.into(),
+            })?;
 
         input.advance(self.stream.total_in() as usize - previous_in);
         output.advance(self.stream.total_out() as usize - previous_out);
@@ -78,9 +90,12 @@ impl Decode for Xz2Decoder {
             Status::Ok => Ok(false),
             Status::StreamEnd => Ok(true),
             Status::GetCheck => panic!("Unexpected lzma integrity check"),
+            // liblzma reports `LZMA_BUF_ERROR` as `MemNeeded`, no progress could be made either for
+            // lack of output space or because the input ended before the stream did
+            Status::MemNeeded if output.unwritten().is_empty() => Ok(false),
             Status::MemNeeded => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "More memory needed",
+                std::io::ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
             )),
         }
     }
@@ -7,6 +7,8 @@ use xz2::stream::{Action, Check, LzmaOptions, Status, Stream};
 
 pub struct Xz2Encoder {
     stream: Stream,
+    format: Xz2FileFormat,
+    level: u32,
 }
 
 impl Debug for Xz2Encoder {
@@ -17,14 +19,28 @@ impl Debug for Xz2Encoder {
 
 impl Xz2Encoder {
     pub fn new(format: Xz2FileFormat, level: u32) -> Self {
-        let stream = match format {
+        Self {
+            stream: Self::stream(&format, level),
+            format,
+            level,
+        }
+    }
+
+    fn stream(format: &Xz2FileFormat, level: u32) -> Stream {
+        match format {
             Xz2FileFormat::Xz => Stream::new_easy_encoder(level, Check::Crc64).unwrap(),
             Xz2FileFormat::Lzma => {
                 Stream::new_lzma_encoder(&LzmaOptions::new_preset(level).unwrap()).unwrap()
             }
-        };
+        }
+    }
 
-        Self { stream }
+    pub(crate) fn backend(&self) -> &Stream {
+        &self.stream
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut Stream {
+        &mut self.stream
     }
 }
 
@@ -99,4 +115,10 @@ impl Encode for Xz2Encoder {
             )),
         }
     }
+
+    fn reset(&mut self) -> Result<()> {
+        // There is no way to reset an xz stream in place
+        self.stream = Self::stream(&self.format, self.level);
+        Ok(())
+    }
 }
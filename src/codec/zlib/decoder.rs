@@ -1,22 +1,47 @@
 use crate::util::PartialBuffer;
 use std::io::Result;
 
+use adler::Adler32;
+
 #[derive(Debug)]
 pub struct ZlibDecoder {
     inner: crate::codec::FlateDecoder,
+    adler: Adler32,
 }
 
 impl ZlibDecoder {
     pub(crate) fn new() -> Self {
         Self {
             inner: crate::codec::FlateDecoder::new(true),
+            adler: Adler32::new(),
         }
     }
+
+    /// Runs `f` and adds the data it decoded to the checksum.
+    fn checked<O: AsRef<[u8]> + AsMut<[u8]>>(
+        &mut self,
+        output: &mut PartialBuffer<O>,
+        f: impl FnOnce(&mut crate::codec::FlateDecoder, &mut PartialBuffer<O>) -> Result<bool>,
+    ) -> Result<bool> {
+        let prior = output.written().len();
+        let result = f(&mut self.inner, output);
+        self.adler.write_slice(&output.written()[prior..]);
+        result
+    }
+
+    pub(crate) fn backend(&self) -> &flate2::Decompress {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut flate2::Decompress {
+        self.inner.backend_mut()
+    }
 }
 
 impl crate::codec::Decode for ZlibDecoder {
     fn reinit(&mut self) -> Result<()> {
         self.inner.reinit()?;
+        self.adler = Adler32::new();
         Ok(())
     }
 
@@ -25,20 +50,28 @@ impl crate::codec::Decode for ZlibDecoder {
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
-        self.inner.decode(input, output)
+        self.checked(output, |inner, output| inner.decode(input, output))
     }
 
     fn flush(
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
-        self.inner.flush(output)
+        self.checked(output, |inner, output| inner.flush(output))
     }
 
     fn finish(
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
-        self.inner.finish(output)
+        self.checked(output, |inner, output| inner.finish(output))
+    }
+
+    fn in_header(&self) -> bool {
+        self.inner.in_header()
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        Some(self.adler.checksum())
     }
 }
@@ -1,19 +1,30 @@
 use crate::{codec::Encode, util::PartialBuffer};
 use std::io::Result;
 
+use adler::Adler32;
 use flate2::Compression;
 
 #[derive(Debug)]
 pub struct ZlibEncoder {
     inner: crate::codec::FlateEncoder,
+    adler: Adler32,
 }
 
 impl ZlibEncoder {
     pub(crate) fn new(level: Compression) -> Self {
         Self {
             inner: crate::codec::FlateEncoder::new(level, true),
+            adler: Adler32::new(),
         }
     }
+
+    pub(crate) fn backend(&self) -> &flate2::Compress {
+        self.inner.backend()
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut flate2::Compress {
+        self.inner.backend_mut()
+    }
 }
 
 impl Encode for ZlibEncoder {
@@ -22,7 +33,10 @@ impl Encode for ZlibEncoder {
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<()> {
-        self.inner.encode(input, output)
+        let prior = input.written().len();
+        self.inner.encode(input, output)?;
+        self.adler.write_slice(&input.written()[prior..]);
+        Ok(())
     }
 
     fn flush(
@@ -38,4 +52,18 @@ impl Encode for ZlibEncoder {
     ) -> Result<bool> {
         self.inner.finish(output)
     }
+
+    fn reset(&mut self) -> Result<()> {
+        self.inner.reset()?;
+        self.adler = Adler32::new();
+        Ok(())
+    }
+
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.inner.set_level(level)
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        Some(self.adler.checksum())
+    }
 }
@@ -1,24 +1,158 @@
-use std::io::Result;
+use std::{
+    cmp::min,
+    fmt,
+    io::{Error, ErrorKind, Result},
+    mem,
+};
 
-use crate::{codec::Decode, unshared::Unshared, util::PartialBuffer};
+use crate::{
+    codec::Decode, error, unshared::Unshared, util::PartialBuffer, zstd::DictionaryProvider,
+};
 use libzstd::stream::raw::{Decoder, Operation};
 
-#[derive(Debug)]
+// The magic number starting every frame, skippable frames have their own
+const MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+// The frame header starts with the magic number and the frame header descriptor
+const DESCRIPTOR_END: usize = 5;
+
+/// Reads the dictionary ID from the start of a frame, or returns how much of it is needed first.
+fn read_dict_id(header: &[u8]) -> std::result::Result<Option<u32>, usize> {
+    if header.len() < DESCRIPTOR_END {
+        return Err(DESCRIPTOR_END);
+    }
+
+    // Skippable frames have no dictionary, and anything else is left for libzstd to reject
+    if header[..4] != MAGIC {
+        return Ok(None);
+    }
+
+    // The window descriptor is left out of single segment frames
+    let descriptor = header[4];
+    let window_len = usize::from(descriptor & 0x20 == 0);
+    let id_len = [0, 1, 2, 4][usize::from(descriptor & 0x03)];
+    let end = DESCRIPTOR_END + window_len + id_len;
+    if header.len() < end {
+        return Err(end);
+    }
+
+    let mut id = [0; 4];
+    id[..id_len].copy_from_slice(&header[end - id_len..end]);
+    Ok(Some(u32::from_le_bytes(id)).filter(|&id| id != 0))
+}
+
 pub struct ZstdDecoder {
     decoder: Unshared<Decoder<'static>>,
+    done: bool,
+    // The dictionary ID from the header of the current frame, once it has started
+    dict_id: Option<Option<u32>>,
+    provider: Option<Box<dyn DictionaryProvider>>,
+    // The ID of the dictionary from the provider loaded into the decoder
+    loaded: Option<u32>,
+    // The start of the current frame, held back from the decoder until the dictionary it needs is
+    // loaded
+    header: Vec<u8>,
 }
 
 impl ZstdDecoder {
     pub(crate) fn new() -> Self {
         Self {
             decoder: Unshared::new(Decoder::new().unwrap()),
+            done: false,
+            dict_id: None,
+            provider: None,
+            loaded: None,
+            header: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_dictionary_provider(provider: Box<dyn DictionaryProvider>) -> Self {
+        Self {
+            provider: Some(provider),
+            ..Self::new()
         }
     }
+
+    /// Buffers the start of a frame until it holds the dictionary ID, then loads the dictionary
+    /// from the provider if it isn't already. Returns whether the ID has been read.
+    fn read_header(&mut self, input: &mut PartialBuffer<impl AsRef<[u8]>>) -> Result<bool> {
+        let id = loop {
+            match read_dict_id(&self.header) {
+                Ok(id) => break id,
+                Err(end) => {
+                    let len = min(end - self.header.len(), input.unwritten().len());
+                    if len == 0 {
+                        return Ok(false);
+                    }
+                    self.header.extend_from_slice(&input.unwritten()[..len]);
+                    input.advance(len);
+                }
+            }
+        };
+        self.dict_id = Some(id);
+
+        if let (Some(id), Some(provider)) = (id, &self.provider) {
+            if self.loaded != Some(id) {
+                let dictionary = provider
+                    .dictionary(id)
+                    .ok_or(error::Error::DictionaryRequired { id: Some(id) })?;
+                *self.decoder.get_mut() = Decoder::with_dictionary(&dictionary)?;
+                self.loaded = Some(id);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn run(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let status = self
+            .decoder
+            .get_mut()
+            .run_on_buffers(input.unwritten(), output.unwritten_mut())
+            .map_err(|err| {
+                // The zstd crate only reports the name of the error code in the message
+                match &*err.to_string() {
+                    "Restored data doesn't match checksum" => error::Error::ChecksumMismatch.into(),
+                    "Dictionary mismatch" => error::Error::DictionaryRequired {
+                        id: self.dict_id.flatten(),
+                    }
+                    .into(),
+                    _ => error::Error::corrupt(err),
+                }
+            })?;
+        input.advance(status.bytes_read);
+        output.advance(status.bytes_written);
+        self.done = status.remaining == 0;
+        Ok(self.done)
+    }
+
+    /// The context is only reachable mutably, as it isn't `Sync`.
+    pub(crate) fn backend_mut(&mut self) -> &mut Decoder<'static> {
+        self.decoder.get_mut()
+    }
+}
+
+impl fmt::Debug for ZstdDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZstdDecoder")
+            .field("decoder", &self.decoder)
+            .field("done", &self.done)
+            .field("dict_id", &self.dict_id)
+            .field("loaded", &self.loaded)
+            .finish()
+    }
 }
 
 impl Decode for ZstdDecoder {
     fn reinit(&mut self) -> Result<()> {
         self.decoder.get_mut().reinit()?;
+        self.done = false;
+        self.dict_id = None;
+        self.header.clear();
         Ok(())
     }
 
@@ -27,13 +161,27 @@ impl Decode for ZstdDecoder {
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
-        let status = self
-            .decoder
-            .get_mut()
-            .run_on_buffers(input.unwritten(), output.unwritten_mut())?;
-        input.advance(status.bytes_read);
-        output.advance(status.bytes_written);
-        Ok(status.remaining == 0)
+        if self.provider.is_some() {
+            if self.dict_id.is_none() && !self.read_header(input)? {
+                return Ok(false);
+            }
+
+            if !self.header.is_empty() {
+                let mut header = PartialBuffer::new(mem::take(&mut self.header));
+                let done = self.run(&mut header, output)?;
+                let read = header.written().len();
+                self.header = header.into_inner();
+                self.header.drain(..read);
+                if done || !self.header.is_empty() {
+                    return Ok(done);
+                }
+            }
+        } else if self.dict_id.is_none() && !input.unwritten().is_empty() {
+            let id = zstd_safe::get_dict_id_from_frame(input.unwritten());
+            self.dict_id = Some(Some(id).filter(|&id| id != 0));
+        }
+
+        self.run(input, output)
     }
 
     fn flush(
@@ -51,10 +199,25 @@ impl Decode for ZstdDecoder {
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
-        let mut out_buf = zstd_safe::OutBuffer::around(output.unwritten_mut());
-        let bytes_left = self.decoder.get_mut().finish(&mut out_buf, true)?;
-        let len = out_buf.as_slice().len();
-        output.advance(len);
-        Ok(bytes_left == 0)
+        if self.done {
+            return Ok(true);
+        }
+
+        self.decode(&mut PartialBuffer::new(&[][..]), output)?;
+
+        if self.done {
+            Ok(true)
+        } else if output.unwritten().is_empty() {
+            Ok(false)
+        } else {
+            Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "reached unexpected EOF",
+            ))
+        }
+    }
+
+    fn in_header(&self) -> bool {
+        self.dict_id.is_none() && !self.header.is_empty()
     }
 }
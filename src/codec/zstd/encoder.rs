@@ -1,18 +1,61 @@
 use crate::{codec::Encode, unshared::Unshared, util::PartialBuffer};
-use libzstd::stream::raw::{Encoder, Operation};
+use libzstd::stream::raw::{CParameter, Encoder, Operation};
 use std::io::Result;
 
+#[derive(Debug)]
+enum Frame {
+    Open,
+    // The frame has reached the maximum size and its epilogue is being written
+    Ending,
+    // The frame has been ended, a new one is started once there is more input
+    Ended,
+}
+
 #[derive(Debug)]
 pub struct ZstdEncoder {
     encoder: Unshared<Encoder<'static>>,
+    max_frame_size: Option<u64>,
+    // The number of bytes of input in the current frame
+    frame_size: u64,
+    frame: Frame,
 }
 
 impl ZstdEncoder {
     pub(crate) fn new(level: i32) -> Self {
         Self {
             encoder: Unshared::new(Encoder::new(level).unwrap()),
+            max_frame_size: None,
+            frame_size: 0,
+            frame: Frame::Open,
         }
     }
+
+    /// Splits the output into independent frames of at most `size` bytes of input each.
+    pub(crate) fn with_max_frame_size(level: i32, size: u64) -> Self {
+        assert!(size > 0, "maximum frame size must be non-zero");
+
+        Self {
+            max_frame_size: Some(size),
+            ..Self::new(level)
+        }
+    }
+
+    /// Writes the epilogue of the current frame, returning whether it has all been written.
+    fn end_frame(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let mut out_buf = zstd_safe::OutBuffer::around(output.unwritten_mut());
+        let bytes_left = self.encoder.get_mut().finish(&mut out_buf, true)?;
+        let len = out_buf.as_slice().len();
+        output.advance(len);
+        Ok(bytes_left == 0)
+    }
+
+    /// The context is only reachable mutably, as it isn't `Sync`.
+    pub(crate) fn backend_mut(&mut self) -> &mut Encoder<'static> {
+        self.encoder.get_mut()
+    }
 }
 
 impl Encode for ZstdEncoder {
@@ -21,19 +64,61 @@ impl Encode for ZstdEncoder {
         input: &mut PartialBuffer<impl AsRef<[u8]>>,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<()> {
-        let status = self
-            .encoder
-            .get_mut()
-            .run_on_buffers(input.unwritten(), output.unwritten_mut())?;
-        input.advance(status.bytes_read);
-        output.advance(status.bytes_written);
-        Ok(())
+        loop {
+            match self.frame {
+                Frame::Open => {}
+                Frame::Ending => {
+                    if !self.end_frame(output)? {
+                        return Ok(());
+                    }
+                    self.frame = Frame::Ended;
+                    continue;
+                }
+                Frame::Ended => {
+                    if input.unwritten().is_empty() {
+                        return Ok(());
+                    }
+                    self.encoder.get_mut().reinit()?;
+                    self.frame_size = 0;
+                    self.frame = Frame::Open;
+                }
+            }
+
+            let len = match self.max_frame_size {
+                Some(max) => (input.unwritten().len() as u64).min(max - self.frame_size) as usize,
+                None => input.unwritten().len(),
+            };
+
+            let status = self
+                .encoder
+                .get_mut()
+                .run_on_buffers(&input.unwritten()[..len], output.unwritten_mut())?;
+            input.advance(status.bytes_read);
+            output.advance(status.bytes_written);
+            self.frame_size += status.bytes_read as u64;
+
+            if Some(self.frame_size) != self.max_frame_size {
+                return Ok(());
+            }
+            self.frame = Frame::Ending;
+        }
     }
 
     fn flush(
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
+        match self.frame {
+            Frame::Open => {}
+            Frame::Ending => {
+                if self.end_frame(output)? {
+                    self.frame = Frame::Ended;
+                }
+                return Ok(matches!(self.frame, Frame::Ended));
+            }
+            Frame::Ended => return Ok(true),
+        }
+
         let mut out_buf = zstd_safe::OutBuffer::around(output.unwritten_mut());
         let bytes_left = self.encoder.get_mut().flush(&mut out_buf)?;
         let len = out_buf.as_slice().len();
@@ -45,10 +130,27 @@ impl Encode for ZstdEncoder {
         &mut self,
         output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
     ) -> Result<bool> {
-        let mut out_buf = zstd_safe::OutBuffer::around(output.unwritten_mut());
-        let bytes_left = self.encoder.get_mut().finish(&mut out_buf, true)?;
-        let len = out_buf.as_slice().len();
-        output.advance(len);
-        Ok(bytes_left == 0)
+        if let Frame::Ended = self.frame {
+            return Ok(true);
+        }
+
+        let done = self.end_frame(output)?;
+        if done {
+            self.frame = Frame::Ended;
+        }
+        Ok(done)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.encoder.get_mut().reinit()?;
+        self.frame_size = 0;
+        self.frame = Frame::Open;
+        Ok(())
+    }
+
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.encoder
+            .get_mut()
+            .set_parameter(CParameter::CompressionLevel(level.into_zstd()))
     }
 }
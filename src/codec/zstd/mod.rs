@@ -1,3 +1,10 @@
+// libzstd's `ZSTD_c_stableInBuffer`/`ZSTD_d_stableOutBuffer` parameters are deliberately not
+// used. They require the caller to keep the whole frame's input (respectively output) in place and
+// unmodified until the frame ends, as libzstd then uses it directly as its window instead of
+// copying. The adapters stream through buffers that are consumed and reused, or handed in fresh by
+// the user on every call, so they can never uphold that and libzstd would fail every frame with
+// `stabilityCondition_notRespected`.
+
 mod decoder;
 mod encoder;
 
@@ -0,0 +1,90 @@
+use std::io::Result;
+
+use crate::{error::Error, stats::Stats};
+
+/// The expected sizes and checksum of an entry embedded in a container format, such as a file in
+/// a ZIP archive, which a reading decoder enforces as it decodes the entry.
+#[derive(Debug, Default)]
+pub(crate) struct Entry {
+    compressed_size: Option<u64>,
+    uncompressed_size: Option<u64>,
+    #[cfg(feature = "flate2")]
+    crc32: Option<(u32, flate2::Crc)>,
+}
+
+impl Entry {
+    pub(crate) fn set_compressed_size(&mut self, size: u64) {
+        self.compressed_size = Some(size);
+    }
+
+    pub(crate) fn set_uncompressed_size(&mut self, size: u64) {
+        self.uncompressed_size = Some(size);
+    }
+
+    #[cfg(feature = "flate2")]
+    pub(crate) fn set_crc32(&mut self, crc: u32) {
+        self.crc32 = Some((crc, flate2::Crc::new()));
+    }
+
+    /// Whether the compressed size is set, in which case the entry ends there rather than at the
+    /// end of the reader and anything after it is left for the caller.
+    pub(crate) fn is_sized(&self) -> bool {
+        self.compressed_size.is_some()
+    }
+
+    /// Returns how much of `len` bytes of input may be consumed given the stats so far, which is
+    /// zero once the whole entry has been consumed.
+    pub(crate) fn input_len(&self, stats: &Stats, len: usize) -> usize {
+        match self.compressed_size {
+            Some(size) => size.saturating_sub(stats.total_in).min(len as u64) as usize,
+            None => len,
+        }
+    }
+
+    /// Accounts for newly decoded `output`, failing as soon as the entry is longer than expected.
+    pub(crate) fn update(&mut self, stats: &Stats, output: &[u8]) -> Result<()> {
+        #[cfg(feature = "flate2")]
+        if let Some((_, crc)) = &mut self.crc32 {
+            crc.update(output);
+        }
+
+        match self.uncompressed_size {
+            Some(size) if stats.total_out > size => Err(Error::ChecksumMismatch.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the entry once decoding has ended, given the stats and the number of bytes of input
+    /// that were consumed from the reader but left undecoded.
+    pub(crate) fn check(&self, stats: &Stats, unconsumed: usize) -> Result<()> {
+        if let Some(size) = self.compressed_size {
+            // The compressed stream ended before the end of the entry
+            if stats.total_in - unconsumed as u64 != size {
+                return Err(Error::UnexpectedTrailingData.into());
+            }
+        }
+
+        if let Some(size) = self.uncompressed_size {
+            if stats.total_out != size {
+                return Err(Error::ChecksumMismatch.into());
+            }
+        }
+
+        #[cfg(feature = "flate2")]
+        if let Some((expected, crc)) = &self.crc32 {
+            if crc.sum() != *expected {
+                return Err(Error::ChecksumMismatch.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts a new entry with the same expectations.
+    pub(crate) fn reset(&mut self) {
+        #[cfg(feature = "flate2")]
+        if let Some((_, crc)) = &mut self.crc32 {
+            crc.reset();
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Errors returned by the encoders and decoders, wrapped in an [`std::io::Error`].
+//!
+//! Failures caused by the data being decoded are reported as an [`Error`], which can be
+//! recovered from the [`std::io::Error`] with [`Error::from_io`] to branch on the cause:
+//!
+//! ```
+//! # #[cfg(feature = "futures-io")]
+//! # futures::executor::block_on(async {
+//! use async_compression::{error::Error, futures::bufread::IdentityDecoder};
+//! use futures::io::AsyncReadExt as _;
+//!
+//! let mut decoder = IdentityDecoder::new(&[0; 1024][..]).with_output_limit(512);
+//!
+//! let err = decoder.read_to_end(&mut Vec::new()).await.unwrap_err();
+//! match Error::from_io(&err) {
+//!     Some(Error::LimitExceeded(exceeded)) => assert_eq!(exceeded.max(), 512),
+//!     _ => panic!("unexpected error: {}", err),
+//! }
+//! # })
+//! ```
+
+use std::{error::Error as StdError, fmt, io, ops::RangeInclusive};
+
+/// The cause of a failure to decode data, returned wrapped in an [`std::io::Error`] of kind
+/// [`InvalidData`](std::io::ErrorKind::InvalidData).
+///
+/// Errors from the underlying streams, and from misusing an encoder or decoder, are returned as
+/// is instead.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Error {
+    /// The compressed data is invalid, with the error reported by the codec.
+    CorruptData(Box<dyn StdError + Send + Sync>),
+    /// A checksum or length stored in the compressed data does not match the decompressed data.
+    ChecksumMismatch,
+    /// More data was given to a decoder after the end of the compressed stream.
+    UnexpectedTrailingData,
+    /// The compressed data needs a preset dictionary which was not provided.
+    ///
+    /// The `id` identifies the dictionary as recorded in the data: the Adler-32 checksum of the
+    /// dictionary for zlib, or the dictionary ID for zstd if the frame header holds one.
+    DictionaryRequired {
+        /// The identifier of the dictionary, if known.
+        id: Option<u32>,
+    },
+    /// A limit configured on the decoder was exceeded.
+    LimitExceeded(LimitExceeded),
+}
+
+impl Error {
+    /// Returns the [`Error`] wrapped in `err`, or `None` if it was not caused by the data being
+    /// decoded.
+    pub fn from_io(err: &io::Error) -> Option<&Self> {
+        err.get_ref()?.downcast_ref()
+    }
+
+    pub(crate) fn corrupt(source: impl Into<Box<dyn StdError + Send + Sync>>) -> io::Error {
+        Error::CorruptData(source.into()).into()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::CorruptData(source) => write!(f, "corrupt compressed data: {}", source),
+            Error::ChecksumMismatch => {
+                write!(f, "checksum of the decompressed data does not match")
+            }
+            Error::UnexpectedTrailingData => {
+                write!(f, "unexpected data after the end of the stream")
+            }
+            Error::DictionaryRequired { id: Some(id) } => {
+                write!(f, "compressed data requires the dictionary {:#010x}", id)
+            }
+            Error::DictionaryRequired { id: None } => {
+                write!(f, "compressed data requires a dictionary")
+            }
+            Error::LimitExceeded(exceeded) => exceeded.fmt(f),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Which limit was exceeded, see [`LimitExceeded::limit`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    /// The number of bytes a decoder may produce, set with `with_output_limit`.
+    Output,
+    /// The number of compressed bytes a decoder may consume, set with `with_input_limit`.
+    Input,
+}
+
+/// The details of a limit exceeded by a decoder, see [`Error::LimitExceeded`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitExceeded {
+    limit: Limit,
+    max: u64,
+}
+
+impl LimitExceeded {
+    pub(crate) fn new(limit: Limit, max: u64) -> Self {
+        Self { limit, max }
+    }
+
+    /// Which limit was exceeded.
+    pub fn limit(&self) -> Limit {
+        self.limit
+    }
+
+    /// The configured maximum number of bytes.
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.limit {
+            Limit::Output => "decompressed output",
+            Limit::Input => "compressed input",
+        };
+        write!(f, "{} exceeded the limit of {} bytes", what, self.max)
+    }
+}
+
+impl StdError for LimitExceeded {}
+
+impl From<LimitExceeded> for io::Error {
+    fn from(err: LimitExceeded) -> Self {
+        Error::LimitExceeded(err).into()
+    }
+}
+
+/// A [`Level::Precise`](crate::Level::Precise) quality not supported by an algorithm, returned by
+/// [`Algorithm::check_level`](crate::Algorithm::check_level).
+///
+/// This converts into an [`std::io::Error`] of kind
+/// [`InvalidInput`](std::io::ErrorKind::InvalidInput).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LevelOutOfRange {
+    algorithm: crate::Algorithm,
+    quality: u32,
+    range: RangeInclusive<u32>,
+}
+
+impl LevelOutOfRange {
+    pub(crate) fn new(
+        algorithm: crate::Algorithm,
+        quality: u32,
+        range: RangeInclusive<u32>,
+    ) -> Self {
+        Self {
+            algorithm,
+            quality,
+            range,
+        }
+    }
+
+    /// The algorithm the quality was checked for.
+    pub fn algorithm(&self) -> crate::Algorithm {
+        self.algorithm
+    }
+
+    /// The unsupported quality.
+    pub fn quality(&self) -> u32 {
+        self.quality
+    }
+
+    /// The range of qualities supported by the algorithm.
+    pub fn range(&self) -> RangeInclusive<u32> {
+        self.range.clone()
+    }
+}
+
+impl fmt::Display for LevelOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "quality {} is outside of the range {}..={} supported by {:?}",
+            self.quality,
+            self.range.start(),
+            self.range.end(),
+            self.algorithm
+        )
+    }
+}
+
+impl StdError for LevelOutOfRange {}
+
+impl From<LevelOutOfRange> for io::Error {
+    fn from(err: LevelOutOfRange) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
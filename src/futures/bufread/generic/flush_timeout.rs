@@ -0,0 +1,66 @@
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use std::sync::Arc;
+
+use crate::unshared::Unshared;
+
+type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Creates the futures completing after a duration, from whichever runtime the encoder is used
+/// on.
+#[derive(Clone)]
+pub(crate) struct Timer(Arc<dyn Fn(Duration) -> Sleep + Send + Sync>);
+
+impl Timer {
+    pub(crate) fn new<F>(sleep: impl Fn(Duration) -> F + Send + Sync + 'static) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        Self(Arc::new(move |duration| Box::pin(sleep(duration))))
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timer").finish()
+    }
+}
+
+/// Tracks how long input has been held in an encoder without being flushed, so it can be flushed
+/// once it has waited longer than the configured timeout. Without a timeout it never expires.
+#[derive(Debug, Default)]
+pub(super) struct FlushTimeout {
+    timeout: Option<(Duration, Timer)>,
+    sleep: Option<Unshared<Sleep>>,
+}
+
+impl FlushTimeout {
+    pub(super) fn set(&mut self, timeout: Duration, timer: Timer) {
+        self.timeout = Some((timeout, timer));
+        self.sleep = None;
+    }
+
+    /// Starts the timer if it is not already running, called whenever the encoder takes input.
+    pub(super) fn start(&mut self) {
+        if let (Some((timeout, timer)), None) = (&self.timeout, &self.sleep) {
+            self.sleep = Some(Unshared::new((timer.0)(*timeout)));
+        }
+    }
+
+    /// Stops the timer, called once everything taken so far has been flushed.
+    pub(super) fn stop(&mut self) {
+        self.sleep = None;
+    }
+
+    pub(super) fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match &mut self.sleep {
+            Some(sleep) => sleep.get_mut().as_mut().poll(cx),
+            None => Poll::Pending,
+        }
+    }
+}
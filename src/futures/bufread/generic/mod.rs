@@ -1,4 +1,6 @@
 mod decoder;
 mod encoder;
+mod flush_timeout;
 
+pub(crate) use self::flush_timeout::Timer;
 pub use self::{decoder::Decoder, encoder::Encoder};
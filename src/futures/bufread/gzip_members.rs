@@ -0,0 +1,181 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{
+    io::{Error, ErrorKind, Result},
+    sync::{Arc, Mutex},
+};
+
+use crate::{codec::GzipDecoder, futures::bufread::Decoder, gzip::Header};
+use futures_core::{ready, stream::Stream};
+use futures_io::{AsyncBufRead, AsyncRead};
+
+#[derive(Debug)]
+enum State {
+    // Check whether another member follows
+    Next,
+    // Decode until the header of the member has been parsed
+    Header,
+    // A member has been handed out to be read
+    Reading,
+    // Discard the rest of a member that was not read to the end
+    Skipping,
+}
+
+#[derive(Debug)]
+struct Shared<R> {
+    decoder: Decoder<R, GzipDecoder>,
+    state: State,
+    // Incremented for each member handed out, so older members can tell they were superseded
+    member: u64,
+}
+
+impl<R: AsyncBufRead + Unpin> Shared<R> {
+    /// Advances to the start of the next member, returning whether there is one.
+    fn poll_next_member(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        loop {
+            match self.state {
+                State::Next => {
+                    let input = ready!(Pin::new(self.decoder.get_mut()).poll_fill_buf(cx))?;
+                    if input.is_empty() {
+                        return Poll::Ready(Ok(false));
+                    }
+                    self.decoder.reset()?;
+                    self.state = State::Header;
+                }
+
+                State::Header => {
+                    ready!(Pin::new(&mut self.decoder).poll_fill_buf(cx))?;
+                    if self.decoder.codec().header().is_none() {
+                        return Poll::Ready(Err(ErrorKind::UnexpectedEof.into()));
+                    }
+                    self.member += 1;
+                    self.state = State::Reading;
+                    return Poll::Ready(Ok(true));
+                }
+
+                State::Reading => self.state = State::Skipping,
+
+                State::Skipping => {
+                    let output = ready!(Pin::new(&mut self.decoder).poll_fill_buf(cx))?;
+                    if output.is_empty() {
+                        self.state = State::Next;
+                    } else {
+                        let len = output.len();
+                        Pin::new(&mut self.decoder).consume(len);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A [`Stream`] of the members of a multi-member gzip stream read from an
+/// [`AsyncBufRead`](futures_io::AsyncBufRead), giving access to the header of each member along
+/// with its decompressed data.
+///
+/// Each [`GzipMember`] should be read before polling for the next one. Whatever is left of a
+/// member when the next one is requested is skipped, and reading it after that fails.
+///
+/// ```
+/// # futures::executor::block_on(async {
+/// use async_compression::futures::bufread::GzipMembers;
+/// use futures::{io::AsyncReadExt, stream::TryStreamExt};
+///
+/// # let compressed: &[u8] = &[];
+/// let mut members = GzipMembers::new(compressed);
+/// while let Some(mut member) = members.try_next().await? {
+///     let name = member.header().filename().map(<[u8]>::to_vec);
+///     let mut contents = Vec::new();
+///     member.read_to_end(&mut contents).await?;
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct GzipMembers<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+    done: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> GzipMembers<R> {
+    /// Creates a new stream of the gzip members read from the given stream.
+    pub fn new(reader: R) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                decoder: Decoder::new(reader, GzipDecoder::new()),
+                state: State::Next,
+                member: 0,
+            })),
+            done: false,
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for GzipMembers<R> {
+    type Item = Result<GzipMember<R>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let mut shared = this.shared.lock().unwrap();
+        match ready!(shared.poll_next_member(cx)) {
+            Ok(true) => {
+                let header = shared.decoder.codec().header().cloned().unwrap_or_default();
+                Poll::Ready(Some(Ok(GzipMember {
+                    shared: this.shared.clone(),
+                    member: shared.member,
+                    header,
+                })))
+            }
+            Ok(false) => {
+                this.done = true;
+                Poll::Ready(None)
+            }
+            Err(err) => {
+                this.done = true;
+                Poll::Ready(Some(Err(err)))
+            }
+        }
+    }
+}
+
+/// A member of a gzip stream returned by [`GzipMembers`].
+///
+/// This implements [`AsyncRead`](futures_io::AsyncRead) and reads the decompressed data of the
+/// member, verified against the checksum in its footer.
+#[derive(Debug)]
+pub struct GzipMember<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+    member: u64,
+    header: Header,
+}
+
+impl<R> GzipMember<R> {
+    /// Returns the header of this member.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncRead for GzipMember<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.member != self.member || !matches!(shared.state, State::Reading) {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Other,
+                "gzip member read after the next member was requested",
+            )));
+        }
+
+        Pin::new(&mut shared.decoder).poll_read(cx, buf)
+    }
+}
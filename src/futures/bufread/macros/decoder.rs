@@ -1,24 +1,47 @@
 macro_rules! decoder {
-    ($(#[$attr:meta])* $name:ident) => {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
         pin_project_lite::pin_project! {
             $(#[$attr])*
             #[derive(Debug)]
             ///
             /// This structure implements an [`AsyncRead`](futures_io::AsyncRead) interface and will
             /// read compressed data from an underlying stream and emit a stream of uncompressed data.
-            pub struct $name<R> {
+            /// It also implements [`AsyncBufRead`](futures_io::AsyncBufRead) so it can be used by
+            /// other adapters expecting a buffered reader.
+            pub struct $name<$inner> {
                 #[pin]
-                inner: crate::futures::bufread::Decoder<R, crate::codec::$name>,
+                inner: crate::futures::bufread::Decoder<$inner, crate::codec::$name>,
             }
         }
 
-        impl<R: futures_io::AsyncBufRead> $name<R> {
+        impl<$inner: futures_io::AsyncBufRead> $name<$inner> {
             /// Creates a new decoder which will read compressed data from the given stream and
             /// emit a uncompressed stream.
-            pub fn new(read: R) -> $name<R> {
-                $name {
-                    inner: crate::futures::bufread::Decoder::new(read, crate::codec::$name::new()),
-                }
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
+            $(
+                /// Creates a new decoder which will read compressed data from the given stream and
+                /// emit a uncompressed stream.
+                ///
+                $($constructor)*
+            )*
+
+            /// Returns a builder for this decoder, taking the same extra arguments as `new`, which
+            /// collects the limits and other options before creating it.
+            pub fn builder(
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> crate::futures::bufread::DecoderBuilder<Self, $inner, ($($arg_ty,)*)> {
+                crate::futures::bufread::DecoderBuilder::new(inner, ($($arg,)*), |builder| Self {
+                    inner: builder.build_with(|inner, ($($arg,)*)| {
+                        Self::new(inner $(, $arg)*).inner
+                    }),
+                })
             }
 
             /// Configure multi-member/frame decoding, if enabled this will reset the decoder state
@@ -28,8 +51,18 @@ macro_rules! decoder {
                 self.inner.multiple_members(enabled);
             }
 
+            /// Configure what happens to data following the end of the compressed stream when
+            /// multi-member/frame decoding is disabled, see
+            /// [`TrailingData`](crate::TrailingData) for the options.
+            ///
+            /// Defaults to [`TrailingData::Stop`](crate::TrailingData::Stop), leaving the data in
+            /// the underlying reader.
+            pub fn trailing_data(&mut self, policy: crate::TrailingData) {
+                self.inner.trailing_data(policy);
+            }
+
             /// Acquires a reference to the underlying reader that this decoder is wrapping.
-            pub fn get_ref(&self) -> &R {
+            pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
             }
 
@@ -38,7 +71,7 @@ macro_rules! decoder {
             ///
             /// Note that care must be taken to avoid tampering with the state of the reader which
             /// may otherwise confuse this decoder.
-            pub fn get_mut(&mut self) -> &mut R {
+            pub fn get_mut(&mut self) -> &mut $inner {
                 self.inner.get_mut()
             }
 
@@ -47,20 +80,224 @@ macro_rules! decoder {
             ///
             /// Note that care must be taken to avoid tampering with the state of the reader which
             /// may otherwise confuse this decoder.
-            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut R> {
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
                 self.project().inner.get_pin_mut()
             }
 
+            /// Returns the total number of compressed bytes this decoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of uncompressed bytes this decoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Returns the number of compressed bytes this decoder has consumed and decoded so
+            /// far, once the end of the stream is reached this is its exact length. Unlike
+            /// [`total_in`](Self::total_in) this excludes any data read ahead but not decoded,
+            /// see [`into_inner_with_leftover`](Self::into_inner_with_leftover).
+            pub fn bytes_consumed(&self) -> u64 {
+                self.inner.bytes_consumed()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this decoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Returns whether this decoder has reached the end of the compressed stream and
+            /// returned all of its output. If reading returns EOF before this the underlying
+            /// reader ended early.
+            pub fn is_done(&self) -> bool {
+                self.inner.is_done()
+            }
+
+            /// Returns whether this decoder is partway through the header of a member/frame,
+            /// having consumed some of it but not all. This is only tracked for the gzip and zlib
+            /// formats and is always `false` for others.
+            pub fn in_header(&self) -> bool {
+                self.inner.in_header()
+            }
+
+            /// Returns the number of members/frames this decoder has completely decoded so far.
+            pub fn member_count(&self) -> u64 {
+                self.inner.member_count()
+            }
+
+            /// Returns a [`Checkpoint`](crate::checkpoint::Checkpoint) at the most recent
+            /// member/frame boundary this decoder has passed and returned all the output before,
+            /// from which decoding can be resumed, see the [`checkpoint`](crate::checkpoint)
+            /// module. Returns `None` if there is no such boundary.
+            ///
+            /// Boundaries are only passed between the members/frames of a stream when
+            /// [multi-member/frame decoding](Self::multiple_members) is enabled, and at its end.
+            pub fn checkpoint(&self) -> Option<crate::checkpoint::Checkpoint> {
+                self.inner.checkpoint()
+            }
+
+            /// Resumes decoding from `checkpoint`, returned by [`checkpoint`](Self::checkpoint)
+            /// on an earlier decoder, see the [`checkpoint`](crate::checkpoint) module.
+            ///
+            /// The underlying reader should carry on from
+            /// [`Checkpoint::total_in`](crate::checkpoint::Checkpoint::total_in) of the
+            /// compressed stream, and the output follows the first
+            /// [`Checkpoint::total_out`](crate::checkpoint::Checkpoint::total_out) bytes returned
+            /// by the earlier decoder. The totals, limits and later checkpoints count from the
+            /// start of the stream.
+            pub fn with_checkpoint(self, checkpoint: &crate::checkpoint::Checkpoint) -> Self {
+                Self {
+                    inner: self.inner.with_checkpoint(checkpoint),
+                }
+            }
+
+            /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once
+            /// more would be produced it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This protects against
+            /// decompression bombs when decoding untrusted data.
+            pub fn with_output_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_output_limit(limit),
+                }
+            }
+
+            /// Limits the number of compressed bytes this decoder will consume to `limit`, once
+            /// more would be needed it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This stops a
+            /// never-ending stream from keeping the decoder busy indefinitely.
+            pub fn with_input_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_input_limit(limit),
+                }
+            }
+
+            /// Configure this decoder to decode an entry embedded in a container format, such as
+            /// a file in a ZIP archive, which is exactly `size` bytes of compressed data.
+            ///
+            /// The decoder consumes exactly that much from the underlying reader and then reports
+            /// the end of the stream, leaving the reader positioned after the entry whatever the
+            /// trailing data policy. It fails with
+            /// [`UnexpectedTrailingData`](crate::error::Error::UnexpectedTrailingData) if the
+            /// compressed stream ends before the end of the entry, or with
+            /// [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the reader does.
+            pub fn with_compressed_size(self, size: u64) -> Self {
+                Self {
+                    inner: self.inner.with_compressed_size(size),
+                }
+            }
+
+            /// Verify that the stream decompresses to exactly `size` bytes, failing with
+            /// [`ChecksumMismatch`](crate::error::Error::ChecksumMismatch) otherwise. This is
+            /// checked as soon as more is produced, and at the end of the stream.
+            pub fn with_uncompressed_size(self, size: u64) -> Self {
+                Self {
+                    inner: self.inner.with_uncompressed_size(size),
+                }
+            }
+
+            /// Verify that the CRC-32 of the decompressed data is `crc` at the end of the stream,
+            /// as stored for each file in a ZIP archive, failing with
+            /// [`ChecksumMismatch`](crate::error::Error::ChecksumMismatch) otherwise.
+            #[cfg(feature = "flate2")]
+            #[cfg_attr(
+                docsrs,
+                doc(cfg(any(feature = "deflate", feature = "gzip", feature = "zlib")))
+            )]
+            pub fn with_crc32(self, crc: u32) -> Self {
+                Self {
+                    inner: self.inner.with_crc32(crc),
+                }
+            }
+
+            /// Resets this decoder to decode a new stream from the same reader, reusing its
+            /// allocations.
+            ///
+            /// Any output not yet read is discarded and the statistics start over, while
+            /// configuration such as the limits and multi-member/frame decoding is kept.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this decoder like [`reset`](Self::reset) to decode a new stream from
+            /// `reader`, returning the previous reader.
+            pub fn reset_with_reader(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_reader(reader)
+            }
+
+            /// Limits how much input this decoder consumes from the underlying reader in a single
+            /// poll to `bytes`, defaults to 1 MiB. Once it is used up the decoder returns the
+            /// output it has produced, or if there is none yields to the executor by waking the
+            /// task and returning `Pending`, so a reader which is always ready such as an
+            /// in-memory buffer doesn't monopolize the task.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `bytes` is zero.
+            pub fn with_yield_budget(self, bytes: usize) -> Self {
+                Self {
+                    inner: self.inner.with_yield_budget(bytes),
+                }
+            }
+
             /// Consumes this decoder returning the underlying reader.
             ///
             /// Note that this may discard internal state of this decoder, so care should be taken
             /// to avoid losing resources when this is called.
-            pub fn into_inner(self) -> R {
+            pub fn into_inner(self) -> $inner {
                 self.inner.into_inner()
             }
+
+            /// Consumes this decoder returning the underlying reader, along with any compressed
+            /// data the decoder has read from it but not decoded.
+            ///
+            /// This decoder only consumes as much from the reader as it decodes so whatever
+            /// follows the end of the stream is normally left in the reader, but some decoders
+            /// may read ahead of it. Any such data is returned here, and comes before whatever is
+            /// left in the reader.
+            pub fn into_inner_with_leftover(self) -> ($inner, Vec<u8>) {
+                self.inner.into_inner_with_leftover()
+            }
+        }
+
+        impl<$inner: futures_io::AsyncRead> $name<crate::futures::bufread::BufReader<$inner>> {
+            /// Creates a new decoder which will read compressed data from the given unbuffered
+            /// stream through an internal [`BufReader`](crate::futures::bufread::BufReader) and
+            /// emit a uncompressed stream, taking the same extra arguments as `new`.
+            pub fn from_read(read: $inner $(, $arg: $arg_ty)*) -> Self {
+                Self::new(crate::futures::bufread::BufReader::new(read) $(, $arg)*)
+            }
+
+            /// Creates a new decoder like [`from_read`](Self::from_read), using an internal buffer
+            /// with the specified capacity.
+            pub fn from_read_with_capacity(
+                capacity: usize,
+                read: $inner
+                $(, $arg: $arg_ty)*
+            ) -> Self {
+                let read = crate::futures::bufread::BufReader::with_capacity(capacity, read);
+                Self::new(read $(, $arg)*)
+            }
         }
 
-        impl<R: futures_io::AsyncBufRead> futures_io::AsyncRead for $name<R> {
+        impl<$inner: futures_io::AsyncBufRead> futures_io::AsyncRead for $name<$inner> {
             fn poll_read(
                 self: std::pin::Pin<&mut Self>,
                 cx: &mut std::task::Context<'_>,
@@ -70,6 +307,19 @@ macro_rules! decoder {
             }
         }
 
+        impl<$inner: futures_io::AsyncBufRead> futures_io::AsyncBufRead for $name<$inner> {
+            fn poll_fill_buf(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<&[u8]>> {
+                self.project().inner.poll_fill_buf(cx)
+            }
+
+            fn consume(self: std::pin::Pin<&mut Self>, amt: usize) {
+                self.project().inner.consume(amt)
+            }
+        }
+
         const _: () = {
             fn _assert() {
                 use crate::util::{_assert_send, _assert_sync};
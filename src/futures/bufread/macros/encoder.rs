@@ -1,11 +1,18 @@
 macro_rules! encoder {
-    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($constructor:tt)* })*) => {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
         pin_project_lite::pin_project! {
             $(#[$attr])*
             #[derive(Debug)]
             ///
             /// This structure implements an [`AsyncRead`](futures_io::AsyncRead) interface and will
             /// read uncompressed data from an underlying stream and emit a stream of compressed data.
+            /// It also implements [`AsyncBufRead`](futures_io::AsyncBufRead) so it can be used by
+            /// other adapters expecting a buffered reader.
             pub struct $name<$inner> {
                 #[pin]
                 inner: crate::futures::bufread::Encoder<$inner, crate::codec::$name>,
@@ -13,6 +20,10 @@ macro_rules! encoder {
         }
 
         impl<$inner: futures_io::AsyncBufRead> $name<$inner> {
+            /// Creates a new encoder which will read uncompressed data from the given stream
+            /// and emit a compressed stream.
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
             $(
                 /// Creates a new encoder which will read uncompressed data from the given stream
                 /// and emit a compressed stream.
@@ -20,6 +31,19 @@ macro_rules! encoder {
                 $($constructor)*
             )*
 
+            /// Returns a builder for this encoder, taking the same extra arguments as `new`, which
+            /// collects the compression level and other options before creating it.
+            pub fn builder(
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> crate::futures::bufread::EncoderBuilder<Self, $inner, ($($arg_ty,)*)> {
+                crate::futures::bufread::EncoderBuilder::new(inner, ($($arg,)*), |builder| Self {
+                    inner: builder.build_with(|inner, ($($arg,)*), level| {
+                        Self::with_quality(inner $(, $arg)*, level).inner
+                    }),
+                })
+            }
+
             /// Acquires a reference to the underlying reader that this encoder is wrapping.
             pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
@@ -43,6 +67,97 @@ macro_rules! encoder {
                 self.project().inner.get_pin_mut()
             }
 
+            /// Returns the total number of uncompressed bytes this encoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of compressed bytes this encoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this encoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Configure this encoder to flush the compressed stream when the underlying reader
+            /// has no more input available and some of the input it has already taken has been
+            /// waiting for longer than `timeout`, so slow streams don't sit in the encoder until
+            /// more data arrives.
+            ///
+            /// The timeout is awaited on the futures returned by `sleep`, such as
+            /// `async_std::task::sleep`, so it works with the timer of any runtime.
+            pub fn with_flush_timeout<F>(
+                self,
+                timeout: core::time::Duration,
+                sleep: impl Fn(core::time::Duration) -> F + Send + Sync + 'static,
+            ) -> Self
+            where
+                F: core::future::Future<Output = ()> + Send + 'static,
+            {
+                Self {
+                    inner: self.inner.with_flush_timeout(timeout, sleep),
+                }
+            }
+
+            /// Changes the compression level used for the rest of the stream, the data already
+            /// compressed is unaffected.
+            ///
+            /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and
+            /// `gzip` encoders flush before switching which costs some compression ratio. Other
+            /// encoders return an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+            pub fn set_level(&mut self, level: crate::Level) -> std::io::Result<()> {
+                self.inner.set_level(level)
+            }
+
+            /// Resets this encoder to compress a new stream from the same reader, reusing its
+            /// allocations where the codec allows it.
+            ///
+            /// Any output not yet read is discarded and the statistics start over.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this encoder like [`reset`](Self::reset) to compress a new stream from
+            /// `reader`, returning the previous reader.
+            pub fn reset_with_reader(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_reader(reader)
+            }
+
+            /// Limits how much input this encoder consumes from the underlying reader in a single
+            /// poll to `bytes`, defaults to 1 MiB. Once it is used up the encoder returns the
+            /// output it has produced, or if there is none yields to the executor by waking the
+            /// task and returning `Pending`, so a reader which is always ready such as an
+            /// in-memory buffer doesn't monopolize the task.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `bytes` is zero.
+            pub fn with_yield_budget(self, bytes: usize) -> Self {
+                Self {
+                    inner: self.inner.with_yield_budget(bytes),
+                }
+            }
+
             /// Consumes this encoder returning the underlying reader.
             ///
             /// Note that this may discard internal state of this encoder, so care should be taken
@@ -52,6 +167,26 @@ macro_rules! encoder {
             }
         }
 
+        impl<$inner: futures_io::AsyncRead> $name<crate::futures::bufread::BufReader<$inner>> {
+            /// Creates a new encoder which will read uncompressed data from the given unbuffered
+            /// stream through an internal [`BufReader`](crate::futures::bufread::BufReader) and
+            /// emit a compressed stream, taking the same extra arguments as `new`.
+            pub fn from_read(read: $inner $(, $arg: $arg_ty)*) -> Self {
+                Self::new(crate::futures::bufread::BufReader::new(read) $(, $arg)*)
+            }
+
+            /// Creates a new encoder like [`from_read`](Self::from_read), using an internal buffer
+            /// with the specified capacity.
+            pub fn from_read_with_capacity(
+                capacity: usize,
+                read: $inner
+                $(, $arg: $arg_ty)*
+            ) -> Self {
+                let read = crate::futures::bufread::BufReader::with_capacity(capacity, read);
+                Self::new(read $(, $arg)*)
+            }
+        }
+
         impl<$inner: futures_io::AsyncBufRead> futures_io::AsyncRead for $name<$inner> {
             fn poll_read(
                 self: std::pin::Pin<&mut Self>,
@@ -62,6 +197,19 @@ macro_rules! encoder {
             }
         }
 
+        impl<$inner: futures_io::AsyncBufRead> futures_io::AsyncBufRead for $name<$inner> {
+            fn poll_fill_buf(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<&[u8]>> {
+                self.project().inner.poll_fill_buf(cx)
+            }
+
+            fn consume(self: std::pin::Pin<&mut Self>, amt: usize) {
+                self.project().inner.consume(amt)
+            }
+        }
+
         const _: () = {
             fn _assert() {
                 use crate::util::{_assert_send, _assert_sync};
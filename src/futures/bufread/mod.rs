@@ -1,10 +1,96 @@
 //! Types which operate over [`AsyncBufRead`](futures_io::AsyncBufRead) streams, both encoders and
 //! decoders for various formats.
+//!
+//! Custom formats can be used with the generic [`Encoder`] and [`Decoder`], see
+//! [`codec`](crate::codec).
+//!
+//! Streams which only implement `AsyncRead` can be used through the `from_read` constructors,
+//! which wrap them in a [`BufReader`].
+//!
+//! The compression format of a stream can be detected without consuming it with [`probe`].
+//!
+//! Data compressed with one format can be re-encoded in another with a [`Transcoder`].
 
 #[macro_use]
 mod macros;
+mod buf_reader;
+mod builder;
 mod generic;
+#[cfg(feature = "gzip")]
+mod gzip_members;
+mod probe;
 
-pub(crate) use generic::{Decoder, Encoder};
+pub use self::{
+    buf_reader::BufReader,
+    builder::{DecoderBuilder, EncoderBuilder},
+    generic::{Decoder, Encoder},
+    probe::probe,
+};
+
+#[cfg(feature = "gzip")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+pub use self::gzip_members::{GzipMember, GzipMembers};
 
 algos!(futures::bufread<R>);
+any!(futures::bufread<R>);
+backends!(futures::bufread<R>);
+
+decoder! {
+    /// A decoder which detects the compression format from the start of the stream and then
+    /// decodes it with the matching decoder.
+    ///
+    /// Formats with a magic number are recognised for each enabled algorithm: `gzip`, `zlib`,
+    /// `zstd`, `xz`, `bzip2`, `lz4` and `snappy`. `brotli` has no header, so if nothing else matches
+    /// and the `brotli` feature is enabled the start of the stream is tried as brotli data. Data
+    /// that is not recognised is passed through unchanged.
+    ///
+    /// Additional formats can be recognised by registering them in a
+    /// [`Formats`](crate::auto::Formats) and using [`with_formats`](Self::with_formats).
+    AutoDecoder<R> {
+        pub fn new(read: R) -> Self {
+            Self::with_formats(read, crate::auto::Formats::new())
+        }
+    } {
+        /// The custom `formats` are checked before the built-in ones.
+        pub fn with_formats(read: R, formats: crate::auto::Formats) -> Self {
+            Self {
+                inner: crate::futures::bufread::Decoder::new(
+                    read,
+                    crate::codec::AutoDecoder::with_formats(formats),
+                ),
+            }
+        }
+    }
+}
+
+decoder! {
+    /// A transcoder which decodes data compressed with one [`Algorithm`](crate::Algorithm) and
+    /// re-encodes it with another, such as to migrate stored gzip data to zstd, without an
+    /// intermediate reader in between.
+    ///
+    /// The decoded data is passed straight from the decoder to the encoder through one internal
+    /// buffer, the data read from this transcoder is compressed with the second algorithm. When
+    /// decoding [multiple members](Self::multiple_members) each member/frame of the input is
+    /// re-encoded as a separate member/frame of the output. The statistics count the compressed
+    /// bytes read and the re-encoded bytes produced.
+    Transcoder<R> {
+        pub fn new(read: R, from: crate::Algorithm, to: crate::Algorithm) -> Self {
+            Self::with_quality(read, from, to, crate::Level::Default)
+        }
+    } {
+        /// The data is re-encoded at the given `level`.
+        pub fn with_quality(
+            read: R,
+            from: crate::Algorithm,
+            to: crate::Algorithm,
+            level: crate::Level,
+        ) -> Self {
+            Self {
+                inner: crate::futures::bufread::Decoder::new(
+                    read,
+                    crate::codec::Transcoder::new(from, to, level),
+                ),
+            }
+        }
+    }
+}
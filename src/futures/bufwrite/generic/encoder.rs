@@ -0,0 +1,404 @@
+use core::{
+    cmp::min,
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::Result;
+
+use crate::{
+    codec::Encode,
+    futures::write::{AsyncBufWrite, BufWriter},
+    pool::{Buffer, BufferPool},
+    stats::{Instrumented, Stats},
+    util::PartialBuffer,
+};
+use futures_core::ready;
+use futures_io::AsyncWrite;
+use pin_project_lite::pin_project;
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+#[derive(Debug)]
+enum State {
+    Encoding,
+    Finishing,
+    Done,
+}
+
+pin_project! {
+    /// A generic encoder which compresses data with any [`Encode`] implementation.
+    ///
+    /// This structure implements an [`AsyncWrite`](futures_io::AsyncWrite) interface and will
+    /// compress data written to it and write the compressed data to an underlying stream. It also
+    /// implements [`AsyncBufWrite`] to allow writing uncompressed data directly into its internal
+    /// buffer.
+    ///
+    /// The encoders for each algorithm in this module are built on this type, it can be used
+    /// directly to wrap a custom format.
+    #[derive(Debug)]
+    pub struct Encoder<W, E: Encode> {
+        #[pin]
+        writer: BufWriter<W>,
+        encoder: Instrumented<E>,
+        // Uncompressed data in `input[consumed..buffered]` is waiting to be encoded
+        input: Buffer,
+        consumed: usize,
+        buffered: usize,
+        state: State,
+        propagate_close: bool,
+        codec_flush: bool,
+    }
+}
+
+impl<W: AsyncWrite, E: Encode> Encoder<W, E> {
+    /// Creates a new encoder which will write compressed data to the given stream using
+    /// `encoder`.
+    pub fn new(writer: W, encoder: E) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, writer, encoder)
+    }
+
+    /// Creates a new encoder like [`new`](Self::new), with internal buffers of the specified
+    /// capacity for both the uncompressed input and the compressed output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, writer: W, encoder: E) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        Self::with_buffers(
+            Buffer::new(capacity),
+            Buffer::new(capacity),
+            writer,
+            encoder,
+        )
+    }
+
+    /// Creates a new encoder like [`new`](Self::new), with its internal buffers taken from
+    /// `pool` when they are first needed and given back to it when this encoder is dropped.
+    pub fn with_pool(pool: &BufferPool, writer: W, encoder: E) -> Self {
+        let (input, output) = (Buffer::pooled(pool), Buffer::pooled(pool));
+        Self::with_buffers(input, output, writer, encoder)
+    }
+
+    fn with_buffers(input: Buffer, output: Buffer, writer: W, encoder: E) -> Self {
+        Self {
+            writer: BufWriter::with_buffer(output, writer),
+            encoder: Instrumented::new(encoder),
+            input,
+            consumed: 0,
+            buffered: 0,
+            state: State::Encoding,
+            propagate_close: true,
+            codec_flush: true,
+        }
+    }
+
+    /// Acquires a reference to the underlying writer that this encoder is wrapping.
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    /// Acquires a pinned mutable reference to the underlying writer that this encoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().writer.get_pin_mut()
+    }
+
+    /// Returns the total number of uncompressed bytes this encoder has consumed so far, not
+    /// counting any still waiting in its internal buffer.
+    pub fn total_in(&self) -> u64 {
+        self.encoder.stats().total_in
+    }
+
+    /// Returns the total number of compressed bytes this encoder has produced so far, including any
+    /// still buffered and not yet written to the underlying writer.
+    pub fn total_out(&self) -> u64 {
+        self.encoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.encoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this encoder so far.
+    pub fn stats(&self) -> Stats {
+        self.encoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Encode::checksum`](crate::codec::Encode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.encoder.checksum()
+    }
+
+    /// Configure whether closing this encoder also closes the underlying writer, enabled by
+    /// default. When disabled it only flushes the underlying writer once the compressed stream
+    /// has been finished, so the writer can be shared with later streams.
+    pub fn with_close_propagation(mut self, enabled: bool) -> Self {
+        self.propagate_close = enabled;
+        self
+    }
+
+    /// Configure whether flushing this encoder also flushes the codec, enabled by default.
+    ///
+    /// A codec flush makes everything written so far decodable by the reader, but with deflate
+    /// based formats each one emits an extra block that costs some compression ratio. When
+    /// disabled flushing only writes out the output the codec has already produced.
+    pub fn with_codec_flush(mut self, enabled: bool) -> Self {
+        self.codec_flush = enabled;
+        self
+    }
+
+    /// Attempts to finish the compressed stream and flush it to the underlying writer, without
+    /// closing the underlying writer.
+    ///
+    /// Once this has completed nothing more can be written to this encoder.
+    pub fn poll_finish(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().do_poll_encode(cx))?;
+        ready!(self.as_mut().do_poll_close(cx))?;
+        ready!(self.project().writer.as_mut().poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Finishes the compressed stream and flushes it to the underlying writer like
+    /// [`poll_finish`](Self::poll_finish), leaving the underlying writer open.
+    pub async fn try_finish(&mut self) -> Result<()>
+    where
+        W: Unpin,
+    {
+        poll_fn(|cx| Pin::new(&mut *self).poll_finish(cx)).await
+    }
+
+    /// Finishes the compressed stream like [`try_finish`](Self::try_finish) and returns the
+    /// underlying writer, which is left open.
+    pub async fn finish(mut self) -> Result<W>
+    where
+        W: Unpin,
+    {
+        self.try_finish().await?;
+        Ok(self.into_inner())
+    }
+
+    /// Changes the compression level used for the rest of the stream, the data already compressed
+    /// is unaffected.
+    ///
+    /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and `gzip`
+    /// encoders flush before switching which costs some compression ratio. Other encoders return
+    /// an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+    pub fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.encoder.set_level(level)
+    }
+
+    /// Resets this encoder to compress a new stream to the same writer, reusing its allocations
+    /// where the codec allows it.
+    ///
+    /// Any input not yet encoded and output not yet written to the underlying writer is discarded
+    /// and the statistics start over, so this should be called once the previous stream has been
+    /// closed.
+    pub fn reset(&mut self) -> Result<()> {
+        self.encoder.reset()?;
+        self.state = State::Encoding;
+        self.writer.discard_buffer();
+        self.consumed = 0;
+        self.buffered = 0;
+        Ok(())
+    }
+
+    /// Resets this encoder like [`reset`](Self::reset) to compress a new stream to `writer`,
+    /// returning the previous writer.
+    pub fn reset_with_writer(&mut self, writer: W) -> Result<W> {
+        self.reset()?;
+        Ok(self.writer.replace_inner(writer))
+    }
+
+    /// Consumes this encoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    /// Consumes this encoder returning the underlying writer and codec, discarding anything that
+    /// is buffered.
+    pub(crate) fn into_parts(self) -> (W, E) {
+        (self.writer.into_inner(), self.encoder.into_inner())
+    }
+
+    /// Encodes all the buffered input, if the writer is not ready for all of it then whatever
+    /// input is left is moved to the start of the buffer.
+    fn do_poll_encode(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.consumed == *this.buffered {
+                *this.consumed = 0;
+                *this.buffered = 0;
+                return Poll::Ready(Ok(()));
+            }
+
+            let output = match this.writer.as_mut().poll_partial_flush_buf(cx)? {
+                Poll::Ready(output) => output,
+                Poll::Pending => {
+                    this.input.copy_within(*this.consumed..*this.buffered, 0);
+                    *this.buffered -= *this.consumed;
+                    *this.consumed = 0;
+                    return Poll::Pending;
+                }
+            };
+            let mut output = PartialBuffer::new(output);
+            let mut input = PartialBuffer::new(&this.input[*this.consumed..*this.buffered]);
+
+            this.encoder.encode(&mut input, &mut output)?;
+
+            *this.consumed += input.written().len();
+            let produced = output.written().len();
+            this.writer.as_mut().produce(produced);
+        }
+    }
+
+    fn do_poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            let output = ready!(this.writer.as_mut().poll_partial_flush_buf(cx))?;
+            let mut output = PartialBuffer::new(output);
+
+            let done = match this.state {
+                State::Encoding => this.encoder.flush(&mut output)?,
+
+                State::Finishing | State::Done => panic!("Flush after close"),
+            };
+
+            let produced = output.written().len();
+            this.writer.as_mut().produce(produced);
+
+            if done {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+
+    fn do_poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            let output = ready!(this.writer.as_mut().poll_partial_flush_buf(cx))?;
+            let mut output = PartialBuffer::new(output);
+
+            *this.state = match this.state {
+                State::Encoding | State::Finishing => {
+                    if this.encoder.finish(&mut output)? {
+                        State::Done
+                    } else {
+                        State::Finishing
+                    }
+                }
+
+                State::Done => State::Done,
+            };
+
+            let produced = output.written().len();
+            this.writer.as_mut().produce(produced);
+
+            if let State::Done = this.state {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
+
+impl<W, E: Encode> Encoder<W, E> {
+    /// Acquires a reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec(&self) -> &E {
+        self.encoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut E {
+        self.encoder.get_mut()
+    }
+}
+
+impl<W: AsyncWrite, E: Encode> AsyncBufWrite for Encoder<W, E> {
+    fn poll_partial_flush_buf(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<&mut [u8]>> {
+        if let State::Finishing | State::Done = self.state {
+            panic!("Write after close");
+        }
+
+        if self.buffered == self.input.len() {
+            // If the writer is not ready there may still be space freed by encoding some input
+            if self.as_mut().do_poll_encode(cx)?.is_pending() && self.buffered == self.input.len() {
+                return Poll::Pending;
+            }
+        }
+
+        let this = self.project();
+        Poll::Ready(Ok(&mut this.input[*this.buffered..]))
+    }
+
+    fn produce(self: Pin<&mut Self>, amt: usize) {
+        *self.project().buffered += amt;
+    }
+}
+
+impl<W: AsyncWrite, E: Encode> AsyncWrite for Encoder<W, E> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let space = ready!(self.as_mut().poll_partial_flush_buf(cx))?;
+        let len = min(space.len(), buf.len());
+        space[..len].copy_from_slice(&buf[..len]);
+        self.produce(len);
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().do_poll_encode(cx))?;
+        if *self.as_mut().project().codec_flush {
+            ready!(self.as_mut().do_poll_flush(cx))?;
+        }
+        ready!(self.project().writer.as_mut().poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().do_poll_encode(cx))?;
+        ready!(self.as_mut().do_poll_close(cx))?;
+        let this = self.project();
+        if *this.propagate_close {
+            ready!(this.writer.poll_close(cx))?;
+        } else {
+            ready!(this.writer.poll_flush(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
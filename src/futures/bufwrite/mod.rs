@@ -0,0 +1,26 @@
+//! Types which operate over [`AsyncWrite`](futures_io::AsyncWrite) streams, both encoders and
+//! decoders for various formats, which also implement [`AsyncBufWrite`] so that data can be
+//! written directly into their internal buffer.
+//!
+//! These work the same as the types in [`write`](crate::futures::write), except that they keep a
+//! buffer of input data which is only passed to the codec once it is full or the stream is
+//! flushed or closed. A producer which can generate its data in place, such as by reading it from
+//! elsewhere, can write it straight into that buffer instead of into its own buffer first.
+//!
+//! Custom formats can be used with the generic [`Encoder`] and [`Decoder`], see
+//! [`codec`](crate::codec).
+
+#[macro_use]
+mod macros;
+mod builder;
+mod generic;
+
+pub use self::{
+    builder::{DecoderBuilder, EncoderBuilder},
+    generic::{Decoder, Encoder},
+};
+pub use crate::futures::write::buf_write::AsyncBufWrite;
+
+algos!(futures::bufwrite<W>);
+any!(futures::bufwrite<W>);
+backends!(futures::bufwrite<W>);
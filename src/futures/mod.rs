@@ -1,4 +1,19 @@
 //! Implementations for IO traits exported by `futures`.
+//!
+//! The [`compress_to_vec`] and [`decompress_to_vec`] functions, and their [`compress_copy`] and
+//! [`decompress_copy`] streaming counterparts, cover compressing or decompressing everything in
+//! one call.
 
 pub mod bufread;
+pub mod bufwrite;
+#[cfg(feature = "futures-sink")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-sink")))]
+pub mod sink;
+#[cfg(feature = "futures-stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-stream")))]
+pub mod stream;
 pub mod write;
+
+mod oneshot;
+
+pub use self::oneshot::{compress_copy, compress_to_vec, decompress_copy, decompress_to_vec};
@@ -0,0 +1,99 @@
+use core::{future::poll_fn, pin::Pin, task::Poll};
+use std::io::{ErrorKind, Result};
+
+use crate::{
+    futures::bufread::{AnyDecoder, AnyEncoder, BufReader},
+    Algorithm, Level, TrailingData,
+};
+use futures_core::ready;
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+/// Copies everything from `reader` to `writer`, then flushes `writer`, returning the number of
+/// bytes written.
+async fn copy_buf<R, W>(mut reader: R, writer: &mut W) -> Result<u64>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut written = 0;
+
+    poll_fn(|cx| loop {
+        let buf = ready!(Pin::new(&mut reader).poll_fill_buf(cx))?;
+        if buf.is_empty() {
+            ready!(Pin::new(&mut *writer).poll_flush(cx))?;
+            return Poll::Ready(Ok(written));
+        }
+
+        let len = ready!(Pin::new(&mut *writer).poll_write(cx, buf))?;
+        if len == 0 {
+            return Poll::Ready(Err(ErrorKind::WriteZero.into()));
+        }
+
+        Pin::new(&mut reader).consume(len);
+        written += len as u64;
+    })
+    .await
+}
+
+/// Compresses everything read from `reader` with `algorithm` at `level` into `writer`, returning
+/// the number of compressed bytes written.
+///
+/// The compressed stream is complete and `writer` is flushed once this returns, but `writer` is
+/// not shut down, so more data can follow it.
+pub async fn compress_copy<R, W>(
+    reader: R,
+    writer: &mut W,
+    algorithm: Algorithm,
+    level: Level,
+) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    copy_buf(
+        AnyEncoder::with_quality(BufReader::new(reader), algorithm, level),
+        writer,
+    )
+    .await
+}
+
+/// Decompresses everything read from `reader` with `algorithm` into `writer`, returning the number
+/// of decompressed bytes written.
+///
+/// Fails with [`Error::UnexpectedTrailingData`](crate::error::Error::UnexpectedTrailingData) if
+/// anything follows the compressed stream. `writer` is flushed but not shut down.
+pub async fn decompress_copy<R, W>(reader: R, writer: &mut W, algorithm: Algorithm) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut decoder = AnyDecoder::new(BufReader::new(reader), algorithm);
+    decoder.trailing_data(TrailingData::Error);
+    copy_buf(decoder, writer).await
+}
+
+/// Compresses `input` with `algorithm` at `level`, returning the compressed data.
+pub async fn compress_to_vec(input: &[u8], algorithm: Algorithm, level: Level) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    copy_buf(
+        AnyEncoder::with_quality(input, algorithm, level),
+        &mut output,
+    )
+    .await?;
+    Ok(output)
+}
+
+/// Decompresses `input` with `algorithm`, returning the decompressed data.
+///
+/// Fails with [`Error::LimitExceeded`](crate::error::Error::LimitExceeded) if the decompressed
+/// data would be longer than `limit` bytes, and with
+/// [`Error::UnexpectedTrailingData`](crate::error::Error::UnexpectedTrailingData) if anything
+/// follows the compressed stream.
+pub async fn decompress_to_vec(input: &[u8], algorithm: Algorithm, limit: u64) -> Result<Vec<u8>> {
+    let mut decoder = AnyDecoder::new(input, algorithm).with_output_limit(limit);
+    decoder.trailing_data(TrailingData::Error);
+
+    let mut output = Vec::new();
+    copy_buf(decoder, &mut output).await?;
+    Ok(output)
+}
@@ -0,0 +1,158 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{poll_send, OUTPUT_BUFFER_SIZE};
+use crate::{
+    codec::Decode,
+    error,
+    stats::Instrumented,
+    util::{write_into, PartialBuffer},
+};
+use bytes::{Bytes, BytesMut};
+use futures_core::ready;
+use futures_sink_03::Sink;
+use pin_project_lite::pin_project;
+
+#[derive(Debug)]
+enum State {
+    Decoding,
+    Finishing,
+    Done,
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Decoder<S, D: Decode> {
+        #[pin]
+        sink: S,
+        decoder: Instrumented<D>,
+        state: State,
+        output: BytesMut,
+    }
+}
+
+impl<S: Sink<Bytes>, D: Decode> Decoder<S, D> {
+    pub(crate) fn new(sink: S, decoder: D) -> Self {
+        Self {
+            sink,
+            decoder: Instrumented::new(decoder),
+            state: State::Decoding,
+            output: BytesMut::new(),
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub(crate) fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().sink
+    }
+
+    pub(crate) fn into_inner(self) -> S {
+        self.sink
+    }
+
+    pub(crate) fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    pub(crate) fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+}
+
+impl<S, D> Sink<Bytes> for Decoder<S, D>
+where
+    S: Sink<Bytes>,
+    S::Error: From<io::Error>,
+    D: Decode,
+{
+    type Error = S::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        let this = self.project();
+
+        if this.output.len() >= OUTPUT_BUFFER_SIZE {
+            ready!(poll_send(this.sink, cx, this.output))?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Bytes) -> Result<(), S::Error> {
+        let this = self.project();
+        let decoder = &mut *this.decoder;
+
+        let mut input = PartialBuffer::new(item);
+        while !input.unwritten().is_empty() {
+            *this.state = match this.state {
+                State::Decoding => {
+                    if write_into(this.output, |output| decoder.decode(&mut input, output))? {
+                        State::Finishing
+                    } else {
+                        State::Decoding
+                    }
+                }
+
+                State::Finishing => {
+                    if write_into(this.output, |output| decoder.finish(output))? {
+                        State::Done
+                    } else {
+                        State::Finishing
+                    }
+                }
+
+                State::Done => {
+                    return Err(io::Error::from(error::Error::UnexpectedTrailingData).into())
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        let mut this = self.project();
+        let decoder = &mut *this.decoder;
+
+        *this.state = match this.state {
+            State::Decoding => {
+                while !write_into(this.output, |output| decoder.flush(output))? {}
+                State::Decoding
+            }
+
+            State::Finishing => {
+                while !write_into(this.output, |output| decoder.finish(output))? {}
+                State::Done
+            }
+
+            State::Done => State::Done,
+        };
+
+        ready!(poll_send(this.sink.as_mut(), cx, this.output))?;
+        this.sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        let mut this = self.project();
+        let decoder = &mut *this.decoder;
+
+        if let State::Decoding | State::Finishing = this.state {
+            while !write_into(this.output, |output| decoder.finish(output))? {}
+            *this.state = State::Done;
+        }
+
+        ready!(poll_send(this.sink.as_mut(), cx, this.output))?;
+        this.sink.poll_close(cx)
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{poll_send, OUTPUT_BUFFER_SIZE};
+use crate::{
+    codec::Encode,
+    util::{write_into, PartialBuffer},
+};
+use bytes::{Bytes, BytesMut};
+use futures_core::ready;
+use futures_sink_03::Sink;
+use pin_project_lite::pin_project;
+
+#[derive(Debug)]
+enum State {
+    Encoding,
+    Done,
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Encoder<S, E: Encode> {
+        #[pin]
+        sink: S,
+        encoder: E,
+        state: State,
+        output: BytesMut,
+        // Whether data has been encoded since the encoder was last flushed
+        unflushed: bool,
+    }
+}
+
+impl<S: Sink<Bytes>, E: Encode> Encoder<S, E> {
+    pub(crate) fn new(sink: S, encoder: E) -> Self {
+        Self {
+            sink,
+            encoder,
+            state: State::Encoding,
+            output: BytesMut::new(),
+            unflushed: false,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub(crate) fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().sink
+    }
+
+    pub(crate) fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S, E> Sink<Bytes> for Encoder<S, E>
+where
+    S: Sink<Bytes>,
+    S::Error: From<io::Error>,
+    E: Encode,
+{
+    type Error = S::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        let this = self.project();
+
+        if this.output.len() >= OUTPUT_BUFFER_SIZE {
+            ready!(poll_send(this.sink, cx, this.output))?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Bytes) -> Result<(), S::Error> {
+        let this = self.project();
+
+        if let State::Done = this.state {
+            panic!("Send after close");
+        }
+
+        let mut input = PartialBuffer::new(item);
+        while !input.unwritten().is_empty() {
+            let encoder = &mut *this.encoder;
+            write_into(this.output, |output| encoder.encode(&mut input, output))?;
+            *this.unflushed = true;
+        }
+
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        let mut this = self.project();
+
+        if *this.unflushed {
+            let encoder = &mut *this.encoder;
+            while !write_into(this.output, |output| encoder.flush(output))? {}
+            *this.unflushed = false;
+        }
+
+        ready!(poll_send(this.sink.as_mut(), cx, this.output))?;
+        this.sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        let mut this = self.project();
+
+        if let State::Encoding = this.state {
+            let encoder = &mut *this.encoder;
+            while !write_into(this.output, |output| encoder.finish(output))? {}
+            *this.state = State::Done;
+        }
+
+        ready!(poll_send(this.sink.as_mut(), cx, this.output))?;
+        this.sink.poll_close(cx)
+    }
+}
@@ -0,0 +1,29 @@
+mod decoder;
+mod encoder;
+
+pub(crate) use self::{decoder::Decoder, encoder::Encoder};
+
+use bytes::{Bytes, BytesMut};
+use futures_core::ready;
+use futures_sink_03::Sink;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The amount of output that is buffered before it's sent to the wrapped sink.
+const OUTPUT_BUFFER_SIZE: usize = 8_000;
+
+/// Sends any buffered `output` to `sink` as a single item.
+fn poll_send<S: Sink<Bytes>>(
+    mut sink: Pin<&mut S>,
+    cx: &mut Context<'_>,
+    output: &mut BytesMut,
+) -> Poll<Result<(), S::Error>> {
+    if !output.is_empty() {
+        ready!(sink.as_mut().poll_ready(cx))?;
+        sink.start_send(output.split().freeze())?;
+    }
+
+    Poll::Ready(Ok(()))
+}
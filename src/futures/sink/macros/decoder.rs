@@ -0,0 +1,122 @@
+macro_rules! decoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($constructor:tt)* })*) => {
+        pin_project_lite::pin_project! {
+            $(#[$attr])*
+            #[derive(Debug)]
+            ///
+            /// This structure implements a [`Sink`](futures_sink_03::Sink) interface and will take
+            /// in compressed data and send it uncompressed to an underlying sink.
+            pub struct $name<$inner> {
+                #[pin]
+                inner: crate::futures::sink::Decoder<$inner, crate::codec::$name>,
+            }
+        }
+
+        impl<$inner: futures_sink_03::Sink<bytes::Bytes>> $name<$inner> {
+            $(
+                /// Creates a new decoder which will take in compressed data and send it
+                /// uncompressed to the given sink.
+                ///
+                $($constructor)*
+            )*
+
+            /// Acquires a reference to the underlying sink that this decoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying sink that this decoder is wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the sink which
+            /// may otherwise confuse this decoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Acquires a pinned mutable reference to the underlying sink that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the sink which
+            /// may otherwise confuse this decoder.
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
+                self.project().inner.get_pin_mut()
+            }
+
+            /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once
+            /// more would be produced it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This protects against
+            /// decompression bombs when decoding untrusted data.
+            pub fn with_output_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_output_limit(limit),
+                }
+            }
+
+            /// Limits the number of compressed bytes this decoder will consume to `limit`, once
+            /// more would be needed it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This stops a
+            /// never-ending stream from keeping the decoder busy indefinitely.
+            pub fn with_input_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_input_limit(limit),
+                }
+            }
+
+            /// Consumes this decoder returning the underlying sink.
+            ///
+            /// Note that this may discard internal state of this decoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner> futures_sink_03::Sink<bytes::Bytes> for $name<$inner>
+        where
+            $inner: futures_sink_03::Sink<bytes::Bytes>,
+            $inner::Error: From<std::io::Error>,
+        {
+            type Error = $inner::Error;
+
+            fn poll_ready(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Result<(), Self::Error>> {
+                self.project().inner.poll_ready(cx)
+            }
+
+            fn start_send(
+                self: std::pin::Pin<&mut Self>,
+                item: bytes::Bytes,
+            ) -> Result<(), Self::Error> {
+                self.project().inner.start_send(item)
+            }
+
+            fn poll_flush(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Result<(), Self::Error>> {
+                self.project().inner.poll_flush(cx)
+            }
+
+            fn poll_close(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Result<(), Self::Error>> {
+                self.project().inner.poll_close(cx)
+            }
+        }
+
+        const _: () = {
+            fn _assert() {
+                use std::{pin::Pin, io::Error};
+                use bytes::Bytes;
+                use futures_sink_03::Sink;
+                use crate::util::{_assert_send, _assert_sync};
+
+                _assert_send::<$name<Pin<Box<dyn Sink<Bytes, Error = Error> + Send>>>>();
+                _assert_sync::<$name<Pin<Box<dyn Sink<Bytes, Error = Error> + Sync>>>>();
+            }
+        };
+    }
+}
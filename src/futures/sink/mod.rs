@@ -0,0 +1,22 @@
+//! Types which operate over [`Sink`](futures_sink_03::Sink)`<`[`Bytes`](bytes::Bytes)`>` sinks,
+//! both encoders and decoders for various formats.
+//!
+//! The items sent into the sink are treated as chunks of a single byte-stream to be
+//! compressed/decompressed, the resulting data is sent on to the wrapped sink. There is not
+//! guaranteed to be a one-to-one relationship between the items sent and the items sent on, the
+//! encoders and decoders will buffer the data and choose their own boundaries at which to send a
+//! new item. [`poll_flush`](futures_sink_03::Sink::poll_flush) flushes the codec and sends on the
+//! data buffered so far, while [`poll_close`](futures_sink_03::Sink::poll_close) finishes the
+//! compressed stream before closing the wrapped sink.
+//!
+//! The wrapped sink's error type must be constructible from an [`io::Error`](std::io::Error) so
+//! that errors from the codec can be returned.
+
+#[macro_use]
+mod macros;
+mod generic;
+
+pub(crate) use generic::{Decoder, Encoder};
+
+algos!(futures::sink<S>);
+any!(futures::sink<S>);
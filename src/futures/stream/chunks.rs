@@ -0,0 +1,132 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::Result;
+
+use bytes::{Bytes, BytesMut};
+use futures_core::{ready, stream::Stream};
+use futures_io::AsyncRead;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// A [`Stream`] of the data read from an [`AsyncRead`](futures_io::AsyncRead) split into
+    /// chunks of a fixed size, such as the compressed output of one of the
+    /// [`bufread`](crate::futures::bufread) encoders.
+    ///
+    /// Every chunk is exactly `size` bytes apart from the last, which holds whatever is left and
+    /// may be shorter, so the chunks can be sent wherever parts have a size limit, or a minimum
+    /// size for all but the last part like S3 multipart uploads. Concatenating the chunks gives
+    /// back the data read. At most one chunk is buffered at a time.
+    ///
+    /// ```
+    /// # futures::executor::block_on(async {
+    /// use async_compression::futures::{bufread::GzipEncoder, stream::Chunks};
+    /// use futures::stream::TryStreamExt;
+    ///
+    /// # let data: &[u8] = &[0; 100_000];
+    /// let mut parts = Chunks::new(GzipEncoder::new(data), 5 * 1024 * 1024);
+    /// while let Some(part) = parts.try_next().await? {
+    ///     assert!(part.len() <= 5 * 1024 * 1024);
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// # }).unwrap();
+    /// ```
+    #[derive(Debug)]
+    pub struct Chunks<R> {
+        #[pin]
+        reader: R,
+        size: usize,
+        // The chunk being filled, in `buffer[..filled]`
+        buffer: BytesMut,
+        filled: usize,
+        done: bool,
+    }
+}
+
+impl<R: AsyncRead> Chunks<R> {
+    /// Creates a new stream of the data read from `reader` in chunks of `size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn new(reader: R, size: usize) -> Self {
+        assert!(size > 0, "chunk size must be non-zero");
+
+        Self {
+            reader,
+            size,
+            buffer: BytesMut::new(),
+            filled: 0,
+            done: false,
+        }
+    }
+
+    /// Acquires a reference to the underlying reader that this stream is wrapping.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Acquires a mutable reference to the underlying reader that this stream is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this stream.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Acquires a pinned mutable reference to the underlying reader that this stream is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this stream.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().reader
+    }
+
+    /// Consumes this stream returning the underlying reader.
+    ///
+    /// Note that this discards the partially filled chunk, if any.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncRead> Stream for Chunks<R> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.done {
+                return Poll::Ready(None);
+            }
+
+            if *this.filled == *this.size {
+                *this.filled = 0;
+                return Poll::Ready(Some(Ok(this.buffer.split_to(*this.size).freeze())));
+            }
+
+            if this.buffer.is_empty() {
+                this.buffer.resize(*this.size, 0);
+            }
+
+            let len = ready!(this
+                .reader
+                .as_mut()
+                .poll_read(cx, &mut this.buffer[*this.filled..]))?;
+
+            if len == 0 {
+                *this.done = true;
+                if *this.filled > 0 {
+                    let chunk = this.buffer.split_to(*this.filled).freeze();
+                    *this.filled = 0;
+                    return Poll::Ready(Some(Ok(chunk)));
+                }
+            }
+
+            *this.filled += len;
+        }
+    }
+}
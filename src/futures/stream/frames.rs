@@ -0,0 +1,165 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::Result;
+
+use crate::{
+    codec::AnyDecoder,
+    error::{Limit, LimitExceeded},
+    futures::bufread::Decoder,
+    Algorithm,
+};
+use bytes::{Bytes, BytesMut};
+use futures_core::{ready, stream::Stream};
+use futures_io::AsyncBufRead;
+
+#[derive(Debug)]
+enum State {
+    // Check whether another frame follows
+    Next,
+    // Decode the current frame into the buffer
+    Decoding,
+    Done,
+}
+
+/// A [`Stream`] of the decompressed data read from an [`AsyncBufRead`](futures_io::AsyncBufRead)
+/// with one item for each member/frame of the compressed stream.
+///
+/// The decompressed data is usually split wherever reads happen to end, this instead yields the
+/// whole contents of each gzip member, zstd frame, xz stream, lz4 frame or bzip2 stream as a
+/// single [`Bytes`], so records framed one per member/frame by the producer are handed out
+/// whole. Formats without members/frames such as `deflate`, `zlib` and `brotli` give a single
+/// item for the whole stream. The blocks within a member/frame aren't exposed by the codecs, so
+/// the output can't be split at those.
+///
+/// Each member/frame is buffered in full before it is yielded, so a limit on its size should be
+/// set with [`with_max_frame_size`](Self::with_max_frame_size) when the input is untrusted.
+///
+/// ```
+/// # #[cfg(feature = "gzip")]
+/// # futures::executor::block_on(async {
+/// use async_compression::{futures::stream::Frames, Algorithm};
+/// use futures::stream::TryStreamExt;
+///
+/// # let compressed: &[u8] = &[];
+/// let mut frames = Frames::new(compressed, Algorithm::Gzip).with_max_frame_size(1024 * 1024);
+/// while let Some(record) = frames.try_next().await? {
+///     // ...
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Frames<R> {
+    decoder: Decoder<R, AnyDecoder>,
+    state: State,
+    // Whether a frame has been decoded, so the decoder needs a reset before the next one
+    started: bool,
+    buffer: BytesMut,
+    max_frame_size: Option<usize>,
+}
+
+impl<R: AsyncBufRead + Unpin> Frames<R> {
+    /// Creates a new stream of the members/frames of the data compressed with `algorithm` read
+    /// from `reader`.
+    pub fn new(reader: R, algorithm: Algorithm) -> Self {
+        Self {
+            decoder: Decoder::new(reader, AnyDecoder::new(algorithm)),
+            state: State::Next,
+            started: false,
+            buffer: BytesMut::new(),
+            max_frame_size: None,
+        }
+    }
+
+    /// Limits the decompressed size of each member/frame to `size` bytes, a larger one fails the
+    /// stream with an [`Error::LimitExceeded`](crate::error::Error::LimitExceeded) for the
+    /// [`Output`](Limit::Output) limit.
+    pub fn with_max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = Some(size);
+        self
+    }
+
+    /// Acquires a reference to the underlying reader that this stream is wrapping.
+    pub fn get_ref(&self) -> &R {
+        self.decoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying reader that this stream is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this stream.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.decoder.get_mut()
+    }
+
+    /// Consumes this stream returning the underlying reader.
+    ///
+    /// Note that this discards the partially decoded member/frame, if any.
+    pub fn into_inner(self) -> R {
+        self.decoder.into_inner()
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for Frames<R> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let this = self.get_mut();
+
+        loop {
+            match this.state {
+                State::Next => {
+                    let input = match ready!(Pin::new(this.decoder.get_mut()).poll_fill_buf(cx)) {
+                        Ok(input) => input,
+                        Err(err) => {
+                            this.state = State::Done;
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                    };
+                    if input.is_empty() {
+                        this.state = State::Done;
+                        return Poll::Ready(None);
+                    }
+                    if this.started {
+                        if let Err(err) = this.decoder.reset() {
+                            this.state = State::Done;
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                    }
+                    this.started = true;
+                    this.state = State::Decoding;
+                }
+
+                State::Decoding => {
+                    let output = match ready!(Pin::new(&mut this.decoder).poll_fill_buf(cx)) {
+                        Ok(output) => output,
+                        Err(err) => {
+                            this.state = State::Done;
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                    };
+                    if output.is_empty() {
+                        this.state = State::Next;
+                        return Poll::Ready(Some(Ok(this.buffer.split().freeze())));
+                    }
+
+                    let len = output.len();
+                    if let Some(max) = this.max_frame_size {
+                        if this.buffer.len() + len > max {
+                            this.state = State::Done;
+                            this.buffer.clear();
+                            let err = LimitExceeded::new(Limit::Output, max as u64);
+                            return Poll::Ready(Some(Err(err.into())));
+                        }
+                    }
+                    this.buffer.extend_from_slice(output);
+                    Pin::new(&mut this.decoder).consume(len);
+                }
+
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    io::Result,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{codec::Decode, error, stats::Instrumented, util::PartialBuffer, TrailingData};
+use bytes::{Buf, Bytes, BytesMut};
+use futures_core::{ready, stream::Stream};
+use pin_project_lite::pin_project;
+
+const OUTPUT_BUFFER_SIZE: usize = 8_000;
+
+#[derive(Debug)]
+enum State {
+    Reading,
+    Writing,
+    Flushing,
+    Trailing,
+    Next,
+    Done,
+    Failed,
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Decoder<S, D: Decode> {
+        #[pin]
+        stream: S,
+        decoder: Instrumented<D>,
+        state: State,
+        input: Bytes,
+        output: BytesMut,
+        multiple_members: bool,
+        trailing_data: TrailingData,
+    }
+}
+
+impl<S: Stream<Item = Result<Bytes>>, D: Decode> Decoder<S, D> {
+    pub fn new(stream: S, decoder: D) -> Self {
+        Self {
+            stream,
+            decoder: Instrumented::new(decoder),
+            state: State::Reading,
+            input: Bytes::new(),
+            output: BytesMut::new(),
+            multiple_members: false,
+            trailing_data: TrailingData::Stop,
+        }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().stream
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    pub fn in_header(&self) -> bool {
+        self.decoder.in_header()
+    }
+
+    pub fn member_count(&self) -> u64 {
+        self.decoder.stats().frames
+    }
+
+    pub fn bytes_consumed(&self) -> u64 {
+        self.decoder.stats().total_in - self.decoder.unconsumed().len() as u64
+    }
+
+    pub fn into_inner_with_leftover(self) -> (S, Bytes) {
+        let unconsumed = self.decoder.unconsumed();
+        let leftover = if unconsumed.is_empty() {
+            self.input
+        } else {
+            let mut leftover = BytesMut::from(unconsumed);
+            leftover.extend_from_slice(&self.input);
+            leftover.freeze()
+        };
+        (self.stream, leftover)
+    }
+
+    pub fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    pub fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+
+    pub fn multiple_members(&mut self, enabled: bool) {
+        self.multiple_members = enabled;
+    }
+
+    pub fn trailing_data(&mut self, policy: TrailingData) {
+        self.trailing_data = policy;
+    }
+}
+
+impl<S: Stream<Item = Result<Bytes>>, D: Decode> Stream for Decoder<S, D> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let this = self.project();
+
+        let (mut stream, input, state, decoder, multiple_members, trailing_data) = (
+            this.stream,
+            this.input,
+            this.state,
+            this.decoder,
+            *this.multiple_members,
+            *this.trailing_data,
+        );
+
+        let mut output = PartialBuffer::new(this.output);
+
+        let result = (|| loop {
+            let output_capacity = output.written().len() + OUTPUT_BUFFER_SIZE;
+            output.get_mut().resize(output_capacity, 0);
+
+            *state = match state {
+                State::Reading => {
+                    if let Some(chunk) = ready!(stream.as_mut().poll_next(cx)) {
+                        *input = chunk?;
+                        State::Writing
+                    } else {
+                        State::Flushing
+                    }
+                }
+
+                State::Writing => {
+                    if input.is_empty() {
+                        State::Reading
+                    } else {
+                        let mut input = PartialBuffer::new(&mut *input);
+
+                        let done = decoder.decode(&mut input, &mut output)?;
+
+                        let input_len = input.written().len();
+                        input.into_inner().advance(input_len);
+
+                        if done {
+                            State::Flushing
+                        } else {
+                            State::Writing
+                        }
+                    }
+                }
+
+                State::Flushing => {
+                    if decoder.finish(&mut output)? {
+                        if multiple_members {
+                            State::Next
+                        } else {
+                            State::Trailing
+                        }
+                    } else {
+                        State::Flushing
+                    }
+                }
+
+                State::Trailing => match trailing_data {
+                    TrailingData::Stop => State::Done,
+                    _ if !input.is_empty() => {
+                        if let TrailingData::Error = trailing_data {
+                            let err = error::Error::UnexpectedTrailingData.into();
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                        input.clear();
+                        State::Trailing
+                    }
+                    _ => {
+                        if let Some(chunk) = ready!(stream.as_mut().poll_next(cx)) {
+                            *input = chunk?;
+                            State::Trailing
+                        } else {
+                            State::Done
+                        }
+                    }
+                },
+
+                State::Next => {
+                    if input.is_empty() {
+                        if let Some(chunk) = ready!(stream.as_mut().poll_next(cx)) {
+                            *input = chunk?;
+                            State::Next
+                        } else {
+                            State::Done
+                        }
+                    } else {
+                        decoder.reinit()?;
+                        State::Writing
+                    }
+                }
+
+                State::Done | State::Failed => {
+                    return Poll::Ready(None);
+                }
+            };
+        })();
+
+        match result {
+            Poll::Ready(Some(Ok(_))) => unreachable!(),
+            Poll::Ready(Some(Err(_))) => {
+                *state = State::Failed;
+                result
+            }
+            Poll::Ready(None) | Poll::Pending => {
+                if output.written().is_empty() {
+                    result
+                } else {
+                    let output_len = output.written().len();
+                    Poll::Ready(Some(Ok(output.into_inner().split_to(output_len).freeze())))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+macro_rules! decoder {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($constructor:tt)* })*) => {
+        pin_project_lite::pin_project! {
+            $(#[$attr])*
+            #[derive(Debug)]
+            ///
+            /// This structure implements a [`Stream`](futures_core::stream::Stream) interface and
+            /// will read compressed data from an underlying stream and emit a stream of
+            /// uncompressed data.
+            pub struct $name<$inner> {
+                #[pin]
+                inner: crate::futures::stream::Decoder<$inner, crate::codec::$name>,
+            }
+        }
+
+        impl<$inner: futures_core::stream::Stream<Item = std::io::Result<bytes::Bytes>>>
+            $name<$inner>
+        {
+            $(
+                /// Creates a new decoder which will read compressed data from the given stream and
+                /// emit an uncompressed stream.
+                ///
+                $($constructor)*
+            )*
+
+            /// Configure multi-member/frame decoding, if enabled this will reset the decoder state
+            /// when reaching the end of a compressed member/frame and expect either the end of the
+            /// wrapped stream or another compressed member/frame to follow.
+            pub fn multiple_members(&mut self, enabled: bool) {
+                self.inner.multiple_members(enabled);
+            }
+
+            /// Configure what happens to data following the end of the compressed stream when
+            /// multi-member/frame decoding is disabled, see
+            /// [`TrailingData`](crate::TrailingData) for the options.
+            ///
+            /// Defaults to [`TrailingData::Stop`](crate::TrailingData::Stop), which stops polling
+            /// the wrapped stream once the compressed stream has ended, the rest of the chunk it
+            /// ended in can be retrieved with
+            /// [`into_inner_with_leftover`](Self::into_inner_with_leftover).
+            pub fn trailing_data(&mut self, policy: crate::TrailingData) {
+                self.inner.trailing_data(policy);
+            }
+
+            /// Acquires a reference to the underlying stream that this decoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying stream that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the stream which
+            /// may otherwise confuse this decoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Acquires a pinned mutable reference to the underlying stream that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the stream which
+            /// may otherwise confuse this decoder.
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
+                self.project().inner.get_pin_mut()
+            }
+
+            /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once
+            /// more would be produced it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This protects against
+            /// decompression bombs when decoding untrusted data.
+            pub fn with_output_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_output_limit(limit),
+                }
+            }
+
+            /// Limits the number of compressed bytes this decoder will consume to `limit`, once
+            /// more would be needed it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This stops a
+            /// never-ending stream from keeping the decoder busy indefinitely.
+            pub fn with_input_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_input_limit(limit),
+                }
+            }
+
+            /// Returns whether this decoder has reached the end of the compressed stream and
+            /// emitted all of its output. If the stream ends before this the wrapped stream ended
+            /// early.
+            pub fn is_done(&self) -> bool {
+                self.inner.is_done()
+            }
+
+            /// Returns whether this decoder is partway through the header of a member/frame,
+            /// having consumed some of it but not all. This is only tracked for the gzip and zlib
+            /// formats and is always `false` for others.
+            pub fn in_header(&self) -> bool {
+                self.inner.in_header()
+            }
+
+            /// Returns the number of members/frames this decoder has completely decoded so far.
+            pub fn member_count(&self) -> u64 {
+                self.inner.member_count()
+            }
+
+            /// Returns the number of compressed bytes this decoder has consumed from the
+            /// underlying stream and decoded so far, once the end of the stream is reached this is
+            /// its exact length. This excludes the rest of the chunk being decoded, see
+            /// [`into_inner_with_leftover`](Self::into_inner_with_leftover).
+            pub fn bytes_consumed(&self) -> u64 {
+                self.inner.bytes_consumed()
+            }
+
+            /// Consumes this decoder returning the underlying stream.
+            ///
+            /// Note that this may discard internal state of this decoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+
+            /// Consumes this decoder returning the underlying stream, along with any compressed
+            /// data the decoder has taken from it but not decoded, such as the remainder of the
+            /// chunk in which the compressed stream ended.
+            pub fn into_inner_with_leftover(self) -> ($inner, bytes::Bytes) {
+                self.inner.into_inner_with_leftover()
+            }
+        }
+
+        impl<$inner: futures_core::stream::Stream<Item = std::io::Result<bytes::Bytes>>>
+            futures_core::stream::Stream for $name<$inner>
+        {
+            type Item = std::io::Result<bytes::Bytes>;
+
+            fn poll_next(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<std::io::Result<bytes::Bytes>>> {
+                self.project().inner.poll_next(cx)
+            }
+        }
+
+        const _: () = {
+            fn _assert() {
+                use std::{pin::Pin, io::Result};
+                use bytes::Bytes;
+                use futures_core::stream::Stream;
+                use crate::util::{_assert_send, _assert_sync};
+
+                _assert_send::<$name<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>>>();
+                _assert_sync::<$name<Pin<Box<dyn Stream<Item = Result<Bytes>> + Sync>>>>();
+            }
+        };
+    }
+}
@@ -0,0 +1,28 @@
+//! Types which operate over [`Stream`](futures_core::stream::Stream)`<Item =
+//! `[`io::Result`](std::io::Result)`<`[`Bytes`](bytes::Bytes)`>>` streams, both encoders and
+//! decoders for various formats.
+//!
+//! The `Stream` is treated as a single byte-stream to be compressed/decompressed, each item is a
+//! chunk of data from this byte-stream. There is not guaranteed to be a one-to-one relationship
+//! between chunks of data from the underlying stream and the resulting compressed/decompressed
+//! stream, the encoders and decoders will buffer the incoming data and choose their own boundaries
+//! at which to yield a new item. When the underlying stream is not ready the encoders flush and
+//! yield the data compressed so far, so that a slow stream doesn't hold back data that has
+//! already been received.
+//!
+//! [`Chunks`] goes the other way, splitting the data read from an `AsyncRead` such as a
+//! [`bufread`](crate::futures::bufread) encoder into a stream of fixed-size chunks, and
+//! [`Frames`] decodes the data read from an `AsyncBufRead` into a stream with one item for each
+//! member/frame of the compressed stream.
+
+#[macro_use]
+mod macros;
+mod chunks;
+mod frames;
+mod generic;
+
+pub use self::{chunks::Chunks, frames::Frames};
+pub(crate) use generic::{Decoder, Encoder};
+
+algos!(futures::stream<S>);
+any!(futures::stream<S>);
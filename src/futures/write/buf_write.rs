@@ -4,8 +4,14 @@ use std::{
     task::{Context, Poll},
 };
 
-pub(crate) trait AsyncBufWrite {
-    /// Attempt to return an internal buffer to write to, flushing data out to the inner reader if
+/// A writer which has an internal buffer that can be written to directly, avoiding copying data
+/// from a separate buffer into it.
+///
+/// Data is written by getting the unfilled part of the buffer from
+/// [`poll_partial_flush_buf`](Self::poll_partial_flush_buf), writing into the start of it, then
+/// telling the writer how much was written with [`produce`](Self::produce).
+pub trait AsyncBufWrite {
+    /// Attempt to return an internal buffer to write to, flushing data out to the inner writer if
     /// it is full.
     ///
     /// On success, returns `Poll::Ready(Ok(buf))`.
@@ -21,12 +27,14 @@ pub(crate) trait AsyncBufWrite {
     /// Tells this buffer that `amt` bytes have been written to its buffer, so they should be
     /// written out to the underlying IO when possible.
     ///
-    /// This function is a lower-level call. It needs to be paired with the `poll_flush_buf` method to
-    /// function properly. This function does not perform any I/O, it simply informs this object
-    /// that some amount of its buffer, returned from `poll_flush_buf`, has been written to and should
-    /// be sent. As such, this function may do odd things if `poll_flush_buf` isn't
-    /// called before calling it.
+    /// This function is a lower-level call. It needs to be paired with the
+    /// [`poll_partial_flush_buf`](Self::poll_partial_flush_buf) method to function properly. This
+    /// function does not perform any I/O, it simply informs this object that some amount of its
+    /// buffer, returned from `poll_partial_flush_buf`, has been written to and should be sent. As
+    /// such, this function may do odd things if `poll_partial_flush_buf` isn't called before
+    /// calling it.
     ///
-    /// The `amt` must be `<=` the number of bytes in the buffer returned by `poll_flush_buf`.
+    /// The `amt` must be `<=` the number of bytes in the buffer returned by
+    /// `poll_partial_flush_buf`.
     fn produce(self: Pin<&mut Self>, amt: usize);
 }
@@ -3,6 +3,7 @@
 // with those methods.
 
 use super::AsyncBufWrite;
+use crate::pool::Buffer;
 use futures_core::ready;
 use futures_io::{AsyncSeek, AsyncWrite, SeekFrom};
 use pin_project_lite::pin_project;
@@ -19,7 +20,7 @@ pin_project! {
     pub struct BufWriter<W> {
         #[pin]
         inner: W,
-        buf: Box<[u8]>,
+        buf: Buffer,
         written: usize,
         buffered: usize,
     }
@@ -34,9 +35,14 @@ impl<W: AsyncWrite> BufWriter<W> {
 
     /// Creates a new `BufWriter` with the specified buffer capacity.
     pub fn with_capacity(cap: usize, inner: W) -> Self {
+        Self::with_buffer(Buffer::new(cap), inner)
+    }
+
+    /// Creates a new `BufWriter` using the given buffer, which is only allocated once it is needed.
+    pub(crate) fn with_buffer(buf: Buffer, inner: W) -> Self {
         Self {
             inner,
-            buf: vec![0; cap].into(),
+            buf,
             written: 0,
             buffered: 0,
         }
@@ -133,6 +139,17 @@ impl<W: AsyncWrite> BufWriter<W> {
         self.project().inner
     }
 
+    /// Discards any buffered data.
+    pub(crate) fn discard_buffer(&mut self) {
+        self.written = 0;
+        self.buffered = 0;
+    }
+
+    /// Replaces the underlying writer, returning the previous one.
+    pub(crate) fn replace_inner(&mut self, inner: W) -> W {
+        std::mem::replace(&mut self.inner, inner)
+    }
+
     /// Consumes this `BufWriter`, returning the underlying writer.
     ///
     /// Note that any leftover data in the internal buffer is lost.
@@ -0,0 +1,154 @@
+use core::{
+    fmt,
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::{IoSlice, Result};
+
+use futures_io::AsyncWrite;
+
+/// The task closing a writer which was dropped before being closed.
+pub type CloseTask = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+/// Consumes a writer which was dropped before being closed.
+type Cleanup<W> = Box<dyn FnOnce(W) + Send>;
+
+/// A guard around a writer, usually an encoder, which makes sure the compressed stream isn't
+/// silently left unfinished when the writer is dropped before it has been closed.
+///
+/// Encoders only write the end of the compressed stream, such as the gzip trailer, when they are
+/// closed, so forgetting to call [`close`](futures_io::AsyncWrite::poll_close) leaves a truncated
+/// stream behind. When this guard is dropped without having been closed:
+///
+/// * if a spawner was given with [`with_spawner`](Self::with_spawner), a task closing the writer
+///   is handed to it;
+/// * otherwise it panics in debug builds, or does nothing in release builds.
+///
+/// [`into_inner`](Self::into_inner) takes the writer back out without any of this, such as to
+/// give up on the stream after an error.
+///
+/// ```
+/// # futures::executor::block_on(async {
+/// use async_compression::futures::write::{FinishOnDrop, GzipEncoder};
+/// use futures::io::AsyncWriteExt;
+/// use std::sync::mpsc;
+///
+/// let (tasks, spawned) = mpsc::channel();
+/// let mut encoder = FinishOnDrop::new(GzipEncoder::new(Vec::new()))
+///     .with_spawner(move |task| tasks.send(task).unwrap());
+/// encoder.write_all(b"example").await?;
+/// drop(encoder);
+///
+/// // The stream is finished by the task handed to the spawner, here run on the current thread
+/// spawned.recv().unwrap().await?;
+/// # Ok::<_, std::io::Error>(())
+/// # }).unwrap();
+/// ```
+pub struct FinishOnDrop<W> {
+    writer: Option<W>,
+    closed: bool,
+    cleanup: Option<Cleanup<W>>,
+}
+
+impl<W: AsyncWrite + Unpin> FinishOnDrop<W> {
+    /// Creates a new guard around `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Some(writer),
+            closed: false,
+            cleanup: None,
+        }
+    }
+
+    /// Hands a task closing the writer to `spawn` when this guard is dropped without having been
+    /// closed, instead of panicking in debug builds.
+    ///
+    /// The task resolves to the result of closing the writer, which it is up to `spawn` to report.
+    pub fn with_spawner(mut self, spawn: impl FnOnce(CloseTask) + Send + 'static) -> Self
+    where
+        W: Send + 'static,
+    {
+        self.cleanup = Some(Box::new(move |mut writer: W| {
+            spawn(Box::pin(async move {
+                poll_fn(|cx| Pin::new(&mut writer).poll_close(cx)).await
+            }))
+        }));
+        self
+    }
+
+    /// Returns whether the writer has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Acquires a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.writer.as_ref().unwrap()
+    }
+
+    /// Acquires a mutable reference to the underlying writer.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this guard.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.as_mut().unwrap()
+    }
+
+    /// Consumes this guard returning the underlying writer, which is then not closed when
+    /// dropped.
+    pub fn into_inner(mut self) -> W {
+        self.writer.take().unwrap()
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for FinishOnDrop<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Pin::new(self.get_mut().get_mut()).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        Pin::new(self.get_mut().get_mut()).poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(self.get_mut().get_mut()).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        let result = Pin::new(this.get_mut()).poll_close(cx);
+        if let Poll::Ready(Ok(())) = result {
+            this.closed = true;
+        }
+        result
+    }
+}
+
+impl<W> Drop for FinishOnDrop<W> {
+    fn drop(&mut self) {
+        let writer = match self.writer.take() {
+            Some(writer) if !self.closed => writer,
+            _ => return,
+        };
+
+        if let Some(cleanup) = self.cleanup.take() {
+            cleanup(writer);
+        } else if cfg!(debug_assertions) && !std::thread::panicking() {
+            panic!("FinishOnDrop dropped without being closed, the compressed stream is truncated");
+        }
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for FinishOnDrop<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FinishOnDrop")
+            .field("writer", &self.writer)
+            .field("closed", &self.closed)
+            .finish()
+    }
+}
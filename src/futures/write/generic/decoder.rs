@@ -2,17 +2,23 @@ use core::{
     pin::Pin,
     task::{Context, Poll},
 };
-use std::io::{Error, ErrorKind, Result};
+use std::io::{Error, ErrorKind, IoSlice, Result};
 
 use crate::{
     codec::Decode,
+    error,
     futures::write::{AsyncBufWrite, BufWriter},
+    pool::{Buffer, BufferPool},
+    stats::{Instrumented, Stats},
     util::PartialBuffer,
+    TrailingData,
 };
 use futures_core::ready;
 use futures_io::AsyncWrite;
 use pin_project_lite::pin_project;
 
+const DEFAULT_BUF_SIZE: usize = 8192;
+
 #[derive(Debug)]
 enum State {
     Decoding,
@@ -21,40 +27,197 @@ enum State {
 }
 
 pin_project! {
+    /// A generic decoder which decompresses data with any [`Decode`] implementation.
+    ///
+    /// This structure implements an [`AsyncWrite`](futures_io::AsyncWrite) interface and will
+    /// decompress data written to it and write the uncompressed data to an underlying stream.
+    ///
+    /// The decoders for each algorithm in this module are built on this type, it can be used
+    /// directly to wrap a custom format.
     #[derive(Debug)]
     pub struct Decoder<W, D: Decode> {
         #[pin]
         writer: BufWriter<W>,
-        decoder: D,
+        decoder: Instrumented<D>,
         state: State,
+        propagate_close: bool,
+        trailing_data: TrailingData,
     }
 }
 
 impl<W: AsyncWrite, D: Decode> Decoder<W, D> {
+    /// Creates a new decoder which will write uncompressed data to the given stream using
+    /// `decoder`.
     pub fn new(writer: W, decoder: D) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, writer, decoder)
+    }
+
+    /// Creates a new decoder like [`new`](Self::new), with an internal buffer of the specified
+    /// capacity for the uncompressed output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, writer: W, decoder: D) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        Self::with_buffer(Buffer::new(capacity), writer, decoder)
+    }
+
+    /// Creates a new decoder like [`new`](Self::new), with its internal buffer taken from `pool`
+    /// when it is first needed and given back to it when this decoder is dropped.
+    pub fn with_pool(pool: &BufferPool, writer: W, decoder: D) -> Self {
+        Self::with_buffer(Buffer::pooled(pool), writer, decoder)
+    }
+
+    fn with_buffer(buffer: Buffer, writer: W, decoder: D) -> Self {
         Self {
-            writer: BufWriter::new(writer),
-            decoder,
+            writer: BufWriter::with_buffer(buffer, writer),
+            decoder: Instrumented::new(decoder),
             state: State::Decoding,
+            propagate_close: true,
+            trailing_data: TrailingData::Error,
         }
     }
 
+    /// Acquires a reference to the underlying writer that this decoder is wrapping.
     pub fn get_ref(&self) -> &W {
         self.writer.get_ref()
     }
 
+    /// Acquires a mutable reference to the underlying writer that this decoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this decoder.
     pub fn get_mut(&mut self) -> &mut W {
         self.writer.get_mut()
     }
 
+    /// Acquires a pinned mutable reference to the underlying writer that this decoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this decoder.
     pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
         self.project().writer.get_pin_mut()
     }
 
+    /// Returns the total number of compressed bytes this decoder has consumed so far.
+    pub fn total_in(&self) -> u64 {
+        self.decoder.stats().total_in
+    }
+
+    /// Returns the total number of uncompressed bytes this decoder has produced so far, including
+    /// any still buffered and not yet written to the underlying writer.
+    pub fn total_out(&self) -> u64 {
+        self.decoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.decoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this decoder so far.
+    pub fn stats(&self) -> Stats {
+        self.decoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Decode::checksum`](crate::codec::Decode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.decoder.checksum()
+    }
+
+    /// Returns whether the end of the compressed stream has been written to this decoder and all
+    /// of its output has been decoded.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Returns whether this decoder is partway through the header of a member/frame, having
+    /// consumed some of it but not all. This is only tracked for the gzip and zlib formats and is
+    /// always `false` for others.
+    pub fn in_header(&self) -> bool {
+        self.decoder.in_header()
+    }
+
+    /// Returns the number of members/frames this decoder has completely decoded so far.
+    pub fn member_count(&self) -> u64 {
+        self.decoder.stats().frames
+    }
+
+    /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once more
+    /// would be produced it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error
+    /// instead. This protects against decompression bombs when decoding untrusted data.
+    pub fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    /// Limits the number of compressed bytes this decoder will consume to `limit`, once more would
+    /// be needed it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error instead.
+    /// This stops a never-ending stream from keeping the decoder busy indefinitely.
+    pub fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+
+    /// Configure what happens to data written after the end of the compressed stream, see
+    /// [`TrailingData`] for the options.
+    ///
+    /// Defaults to [`TrailingData::Error`]. With [`TrailingData::Stop`] the write reaching the end
+    /// of the stream is cut short there and any later writes return `Ok(0)`, leaving the trailing
+    /// data with the caller.
+    pub fn trailing_data(&mut self, policy: TrailingData) {
+        self.trailing_data = policy;
+    }
+
+    /// Configure whether closing this decoder also closes the underlying writer, enabled by
+    /// default. When disabled it only flushes the underlying writer once the compressed stream
+    /// has been fully decoded, so the writer can be shared with later streams.
+    pub fn with_close_propagation(mut self, enabled: bool) -> Self {
+        self.propagate_close = enabled;
+        self
+    }
+
+    /// Resets this decoder to decode a new stream to the same writer, reusing its allocations.
+    ///
+    /// Any output not yet written to the underlying writer is discarded and the statistics start
+    /// over, so this should be called once the previous stream has been closed. Configuration
+    /// such as the limits and trailing data policy is kept.
+    pub fn reset(&mut self) -> Result<()> {
+        self.decoder.reset()?;
+        self.state = State::Decoding;
+        self.writer.discard_buffer();
+        Ok(())
+    }
+
+    /// Resets this decoder like [`reset`](Self::reset) to decode a new stream to `writer`,
+    /// returning the previous writer.
+    pub fn reset_with_writer(&mut self, writer: W) -> Result<W> {
+        self.reset()?;
+        Ok(self.writer.replace_inner(writer))
+    }
+
+    /// Consumes this decoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this decoder, so care should be taken to
+    /// avoid losing resources when this is called.
     pub fn into_inner(self) -> W {
         self.writer.into_inner()
     }
 
+    /// Consumes this decoder returning the underlying writer and codec, discarding anything that
+    /// is buffered.
+    pub(crate) fn into_parts(self) -> (W, D) {
+        (self.writer.into_inner(), self.decoder.into_inner())
+    }
+
     fn do_poll_write(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -83,7 +246,16 @@ impl<W: AsyncWrite, D: Decode> Decoder<W, D> {
                     }
                 }
 
-                State::Done => panic!("Write after end of stream"),
+                State::Done => {
+                    match this.trailing_data {
+                        TrailingData::Error => {
+                            return Poll::Ready(Err(error::Error::UnexpectedTrailingData.into()));
+                        }
+                        TrailingData::Ignore => input.advance(input.unwritten().len()),
+                        TrailingData::Stop => {}
+                    }
+                    return Poll::Ready(Ok(()));
+                }
             };
 
             let produced = output.written().len();
@@ -135,6 +307,18 @@ impl<W: AsyncWrite, D: Decode> Decoder<W, D> {
     }
 }
 
+impl<W, D: Decode> Decoder<W, D> {
+    /// Acquires a reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec(&self) -> &D {
+        self.decoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut D {
+        self.decoder.get_mut()
+    }
+}
+
 impl<W: AsyncWrite, D: Decode> AsyncWrite for Decoder<W, D> {
     fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
         if buf.is_empty() {
@@ -149,6 +333,35 @@ impl<W: AsyncWrite, D: Decode> AsyncWrite for Decoder<W, D> {
         }
     }
 
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        let mut written = 0;
+
+        // Each slice is fed through in turn until the output can't take any more of them or the
+        // end of the stream is reached, anything after that is left for the next write
+        for buf in bufs.iter().filter(|buf| !buf.is_empty()) {
+            if written > 0 && matches!(self.state, State::Done) {
+                break;
+            }
+
+            let mut input = PartialBuffer::new(&**buf);
+            let result = self.as_mut().do_poll_write(cx, &mut input)?;
+            written += input.written().len();
+
+            match result {
+                Poll::Pending if written == 0 => return Poll::Pending,
+                Poll::Pending => break,
+                Poll::Ready(()) if !input.unwritten().is_empty() => break,
+                Poll::Ready(()) => {}
+            }
+        }
+
+        Poll::Ready(Ok(written))
+    }
+
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
         ready!(self.as_mut().do_poll_flush(cx))?;
         ready!(self.project().writer.as_mut().poll_flush(cx))?;
@@ -163,7 +376,12 @@ impl<W: AsyncWrite, D: Decode> AsyncWrite for Decoder<W, D> {
         ready!(self.as_mut().do_poll_flush(cx))?;
 
         if let State::Done = self.as_mut().project().state {
-            ready!(self.as_mut().project().writer.as_mut().poll_close(cx))?;
+            let this = self.as_mut().project();
+            if *this.propagate_close {
+                ready!(this.writer.poll_close(cx))?;
+            } else {
+                ready!(this.writer.poll_flush(cx))?;
+            }
             Poll::Ready(Ok(()))
         } else {
             Poll::Ready(Err(Error::new(
@@ -1,28 +1,74 @@
 macro_rules! decoder {
-    ($(#[$attr:meta])* $name:ident) => {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
         pin_project_lite::pin_project! {
             $(#[$attr])*
             #[derive(Debug)]
             ///
             /// This structure implements an [`AsyncWrite`](futures_io::AsyncWrite) interface and will
             /// take in compressed data and write it uncompressed to an underlying stream.
-            pub struct $name<W> {
+            pub struct $name<$inner> {
                 #[pin]
-                inner: crate::futures::write::Decoder<W, crate::codec::$name>,
+                inner: crate::futures::write::Decoder<$inner, crate::codec::$name>,
             }
         }
 
-        impl<W: futures_io::AsyncWrite> $name<W> {
-            /// Creates a new decoder which will take in compressed data and write it uncompressedd
-            /// to the given stream.
-            pub fn new(read: W) -> $name<W> {
-                $name {
-                    inner: crate::futures::write::Decoder::new(read, crate::codec::$name::new()),
+        impl<$inner: futures_io::AsyncWrite> $name<$inner> {
+            /// Creates a new decoder which will take in compressed data and write it
+            /// uncompressed to the given stream.
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
+            $(
+                /// Creates a new decoder which will take in compressed data and write it
+                /// uncompressed to the given stream.
+                ///
+                $($constructor)*
+            )*
+
+            /// Returns a builder for this decoder, taking the same extra arguments as `new`, which
+            /// collects the limits and other options before creating it.
+            pub fn builder(
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> crate::futures::write::DecoderBuilder<Self, $inner, ($($arg_ty,)*)> {
+                crate::futures::write::DecoderBuilder::new(inner, ($($arg,)*), |builder| Self {
+                    inner: builder.build_with(|inner, ($($arg,)*)| {
+                        Self::new(inner $(, $arg)*).inner
+                    }),
+                })
+            }
+
+            /// Creates a new decoder like `new`, taking the same extra arguments, with an internal
+            /// buffer of the specified capacity for the uncompressed output.
+            ///
+            /// Panics if `capacity` is zero.
+            pub fn with_capacity(capacity: usize, inner: $inner $(, $arg: $arg_ty)*) -> Self {
+                let (inner, codec) = Self::new(inner $(, $arg)*).inner.into_parts();
+                Self {
+                    inner: crate::futures::write::Decoder::with_capacity(capacity, inner, codec),
+                }
+            }
+
+            /// Creates a new decoder like `new`, taking the same extra arguments, with its internal
+            /// buffer taken from `pool` when it is first needed and given back to it
+            /// when this decoder is dropped.
+            pub fn with_pool(
+                pool: &crate::pool::BufferPool,
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> Self {
+                let (inner, codec) = Self::new(inner $(, $arg)*).inner.into_parts();
+                Self {
+                    inner: crate::futures::write::Decoder::with_pool(pool, inner, codec),
                 }
             }
 
             /// Acquires a reference to the underlying reader that this decoder is wrapping.
-            pub fn get_ref(&self) -> &W {
+            pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
             }
 
@@ -31,7 +77,7 @@ macro_rules! decoder {
             ///
             /// Note that care must be taken to avoid tampering with the state of the reader which
             /// may otherwise confuse this decoder.
-            pub fn get_mut(&mut self) -> &mut W {
+            pub fn get_mut(&mut self) -> &mut $inner {
                 self.inner.get_mut()
             }
 
@@ -40,20 +86,127 @@ macro_rules! decoder {
             ///
             /// Note that care must be taken to avoid tampering with the state of the reader which
             /// may otherwise confuse this decoder.
-            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut W> {
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
                 self.project().inner.get_pin_mut()
             }
 
+            /// Returns the total number of compressed bytes this decoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of uncompressed bytes this decoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this decoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Returns whether the end of the compressed stream has been written to this decoder
+            /// and all of its output has been decoded.
+            pub fn is_done(&self) -> bool {
+                self.inner.is_done()
+            }
+
+            /// Returns whether this decoder is partway through the header of a member/frame,
+            /// having consumed some of it but not all. This is only tracked for the gzip and zlib
+            /// formats and is always `false` for others.
+            pub fn in_header(&self) -> bool {
+                self.inner.in_header()
+            }
+
+            /// Returns the number of members/frames this decoder has completely decoded so far.
+            pub fn member_count(&self) -> u64 {
+                self.inner.member_count()
+            }
+
+            /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once
+            /// more would be produced it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This protects against
+            /// decompression bombs when decoding untrusted data.
+            pub fn with_output_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_output_limit(limit),
+                }
+            }
+
+            /// Limits the number of compressed bytes this decoder will consume to `limit`, once
+            /// more would be needed it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This stops a
+            /// never-ending stream from keeping the decoder busy indefinitely.
+            pub fn with_input_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_input_limit(limit),
+                }
+            }
+
+            /// Configure what happens to data written after the end of the compressed stream, see
+            /// [`TrailingData`](crate::TrailingData) for the options.
+            ///
+            /// Defaults to [`TrailingData::Error`](crate::TrailingData::Error). With
+            /// [`TrailingData::Stop`](crate::TrailingData::Stop) the write reaching the end of the
+            /// stream is cut short there and any later writes return `Ok(0)`, leaving the trailing
+            /// data with the caller.
+            pub fn trailing_data(&mut self, policy: crate::TrailingData) {
+                self.inner.trailing_data(policy);
+            }
+
+            /// Configure whether closing this decoder also closes the underlying writer,
+            /// enabled by default. When disabled it only flushes the underlying writer once the
+            /// compressed stream has been fully decoded, so the writer can be shared with
+            /// later streams.
+            pub fn with_close_propagation(self, enabled: bool) -> Self {
+                Self {
+                    inner: self.inner.with_close_propagation(enabled),
+                }
+            }
+
+            /// Resets this decoder to decode a new stream to the same writer, reusing its
+            /// allocations.
+            ///
+            /// Any output not yet written to the underlying writer is discarded and the statistics
+            /// start over, so this should be called once the previous stream has been closed.
+            /// Configuration such as the limits and trailing data policy is kept.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this decoder like [`reset`](Self::reset) to decode a new stream to `writer`,
+            /// returning the previous writer.
+            pub fn reset_with_writer(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_writer(writer)
+            }
+
             /// Consumes this decoder returning the underlying reader.
             ///
             /// Note that this may discard internal state of this decoder, so care should be taken
             /// to avoid losing resources when this is called.
-            pub fn into_inner(self) -> W {
+            pub fn into_inner(self) -> $inner {
                 self.inner.into_inner()
             }
         }
 
-        impl<W: futures_io::AsyncWrite> futures_io::AsyncWrite for $name<W> {
+        impl<$inner: futures_io::AsyncWrite> futures_io::AsyncWrite for $name<$inner> {
             fn poll_write(
                 self: std::pin::Pin<&mut Self>,
                 cx: &mut std::task::Context<'_>,
@@ -62,6 +215,14 @@ macro_rules! decoder {
                 self.project().inner.poll_write(cx, buf)
             }
 
+            fn poll_write_vectored(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                bufs: &[std::io::IoSlice<'_>],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.project().inner.poll_write_vectored(cx, bufs)
+            }
+
             fn poll_flush(
                 self: std::pin::Pin<&mut Self>,
                 cx: &mut std::task::Context<'_>,
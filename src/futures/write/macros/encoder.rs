@@ -1,5 +1,10 @@
 macro_rules! encoder {
-    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($constructor:tt)* })*) => {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
         pin_project_lite::pin_project! {
             $(#[$attr])*
             #[derive(Debug)]
@@ -13,6 +18,10 @@ macro_rules! encoder {
         }
 
         impl<$inner: futures_io::AsyncWrite> $name<$inner> {
+            /// Creates a new encoder which will take in uncompressed data and write it
+            /// compressed to the given stream.
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
             $(
                 /// Creates a new encoder which will take in uncompressed data and write it
                 /// compressed to the given stream.
@@ -20,6 +29,44 @@ macro_rules! encoder {
                 $($constructor)*
             )*
 
+            /// Returns a builder for this encoder, taking the same extra arguments as `new`, which
+            /// collects the compression level and other options before creating it.
+            pub fn builder(
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> crate::futures::write::EncoderBuilder<Self, $inner, ($($arg_ty,)*)> {
+                crate::futures::write::EncoderBuilder::new(inner, ($($arg,)*), |builder| Self {
+                    inner: builder.build_with(|inner, ($($arg,)*), level| {
+                        Self::with_quality(inner $(, $arg)*, level).inner
+                    }),
+                })
+            }
+
+            /// Creates a new encoder like `new`, taking the same extra arguments, with an internal
+            /// buffer of the specified capacity for the compressed output.
+            ///
+            /// Panics if `capacity` is zero.
+            pub fn with_capacity(capacity: usize, inner: $inner $(, $arg: $arg_ty)*) -> Self {
+                let (inner, codec) = Self::new(inner $(, $arg)*).inner.into_parts();
+                Self {
+                    inner: crate::futures::write::Encoder::with_capacity(capacity, inner, codec),
+                }
+            }
+
+            /// Creates a new encoder like `new`, taking the same extra arguments, with its internal
+            /// buffer taken from `pool` when it is first needed and given back to it
+            /// when this encoder is dropped.
+            pub fn with_pool(
+                pool: &crate::pool::BufferPool,
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> Self {
+                let (inner, codec) = Self::new(inner $(, $arg)*).inner.into_parts();
+                Self {
+                    inner: crate::futures::write::Encoder::with_pool(pool, inner, codec),
+                }
+            }
+
             /// Acquires a reference to the underlying writer that this encoder is wrapping.
             pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
@@ -43,6 +90,188 @@ macro_rules! encoder {
                 self.project().inner.get_pin_mut()
             }
 
+            /// Returns the total number of uncompressed bytes this encoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of compressed bytes this encoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this encoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Configure whether closing this encoder also closes the underlying writer,
+            /// enabled by default. When disabled it only flushes the underlying writer once the
+            /// compressed stream has been finished, so the writer can be shared with
+            /// later streams.
+            pub fn with_close_propagation(self, enabled: bool) -> Self {
+                Self {
+                    inner: self.inner.with_close_propagation(enabled),
+                }
+            }
+
+            /// Configure whether flushing this encoder also flushes the codec, enabled by
+            /// default.
+            ///
+            /// A codec flush makes everything written so far decodable by the reader, but with
+            /// deflate based formats each one emits an extra block that costs some compression
+            /// ratio. When disabled flushing only writes out the output the codec has already
+            /// produced.
+            pub fn with_codec_flush(self, enabled: bool) -> Self {
+                Self {
+                    inner: self.inner.with_codec_flush(enabled),
+                }
+            }
+
+            /// Configure this encoder to flush the codec and the underlying writer by itself once
+            /// `after` uncompressed bytes have been written since the last flush, so interactive
+            /// streams reach the reader without explicit calls to `flush`. An `after` of 1 flushes
+            /// after every write.
+            ///
+            /// Like explicit flushes each automatic flush costs some compression ratio. If the
+            /// flush can't complete straight away the write still succeeds, and the flush is
+            /// completed by the next call on this encoder.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `after` is zero.
+            pub fn with_auto_flush(self, after: usize) -> Self {
+                Self {
+                    inner: self.inner.with_auto_flush(after),
+                }
+            }
+
+            /// Configure this encoder to accumulate the data written to it until `high` bytes are
+            /// buffered before handing any of it to the codec, which then takes the buffered data
+            /// until no more than `low` bytes are left. When the data is written in many small
+            /// chunks this gives the codec larger pieces to work with, which improves the
+            /// compression ratio of some codecs and cuts the overhead of calling them.
+            ///
+            /// Flushing, closing and checkpoints hand all the buffered data to the codec first.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `low` is not less than `high`.
+            pub fn with_input_watermarks(self, low: usize, high: usize) -> Self {
+                Self {
+                    inner: self.inner.with_input_watermarks(low, high),
+                }
+            }
+
+            /// Resumes the stream from `checkpoint`, taken by
+            /// [`poll_checkpoint`](Self::poll_checkpoint) on an earlier encoder, see the
+            /// [`checkpoint`](crate::checkpoint) module.
+            ///
+            /// The data written to this encoder should carry on from
+            /// [`Checkpoint::total_in`](crate::checkpoint::Checkpoint::total_in), and the
+            /// compressed output follow the first
+            /// [`Checkpoint::total_out`](crate::checkpoint::Checkpoint::total_out) bytes written
+            /// by the earlier encoder. The totals and later checkpoints count from the start of
+            /// the stream.
+            pub fn with_checkpoint(self, checkpoint: &crate::checkpoint::Checkpoint) -> Self {
+                Self {
+                    inner: self.inner.with_checkpoint(checkpoint),
+                }
+            }
+
+            /// Attempts to end the current member/frame and flush it to the underlying writer,
+            /// returning a [`Checkpoint`](crate::checkpoint::Checkpoint) from which the stream can
+            /// be resumed, see the [`checkpoint`](crate::checkpoint) module.
+            ///
+            /// Anything written after this starts a new member/frame.
+            pub fn poll_checkpoint(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<crate::checkpoint::Checkpoint>> {
+                self.project().inner.poll_checkpoint(cx)
+            }
+
+            /// Ends the current member/frame and flushes it to the underlying writer like
+            /// [`poll_checkpoint`](Self::poll_checkpoint), returning a
+            /// [`Checkpoint`](crate::checkpoint::Checkpoint) from which the stream can be resumed.
+            pub async fn checkpoint(&mut self) -> std::io::Result<crate::checkpoint::Checkpoint>
+            where
+                $inner: Unpin,
+            {
+                self.inner.checkpoint().await
+            }
+
+            /// Attempts to finish the compressed stream and flush it to the underlying writer,
+            /// without closing the underlying writer.
+            ///
+            /// Once this has completed nothing more can be written to this encoder.
+            pub fn poll_finish(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                self.project().inner.poll_finish(cx)
+            }
+
+            /// Finishes the compressed stream and flushes it to the underlying writer like
+            /// [`poll_finish`](Self::poll_finish), leaving the underlying writer open.
+            pub async fn try_finish(&mut self) -> std::io::Result<()>
+            where
+                $inner: Unpin,
+            {
+                self.inner.try_finish().await
+            }
+
+            /// Finishes the compressed stream like [`try_finish`](Self::try_finish) and returns
+            /// the underlying writer, which is left open.
+            pub async fn finish(self) -> std::io::Result<$inner>
+            where
+                $inner: Unpin,
+            {
+                self.inner.finish().await
+            }
+
+            /// Changes the compression level used for the rest of the stream, the data already
+            /// compressed is unaffected.
+            ///
+            /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and
+            /// `gzip` encoders flush before switching which costs some compression ratio. Other
+            /// encoders return an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+            pub fn set_level(&mut self, level: crate::Level) -> std::io::Result<()> {
+                self.inner.set_level(level)
+            }
+
+            /// Resets this encoder to compress a new stream to the same writer, reusing its
+            /// allocations where the codec allows it.
+            ///
+            /// Any output not yet written to the underlying writer is discarded and the statistics
+            /// start over, so this should be called once the previous stream has been closed.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this encoder like [`reset`](Self::reset) to compress a new stream to
+            /// `writer`, returning the previous writer.
+            pub fn reset_with_writer(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_writer(writer)
+            }
+
             /// Consumes this encoder returning the underlying writer.
             ///
             /// Note that this may discard internal state of this encoder, so care should be taken
@@ -61,6 +290,14 @@ macro_rules! encoder {
                 self.project().inner.poll_write(cx, buf)
             }
 
+            fn poll_write_vectored(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                bufs: &[std::io::IoSlice<'_>],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.project().inner.poll_write_vectored(cx, bufs)
+            }
+
             fn poll_flush(
                 self: std::pin::Pin<&mut Self>,
                 cx: &mut std::task::Context<'_>,
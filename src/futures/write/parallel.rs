@@ -0,0 +1,134 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{io::Result, sync::Arc};
+
+use crate::{
+    parallel::{Blocks, Executor},
+    Algorithm, Level,
+};
+use futures_core::ready;
+use futures_io::AsyncWrite;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// An encoder which compresses blocks of the data written to it in parallel on an
+    /// [`Executor`], and writes the compressed data to an underlying stream, see the
+    /// [`parallel`](crate::parallel) module.
+    #[derive(Debug)]
+    pub struct ParallelEncoder<W> {
+        #[pin]
+        writer: W,
+        blocks: Blocks,
+    }
+}
+
+impl<W: AsyncWrite> ParallelEncoder<W> {
+    /// Creates a new encoder which will take in uncompressed data and write it compressed to the
+    /// given stream, compressing blocks with `algorithm` at `level` on `executor`.
+    ///
+    /// Returns an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if streams
+    /// of `algorithm` can't be concatenated.
+    pub fn new(
+        writer: W,
+        algorithm: Algorithm,
+        level: Level,
+        executor: impl Executor + 'static,
+    ) -> Result<Self> {
+        Ok(Self {
+            writer,
+            blocks: Blocks::new(algorithm, level, Arc::new(executor))?,
+        })
+    }
+
+    /// Sets how much uncompressed data goes into each block, 1 MiB by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.blocks.set_block_size(block_size);
+        self
+    }
+
+    /// Sets how many blocks can be handed to the executor before writes wait for the first of
+    /// them to be compressed, twice the available parallelism by default. This bounds the memory
+    /// used to about `max_in_flight` blocks of input and output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_in_flight` is zero.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.blocks.set_max_in_flight(max_in_flight);
+        self
+    }
+
+    /// Acquires a reference to the underlying writer that this encoder is wrapping.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Acquires a pinned mutable reference to the underlying writer that this encoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().writer
+    }
+
+    /// Returns the total number of uncompressed bytes written to this encoder so far.
+    pub fn total_in(&self) -> u64 {
+        self.blocks.total_in()
+    }
+
+    /// Returns the total number of compressed bytes written to the underlying writer so far.
+    pub fn total_out(&self) -> u64 {
+        self.blocks.total_out()
+    }
+
+    /// Consumes this encoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for ParallelEncoder<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.project();
+        let mut writer = this.writer;
+        this.blocks.poll_write(cx, buf, &mut |cx, output| {
+            writer.as_mut().poll_write(cx, output)
+        })
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let mut writer = this.writer;
+        ready!(this
+            .blocks
+            .poll_flush(cx, &mut |cx, output| writer.as_mut().poll_write(cx, output)))?;
+        writer.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.project();
+        let mut writer = this.writer;
+        ready!(this
+            .blocks
+            .poll_close(cx, &mut |cx, output| writer.as_mut().poll_write(cx, output)))?;
+        writer.poll_close(cx)
+    }
+}
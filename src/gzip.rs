@@ -0,0 +1,68 @@
+//! Types specific to the gzip format.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The header of a gzip member, as described in [RFC 1952].
+///
+/// [RFC 1952]: https://www.rfc-editor.org/rfc/rfc1952#section-2.3
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Header {
+    pub(crate) text: bool,
+    pub(crate) mtime: u32,
+    pub(crate) extra_flags: u8,
+    pub(crate) operating_system: u8,
+    pub(crate) extra: Option<Vec<u8>>,
+    pub(crate) filename: Option<Vec<u8>>,
+    pub(crate) comment: Option<Vec<u8>>,
+}
+
+impl Header {
+    /// Whether the member is marked as probably being text.
+    pub fn is_text(&self) -> bool {
+        self.text
+    }
+
+    /// The modification time of the original file as seconds since the Unix epoch, zero if none
+    /// was recorded.
+    pub fn mtime(&self) -> u32 {
+        self.mtime
+    }
+
+    /// The modification time of the original file, or `None` if none was recorded.
+    pub fn mtime_as_system_time(&self) -> Option<SystemTime> {
+        match self.mtime {
+            0 => None,
+            mtime => Some(UNIX_EPOCH + Duration::from_secs(mtime.into())),
+        }
+    }
+
+    /// The extra flags describing the compression used, 2 for the best compression and 4 for the
+    /// fastest.
+    pub fn extra_flags(&self) -> u8 {
+        self.extra_flags
+    }
+
+    /// The operating system the member was created on, 255 if unknown.
+    pub fn operating_system(&self) -> u8 {
+        self.operating_system
+    }
+
+    /// The extra field, if present.
+    pub fn extra(&self) -> Option<&[u8]> {
+        self.extra.as_deref()
+    }
+
+    /// The name of the original file without its directory, if present.
+    ///
+    /// This is meant to be ISO 8859-1 (Latin-1) text, but is returned as is.
+    pub fn filename(&self) -> Option<&[u8]> {
+        self.filename.as_deref()
+    }
+
+    /// The comment, if present.
+    ///
+    /// This is meant to be ISO 8859-1 (Latin-1) text, but is returned as is.
+    pub fn comment(&self) -> Option<&[u8]> {
+        self.comment.as_deref()
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::BoxError;
+use crate::{
+    codec::Decode,
+    stats::Instrumented,
+    util::{write_into, PartialBuffer},
+};
+use bytes::{Buf, Bytes, BytesMut};
+use http_body_1::{Body, Frame};
+use pin_project_lite::pin_project;
+
+#[derive(Debug)]
+enum State {
+    Reading,
+    Finishing,
+    // The end of the compressed stream has been reached, read the rest of the body looking for
+    // trailers
+    Draining,
+    Trailers,
+    Done,
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Decoder<B, D: Decode> {
+        #[pin]
+        body: B,
+        decoder: Instrumented<D>,
+        state: State,
+        output: BytesMut,
+        // Whether the body has returned its last frame
+        body_done: bool,
+        trailers: Option<Frame<Bytes>>,
+    }
+}
+
+impl<B: Body, D: Decode> Decoder<B, D> {
+    pub(crate) fn new(body: B, decoder: D) -> Self {
+        Self {
+            body,
+            decoder: Instrumented::new(decoder),
+            state: State::Reading,
+            output: BytesMut::new(),
+            body_done: false,
+            trailers: None,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &B {
+        &self.body
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut B {
+        &mut self.body
+    }
+
+    pub(crate) fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut B> {
+        self.project().body
+    }
+
+    pub(crate) fn into_inner(self) -> B {
+        self.body
+    }
+
+    pub(crate) fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    pub(crate) fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+}
+
+impl<B, D> Body for Decoder<B, D>
+where
+    B: Body,
+    B::Error: Into<BoxError>,
+    D: Decode,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        let mut this = self.project();
+
+        loop {
+            match this.state {
+                State::Reading | State::Draining => {
+                    let frame = match this.body.as_mut().poll_frame(cx) {
+                        Poll::Ready(frame) => frame.transpose().map_err(Into::into)?,
+                        Poll::Pending if !this.output.is_empty() => break,
+                        Poll::Pending => return Poll::Pending,
+                    };
+
+                    let draining = matches!(this.state, State::Draining);
+                    match frame.map(Frame::into_data) {
+                        Some(Ok(_)) if draining => {}
+                        Some(Ok(mut data)) => {
+                            while data.has_remaining() {
+                                let mut input = PartialBuffer::new(data.chunk());
+                                let decoder = &mut *this.decoder;
+                                let done = write_into(this.output, |output| {
+                                    decoder.decode(&mut input, output)
+                                })?;
+                                let len = input.written().len();
+                                data.advance(len);
+
+                                if done {
+                                    *this.state = State::Finishing;
+                                    break;
+                                }
+                            }
+                        }
+                        Some(Err(frame)) => {
+                            if let Ok(trailers) = frame.into_trailers() {
+                                *this.trailers = Some(Frame::trailers(trailers));
+                            }
+                            *this.body_done = true;
+                        }
+                        None => *this.body_done = true,
+                    }
+
+                    if *this.body_done {
+                        *this.state = if draining {
+                            State::Trailers
+                        } else {
+                            State::Finishing
+                        };
+                    }
+                }
+
+                State::Finishing => {
+                    let decoder = &mut *this.decoder;
+                    while !write_into(this.output, |output| decoder.finish(output))? {}
+                    *this.state = if *this.body_done {
+                        State::Trailers
+                    } else {
+                        State::Draining
+                    };
+                }
+
+                State::Trailers => {
+                    if !this.output.is_empty() {
+                        break;
+                    }
+                    *this.state = State::Done;
+                    if let Some(trailers) = this.trailers.take() {
+                        return Poll::Ready(Some(Ok(trailers)));
+                    }
+                }
+
+                State::Done => return Poll::Ready(None),
+            }
+
+            if this.output.len() >= super::OUTPUT_BUFFER_SIZE {
+                break;
+            }
+        }
+
+        Poll::Ready(Some(Ok(Frame::data(this.output.split().freeze()))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::BoxError;
+use crate::{
+    codec::Encode,
+    util::{write_into, PartialBuffer},
+};
+use bytes::{Buf, Bytes, BytesMut};
+use http_body_1::{Body, Frame};
+use pin_project_lite::pin_project;
+
+#[derive(Debug)]
+enum State {
+    Reading,
+    Finishing,
+    Trailers,
+    Done,
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub struct Encoder<B, E: Encode> {
+        #[pin]
+        body: B,
+        encoder: E,
+        state: State,
+        output: BytesMut,
+        // Whether data has been encoded since the encoder was last flushed
+        unflushed: bool,
+        trailers: Option<Frame<Bytes>>,
+    }
+}
+
+impl<B: Body, E: Encode> Encoder<B, E> {
+    pub(crate) fn new(body: B, encoder: E) -> Self {
+        Self {
+            body,
+            encoder,
+            state: State::Reading,
+            output: BytesMut::new(),
+            unflushed: false,
+            trailers: None,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &B {
+        &self.body
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut B {
+        &mut self.body
+    }
+
+    pub(crate) fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut B> {
+        self.project().body
+    }
+
+    pub(crate) fn into_inner(self) -> B {
+        self.body
+    }
+}
+
+impl<B, E> Body for Encoder<B, E>
+where
+    B: Body,
+    B::Error: Into<BoxError>,
+    E: Encode,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        let mut this = self.project();
+
+        loop {
+            match this.state {
+                State::Reading => {
+                    let frame = match this.body.as_mut().poll_frame(cx) {
+                        Poll::Ready(frame) => frame,
+                        // Don't hold on to data while waiting for the body, send what has been
+                        // encoded so far
+                        Poll::Pending if *this.unflushed => {
+                            let encoder = &mut *this.encoder;
+                            while !write_into(this.output, |output| encoder.flush(output))? {}
+                            *this.unflushed = false;
+                            continue;
+                        }
+                        Poll::Pending if !this.output.is_empty() => break,
+                        Poll::Pending => return Poll::Pending,
+                    };
+
+                    match frame.transpose().map_err(Into::into)? {
+                        Some(frame) => match frame.into_data() {
+                            Ok(mut data) => {
+                                while data.has_remaining() {
+                                    let mut input = PartialBuffer::new(data.chunk());
+                                    let encoder = &mut *this.encoder;
+                                    write_into(this.output, |output| {
+                                        encoder.encode(&mut input, output)
+                                    })?;
+                                    let len = input.written().len();
+                                    data.advance(len);
+                                }
+                                *this.unflushed = true;
+                            }
+                            Err(frame) => {
+                                if let Ok(trailers) = frame.into_trailers() {
+                                    *this.trailers = Some(Frame::trailers(trailers));
+                                }
+                                *this.state = State::Finishing;
+                            }
+                        },
+                        None => *this.state = State::Finishing,
+                    }
+                }
+
+                State::Finishing => {
+                    let encoder = &mut *this.encoder;
+                    while !write_into(this.output, |output| encoder.finish(output))? {}
+                    *this.state = State::Trailers;
+                }
+
+                State::Trailers => {
+                    if !this.output.is_empty() {
+                        break;
+                    }
+                    *this.state = State::Done;
+                    if let Some(trailers) = this.trailers.take() {
+                        return Poll::Ready(Some(Ok(trailers)));
+                    }
+                }
+
+                State::Done => return Poll::Ready(None),
+            }
+
+            if this.output.len() >= super::OUTPUT_BUFFER_SIZE {
+                break;
+            }
+        }
+
+        Poll::Ready(Some(Ok(Frame::data(this.output.split().freeze()))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+}
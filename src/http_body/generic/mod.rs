@@ -0,0 +1,11 @@
+mod decoder;
+mod encoder;
+
+pub(crate) use self::{decoder::Decoder, encoder::Encoder};
+
+/// The amount of output that is buffered before it's sent as a data frame.
+const OUTPUT_BUFFER_SIZE: usize = 8_000;
+
+/// The error type of the bodies, which can be either an error from the wrapped body or an IO
+/// error from the codec.
+pub(crate) type BoxError = Box<dyn std::error::Error + Send + Sync>;
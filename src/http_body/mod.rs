@@ -0,0 +1,248 @@
+//! Types which wrap [`http_body::Body`](http_body_1::Body) implementations, compressing or
+//! decompressing the data frames of the body.
+//!
+//! The data frames are treated as a single byte-stream to be compressed/decompressed, there is not
+//! guaranteed to be a one-to-one relationship between the data frames of the wrapped body and the
+//! resulting body. Trailers are passed through unchanged after the data.
+//!
+//! The algorithm is chosen at runtime with an [`Algorithm`], or from the headers of an HTTP
+//! request or response.
+
+mod generic;
+
+use self::generic::BoxError;
+use crate::{codec, Algorithm, Level};
+use http_body_1::{Body, Frame, SizeHint};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pin_project_lite::pin_project! {
+    /// A body which compresses the data frames of the wrapped body.
+    ///
+    /// When the wrapped body is waiting for more data, the data compressed so far is flushed and
+    /// sent, so that streaming responses aren't delayed.
+    #[derive(Debug)]
+    pub struct CompressedBody<B> {
+        #[pin]
+        inner: generic::Encoder<B, codec::AnyEncoder>,
+    }
+}
+
+impl<B: Body> CompressedBody<B> {
+    /// Creates a new body which compresses the data of `body` with the given algorithm.
+    pub fn new(body: B, algorithm: Algorithm) -> Self {
+        Self::with_quality(body, algorithm, Level::Default)
+    }
+
+    /// Creates a new body which compresses the data of `body` with the given algorithm and
+    /// compression level.
+    pub fn with_quality(body: B, algorithm: Algorithm, level: Level) -> Self {
+        Self {
+            inner: generic::Encoder::new(body, codec::AnyEncoder::new(algorithm, level)),
+        }
+    }
+
+    /// Creates a new body which compresses the data of `body` with the encoding negotiated from
+    /// the `Accept-Encoding` header of a request.
+    ///
+    /// The returned token is the content-coding used, to send as the `Content-Encoding` of the
+    /// response. See
+    /// [`AnyEncoder::from_accept_encoding`](crate::futures::bufread::AnyEncoder::from_accept_encoding)
+    /// for how the encoding is chosen.
+    pub fn from_accept_encoding(body: B, accept_encoding: &str) -> Option<(&'static str, Self)> {
+        let (token, encoder) =
+            codec::AnyEncoder::from_accept_encoding(accept_encoding, Level::Default)?;
+
+        Some((
+            token,
+            Self {
+                inner: generic::Encoder::new(body, encoder),
+            },
+        ))
+    }
+
+    /// Acquires a reference to the underlying body that this body is wrapping.
+    pub fn get_ref(&self) -> &B {
+        self.inner.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying body that this body is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the body which may
+    /// otherwise confuse this body.
+    pub fn get_mut(&mut self) -> &mut B {
+        self.inner.get_mut()
+    }
+
+    /// Acquires a pinned mutable reference to the underlying body that this body is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the body which may
+    /// otherwise confuse this body.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut B> {
+        self.project().inner.get_pin_mut()
+    }
+
+    /// Consumes this body returning the underlying body.
+    ///
+    /// Note that this may discard internal state of this body, so care should be taken to avoid
+    /// losing resources when this is called.
+    pub fn into_inner(self) -> B {
+        self.inner.into_inner()
+    }
+}
+
+impl<B> Body for CompressedBody<B>
+where
+    B: Body,
+    B::Error: Into<BoxError>,
+{
+    type Data = bytes::Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A body which decompresses the data frames of the wrapped body.
+    ///
+    /// Any data after the end of the compressed stream is ignored.
+    #[derive(Debug)]
+    pub struct DecompressedBody<B> {
+        #[pin]
+        inner: generic::Decoder<B, codec::AnyDecoder>,
+    }
+}
+
+impl<B: Body> DecompressedBody<B> {
+    /// Creates a new body which decompresses the data of `body` with the given algorithm.
+    pub fn new(body: B, algorithm: Algorithm) -> Self {
+        Self {
+            inner: generic::Decoder::new(body, codec::AnyDecoder::new(algorithm)),
+        }
+    }
+
+    /// Creates a new body which decompresses the data of `body` according to an HTTP
+    /// `Content-Encoding` header.
+    ///
+    /// See
+    /// [`AnyDecoder::from_content_encoding`](crate::futures::bufread::AnyDecoder::from_content_encoding)
+    /// for the supported content-codings. Returns an error of kind
+    /// [`InvalidInput`](std::io::ErrorKind::InvalidInput) if any of them is not supported.
+    pub fn from_content_encoding(body: B, encoding: &str) -> std::io::Result<Self> {
+        let decoder = codec::AnyDecoder::from_content_encoding(encoding).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unsupported content encoding `{}`", encoding),
+            )
+        })?;
+
+        Ok(Self {
+            inner: generic::Decoder::new(body, decoder),
+        })
+    }
+
+    /// Limits the number of decompressed bytes this body will produce to `limit`, once more would
+    /// be produced it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error instead.
+    /// This protects against decompression bombs when decoding untrusted data.
+    pub fn with_output_limit(self, limit: u64) -> Self {
+        Self {
+            inner: self.inner.with_output_limit(limit),
+        }
+    }
+
+    /// Limits the number of compressed bytes this body will consume to `limit`, once more would be
+    /// needed it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error instead. This
+    /// stops a never-ending body from keeping the decoder busy indefinitely.
+    pub fn with_input_limit(self, limit: u64) -> Self {
+        Self {
+            inner: self.inner.with_input_limit(limit),
+        }
+    }
+
+    /// Acquires a reference to the underlying body that this body is wrapping.
+    pub fn get_ref(&self) -> &B {
+        self.inner.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying body that this body is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the body which may
+    /// otherwise confuse this body.
+    pub fn get_mut(&mut self) -> &mut B {
+        self.inner.get_mut()
+    }
+
+    /// Acquires a pinned mutable reference to the underlying body that this body is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the body which may
+    /// otherwise confuse this body.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut B> {
+        self.project().inner.get_pin_mut()
+    }
+
+    /// Consumes this body returning the underlying body.
+    ///
+    /// Note that this may discard internal state of this body, so care should be taken to avoid
+    /// losing resources when this is called.
+    pub fn into_inner(self) -> B {
+        self.inner.into_inner()
+    }
+}
+
+impl<B> Body for DecompressedBody<B>
+where
+    B: Body,
+    B::Error: Into<BoxError>,
+{
+    type Data = bytes::Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project().inner.poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
+}
+
+const _: () = {
+    fn _assert() {
+        use crate::util::{_assert_send, _assert_sync};
+
+        _assert_send::<
+            CompressedBody<Pin<Box<dyn Body<Data = bytes::Bytes, Error = BoxError> + Send>>>,
+        >();
+        _assert_sync::<
+            CompressedBody<Pin<Box<dyn Body<Data = bytes::Bytes, Error = BoxError> + Sync>>>,
+        >();
+        _assert_send::<
+            DecompressedBody<Pin<Box<dyn Body<Data = bytes::Bytes, Error = BoxError> + Send>>>,
+        >();
+        _assert_sync::<
+            DecompressedBody<Pin<Box<dyn Body<Data = bytes::Bytes, Error = BoxError> + Sync>>>,
+        >();
+    }
+};
@@ -17,6 +17,7 @@
 //!  `all`   | Activates all implementations and algorithms.
 //!  `all-implementations` | Activates all implementations, needs to be paired with a selection of algorithms
 //!  `all-algorithms` | Activates all algorithms, needs to be paired with a selection of implementations
+//!  `all-pure-rust-algorithms` | Activates all algorithms that are implemented in pure Rust, needs to be paired with a selection of implementations
 //!
 
 //! ## IO implementation
@@ -37,6 +38,30 @@
     not(feature = "futures-io"),
     doc = "`futures-io` (*inactive*) | `futures::io::AsyncBufRead`, `futures::io::AsyncWrite`"
 )]
+#![cfg_attr(
+    feature = "futures-sink",
+    doc = "[`futures-sink`](crate::futures::sink) | [`futures::sink::Sink`](futures_sink_03::Sink)`<bytes::Bytes>`"
+)]
+#![cfg_attr(
+    not(feature = "futures-sink"),
+    doc = "`futures-sink` (*inactive*) | `futures::sink::Sink<bytes::Bytes>`"
+)]
+#![cfg_attr(
+    feature = "futures-stream",
+    doc = "[`futures-stream`](crate::futures::stream) | [`futures::stream::Stream`](futures_core::stream::Stream)`<Item = io::Result<bytes::Bytes>>`"
+)]
+#![cfg_attr(
+    not(feature = "futures-stream"),
+    doc = "`futures-stream` (*inactive*) | `futures::stream::Stream<Item = io::Result<bytes::Bytes>>`"
+)]
+#![cfg_attr(
+    feature = "http-body",
+    doc = "[`http-body`](crate::http_body) | [`http_body::Body`](http_body_1::Body)"
+)]
+#![cfg_attr(
+    not(feature = "http-body"),
+    doc = "`http-body` (*inactive*) | `http_body::Body`"
+)]
 #![cfg_attr(
     feature = "futures-bufread",
     doc = "`futures-bufread` | (*deprecated*, use `futures-io`)"
@@ -45,6 +70,38 @@
     feature = "futures-write",
     doc = "`futures-write` | (*deprecated*, use `futures-io`)"
 )]
+#![cfg_attr(
+    feature = "libdeflate",
+    doc = "[`libdeflate`](crate::libdeflate) | One-shot compression of fully buffered data with [`libdeflate`](::libdeflater)"
+)]
+#![cfg_attr(
+    not(feature = "libdeflate"),
+    doc = "`libdeflate` (*inactive*) | One-shot compression of fully buffered data with `libdeflate`"
+)]
+#![cfg_attr(
+    feature = "owned-buf",
+    doc = "[`owned-buf`](crate::owned) | Owned buffers, for completion-based runtimes such as `compio` and `monoio`"
+)]
+#![cfg_attr(
+    not(feature = "owned-buf"),
+    doc = "`owned-buf` (*inactive*) | Owned buffers, for completion-based runtimes such as `compio` and `monoio`"
+)]
+#![cfg_attr(
+    feature = "rayon",
+    doc = "[`rayon`](crate::parallel) | Compressing blocks of a stream in parallel on [`rayon`](::rayon) thread pools"
+)]
+#![cfg_attr(
+    not(feature = "rayon"),
+    doc = "`rayon` (*inactive*) | Compressing blocks of a stream in parallel on `rayon` thread pools"
+)]
+#![cfg_attr(
+    feature = "std-io",
+    doc = "[`std-io`](crate::std_io) | [`std::io::BufRead`], [`std::io::Write`]"
+)]
+#![cfg_attr(
+    not(feature = "std-io"),
+    doc = "`std-io` (*inactive*) | `std::io::BufRead`, `std::io::Write`"
+)]
 #![cfg_attr(
     feature = "stream",
     doc = "[`stream`] | (*deprecated*, see [`async-compression:stream`](crate::stream) docs for migration)"
@@ -77,6 +134,46 @@
     not(feature = "tokio"),
     doc = "`tokio` (*inactive*) | `tokio::io::AsyncBufRead`, `tokio::io::AsyncWrite`"
 )]
+#![cfg_attr(
+    feature = "tokio-blocking",
+    doc = "[`tokio-blocking`](crate::tokio::blocking) | Running codecs on the [`tokio`](::tokio) blocking thread pool"
+)]
+#![cfg_attr(
+    not(feature = "tokio-blocking"),
+    doc = "`tokio-blocking` (*inactive*) | Running codecs on the `tokio` blocking thread pool"
+)]
+#![cfg_attr(
+    feature = "tokio-fs",
+    doc = "[`tokio-fs`](crate::tokio::fs) | Compressing and decompressing files with [`tokio::fs`](::tokio::fs)"
+)]
+#![cfg_attr(
+    not(feature = "tokio-fs"),
+    doc = "`tokio-fs` (*inactive*) | Compressing and decompressing files with `tokio::fs`"
+)]
+#![cfg_attr(
+    feature = "tokio-time",
+    doc = "[`tokio-time`](crate::tokio::bufread::Encoder::with_flush_timeout) | Timed flushing of [`tokio::bufread`](crate::tokio::bufread) encoders"
+)]
+#![cfg_attr(
+    not(feature = "tokio-time"),
+    doc = "`tokio-time` (*inactive*) | Timed flushing of `tokio::bufread` encoders"
+)]
+#![cfg_attr(
+    all(feature = "tokio-uring", target_os = "linux"),
+    doc = "[`tokio-uring`](crate::tokio::fs::uring) | Compressing and decompressing files with `io_uring` through [`tokio-uring`](tokio_uring_05), on Linux"
+)]
+#![cfg_attr(
+    not(all(feature = "tokio-uring", target_os = "linux")),
+    doc = "`tokio-uring` (*inactive*) | Compressing and decompressing files with `io_uring` through `tokio-uring`, on Linux"
+)]
+#![cfg_attr(
+    feature = "tokio-util",
+    doc = "[`tokio-util`](crate::tokio::codec) | [`tokio_util::codec::Decoder`](tokio_util_07::codec::Decoder), [`tokio_util::codec::Encoder`](tokio_util_07::codec::Encoder)"
+)]
+#![cfg_attr(
+    not(feature = "tokio-util"),
+    doc = "`tokio-util` (*inactive*) | `tokio_util::codec::Decoder`, `tokio_util::codec::Encoder`"
+)]
 //!
 
 //! ## Compression algorithm
@@ -105,11 +202,11 @@
 )]
 #![cfg_attr(
     feature = "deflate",
-    doc = "`deflate` | [`DeflateEncoder`](?search=DeflateEncoder), [`DeflateDecoder`](?search=DeflateDecoder)"
+    doc = "`deflate` | [`DeflateEncoder`](?search=DeflateEncoder), [`DeflateDecoder`](?search=DeflateDecoder), [`PerMessageDeflate`](crate::permessage_deflate::PerMessageDeflate)"
 )]
 #![cfg_attr(
     not(feature = "deflate"),
-    doc = "`deflate` (*inactive*) | `DeflateEncoder`, `DeflateDecoder`"
+    doc = "`deflate` (*inactive*) | `DeflateEncoder`, `DeflateDecoder`, `PerMessageDeflate`"
 )]
 #![cfg_attr(
     feature = "gzip",
@@ -119,6 +216,22 @@
     not(feature = "gzip"),
     doc = "`gzip` (*inactive*) | `GzipEncoder`, `GzipDecoder`"
 )]
+#![cfg_attr(
+    feature = "isal",
+    doc = "`isal` | [`IsalGzipEncoder`](?search=IsalGzipEncoder), [`IsalGzipDecoder`](?search=IsalGzipDecoder), [`IsalDeflateEncoder`](?search=IsalDeflateEncoder), [`IsalDeflateDecoder`](?search=IsalDeflateDecoder), backed by ISA-L's igzip on x86_64"
+)]
+#![cfg_attr(
+    not(feature = "isal"),
+    doc = "`isal` (*inactive*) | `IsalGzipEncoder`, `IsalGzipDecoder`, `IsalDeflateEncoder`, `IsalDeflateDecoder`, backed by ISA-L's igzip on x86_64"
+)]
+#![cfg_attr(
+    feature = "lz4",
+    doc = "`lz4` | [`Lz4Encoder`](?search=Lz4Encoder), [`Lz4Decoder`](?search=Lz4Decoder), [`Lz4BlockEncoder`](?search=Lz4BlockEncoder), [`Lz4BlockDecoder`](?search=Lz4BlockDecoder)"
+)]
+#![cfg_attr(
+    not(feature = "lz4"),
+    doc = "`lz4` (*inactive*) | `Lz4Encoder`, `Lz4Decoder`, `Lz4BlockEncoder`, `Lz4BlockDecoder`"
+)]
 #![cfg_attr(
     feature = "lzma",
     doc = "`lzma` | [`LzmaEncoder`](?search=LzmaEncoder), [`LzmaDecoder`](?search=LzmaDecoder)"
@@ -127,6 +240,14 @@
     not(feature = "lzma"),
     doc = "`lzma` (*inactive*) | `LzmaEncoder`, `LzmaDecoder`"
 )]
+#![cfg_attr(
+    feature = "snappy",
+    doc = "`snappy` | [`SnappyEncoder`](?search=SnappyEncoder), [`SnappyDecoder`](?search=SnappyDecoder), [`SnappyBlockEncoder`](?search=SnappyBlockEncoder), [`SnappyBlockDecoder`](?search=SnappyBlockDecoder)"
+)]
+#![cfg_attr(
+    not(feature = "snappy"),
+    doc = "`snappy` (*inactive*) | `SnappyEncoder`, `SnappyDecoder`, `SnappyBlockEncoder`, `SnappyBlockDecoder`"
+)]
 #![cfg_attr(
     feature = "xz",
     doc = "`xz` | [`XzEncoder`](?search=XzEncoder), [`XzDecoder`](?search=XzDecoder)"
@@ -152,6 +273,26 @@
     doc = "`zstd` (*inactive*) | `ZstdEncoder`, `ZstdDecoder`"
 )]
 //!
+//! `IdentityEncoder` and `IdentityDecoder` are always available, they pass data through
+//! unchanged so that uncompressed data can be handled with the same code as the algorithms.
+//!
+
+//! ## Tracing
+//!
+//! Enabling the `tracing` feature makes the encoders and decoders in the IO modules emit
+//! [`tracing`](https://docs.rs/tracing) events at the `DEBUG` level whenever a flush completes, a
+//! frame or member is finished, a decoder is reinitialized for another member or the codec returns
+//! an error. Each event carries the same byte and frame counts as the `stats` accessors.
+//!
+
+//! ## WebAssembly
+//!
+//! The algorithms enabled by `all-pure-rust-algorithms` have no native dependencies, so they can
+//! be built for `wasm32-unknown-unknown` without a C toolchain. `zstd` can also be built for that
+//! target, but requires a `clang` capable of targeting WebAssembly to build the bundled C library.
+//! `bzip2`, `lz4`, `lzma` and `xz` depend on C libraries that need a libc, enabling any of them
+//! when building for `wasm32-unknown-unknown` is a compile error.
+//!
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![warn(
@@ -160,15 +301,86 @@
     missing_copy_implementations,
     missing_debug_implementations
 )]
-#![cfg_attr(not(all), allow(unused))]
+#![cfg_attr(not(feature = "all"), allow(unused))]
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+macro_rules! unsupported_on_wasm {
+    ($($feature:literal),*) => {
+        $(
+            #[cfg(feature = $feature)]
+            compile_error!(concat!(
+                "the `",
+                $feature,
+                "` feature depends on a C library that can't be built for wasm32-unknown-unknown, ",
+                "see the `all-pure-rust-algorithms` feature for the algorithms that can be",
+            ));
+        )*
+    };
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+unsupported_on_wasm!("bzip2", "libdeflate", "lz4", "lzma", "xz");
+
+#[cfg(all(feature = "isal", not(target_arch = "x86_64")))]
+compile_error!("the `isal` feature is only supported on x86_64");
 
 #[macro_use]
 mod macros;
-mod codec;
 
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "futures-io", feature = "tokio"))))]
+pub mod auto;
+#[cfg(feature = "bzip2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bzip2")))]
+pub mod bzip2;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "futures-io", feature = "tokio"))))]
+pub mod checkpoint;
+pub mod codec;
+pub mod error;
 #[cfg(feature = "futures-io")]
 #[cfg_attr(docsrs, doc(cfg(feature = "futures-io")))]
 pub mod futures;
+#[cfg(feature = "gzip")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+pub mod gzip;
+#[cfg(feature = "http-body")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http-body")))]
+pub mod http_body;
+#[cfg(feature = "libdeflate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "libdeflate")))]
+pub mod libdeflate;
+#[cfg(feature = "owned-buf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "owned-buf")))]
+pub mod owned;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "futures-io", feature = "tokio"))))]
+pub mod parallel;
+#[cfg(feature = "deflate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+pub mod permessage_deflate;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "futures-io", feature = "tokio"))))]
+pub mod pool;
+#[cfg(any(
+    feature = "futures-io",
+    feature = "http-body",
+    feature = "std-io",
+    feature = "tokio"
+))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(
+        feature = "futures-io",
+        feature = "http-body",
+        feature = "std-io",
+        feature = "tokio"
+    )))
+)]
+pub mod stats;
+#[cfg(feature = "std-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std-io")))]
+pub mod std_io;
 #[cfg(feature = "stream")]
 #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
 pub mod stream;
@@ -181,7 +393,12 @@ pub mod tokio_02;
 #[cfg(feature = "tokio-03")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio-03")))]
 pub mod tokio_03;
+#[cfg(feature = "zstd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+pub mod zstd;
 
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+mod entry;
 mod unshared;
 mod util;
 
@@ -201,7 +418,9 @@ pub enum Level {
     /// Precise quality based on the underlying compression algorithms'
     /// qualities. The interpretation of this depends on the algorithm chosen
     /// and the specific implementation backing it.
-    /// Qualities are implicitly clamped to the algorithm's maximum.
+    /// Qualities are implicitly clamped into the algorithm's
+    /// [`level_range`](Algorithm::level_range), use [`Algorithm::check_level`] to reject them
+    /// instead.
     Precise(u32),
 }
 
@@ -219,12 +438,12 @@ impl Level {
     }
 
     #[cfg(feature = "bzip2")]
-    fn into_bzip2(self) -> bzip2::Compression {
+    fn into_bzip2(self) -> ::bzip2::Compression {
         match self {
-            Self::Fastest => bzip2::Compression::fast(),
-            Self::Best => bzip2::Compression::best(),
-            Self::Precise(quality) => bzip2::Compression::new(quality.max(1).min(9)),
-            Self::Default => bzip2::Compression::default(),
+            Self::Fastest => ::bzip2::Compression::fast(),
+            Self::Best => ::bzip2::Compression::best(),
+            Self::Precise(quality) => ::bzip2::Compression::new(quality.max(1).min(9)),
+            Self::Default => ::bzip2::Compression::default(),
         }
     }
 
@@ -248,6 +467,38 @@ impl Level {
         }
     }
 
+    #[cfg(feature = "isal")]
+    fn into_isal(self) -> u32 {
+        match self {
+            Self::Fastest => 0,
+            Self::Best => 3,
+            Self::Precise(quality) => quality.min(3),
+            Self::Default => 1,
+        }
+    }
+
+    #[cfg(feature = "libdeflate")]
+    fn into_libdeflate(self) -> libdeflater::CompressionLvl {
+        match self {
+            Self::Fastest => libdeflater::CompressionLvl::fastest(),
+            Self::Best => libdeflater::CompressionLvl::best(),
+            Self::Precise(quality) => {
+                libdeflater::CompressionLvl::new(quality.min(12) as i32).unwrap()
+            }
+            Self::Default => libdeflater::CompressionLvl::default(),
+        }
+    }
+
+    #[cfg(feature = "lz4")]
+    fn into_lz4(self) -> u32 {
+        match self {
+            Self::Fastest => 0,
+            Self::Best => 12,
+            Self::Precise(quality) => quality.min(12),
+            Self::Default => 0,
+        }
+    }
+
     #[cfg(feature = "xz2")]
     fn into_xz2(self) -> u32 {
         match self {
@@ -258,3 +509,143 @@ impl Level {
         }
     }
 }
+
+/// A compression algorithm, used to choose a codec at runtime with the `AnyEncoder` and
+/// `AnyDecoder` types.
+///
+/// Only the algorithms whose features are enabled are available.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// The brotli format.
+    #[cfg(feature = "brotli")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "brotli")))]
+    Brotli,
+    /// The bzip2 format.
+    #[cfg(feature = "bzip2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bzip2")))]
+    Bzip2,
+    /// The raw deflate format.
+    #[cfg(feature = "deflate")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+    Deflate,
+    /// The gzip format.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    Gzip,
+    /// The lz4 frame format.
+    #[cfg(feature = "lz4")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lz4")))]
+    Lz4,
+    /// The lzma format.
+    #[cfg(feature = "lzma")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lzma")))]
+    Lzma,
+    /// The snappy framed format.
+    #[cfg(feature = "snappy")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "snappy")))]
+    Snappy,
+    /// The xz format.
+    #[cfg(feature = "xz")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xz")))]
+    Xz,
+    /// The zlib format.
+    #[cfg(feature = "zlib")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zlib")))]
+    Zlib,
+    /// The zstd format.
+    #[cfg(feature = "zstd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zstd")))]
+    Zstd,
+}
+
+impl Algorithm {
+    /// Returns the range of [`Level::Precise`] qualities supported by this algorithm, qualities
+    /// outside of it are clamped into it.
+    pub fn level_range(self) -> core::ops::RangeInclusive<u32> {
+        match self {
+            #[cfg(feature = "brotli")]
+            Self::Brotli => 0..=11,
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => 1..=9,
+            #[cfg(feature = "deflate")]
+            Self::Deflate => 0..=10,
+            #[cfg(feature = "gzip")]
+            Self::Gzip => 0..=10,
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => 0..=12,
+            #[cfg(feature = "lzma")]
+            Self::Lzma => 0..=9,
+            #[cfg(feature = "snappy")]
+            Self::Snappy => 0..=0,
+            #[cfg(feature = "xz")]
+            Self::Xz => 0..=9,
+            #[cfg(feature = "zlib")]
+            Self::Zlib => 0..=10,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => 0..=21,
+        }
+    }
+
+    /// Returns the precise quality the encoders for this algorithm use for `level`, after any
+    /// clamping.
+    ///
+    /// Snappy has no compression levels, so this is always 0 for it.
+    pub fn resolve_level(self, level: Level) -> u32 {
+        let _ = level;
+        match self {
+            #[cfg(feature = "brotli")]
+            Self::Brotli => level.into_brotli(Default::default()).quality as u32,
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => level.into_bzip2().level(),
+            #[cfg(feature = "deflate")]
+            Self::Deflate => level.into_flate2().level(),
+            #[cfg(feature = "gzip")]
+            Self::Gzip => level.into_flate2().level(),
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => level.into_lz4(),
+            #[cfg(feature = "lzma")]
+            Self::Lzma => level.into_xz2(),
+            #[cfg(feature = "snappy")]
+            Self::Snappy => 0,
+            #[cfg(feature = "xz")]
+            Self::Xz => level.into_xz2(),
+            #[cfg(feature = "zlib")]
+            Self::Zlib => level.into_flate2().level(),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => level.into_zstd() as u32,
+        }
+    }
+
+    /// Checks that `level` is supported by this algorithm, returning the precise quality it
+    /// resolves to like [`resolve_level`](Self::resolve_level), or an error for a
+    /// [`Level::Precise`] quality outside of the [`level_range`](Self::level_range) instead of
+    /// clamping it.
+    pub fn check_level(self, level: Level) -> Result<u32, error::LevelOutOfRange> {
+        match level {
+            Level::Precise(quality) if !self.level_range().contains(&quality) => Err(
+                error::LevelOutOfRange::new(self, quality, self.level_range()),
+            ),
+            _ => Ok(self.resolve_level(level)),
+        }
+    }
+}
+
+/// What a decoder does with data following the end of the compressed stream, configured with the
+/// `trailing_data` method of the decoders.
+///
+/// This has no effect on decoders with multi-member/frame decoding enabled, as any data following
+/// a member/frame is decoded as the next one.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TrailingData {
+    /// Fail with [`Error::UnexpectedTrailingData`](crate::error::Error::UnexpectedTrailingData)
+    /// if any data follows the end of the compressed stream.
+    Error,
+    /// Consume and discard any data following the end of the compressed stream.
+    Ignore,
+    /// Stop at the end of the compressed stream and leave any data following it unconsumed, so
+    /// the caller can retrieve it from the underlying reader or from the unwritten part of the
+    /// input.
+    Stop,
+}
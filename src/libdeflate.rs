@@ -0,0 +1,110 @@
+//! One-shot compression and decompression of fully buffered data with
+//! [`libdeflate`](https://github.com/ebiggers/libdeflate).
+//!
+//! When all of the data is already in memory, such as an HTTP response of a few hundred KB,
+//! compressing it in a single call with `libdeflate` is a lot faster than going through the
+//! streaming `deflate`, `gzip` and `zlib` codecs, which have to be ready to stop and resume
+//! anywhere. The output is compatible with theirs, either way of decompressing reads data
+//! compressed by the other.
+//!
+//! ```
+//! use async_compression::{
+//!     libdeflate::{self, Format},
+//!     Level,
+//! };
+//!
+//! let compressed = libdeflate::compress(Format::Gzip, Level::Default, b"example")?;
+//! let decompressed = libdeflate::decompress(Format::Gzip, &compressed, 1024 * 1024)?;
+//! assert_eq!(decompressed, b"example");
+//! # Ok::<_, std::io::Error>(())
+//! ```
+
+use std::{
+    convert::TryInto,
+    io::{Error, ErrorKind, Result},
+};
+
+use crate::{
+    error::{self, Limit, LimitExceeded},
+    Level,
+};
+use libdeflater::{Compressor, DecompressionError, Decompressor};
+
+/// The smallest buffer tried for the decompressed data.
+const MIN_OUTPUT_LEN: usize = 4 * 1024;
+
+/// The formats supported by [`compress`] and [`decompress`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// The raw deflate format.
+    Deflate,
+    /// The gzip format, limited to a single member.
+    Gzip,
+    /// The zlib format.
+    Zlib,
+}
+
+/// Compresses `input` into a complete stream of `format` at `level`.
+///
+/// `libdeflate` supports qualities from 0, which stores the data uncompressed, up to 12.
+pub fn compress(format: Format, level: Level, input: &[u8]) -> Result<Vec<u8>> {
+    let mut compressor = Compressor::new(level.into_libdeflate());
+
+    let bound = match format {
+        Format::Deflate => compressor.deflate_compress_bound(input.len()),
+        Format::Gzip => compressor.gzip_compress_bound(input.len()),
+        Format::Zlib => compressor.zlib_compress_bound(input.len()),
+    };
+    let mut output = vec![0; bound];
+
+    let len = match format {
+        Format::Deflate => compressor.deflate_compress(input, &mut output),
+        Format::Gzip => compressor.gzip_compress(input, &mut output),
+        Format::Zlib => compressor.zlib_compress(input, &mut output),
+    }
+    .map_err(|err| Error::new(ErrorKind::Other, err))?;
+
+    output.truncate(len);
+    Ok(output)
+}
+
+/// Decompresses `input`, which must be exactly one complete stream of `format`.
+///
+/// The decompressed data is produced into a single buffer, which is grown as needed up to `limit`
+/// bytes, after which an [`Error::LimitExceeded`](error::Error::LimitExceeded) is returned. For
+/// gzip the buffer starts at the length recorded in the stream, so it usually only takes one
+/// attempt.
+pub fn decompress(format: Format, input: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut decompressor = Decompressor::new();
+
+    let hint = match format {
+        Format::Gzip if input.len() >= 4 => {
+            u32::from_le_bytes(input[input.len() - 4..].try_into().unwrap()) as usize
+        }
+        _ => input.len().saturating_mul(4),
+    };
+    let mut len = hint.max(MIN_OUTPUT_LEN).min(limit);
+
+    loop {
+        let mut output = vec![0; len];
+        let result = match format {
+            Format::Deflate => decompressor.deflate_decompress(input, &mut output),
+            Format::Gzip => decompressor.gzip_decompress(input, &mut output),
+            Format::Zlib => decompressor.zlib_decompress(input, &mut output),
+        };
+
+        match result {
+            Ok(written) => {
+                output.truncate(written);
+                return Ok(output);
+            }
+            Err(DecompressionError::InsufficientSpace) if len < limit => {
+                len = len.saturating_mul(2).min(limit);
+            }
+            Err(DecompressionError::InsufficientSpace) => {
+                return Err(LimitExceeded::new(Limit::Output, limit as u64).into());
+            }
+            Err(err) => return Err(error::Error::corrupt(err)),
+        }
+    }
+}
@@ -1,12 +1,25 @@
 macro_rules! algos {
-    (@algo $algo:ident [$algo_s:expr] $decoder:ident $encoder:ident<$inner:ident> $({ $($constructor:tt)* })*) => {
+    (@algo $($mod:ident)::+; $(#[$attr:meta])* $algo:ident [$algo_s:expr]
+        $decoder:ident $({ $($decoder_constructor:tt)* })*
+        $encoder:ident<$inner:ident> $({ $($constructor:tt)* })*
+    ) => {
         #[cfg(feature = $algo_s)]
         decoder! {
             /// A
             #[doc = $algo_s]
             /// decoder, or decompressor.
+            $(#[$attr])*
             #[cfg_attr(docsrs, doc(cfg(feature = $algo_s)))]
-            $decoder
+            $decoder<$inner> {
+                pub fn new(inner: $inner) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codec::$decoder::new(),
+                        ),
+                    }
+                }
+            } $({ $($decoder_constructor)* })*
         }
 
         #[cfg(feature = $algo_s)]
@@ -14,6 +27,7 @@ macro_rules! algos {
             /// A
             #[doc = $algo_s]
             /// encoder, or compressor.
+            $(#[$attr])*
             #[cfg_attr(docsrs, doc(cfg(feature = $algo_s)))]
             $encoder<$inner> {
                 pub fn new(inner: $inner) -> Self {
@@ -24,7 +38,7 @@ macro_rules! algos {
     };
 
     ($($mod:ident)::+<$inner:ident>) => {
-        algos!(@algo brotli ["brotli"] BrotliDecoder BrotliEncoder<$inner> {
+        algos!(@algo $($mod)::+; brotli ["brotli"] BrotliDecoder BrotliEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 let params = brotli::enc::backward_references::BrotliEncoderParams::default();
                 Self {
@@ -36,7 +50,18 @@ macro_rules! algos {
             }
         });
 
-        algos!(@algo bzip2 ["bzip2"] BzDecoder BzEncoder<$inner> {
+        algos!(@algo $($mod)::+; bzip2 ["bzip2"] BzDecoder {
+            /// The decoder uses bzip2's small decompression mode, which needs a bit over half the
+            /// memory at about half the speed, see [`bzip2`](crate::bzip2).
+            pub fn with_small_memory(inner: $inner) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Decoder::new(
+                        inner,
+                        crate::codec::BzDecoder::with_small_memory(true),
+                    ),
+                }
+            }
+        } BzEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 Self {
                     inner: crate::$($mod::)+generic::Encoder::new(
@@ -47,7 +72,7 @@ macro_rules! algos {
             }
         });
 
-        algos!(@algo deflate ["deflate"] DeflateDecoder DeflateEncoder<$inner> {
+        algos!(@algo $($mod)::+; deflate ["deflate"] DeflateDecoder DeflateEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 Self {
                     inner: crate::$($mod::)+generic::Encoder::new(
@@ -58,7 +83,7 @@ macro_rules! algos {
             }
         });
 
-        algos!(@algo gzip ["gzip"] GzipDecoder GzipEncoder<$inner> {
+        algos!(@algo $($mod)::+; gzip ["gzip"] GzipDecoder GzipEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 Self {
                     inner: crate::$($mod::)+generic::Encoder::new(
@@ -69,7 +94,7 @@ macro_rules! algos {
             }
         });
 
-        algos!(@algo zlib ["zlib"] ZlibDecoder ZlibEncoder<$inner> {
+        algos!(@algo $($mod)::+; zlib ["zlib"] ZlibDecoder ZlibEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 Self {
                     inner: crate::$($mod::)+generic::Encoder::new(
@@ -80,7 +105,22 @@ macro_rules! algos {
             }
         });
 
-        algos!(@algo zstd ["zstd"] ZstdDecoder ZstdEncoder<$inner> {
+        algos!(@algo $($mod)::+; zstd ["zstd"] ZstdDecoder {
+            /// Frames which reference a dictionary are decoded with the dictionary looked up by
+            /// their dictionary ID from `provider`, see
+            /// [`DictionaryProvider`](crate::zstd::DictionaryProvider).
+            pub fn with_dictionary_provider(
+                inner: $inner,
+                provider: impl crate::zstd::DictionaryProvider + 'static,
+            ) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Decoder::new(
+                        inner,
+                        crate::codec::ZstdDecoder::with_dictionary_provider(Box::new(provider)),
+                    ),
+                }
+            }
+        } ZstdEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 Self {
                     inner: crate::$($mod::)+generic::Encoder::new(
@@ -89,9 +129,30 @@ macro_rules! algos {
                     ),
                 }
             }
+        } {
+            /// The output is split into independent frames of at most `max_frame_size` bytes of
+            /// uncompressed data each, which can be decoded on their own for parallel or random
+            /// access reads. Decoding all of it needs multi-member/frame decoding.
+            ///
+            /// Panics if `max_frame_size` is zero.
+            pub fn with_max_frame_size(
+                inner: $inner,
+                level: crate::Level,
+                max_frame_size: u64,
+            ) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codec::ZstdEncoder::with_max_frame_size(
+                            level.into_zstd(),
+                            max_frame_size,
+                        ),
+                    ),
+                }
+            }
         });
 
-        algos!(@algo xz ["xz"] XzDecoder XzEncoder<$inner> {
+        algos!(@algo $($mod)::+; xz ["xz"] XzDecoder XzEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 Self {
                     inner: crate::$($mod::)+generic::Encoder::new(
@@ -102,7 +163,7 @@ macro_rules! algos {
             }
         });
 
-        algos!(@algo lzma ["lzma"] LzmaDecoder LzmaEncoder<$inner> {
+        algos!(@algo $($mod)::+; lzma ["lzma"] LzmaDecoder LzmaEncoder<$inner> {
             pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
                 Self {
                     inner: crate::$($mod::)+generic::Encoder::new(
@@ -112,5 +173,399 @@ macro_rules! algos {
                 }
             }
         });
+
+        algos!(@algo $($mod)::+; lz4 ["lz4"] Lz4Decoder Lz4Encoder<$inner> {
+            pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codec::Lz4Encoder::new(level.into_lz4()),
+                    ),
+                }
+            }
+        });
+
+        algos!(@algo $($mod)::+;
+            ///
+            /// This operates on a single raw LZ4 block without any framing, as used by protocols
+            /// that carry their own framing around LZ4 blocks. The whole stream is buffered and
+            /// processed as one block once the input is finished, so the maximum uncompressed size
+            /// of the block must be agreed on by both sides.
+            lz4 ["lz4"]
+            Lz4BlockDecoder {
+                /// The `max_block_size` is the largest uncompressed size the block is allowed to
+                /// decompress to.
+                pub fn with_block_size(inner: $inner, max_block_size: usize) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codec::Lz4BlockDecoder::new_with_block_size(max_block_size),
+                        ),
+                    }
+                }
+            }
+            Lz4BlockEncoder<$inner> {
+                pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codec::Lz4BlockEncoder::new(level.into_lz4()),
+                        ),
+                    }
+                }
+            } {
+                /// The `max_block_size` is the largest uncompressed size the block is allowed to
+                /// have, writing more data than this will result in an error.
+                pub fn with_quality_and_block_size(
+                    inner: $inner,
+                    level: crate::Level,
+                    max_block_size: usize,
+                ) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codec::Lz4BlockEncoder::new_with_block_size(
+                                level.into_lz4(),
+                                max_block_size,
+                            ),
+                        ),
+                    }
+                }
+            }
+        );
+
+        algos!(@algo $($mod)::+; snappy ["snappy"] SnappyDecoder SnappyEncoder<$inner> {
+            /// Snappy has no compression levels, so `level` is ignored.
+            pub fn with_quality(inner: $inner, _level: crate::Level) -> Self {
+                Self {
+                    inner: crate::$($mod::)+generic::Encoder::new(
+                        inner,
+                        crate::codec::SnappyEncoder::new(),
+                    ),
+                }
+            }
+        });
+
+        algos!(@algo $($mod)::+;
+            ///
+            /// This operates on a single raw Snappy block without the framing format, as used by
+            /// formats that define their own chunking such as Parquet. The block starts with the
+            /// length of the uncompressed data, so the whole stream is buffered and processed as
+            /// one block once the input is finished.
+            snappy ["snappy"]
+            SnappyBlockDecoder
+            SnappyBlockEncoder<$inner> {
+                /// Snappy has no compression levels, so `level` is ignored.
+                pub fn with_quality(inner: $inner, _level: crate::Level) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codec::SnappyBlockEncoder::new(),
+                        ),
+                    }
+                }
+            }
+        );
+
+        #[cfg(feature = "isal")]
+        decoder! {
+            /// A gzip decoder, or decompressor, backed by ISA-L's igzip.
+            ///
+            /// This decodes the same data as the `gzip` decoder using the hand optimized x86_64
+            /// implementation of deflate from [ISA-L](https://github.com/intel/isa-l), which is
+            /// several times faster than zlib.
+            #[cfg_attr(docsrs, doc(cfg(feature = "isal")))]
+            IsalGzipDecoder<$inner> {
+                pub fn new(inner: $inner) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codec::IsalGzipDecoder::new(),
+                        ),
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "isal")]
+        encoder! {
+            /// A gzip encoder, or compressor, backed by ISA-L's igzip.
+            ///
+            /// This writes the same format as the `gzip` encoder using the hand optimized x86_64
+            /// implementation of deflate from [ISA-L](https://github.com/intel/isa-l), which is
+            /// several times faster than zlib at its lower levels, for a somewhat larger output.
+            #[cfg_attr(docsrs, doc(cfg(feature = "isal")))]
+            IsalGzipEncoder<$inner> {
+                pub fn new(inner: $inner) -> Self {
+                    Self::with_quality(inner, crate::Level::Default)
+                }
+            } {
+                /// ISA-L has levels from 0 to 3, defaulting to 1.
+                pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codec::IsalGzipEncoder::new(level.into_isal()),
+                        ),
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "isal")]
+        decoder! {
+            /// A deflate decoder, or decompressor, backed by ISA-L's igzip.
+            ///
+            /// This decodes the same data as the `deflate` decoder, see
+            /// [`IsalGzipDecoder`](IsalGzipDecoder).
+            #[cfg_attr(docsrs, doc(cfg(feature = "isal")))]
+            IsalDeflateDecoder<$inner> {
+                pub fn new(inner: $inner) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codec::IsalDeflateDecoder::new(),
+                        ),
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "isal")]
+        encoder! {
+            /// A deflate encoder, or compressor, backed by ISA-L's igzip.
+            ///
+            /// This writes the same format as the `deflate` encoder, see
+            /// [`IsalGzipEncoder`](IsalGzipEncoder).
+            #[cfg_attr(docsrs, doc(cfg(feature = "isal")))]
+            IsalDeflateEncoder<$inner> {
+                pub fn new(inner: $inner) -> Self {
+                    Self::with_quality(inner, crate::Level::Default)
+                }
+            } {
+                /// ISA-L has levels from 0 to 3, defaulting to 1.
+                pub fn with_quality(inner: $inner, level: crate::Level) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codec::IsalDeflateEncoder::new(level.into_isal()),
+                        ),
+                    }
+                }
+            }
+        }
+
+        decoder! {
+            /// An identity decoder, which passes data through unchanged.
+            ///
+            /// This allows uncompressed data to be handled the same way as the algorithms.
+            IdentityDecoder<$inner> {
+                pub fn new(inner: $inner) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codec::IdentityDecoder::new(),
+                        ),
+                    }
+                }
+            }
+        }
+
+        encoder! {
+            /// An identity encoder, which passes data through unchanged.
+            ///
+            /// This allows uncompressed data to be handled the same way as the algorithms.
+            IdentityEncoder<$inner> {
+                pub fn new(inner: $inner) -> Self {
+                    Self::with_quality(inner, crate::Level::Default)
+                }
+            } {
+                /// There is no compression, so `level` is ignored.
+                pub fn with_quality(inner: $inner, _level: crate::Level) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codec::IdentityEncoder::new(),
+                        ),
+                    }
+                }
+            }
+        }
     }
 }
+
+macro_rules! any {
+    ($($mod:ident)::+<$inner:ident>) => {
+        decoder! {
+            /// A decoder for an [`Algorithm`](crate::Algorithm) chosen at runtime, wrapping the
+            /// decoder for that algorithm.
+            AnyDecoder<$inner> {
+                pub fn new(inner: $inner, algorithm: crate::Algorithm) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codec::AnyDecoder::new(algorithm),
+                        ),
+                    }
+                }
+            } {
+                /// The data is decoded from multiple stacked encodings, with `algorithms` listed
+                /// in the order they were applied, so they are decoded in reverse order. With no
+                /// algorithms the data is passed through unchanged.
+                pub fn chained(inner: $inner, algorithms: &[crate::Algorithm]) -> Self {
+                    let decoders = algorithms
+                        .iter()
+                        .rev()
+                        .map(|&algorithm| crate::codec::AnyDecoder::new(algorithm))
+                        .collect();
+
+                    Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(
+                            inner,
+                            crate::codec::AnyDecoder::chain(decoders),
+                        ),
+                    }
+                }
+            } {
+                /// The decoder is chosen from an HTTP `Content-Encoding` value, a comma-separated
+                /// list of the content-codings in the order they were applied, such as
+                /// `gzip, br`. The supported content-codings are `identity`, `br`, `deflate`,
+                /// `gzip` (or `x-gzip`) and `zstd`, depending on the enabled features. Matching is
+                /// case-insensitive.
+                ///
+                /// `deflate` is specified as zlib data, but as some servers send raw deflate data
+                /// the stream is checked for a zlib header to decide how to decode it.
+                ///
+                /// Returns an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if
+                /// any of the content-codings is not supported.
+                pub fn from_content_encoding(
+                    inner: $inner,
+                    encoding: &str,
+                ) -> std::io::Result<Self> {
+                    let decoder = crate::codec::AnyDecoder::from_content_encoding(encoding)
+                        .ok_or_else(|| {
+                            std::io::Error::new(
+                                std::io::ErrorKind::InvalidInput,
+                                format!("unsupported content encoding `{}`", encoding),
+                            )
+                        })?;
+
+                    Ok(Self {
+                        inner: crate::$($mod::)+generic::Decoder::new(inner, decoder),
+                    })
+                }
+            }
+        }
+
+        encoder! {
+            /// An encoder for an [`Algorithm`](crate::Algorithm) chosen at runtime, wrapping the
+            /// encoder for that algorithm.
+            AnyEncoder<$inner> {
+                pub fn new(inner: $inner, algorithm: crate::Algorithm) -> Self {
+                    Self::with_quality(inner, algorithm, crate::Level::Default)
+                }
+            } {
+                pub fn with_quality(
+                    inner: $inner,
+                    algorithm: crate::Algorithm,
+                    level: crate::Level,
+                ) -> Self {
+                    Self {
+                        inner: crate::$($mod::)+generic::Encoder::new(
+                            inner,
+                            crate::codec::AnyEncoder::new(algorithm, level),
+                        ),
+                    }
+                }
+            } {
+                /// The encoding is negotiated from the `Accept-Encoding` header of a request, the
+                /// returned token is the content-coding used, to send as the `Content-Encoding` of
+                /// the response.
+                ///
+                /// The client's qvalues are respected, with ties broken by preferring `br`, `zstd`,
+                /// `gzip` then `deflate` out of those with their features enabled. `identity` is
+                /// chosen if the client prefers it or doesn't accept any of those, in which case
+                /// the data is passed through unchanged. Returns `None` if the client doesn't
+                /// accept `identity` either.
+                pub fn from_accept_encoding(
+                    inner: $inner,
+                    accept_encoding: &str,
+                ) -> Option<(&'static str, Self)> {
+                    Self::from_accept_encoding_with_quality(
+                        inner,
+                        accept_encoding,
+                        crate::Level::Default,
+                    )
+                }
+            } {
+                /// The encoding is negotiated from the `Accept-Encoding` header of a request as
+                /// for [`from_accept_encoding`](Self::from_accept_encoding), and compresses with
+                /// the specified compression level.
+                pub fn from_accept_encoding_with_quality(
+                    inner: $inner,
+                    accept_encoding: &str,
+                    level: crate::Level,
+                ) -> Option<(&'static str, Self)> {
+                    let (token, encoder) =
+                        crate::codec::AnyEncoder::from_accept_encoding(accept_encoding, level)?;
+
+                    Some((
+                        token,
+                        Self {
+                            inner: crate::$($mod::)+generic::Encoder::new(inner, encoder),
+                        },
+                    ))
+                }
+            }
+        }
+    };
+}
+
+macro_rules! backends {
+    ($($mod:ident)::+<$inner:ident>) => {
+        backends!(@ref ["deflate"] DeflateDecoder<$inner> flate2::Decompress);
+        backends!(@ref ["deflate"] DeflateEncoder<$inner> flate2::Compress);
+        backends!(@ref ["gzip"] GzipDecoder<$inner> flate2::Decompress);
+        backends!(@ref ["gzip"] GzipEncoder<$inner> flate2::Compress);
+        backends!(@ref ["zlib"] ZlibDecoder<$inner> flate2::Decompress);
+        backends!(@ref ["zlib"] ZlibEncoder<$inner> flate2::Compress);
+        backends!(@ref ["xz"] XzDecoder<$inner> xz2::stream::Stream);
+        backends!(@ref ["xz"] XzEncoder<$inner> xz2::stream::Stream);
+        backends!(@ref ["lzma"] LzmaDecoder<$inner> xz2::stream::Stream);
+        backends!(@ref ["lzma"] LzmaEncoder<$inner> xz2::stream::Stream);
+        // The zstd contexts aren't `Sync`, so they are only reachable mutably
+        backends!(@mut ["zstd"] ZstdDecoder<$inner> libzstd::stream::raw::Decoder<'static>);
+        backends!(@mut ["zstd"] ZstdEncoder<$inner> libzstd::stream::raw::Encoder<'static>);
+    };
+
+    (@ref [$algo_s:expr] $name:ident<$inner:ident> $backend:ty) => {
+        #[cfg(feature = $algo_s)]
+        impl<$inner> $name<$inner> {
+            /// Acquires a reference to the state of the library implementing the codec, to query
+            /// details of it which aren't exposed by this type.
+            pub fn backend(&self) -> &$backend {
+                self.inner.codec().backend()
+            }
+
+            backends!(@mut_fn $backend);
+        }
+    };
+
+    (@mut [$algo_s:expr] $name:ident<$inner:ident> $backend:ty) => {
+        #[cfg(feature = $algo_s)]
+        impl<$inner> $name<$inner> {
+            backends!(@mut_fn $backend);
+        }
+    };
+
+    (@mut_fn $backend:ty) => {
+        /// Acquires a mutable reference to the state of the library implementing the codec, to
+        /// query or change details of it which aren't exposed by this type.
+        ///
+        /// Note that care must be taken to avoid tampering with the state of the codec which may
+        /// otherwise confuse this type, such as by resetting it or passing data through it.
+        pub fn backend_mut(&mut self) -> &mut $backend {
+            self.inner.codec_mut().backend_mut()
+        }
+    };
+}
@@ -0,0 +1,243 @@
+//! Encoders and decoders taking and returning owned buffers, for completion-based runtimes such
+//! as `compio` and `monoio`.
+//!
+//! The IO operations of those runtimes take ownership of their buffer while the operation is in
+//! flight and hand it back when it completes, so the adapters built on `poll_read` and
+//! `poll_write`, which borrow the caller's buffers, can't be used with them. [`OwnedBufEncoder`]
+//! and [`OwnedBufDecoder`] don't do any IO themselves: each call takes the buffer a read
+//! completed with and the buffer to produce output into, processes all of the input, and hands
+//! both buffers back to be passed on to the next read and write. No buffer is borrowed across an
+//! `.await`, so they can be used with the owned buffer IO traits of any runtime.
+//!
+//! ```
+//! use async_compression::{owned::OwnedBufEncoder, Algorithm, Level};
+//! # #[cfg(feature = "gzip")]
+//! # {
+//! # let chunks: Vec<Vec<u8>> = vec![b"example".to_vec()];
+//! # let mut compressed = Vec::new();
+//!
+//! let mut encoder = OwnedBufEncoder::new(Algorithm::Gzip, Level::Default);
+//! let mut output = Vec::new();
+//! for input in chunks {
+//!     // `input` is the buffer a read completed with
+//!     let (result, input) = encoder.encode(input, output);
+//!     output = result?;
+//!     // ... submit a write of `output`, and reuse the buffers it and `input` are handed back in
+//! #   compressed.extend_from_slice(&output);
+//!     output.clear();
+//! }
+//! output = encoder.finish(output)?;
+//! # compressed.extend_from_slice(&output);
+//! # }
+//! # Ok::<_, std::io::Error>(())
+//! ```
+
+use std::io::{Error, ErrorKind, Result};
+
+use crate::{
+    codec::{AnyDecoder, AnyEncoder, Decode, Encode},
+    util::PartialBuffer,
+    Algorithm, Level,
+};
+
+/// The result of an operation which hands back the buffer it was given, whether or not it
+/// succeeded, as the IO operations of completion-based runtimes do.
+pub type BufResult<T, B> = (Result<T>, B);
+
+/// How much space is made at the end of the output for each call into the codec.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Runs `f` with space to write at the end of `output`, keeping whatever it writes.
+fn write_into<T>(
+    output: &mut Vec<u8>,
+    f: impl FnOnce(&mut PartialBuffer<&mut [u8]>) -> Result<T>,
+) -> Result<(T, usize)> {
+    let len = output.len();
+    output.resize(len + CHUNK_SIZE, 0);
+
+    let mut buffer = PartialBuffer::new(&mut output[len..]);
+    let result = f(&mut buffer);
+    let written = buffer.written().len();
+    output.truncate(len + written);
+
+    result.map(|result| (result, written))
+}
+
+/// An encoder for an [`Algorithm`] chosen at runtime, compressing owned input buffers into owned
+/// output buffers, see the [module level documentation](self).
+#[derive(Debug)]
+pub struct OwnedBufEncoder {
+    encoder: AnyEncoder,
+    total_in: u64,
+    total_out: u64,
+    finished: bool,
+}
+
+impl OwnedBufEncoder {
+    /// Creates a new encoder compressing with `algorithm` at `level`.
+    pub fn new(algorithm: Algorithm, level: Level) -> Self {
+        Self {
+            encoder: AnyEncoder::new(algorithm, level),
+            total_in: 0,
+            total_out: 0,
+            finished: false,
+        }
+    }
+
+    /// Compresses all of `input`, appending the compressed data to `output`.
+    ///
+    /// Returns `output` with the data appended along with `input`, which is handed back even if
+    /// compressing fails. Some of the compressed data may be held in the encoder until more input
+    /// is given, or it is flushed or finished.
+    pub fn encode<B: AsRef<[u8]>>(
+        &mut self,
+        input: B,
+        mut output: Vec<u8>,
+    ) -> BufResult<Vec<u8>, B> {
+        let result = self.encode_into(input.as_ref(), &mut output);
+        (result.map(|()| output), input)
+    }
+
+    fn encode_into(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        if self.finished {
+            return Err(Error::new(ErrorKind::Other, "encode after finish"));
+        }
+
+        let mut input = PartialBuffer::new(input);
+        while !input.unwritten().is_empty() {
+            let before = input.written().len();
+            let ((), written) =
+                write_into(output, |output| self.encoder.encode(&mut input, output))?;
+            self.total_in += (input.written().len() - before) as u64;
+            self.total_out += written as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Flushes the compressed data of all the input given so far to `output`, so it can be
+    /// decompressed without waiting for the rest of the stream.
+    pub fn flush(&mut self, mut output: Vec<u8>) -> Result<Vec<u8>> {
+        loop {
+            let (done, written) = write_into(&mut output, |output| self.encoder.flush(output))?;
+            self.total_out += written as u64;
+            if done {
+                return Ok(output);
+            }
+        }
+    }
+
+    /// Finishes the compressed stream, appending the rest of it to `output`.
+    ///
+    /// No more input can be given after this.
+    pub fn finish(&mut self, mut output: Vec<u8>) -> Result<Vec<u8>> {
+        self.finished = true;
+        loop {
+            let (done, written) = write_into(&mut output, |output| self.encoder.finish(output))?;
+            self.total_out += written as u64;
+            if done {
+                return Ok(output);
+            }
+        }
+    }
+
+    /// Returns the total number of uncompressed bytes this encoder has been given so far.
+    pub fn total_in(&self) -> u64 {
+        self.total_in
+    }
+
+    /// Returns the total number of compressed bytes this encoder has produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.total_out
+    }
+}
+
+/// A decoder for an [`Algorithm`] chosen at runtime, decompressing owned input buffers into
+/// owned output buffers, see the [module level documentation](self).
+#[derive(Debug)]
+pub struct OwnedBufDecoder {
+    decoder: AnyDecoder,
+    total_in: u64,
+    total_out: u64,
+    done: bool,
+}
+
+impl OwnedBufDecoder {
+    /// Creates a new decoder decompressing data compressed with `algorithm`.
+    pub fn new(algorithm: Algorithm) -> Self {
+        Self {
+            decoder: AnyDecoder::new(algorithm),
+            total_in: 0,
+            total_out: 0,
+            done: false,
+        }
+    }
+
+    /// Decompresses `input`, appending the decompressed data to `output`.
+    ///
+    /// Returns `output` with the data appended along with `input`, which is handed back even if
+    /// decompressing fails. All of `input` is consumed unless the end of the compressed stream is
+    /// reached partway through it, in which case [`is_done`](Self::is_done) returns `true` and the
+    /// data following the stream starts after the first [`total_in`](Self::total_in) bytes of the
+    /// input given.
+    pub fn decode<B: AsRef<[u8]>>(
+        &mut self,
+        input: B,
+        mut output: Vec<u8>,
+    ) -> BufResult<Vec<u8>, B> {
+        let result = self.decode_into(input.as_ref(), &mut output);
+        (result.map(|()| output), input)
+    }
+
+    fn decode_into(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let mut input = PartialBuffer::new(input);
+        while !self.done && !input.unwritten().is_empty() {
+            let before = input.written().len();
+            let (done, written) =
+                write_into(output, |output| self.decoder.decode(&mut input, output))?;
+            self.total_in += (input.written().len() - before) as u64;
+            self.total_out += written as u64;
+
+            if done {
+                self.finish_into(output)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish_into(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        while !self.done {
+            let (done, written) = write_into(output, |output| self.decoder.finish(output))?;
+            self.total_out += written as u64;
+            self.done = done;
+        }
+
+        Ok(())
+    }
+
+    /// Finishes decoding once there is no more input, appending the rest of the decompressed data
+    /// to `output`.
+    ///
+    /// Returns an error of kind [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the
+    /// compressed stream is incomplete.
+    pub fn finish(&mut self, mut output: Vec<u8>) -> Result<Vec<u8>> {
+        self.finish_into(&mut output)?;
+        Ok(output)
+    }
+
+    /// Returns whether the end of the compressed stream has been reached.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Returns the total number of compressed bytes this decoder has consumed so far.
+    pub fn total_in(&self) -> u64 {
+        self.total_in
+    }
+
+    /// Returns the total number of decompressed bytes this decoder has produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.total_out
+    }
+}
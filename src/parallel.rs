@@ -0,0 +1,344 @@
+//! Compressing a single stream on many threads at once.
+//!
+//! The `ParallelEncoder` types in the `write` modules split their input into blocks of a fixed
+//! size, and compress each block into a complete, independent compressed stream on an
+//! [`Executor`], such as a `rayon` thread pool. The blocks are written out in order, so the output
+//! is the concatenation of those streams.
+//!
+//! This only works for formats where concatenated streams decode to the concatenation of their
+//! contents: `bzip2`, `gzip`, `lz4`, `snappy`, `xz` and `zstd`. Decoders need multi-member/frame
+//! decoding enabled to read past the first block. Each block is compressed without the context of
+//! the data before it, which costs some compression ratio, more so with smaller blocks.
+//!
+//! ```
+//! # #[cfg(all(feature = "tokio", feature = "gzip", feature = "rayon"))]
+//! # fn main() -> std::io::Result<()> {
+//! use async_compression::{
+//!     parallel::RayonGlobalPool, tokio::write::ParallelEncoder, Algorithm, Level,
+//! };
+//!
+//! let encoder =
+//!     ParallelEncoder::new(Vec::new(), Algorithm::Gzip, Level::Default, RayonGlobalPool)?
+//!         .with_block_size(128 * 1024);
+//! // ... write to the encoder, each 128 KiB of input is compressed on rayon's global thread pool
+//! #   drop(encoder);
+//! # Ok(())
+//! # }
+//! # #[cfg(not(all(feature = "tokio", feature = "gzip", feature = "rayon")))]
+//! # fn main() {}
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{Error, ErrorKind, Result},
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    codec::{AnyEncoder, Encode},
+    util::PartialBuffer,
+    Algorithm, Level,
+};
+
+const DEFAULT_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Writes compressed output to the underlying writer of an encoder.
+type WriteOutput<'a> = dyn FnMut(&mut Context<'_>, &[u8]) -> Poll<Result<usize>> + 'a;
+
+/// Runs the compression of blocks for a parallel encoder.
+///
+/// This is implemented for `rayon` thread pools with the `rayon` feature, and can be implemented
+/// to use any other thread pool.
+pub trait Executor: Send + Sync {
+    /// Runs `task` on another thread. Each task compresses one block, which the encoder waits for
+    /// once it needs to write it out, so every task must eventually be run.
+    fn execute(&self, task: Box<dyn FnOnce() + Send>);
+}
+
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+impl Executor for rayon::ThreadPool {
+    fn execute(&self, task: Box<dyn FnOnce() + Send>) {
+        self.spawn(task);
+    }
+}
+
+/// Runs tasks on the global `rayon` thread pool.
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RayonGlobalPool;
+
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+impl Executor for RayonGlobalPool {
+    fn execute(&self, task: Box<dyn FnOnce() + Send>) {
+        rayon::spawn(task);
+    }
+}
+
+impl<E: Executor + ?Sized> Executor for Arc<E> {
+    fn execute(&self, task: Box<dyn FnOnce() + Send>) {
+        (**self).execute(task);
+    }
+}
+
+/// Compresses `input` into a complete stream.
+fn compress(algorithm: Algorithm, level: Level, input: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = AnyEncoder::new(algorithm, level);
+    let mut input = PartialBuffer::new(input);
+    let mut output = Vec::with_capacity(input.unwritten().len() / 2 + 64);
+
+    // Calls `f` with space for more output at the end of `output`
+    let mut with_spare = |f: &mut dyn FnMut(&mut PartialBuffer<&mut [u8]>) -> Result<bool>| {
+        let len = output.len();
+        output.resize(len + 16 * 1024, 0);
+        let mut spare = PartialBuffer::new(&mut output[len..]);
+        let result = f(&mut spare);
+        let written = spare.written().len();
+        output.truncate(len + written);
+        result
+    };
+
+    while !input.unwritten().is_empty() {
+        with_spare(&mut |output| encoder.encode(&mut input, output).map(|()| false))?;
+    }
+    while !with_spare(&mut |output| encoder.finish(output))? {}
+
+    Ok(output)
+}
+
+#[derive(Default)]
+struct SlotState {
+    result: Option<Result<Vec<u8>>>,
+    waker: Option<Waker>,
+}
+
+/// Where the task compressing a block stores its output.
+#[derive(Default)]
+struct Slot {
+    state: Mutex<SlotState>,
+}
+
+impl Slot {
+    fn complete(&self, result: Result<Vec<u8>>) {
+        let mut state = self.state.lock().unwrap();
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Takes the output if the block has been compressed, registering the waker to be woken
+    /// when it is if `cx` is given.
+    fn poll_take(&self, cx: Option<&mut Context<'_>>) -> Poll<Result<Vec<u8>>> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                if let Some(cx) = cx {
+                    state.waker = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The state shared by the `ParallelEncoder` types, independent of the IO traits.
+pub(crate) struct Blocks {
+    executor: Arc<dyn Executor>,
+    algorithm: Algorithm,
+    level: Level,
+    block_size: usize,
+    max_in_flight: usize,
+    input: Vec<u8>,
+    in_flight: VecDeque<Arc<Slot>>,
+    output: Vec<u8>,
+    pos: usize,
+    submitted: bool,
+    total_in: u64,
+    total_out: u64,
+}
+
+impl fmt::Debug for Blocks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blocks")
+            .field("algorithm", &self.algorithm)
+            .field("block_size", &self.block_size)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("in_flight", &self.in_flight.len())
+            .finish()
+    }
+}
+
+impl Blocks {
+    pub(crate) fn new(
+        algorithm: Algorithm,
+        level: Level,
+        executor: Arc<dyn Executor>,
+    ) -> Result<Self> {
+        let concatenable = match algorithm {
+            #[cfg(feature = "bzip2")]
+            Algorithm::Bzip2 => true,
+            #[cfg(feature = "gzip")]
+            Algorithm::Gzip => true,
+            #[cfg(feature = "lz4")]
+            Algorithm::Lz4 => true,
+            #[cfg(feature = "snappy")]
+            Algorithm::Snappy => true,
+            #[cfg(feature = "xz")]
+            Algorithm::Xz => true,
+            #[cfg(feature = "zstd")]
+            Algorithm::Zstd => true,
+            _ => false,
+        };
+        if !concatenable {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{:?} streams can't be concatenated, so can't be compressed in parallel",
+                    algorithm
+                ),
+            ));
+        }
+
+        Ok(Self {
+            executor,
+            algorithm,
+            level,
+            block_size: DEFAULT_BLOCK_SIZE,
+            max_in_flight: std::thread::available_parallelism().map_or(4, |n| n.get() * 2),
+            input: Vec::new(),
+            in_flight: VecDeque::new(),
+            output: Vec::new(),
+            pos: 0,
+            submitted: false,
+            total_in: 0,
+            total_out: 0,
+        })
+    }
+
+    pub(crate) fn set_block_size(&mut self, block_size: usize) {
+        assert!(block_size > 0, "block size must be non-zero");
+        self.block_size = block_size;
+    }
+
+    pub(crate) fn set_max_in_flight(&mut self, max_in_flight: usize) {
+        assert!(max_in_flight > 0, "blocks in flight must be non-zero");
+        self.max_in_flight = max_in_flight;
+    }
+
+    pub(crate) fn total_in(&self) -> u64 {
+        self.total_in
+    }
+
+    pub(crate) fn total_out(&self) -> u64 {
+        self.total_out
+    }
+
+    /// Hands the input gathered so far to the executor as the next block.
+    fn submit(&mut self) {
+        let input = core::mem::take(&mut self.input);
+        let slot = Arc::new(Slot::default());
+        let (algorithm, level) = (self.algorithm, self.level);
+
+        let task_slot = slot.clone();
+        self.executor.execute(Box::new(move || {
+            task_slot.complete(compress(algorithm, level, &input));
+        }));
+
+        self.in_flight.push_back(slot);
+        self.submitted = true;
+    }
+
+    /// Writes out the blocks compressed so far in order with `write`, waiting for all of them if
+    /// `all` is set, or only until fewer than the maximum number of blocks are in flight.
+    fn poll_drain(
+        &mut self,
+        cx: &mut Context<'_>,
+        write: &mut WriteOutput<'_>,
+        all: bool,
+    ) -> Poll<Result<()>> {
+        loop {
+            while self.pos < self.output.len() {
+                let len = match write(cx, &self.output[self.pos..]) {
+                    Poll::Ready(result) => result?,
+                    Poll::Pending => return Poll::Pending,
+                };
+                if len == 0 {
+                    return Poll::Ready(Err(ErrorKind::WriteZero.into()));
+                }
+                self.pos += len;
+                self.total_out += len as u64;
+            }
+
+            let front = match self.in_flight.front() {
+                Some(front) => front,
+                None => return Poll::Ready(Ok(())),
+            };
+
+            let must_wait = all || self.in_flight.len() >= self.max_in_flight;
+            match front.poll_take(if must_wait { Some(cx) } else { None }) {
+                Poll::Ready(output) => {
+                    self.in_flight.pop_front();
+                    self.output = output?;
+                    self.pos = 0;
+                }
+                Poll::Pending if must_wait => return Poll::Pending,
+                Poll::Pending => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    pub(crate) fn poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        write: &mut WriteOutput<'_>,
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if self.input.len() >= self.block_size {
+            match self.poll_drain(cx, write, false) {
+                Poll::Ready(result) => result?,
+                Poll::Pending => return Poll::Pending,
+            }
+            self.submit();
+        }
+
+        let len = core::cmp::min(buf.len(), self.block_size - self.input.len());
+        self.input.extend_from_slice(&buf[..len]);
+        self.total_in += len as u64;
+        Poll::Ready(Ok(len))
+    }
+
+    /// Ends the current block early and writes out everything written so far.
+    pub(crate) fn poll_flush(
+        &mut self,
+        cx: &mut Context<'_>,
+        write: &mut WriteOutput<'_>,
+    ) -> Poll<Result<()>> {
+        if !self.input.is_empty() {
+            self.submit();
+        }
+        self.poll_drain(cx, write, true)
+    }
+
+    /// Writes out everything, compressing an empty block if nothing was written so the output is
+    /// still a valid compressed stream.
+    pub(crate) fn poll_close(
+        &mut self,
+        cx: &mut Context<'_>,
+        write: &mut WriteOutput<'_>,
+    ) -> Poll<Result<()>> {
+        if !self.submitted {
+            self.submit();
+        }
+        self.poll_flush(cx, write)
+    }
+}
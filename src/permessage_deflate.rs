@@ -0,0 +1,322 @@
+//! Per-message compression for WebSocket connections using the `permessage-deflate` extension
+//! from [RFC 7692](https://tools.ietf.org/html/rfc7692).
+//!
+//! Unlike the rest of this crate, which models a single continuous stream, each WebSocket message
+//! is compressed on its own. The compressed data of each message is flushed and has the trailing
+//! `0x00 0x00 0xff 0xff` of the flush removed, and by default the sliding window is kept between
+//! messages so that later messages can refer back to data from earlier ones. This "context
+//! takeover" can be disabled separately for each direction when negotiating the extension.
+//!
+//! ```
+//! use async_compression::permessage_deflate::{Params, PerMessageDeflate, Role};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! // The client offered the extension, and the server accepted it with these parameters
+//! let params: Params = "permessage-deflate; client_no_context_takeover".parse()?;
+//!
+//! let mut server = PerMessageDeflate::new(Role::Server, &params)?;
+//! let mut client = PerMessageDeflate::new(Role::Client, &params)?;
+//!
+//! let compressed = server.compress(b"Hello")?;
+//! assert_eq!(client.decompress(&compressed)?, b"Hello");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    codec::{Decode, Encode, FlateDecoder, FlateEncoder},
+    util::PartialBuffer,
+    Level,
+};
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
+};
+
+/// The extension name used in the `Sec-WebSocket-Extensions` header.
+const EXTENSION_NAME: &str = "permessage-deflate";
+
+/// The empty stored block written by a sync flush, removed from the end of each compressed
+/// message and added back before decompressing it.
+const TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The size of the LZ77 sliding window used when compressing, as a base-2 logarithm.
+const WINDOW_BITS: u8 = 15;
+
+/// Which end of the WebSocket connection is compressing and decompressing messages, this decides
+/// which of the negotiated [`Params`] apply to each direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// The client end, which compresses with the `client_*` parameters and decompresses with the
+    /// `server_*` parameters.
+    Client,
+
+    /// The server end, which compresses with the `server_*` parameters and decompresses with the
+    /// `client_*` parameters.
+    Server,
+}
+
+/// The parameters of the `permessage-deflate` extension, as sent in the
+/// `Sec-WebSocket-Extensions` header.
+///
+/// These can be parsed from and formatted to a single extension from the header, such as
+/// `permessage-deflate; server_no_context_takeover; client_max_window_bits=10`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Params {
+    /// Whether the server resets its compression context after each message.
+    pub server_no_context_takeover: bool,
+
+    /// Whether the client resets its compression context after each message.
+    pub client_no_context_takeover: bool,
+
+    /// The largest sliding window the server may compress with, as a base-2 logarithm from 8 to
+    /// 15.
+    pub server_max_window_bits: Option<u8>,
+
+    /// The largest sliding window the client may compress with, as a base-2 logarithm from 8 to
+    /// 15.
+    ///
+    /// In a client's offer this parameter may be sent without a value, to signal that the client
+    /// supports it, which is parsed as `Some(15)`.
+    pub client_max_window_bits: Option<u8>,
+}
+
+impl FromStr for Params {
+    type Err = Error;
+
+    /// Parses the parameters from a single extension of a `Sec-WebSocket-Extensions` header,
+    /// returning an error of kind [`InvalidData`](ErrorKind::InvalidData) if it isn't a valid
+    /// `permessage-deflate` extension.
+    fn from_str(extension: &str) -> Result<Self> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_owned());
+
+        let mut parts = extension.split(';').map(str::trim);
+
+        match parts.next() {
+            Some(name) if name.eq_ignore_ascii_case(EXTENSION_NAME) => {}
+            _ => return Err(invalid("not a permessage-deflate extension")),
+        }
+
+        let mut params = Params::default();
+        let (mut server_window, mut client_window) = (false, false);
+
+        for part in parts {
+            let (name, value) = match part.find('=') {
+                Some(index) => {
+                    let value = part[index + 1..].trim().trim_matches('"');
+                    (part[..index].trim(), Some(value))
+                }
+                None => (part, None),
+            };
+
+            let flag = |seen: &mut bool| {
+                if value.is_some() {
+                    Err(invalid("unexpected permessage-deflate parameter value"))
+                } else if std::mem::replace(seen, true) {
+                    Err(invalid("duplicate permessage-deflate parameter"))
+                } else {
+                    Ok(())
+                }
+            };
+
+            let window_bits = |seen: &mut bool, value: Option<&str>| {
+                if std::mem::replace(seen, true) {
+                    return Err(invalid("duplicate permessage-deflate parameter"));
+                }
+                match value.map(str::parse) {
+                    Some(Ok(bits @ 8..=15)) => Ok(bits),
+                    None => Ok(WINDOW_BITS),
+                    Some(_) => Err(invalid("invalid permessage-deflate window bits")),
+                }
+            };
+
+            match &*name.to_ascii_lowercase() {
+                "server_no_context_takeover" => flag(&mut params.server_no_context_takeover)?,
+                "client_no_context_takeover" => flag(&mut params.client_no_context_takeover)?,
+                "server_max_window_bits" => {
+                    // Only the client's window bits may be sent without a value
+                    if value.is_none() {
+                        return Err(invalid("missing permessage-deflate parameter value"));
+                    }
+                    params.server_max_window_bits = Some(window_bits(&mut server_window, value)?);
+                }
+                "client_max_window_bits" => {
+                    params.client_max_window_bits = Some(window_bits(&mut client_window, value)?);
+                }
+                _ => return Err(invalid("unknown permessage-deflate parameter")),
+            }
+        }
+
+        Ok(params)
+    }
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(EXTENSION_NAME)?;
+        if self.server_no_context_takeover {
+            f.write_str("; server_no_context_takeover")?;
+        }
+        if self.client_no_context_takeover {
+            f.write_str("; client_no_context_takeover")?;
+        }
+        if let Some(bits) = self.server_max_window_bits {
+            write!(f, "; server_max_window_bits={}", bits)?;
+        }
+        if let Some(bits) = self.client_max_window_bits {
+            write!(f, "; client_max_window_bits={}", bits)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compresses and decompresses the messages of one end of a WebSocket connection which
+/// negotiated the `permessage-deflate` extension.
+///
+/// Messages must be passed to [`compress`](Self::compress) and
+/// [`decompress`](Self::decompress) in the order they are sent and received, as by default each
+/// message is compressed relative to the ones before it. Only messages which were sent with the
+/// "Per-Message Compressed" bit set should be decompressed.
+#[derive(Debug)]
+pub struct PerMessageDeflate {
+    encoder: FlateEncoder,
+    decoder: FlateDecoder,
+    level: Level,
+    compress_no_context_takeover: bool,
+    decompress_no_context_takeover: bool,
+}
+
+impl PerMessageDeflate {
+    /// Creates a new compressor and decompressor for the given end of the connection, using the
+    /// negotiated parameters.
+    ///
+    /// Returns an error of kind [`InvalidInput`](ErrorKind::InvalidInput) if the parameters limit
+    /// the sliding window this end compresses with to less than 15 bits, which isn't supported.
+    /// Any window size is supported for decompression.
+    pub fn new(role: Role, params: &Params) -> Result<Self> {
+        Self::with_quality(role, params, Level::Default)
+    }
+
+    /// Creates a new compressor and decompressor for the given end of the connection, using the
+    /// negotiated parameters and compressing with the specified compression level.
+    ///
+    /// See [`new`](Self::new) for the parameters which are supported.
+    pub fn with_quality(role: Role, params: &Params, level: Level) -> Result<Self> {
+        let (compress_window_bits, compress_no_context_takeover, decompress_no_context_takeover) =
+            match role {
+                Role::Client => (
+                    params.client_max_window_bits,
+                    params.client_no_context_takeover,
+                    params.server_no_context_takeover,
+                ),
+                Role::Server => (
+                    params.server_max_window_bits,
+                    params.server_no_context_takeover,
+                    params.client_no_context_takeover,
+                ),
+            };
+
+        if matches!(compress_window_bits, Some(bits) if bits < WINDOW_BITS) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "compressing with a permessage-deflate window smaller than 15 bits is not supported",
+            ));
+        }
+
+        Ok(Self {
+            encoder: FlateEncoder::new(level.into_flate2(), false),
+            decoder: FlateDecoder::new(false),
+            level,
+            compress_no_context_takeover,
+            decompress_no_context_takeover,
+        })
+    }
+
+    /// Compresses a message to be sent with the "Per-Message Compressed" bit set.
+    pub fn compress(&mut self, message: &[u8]) -> Result<Vec<u8>> {
+        // An empty message is sent as a lone empty stored block, which leaves the context as is
+        if message.is_empty() {
+            return Ok(vec![0x00]);
+        }
+
+        let encoder = &mut self.encoder;
+        let mut input = PartialBuffer::new(message);
+        let mut output = Vec::new();
+
+        while !input.unwritten().is_empty() {
+            write_into(&mut output, |output| encoder.encode(&mut input, output))?;
+        }
+        while !write_into(&mut output, |output| encoder.flush(output))? {}
+
+        if !output.ends_with(&TAIL) {
+            return Err(Error::new(
+                ErrorKind::Other,
+                "flushed deflate data did not end with an empty stored block",
+            ));
+        }
+        output.truncate(output.len() - TAIL.len());
+
+        if self.compress_no_context_takeover {
+            self.encoder = FlateEncoder::new(self.level.into_flate2(), false);
+        }
+
+        Ok(output)
+    }
+
+    /// Decompresses a message that was received with the "Per-Message Compressed" bit set.
+    ///
+    /// If the message contains a deflate block with `BFINAL` set, anything after that block
+    /// (normally an empty stored block) is ignored and the next message is decompressed with a
+    /// new context.
+    pub fn decompress(&mut self, message: &[u8]) -> Result<Vec<u8>> {
+        let decoder = &mut self.decoder;
+        let mut output = Vec::new();
+        let mut ended = false;
+
+        // Add back the end of the flush so everything the message contains is decompressed
+        for chunk in [message, &TAIL[..]].iter() {
+            let mut input = PartialBuffer::new(chunk);
+            while !ended && !input.unwritten().is_empty() {
+                ended = write_into(&mut output, |output| decoder.decode(&mut input, output))?;
+            }
+        }
+        if !ended {
+            while !write_into(&mut output, |output| decoder.flush(output))? {}
+        }
+
+        // A final block ends the sender's context, so the next message starts with a new one
+        if ended || self.decompress_no_context_takeover {
+            self.decoder.reinit()?;
+        }
+
+        Ok(output)
+    }
+}
+
+/// Runs `f` with space to write at the end of `output`, keeping whatever it writes.
+fn write_into<T>(
+    output: &mut Vec<u8>,
+    f: impl FnOnce(&mut PartialBuffer<&mut [u8]>) -> Result<T>,
+) -> Result<T> {
+    const CHUNK_SIZE: usize = 8_000;
+
+    let len = output.len();
+    output.resize(len + CHUNK_SIZE, 0);
+
+    let mut buffer = PartialBuffer::new(&mut output[len..]);
+    let result = f(&mut buffer);
+    let written = buffer.written().len();
+
+    output.truncate(len + written);
+    result
+}
+
+const _: () = {
+    fn _assert() {
+        use crate::util::{_assert_send, _assert_sync};
+
+        _assert_send::<PerMessageDeflate>();
+        _assert_sync::<PerMessageDeflate>();
+    }
+};
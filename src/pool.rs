@@ -0,0 +1,175 @@
+//! A pool of buffers that can be shared between encoders and decoders.
+//!
+//! Each encoder and decoder in the `write` and `bufwrite` modules, and the `BufReader` used by
+//! the `bufread` modules, needs an internal buffer. When many short-lived adapters are created,
+//! such as one per HTTP request, allocating and freeing those buffers every time can add up. The
+//! `with_pool` constructors instead take their buffers from a [`BufferPool`] and give them back
+//! when the adapter is dropped, so they can be reused by the next one.
+//!
+//! ```
+//! # #[cfg(all(feature = "tokio", feature = "gzip"))]
+//! # fn main() {
+//! use async_compression::{pool::BufferPool, tokio::write::GzipEncoder};
+//!
+//! // Keep up to 64 idle buffers of 8 KiB around for reuse
+//! let pool = BufferPool::new(8 * 1024, 64);
+//!
+//! for _ in 0..3 {
+//!     let encoder = GzipEncoder::with_pool(&pool, Vec::new());
+//!     // ... write to the encoder, its buffer is taken from the pool on first use and given back
+//!     // when it is dropped
+//! #   drop(encoder);
+//! }
+//! # }
+//! # #[cfg(not(all(feature = "tokio", feature = "gzip")))]
+//! # fn main() {}
+//! ```
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+/// A pool of fixed-size buffers, which can be cheaply cloned to share it between tasks and
+/// threads.
+///
+/// Buffers are only allocated when an adapter first needs one and there is no idle buffer in the
+/// pool. At most `max_idle` buffers are kept when they are given back, any more are freed.
+/// Buffers are zeroed before being reused so no data is shared between adapters.
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    buffer_size: usize,
+    max_idle: usize,
+    idle: Mutex<Vec<Box<[u8]>>>,
+}
+
+impl BufferPool {
+    /// Creates a new, empty pool of buffers of `buffer_size` bytes, keeping at most `max_idle` of
+    /// them around for reuse.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer_size` is zero.
+    pub fn new(buffer_size: usize, max_idle: usize) -> Self {
+        assert!(buffer_size > 0, "buffer capacity must be non-zero");
+
+        Self {
+            inner: Arc::new(Inner {
+                buffer_size,
+                max_idle,
+                idle: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// The size of the buffers in this pool.
+    pub fn buffer_size(&self) -> usize {
+        self.inner.buffer_size
+    }
+
+    /// The number of buffers currently waiting in this pool to be reused.
+    pub fn idle(&self) -> usize {
+        self.inner.idle.lock().map_or(0, |idle| idle.len())
+    }
+
+    fn take(&self) -> Box<[u8]> {
+        let buffer = self.inner.idle.lock().ok().and_then(|mut idle| idle.pop());
+        buffer.unwrap_or_else(|| vec![0; self.inner.buffer_size].into())
+    }
+
+    fn give(&self, mut buffer: Box<[u8]>) {
+        if let Ok(mut idle) = self.inner.idle.lock() {
+            if idle.len() < self.inner.max_idle {
+                buffer.fill(0);
+                idle.push(buffer);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("buffer_size", &self.inner.buffer_size)
+            .field("max_idle", &self.inner.max_idle)
+            .field("idle", &self.idle())
+            .finish()
+    }
+}
+
+/// An internal buffer of an adapter, which is only allocated (or taken from its pool) the first
+/// time it is mutably accessed, and is given back to its pool when dropped.
+///
+/// Until then it derefs to an empty slice, use [`len`](Self::len) for its size.
+pub(crate) struct Buffer {
+    data: Box<[u8]>,
+    size: usize,
+    pool: Option<BufferPool>,
+}
+
+impl Buffer {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            data: Box::default(),
+            size,
+            pool: None,
+        }
+    }
+
+    pub(crate) fn pooled(pool: &BufferPool) -> Self {
+        Self {
+            data: Box::default(),
+            size: pool.buffer_size(),
+            pool: Some(pool.clone()),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.size
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl DerefMut for Buffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        if self.data.is_empty() {
+            self.data = match &self.pool {
+                Some(pool) => pool.take(),
+                None => vec![0; self.size].into(),
+            };
+        }
+        &mut self.data
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        if let Some(pool) = &self.pool {
+            if !self.data.is_empty() {
+                pool.give(std::mem::take(&mut self.data));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Buffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Buffer")
+            .field("size", &self.size)
+            .field("allocated", &!self.data.is_empty())
+            .field("pooled", &self.pool.is_some())
+            .finish()
+    }
+}
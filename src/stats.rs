@@ -0,0 +1,364 @@
+//! Statistics about the data that went through an encoder or decoder.
+
+use std::{
+    cmp::min,
+    io::Result,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    codec::{Decode, Encode},
+    error::{Limit, LimitExceeded},
+    util::PartialBuffer,
+};
+
+/// A snapshot of the statistics of an encoder or decoder, as returned by their `stats` method.
+///
+/// All counts are taken at the codec, so for adapters that buffer their input or output they may
+/// differ from what has been read from or written to the underlying stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub(crate) total_in: u64,
+    pub(crate) total_out: u64,
+    pub(crate) flushes: u64,
+    pub(crate) frames: u64,
+    pub(crate) codec_time: Option<Duration>,
+}
+
+impl Stats {
+    /// The total number of bytes consumed by the codec, compressed bytes for a decoder and
+    /// uncompressed bytes for an encoder.
+    pub fn total_in(&self) -> u64 {
+        self.total_in
+    }
+
+    /// The total number of bytes produced by the codec, uncompressed bytes for a decoder and
+    /// compressed bytes for an encoder.
+    pub fn total_out(&self) -> u64 {
+        self.total_out
+    }
+
+    /// The number of bytes produced for every byte consumed, or `None` if nothing has been
+    /// consumed yet.
+    ///
+    /// For an encoder this is the compressed size relative to the original size, so lower is
+    /// better, for a decoder it is the inverse.
+    pub fn ratio(&self) -> Option<f64> {
+        if self.total_in == 0 {
+            None
+        } else {
+            Some(self.total_out as f64 / self.total_in as f64)
+        }
+    }
+
+    /// The number of times a flush of the codec has completed, this is always zero for readers.
+    pub fn flushes(&self) -> u64 {
+        self.flushes
+    }
+
+    /// The number of frames or members that have been completely encoded or decoded.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// The cumulative wall time spent inside the codec's encode, decode, flush and finish calls,
+    /// or `None` if recording it has not been enabled with `record_codec_time`.
+    pub fn codec_time(&self) -> Option<Duration> {
+        self.codec_time
+    }
+}
+
+/// Wraps a codec to keep its [`Stats`] up to date, emitting a `tracing` event whenever a flush
+/// completes, a frame finishes or the codec fails if that feature is enabled.
+///
+/// It also enforces the limits configured on a decoder, never letting the codec consume input past
+/// the input limit, or produce more than one byte past the output limit so the excess can be
+/// detected without being exposed.
+#[derive(Debug)]
+pub(crate) struct Instrumented<C> {
+    codec: C,
+    stats: Stats,
+    input_limit: Option<u64>,
+    output_limit: Option<u64>,
+}
+
+macro_rules! event {
+    ($stats:expr, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            total_in = $stats.total_in,
+            total_out = $stats.total_out,
+            frames = $stats.frames,
+            $($arg)+
+        );
+    };
+}
+
+impl<C> Instrumented<C> {
+    pub(crate) fn new(codec: C) -> Self {
+        Self {
+            codec,
+            stats: Stats::default(),
+            input_limit: None,
+            output_limit: None,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &C {
+        &self.codec
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    pub(crate) fn into_inner(self) -> C {
+        self.codec
+    }
+
+    pub(crate) fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub(crate) fn record_codec_time(&mut self, enabled: bool) {
+        self.stats.codec_time = if enabled {
+            Some(self.stats.codec_time.unwrap_or_default())
+        } else {
+            None
+        };
+    }
+
+    pub(crate) fn set_input_limit(&mut self, limit: u64) {
+        self.input_limit = Some(limit);
+    }
+
+    pub(crate) fn set_output_limit(&mut self, limit: u64) {
+        self.output_limit = Some(limit);
+    }
+
+    /// Starts the totals from `checkpoint`, for a codec resuming a stream from it.
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    pub(crate) fn resume(&mut self, checkpoint: &crate::checkpoint::Checkpoint) {
+        self.stats.total_in = checkpoint.total_in();
+        self.stats.total_out = checkpoint.total_out();
+    }
+
+    /// Starts the statistics over, keeping whether the codec time is recorded.
+    fn reset_stats(&mut self) {
+        self.stats = Stats {
+            codec_time: self.stats.codec_time.map(|_| Duration::ZERO),
+            ..Stats::default()
+        };
+    }
+
+    fn call<T>(&mut self, f: impl FnOnce(&mut C) -> Result<T>) -> Result<T> {
+        let result = match &mut self.stats.codec_time {
+            Some(codec_time) => {
+                let start = Instant::now();
+                let result = f(&mut self.codec);
+                *codec_time += start.elapsed();
+                result
+            }
+            None => f(&mut self.codec),
+        };
+
+        if let Err(_err) = &result {
+            event!(self.stats, error = %_err, "codec failed");
+        }
+
+        result
+    }
+
+    fn process<I, O, T, F>(
+        &mut self,
+        input: &mut PartialBuffer<I>,
+        output: &mut PartialBuffer<O>,
+        f: F,
+    ) -> Result<T>
+    where
+        I: AsRef<[u8]>,
+        O: AsRef<[u8]> + AsMut<[u8]>,
+        F: FnOnce(&mut C, &mut PartialBuffer<&[u8]>, &mut PartialBuffer<&mut [u8]>) -> Result<T>,
+    {
+        let unread = input.unwritten();
+        let len = match self.input_limit {
+            Some(limit) => {
+                let allowed = limit.saturating_sub(self.stats.total_in);
+                if allowed == 0 && !unread.is_empty() {
+                    event!(self.stats, "input limit exceeded");
+                    return Err(LimitExceeded::new(Limit::Input, limit).into());
+                }
+                min(unread.len() as u64, allowed) as usize
+            }
+            None => unread.len(),
+        };
+
+        let mut window = PartialBuffer::new(&unread[..len]);
+        let result = self.produce(output, |codec, output| f(codec, &mut window, output));
+        let consumed = window.written().len();
+        input.advance(consumed);
+        self.stats.total_in += consumed as u64;
+        result
+    }
+
+    fn produce<O, T>(
+        &mut self,
+        output: &mut PartialBuffer<O>,
+        f: impl FnOnce(&mut C, &mut PartialBuffer<&mut [u8]>) -> Result<T>,
+    ) -> Result<T>
+    where
+        O: AsRef<[u8]> + AsMut<[u8]>,
+    {
+        let unwritten = output.unwritten_mut();
+        let len = match self.output_limit {
+            Some(limit) => {
+                let allowed = limit.saturating_sub(self.stats.total_out).saturating_add(1);
+                min(unwritten.len() as u64, allowed) as usize
+            }
+            None => unwritten.len(),
+        };
+
+        let mut window = PartialBuffer::new(&mut unwritten[..len]);
+        let result = self.call(|codec| f(codec, &mut window));
+        let mut produced = window.written().len() as u64;
+        let result = result?;
+
+        if let Some(limit) = self.output_limit {
+            if self.stats.total_out + produced > limit {
+                produced = limit - self.stats.total_out;
+                output.advance(produced as usize);
+                self.stats.total_out += produced;
+                event!(self.stats, "output limit exceeded");
+                return Err(LimitExceeded::new(Limit::Output, limit).into());
+            }
+        }
+
+        output.advance(produced as usize);
+        self.stats.total_out += produced;
+        Ok(result)
+    }
+
+    fn flushed(&mut self, done: bool) -> bool {
+        if done {
+            self.stats.flushes += 1;
+            event!(self.stats, "flush completed");
+        }
+        done
+    }
+
+    fn finished(&mut self, done: bool) -> bool {
+        if done {
+            self.stats.frames += 1;
+            event!(self.stats, "frame finished");
+        }
+        done
+    }
+}
+
+impl<E: Encode> Instrumented<E> {
+    /// Resets the codec to start a new member/frame at a checkpoint, keeping the statistics.
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    pub(crate) fn restart(&mut self) -> Result<()> {
+        self.call(|codec| codec.reset())?;
+        event!(self.stats, "restarted at checkpoint");
+        Ok(())
+    }
+}
+
+impl<E: Encode> Encode for Instrumented<E> {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        self.process(input, output, |codec, input, output| {
+            codec.encode(input, output)
+        })
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let done = self.produce(output, |codec, output| codec.flush(output))?;
+        Ok(self.flushed(done))
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let done = self.produce(output, |codec, output| codec.finish(output))?;
+        Ok(self.finished(done))
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.call(|codec| codec.reset())?;
+        self.reset_stats();
+        event!(self.stats, "reset");
+        Ok(())
+    }
+
+    fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.call(|codec| codec.set_level(level))?;
+        event!(self.stats, ?level, "set level");
+        Ok(())
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        self.codec.checksum()
+    }
+}
+
+impl<D: Decode> Decode for Instrumented<D> {
+    fn reinit(&mut self) -> Result<()> {
+        self.call(|codec| codec.reinit())?;
+        event!(self.stats, "reinitialized for next member");
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.call(|codec| codec.reset())?;
+        self.reset_stats();
+        event!(self.stats, "reset");
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        self.process(input, output, |codec, input, output| {
+            codec.decode(input, output)
+        })
+    }
+
+    fn flush(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let done = self.produce(output, |codec, output| codec.flush(output))?;
+        Ok(self.flushed(done))
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let done = self.produce(output, |codec, output| codec.finish(output))?;
+        Ok(self.finished(done))
+    }
+
+    fn unconsumed(&self) -> &[u8] {
+        self.codec.unconsumed()
+    }
+
+    fn in_header(&self) -> bool {
+        self.codec.in_header()
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        self.codec.checksum()
+    }
+}
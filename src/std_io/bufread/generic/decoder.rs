@@ -0,0 +1,340 @@
+use core::{cmp::min, mem};
+use std::io::{BufRead, Read, Result};
+
+use crate::{
+    codec::Decode,
+    error,
+    stats::{Instrumented, Stats},
+    util::PartialBuffer,
+    TrailingData,
+};
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+#[derive(Debug)]
+enum State {
+    Decoding,
+    Flushing,
+    Trailing,
+    Done,
+    Next,
+}
+
+/// A generic decoder which decompresses data with any [`Decode`] implementation.
+///
+/// This structure implements a [`Read`](std::io::Read) interface and will read compressed data
+/// from an underlying stream and emit a stream of uncompressed data. It also implements
+/// [`BufRead`](std::io::BufRead) so it can be used by other adapters expecting a buffered reader
+/// without wrapping it in a `BufReader`.
+///
+/// The decoders for each algorithm in this module are built on this type, it can be used
+/// directly to wrap a custom format.
+#[derive(Debug)]
+pub struct Decoder<R, D: Decode> {
+    reader: R,
+    decoder: Instrumented<D>,
+    state: State,
+    // Output produced by `fill_buf` in `buffer[pos..filled]` that has not been consumed
+    buffer: Vec<u8>,
+    pos: usize,
+    filled: usize,
+    multiple_members: bool,
+    trailing_data: TrailingData,
+}
+
+impl<R: BufRead, D: Decode> Decoder<R, D> {
+    /// Creates a new decoder which will read compressed data from the given stream using `decoder`.
+    pub fn new(reader: R, decoder: D) -> Self {
+        Self {
+            reader,
+            decoder: Instrumented::new(decoder),
+            state: State::Decoding,
+            buffer: Vec::new(),
+            pos: 0,
+            filled: 0,
+            multiple_members: false,
+            trailing_data: TrailingData::Stop,
+        }
+    }
+
+    /// Acquires a reference to the underlying reader that this decoder is wrapping.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Acquires a mutable reference to the underlying reader that this decoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this decoder.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the total number of compressed bytes this decoder has consumed from the underlying
+    /// reader so far.
+    pub fn total_in(&self) -> u64 {
+        self.decoder.stats().total_in
+    }
+
+    /// Returns the total number of uncompressed bytes this decoder has produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.decoder.stats().total_out
+    }
+
+    /// Returns the number of compressed bytes this decoder has consumed and decoded so far, once
+    /// the end of the stream is reached this is its exact length. Unlike
+    /// [`total_in`](Self::total_in) this excludes any data read ahead but not decoded, see
+    /// [`into_inner_with_leftover`](Self::into_inner_with_leftover).
+    pub fn bytes_consumed(&self) -> u64 {
+        self.decoder.stats().total_in - self.decoder.unconsumed().len() as u64
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.decoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this decoder so far.
+    pub fn stats(&self) -> Stats {
+        self.decoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Decode::checksum`](crate::codec::Decode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.decoder.checksum()
+    }
+
+    /// Returns whether this decoder has reached the end of the compressed stream and returned
+    /// all of its output. If reading returns EOF before this the underlying reader ended early.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Returns whether this decoder is partway through the header of a member/frame, having
+    /// consumed some of it but not all. This is only tracked for the gzip and zlib formats and is
+    /// always `false` for others.
+    pub fn in_header(&self) -> bool {
+        self.decoder.in_header()
+    }
+
+    /// Returns the number of members/frames this decoder has completely decoded so far.
+    pub fn member_count(&self) -> u64 {
+        self.decoder.stats().frames
+    }
+
+    /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once more
+    /// would be produced it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error
+    /// instead. This protects against decompression bombs when decoding untrusted data.
+    pub fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    /// Limits the number of compressed bytes this decoder will consume to `limit`, once more would
+    /// be needed it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error instead.
+    /// This stops a never-ending stream from keeping the decoder busy indefinitely.
+    pub fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+
+    /// Consumes this decoder returning the underlying reader.
+    ///
+    /// Note that this may discard internal state of this decoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Consumes this decoder returning the underlying reader, along with any compressed data the
+    /// decoder has read from it but not decoded.
+    ///
+    /// This decoder only consumes as much from the reader as it decodes so whatever follows the
+    /// end of the stream is normally left in the reader, but some decoders may read ahead of it.
+    /// Any such data is returned here, and comes before whatever is left in the reader.
+    pub fn into_inner_with_leftover(self) -> (R, Vec<u8>) {
+        let leftover = self.decoder.unconsumed().to_vec();
+        (self.reader, leftover)
+    }
+
+    /// Configure multi-member/frame decoding, if enabled this will reinitialize the decoder
+    /// when reaching the end of a compressed member/frame and expect either EOF or another
+    /// compressed member/frame to follow it in the stream.
+    pub fn multiple_members(&mut self, enabled: bool) {
+        self.multiple_members = enabled;
+    }
+
+    /// Configure what happens to data following the end of the compressed stream when
+    /// multi-member/frame decoding is disabled, see [`TrailingData`] for the options.
+    ///
+    /// Defaults to [`TrailingData::Stop`], leaving the data in the underlying reader.
+    pub fn trailing_data(&mut self, policy: TrailingData) {
+        self.trailing_data = policy;
+    }
+
+    /// Resets this decoder to decode a new stream from the same reader, reusing its allocations.
+    ///
+    /// Any output not yet read is discarded and the statistics start over, while configuration
+    /// such as the limits and multi-member/frame decoding is kept.
+    pub fn reset(&mut self) -> Result<()> {
+        self.decoder.reset()?;
+        self.state = State::Decoding;
+        self.pos = 0;
+        self.filled = 0;
+        Ok(())
+    }
+
+    /// Resets this decoder like [`reset`](Self::reset) to decode a new stream from `reader`,
+    /// returning the previous reader.
+    pub fn reset_with_reader(&mut self, reader: R) -> Result<R> {
+        self.reset()?;
+        Ok(mem::replace(&mut self.reader, reader))
+    }
+
+    fn do_read(&mut self, output: &mut PartialBuffer<&mut [u8]>) -> Result<()> {
+        loop {
+            // Return what is already available rather than blocking on the reader for more, as
+            // an asynchronous read would
+            let reads = match self.state {
+                State::Decoding | State::Next => true,
+                State::Trailing => !matches!(self.trailing_data, TrailingData::Stop),
+                State::Flushing | State::Done => false,
+            };
+            if reads && !output.written().is_empty() {
+                return Ok(());
+            }
+
+            self.state = match self.state {
+                State::Decoding => {
+                    let input = self.reader.fill_buf()?;
+                    if input.is_empty() {
+                        // Avoid attempting to reinitialise the decoder if the reader
+                        // has returned EOF.
+                        self.multiple_members = false;
+                        State::Flushing
+                    } else {
+                        let mut input = PartialBuffer::new(input);
+                        let done = self.decoder.decode(&mut input, output)?;
+                        let len = input.written().len();
+                        self.reader.consume(len);
+                        if done {
+                            State::Flushing
+                        } else {
+                            State::Decoding
+                        }
+                    }
+                }
+
+                State::Flushing => {
+                    if self.decoder.finish(output)? {
+                        if self.multiple_members {
+                            self.decoder.reinit()?;
+                            State::Next
+                        } else {
+                            State::Trailing
+                        }
+                    } else {
+                        State::Flushing
+                    }
+                }
+
+                State::Trailing => match self.trailing_data {
+                    TrailingData::Stop => State::Done,
+                    policy => {
+                        let input = self.reader.fill_buf()?;
+                        if input.is_empty() {
+                            State::Done
+                        } else if let TrailingData::Ignore = policy {
+                            let len = input.len();
+                            self.reader.consume(len);
+                            State::Trailing
+                        } else {
+                            return Err(error::Error::UnexpectedTrailingData.into());
+                        }
+                    }
+                },
+
+                State::Done => State::Done,
+
+                State::Next => {
+                    let input = self.reader.fill_buf()?;
+                    if input.is_empty() {
+                        State::Done
+                    } else {
+                        State::Decoding
+                    }
+                }
+            };
+
+            if let State::Done = self.state {
+                return Ok(());
+            }
+            if output.unwritten().is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<R, D: Decode> Decoder<R, D> {
+    /// Acquires a reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec(&self) -> &D {
+        self.decoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut D {
+        self.decoder.get_mut()
+    }
+}
+
+impl<R: BufRead, D: Decode> Read for Decoder<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Return any output left over from `fill_buf` before producing more
+        if self.pos < self.filled {
+            let len = min(buf.len(), self.filled - self.pos);
+            buf[..len].copy_from_slice(&self.buffer[self.pos..self.pos + len]);
+            self.pos += len;
+            return Ok(len);
+        }
+
+        let mut output = PartialBuffer::new(buf);
+        self.do_read(&mut output)?;
+        Ok(output.written().len())
+    }
+}
+
+impl<R: BufRead, D: Decode> BufRead for Decoder<R, D> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos == self.filled {
+            // The buffer is only allocated once it is needed, so plain `Read` usage doesn't pay
+            // for it
+            let mut buffer = mem::take(&mut self.buffer);
+            buffer.resize(DEFAULT_BUF_SIZE, 0);
+
+            let mut output = PartialBuffer::new(&mut buffer[..]);
+            let result = self.do_read(&mut output);
+            let len = output.written().len();
+
+            self.buffer = buffer;
+            self.pos = 0;
+            self.filled = len;
+            result?;
+        }
+
+        Ok(&self.buffer[self.pos..self.filled])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = min(self.pos + amt, self.filled);
+    }
+}
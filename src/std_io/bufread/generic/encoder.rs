@@ -0,0 +1,233 @@
+use core::{cmp::min, mem};
+use std::io::{BufRead, Read, Result};
+
+use crate::{
+    codec::Encode,
+    stats::{Instrumented, Stats},
+    util::PartialBuffer,
+};
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+#[derive(Debug)]
+enum State {
+    Encoding,
+    Flushing,
+    Done,
+}
+
+/// A generic encoder which compresses data with any [`Encode`] implementation.
+///
+/// This structure implements a [`Read`](std::io::Read) interface and will read uncompressed data
+/// from an underlying stream and emit a stream of compressed data. It also implements
+/// [`BufRead`](std::io::BufRead) so it can be used by other adapters expecting a buffered reader
+/// without wrapping it in a `BufReader`.
+///
+/// The encoders for each algorithm in this module are built on this type, it can be used
+/// directly to wrap a custom format.
+#[derive(Debug)]
+pub struct Encoder<R, E: Encode> {
+    reader: R,
+    encoder: Instrumented<E>,
+    state: State,
+    // Output produced by `fill_buf` in `buffer[pos..filled]` that has not been consumed
+    buffer: Vec<u8>,
+    pos: usize,
+    filled: usize,
+}
+
+impl<R: BufRead, E: Encode> Encoder<R, E> {
+    /// Creates a new encoder which will read uncompressed data from the given stream using
+    /// `encoder`.
+    pub fn new(reader: R, encoder: E) -> Self {
+        Self {
+            reader,
+            encoder: Instrumented::new(encoder),
+            state: State::Encoding,
+            buffer: Vec::new(),
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Acquires a reference to the underlying reader that this encoder is wrapping.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Acquires a mutable reference to the underlying reader that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this encoder.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the total number of uncompressed bytes this encoder has consumed from the underlying
+    /// reader so far.
+    pub fn total_in(&self) -> u64 {
+        self.encoder.stats().total_in
+    }
+
+    /// Returns the total number of compressed bytes this encoder has produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.encoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.encoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this encoder so far.
+    pub fn stats(&self) -> Stats {
+        self.encoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Encode::checksum`](crate::codec::Encode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.encoder.checksum()
+    }
+
+    /// Changes the compression level used for the rest of the stream, the data already compressed
+    /// is unaffected.
+    ///
+    /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and `gzip`
+    /// encoders flush before switching which costs some compression ratio. Other encoders return
+    /// an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+    pub fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.encoder.set_level(level)
+    }
+
+    /// Resets this encoder to compress a new stream from the same reader, reusing its
+    /// allocations where the codec allows it.
+    ///
+    /// Any output not yet read is discarded and the statistics start over.
+    pub fn reset(&mut self) -> Result<()> {
+        self.encoder.reset()?;
+        self.state = State::Encoding;
+        self.pos = 0;
+        self.filled = 0;
+        Ok(())
+    }
+
+    /// Resets this encoder like [`reset`](Self::reset) to compress a new stream from `reader`,
+    /// returning the previous reader.
+    pub fn reset_with_reader(&mut self, reader: R) -> Result<R> {
+        self.reset()?;
+        Ok(mem::replace(&mut self.reader, reader))
+    }
+
+    /// Consumes this encoder returning the underlying reader.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn do_read(&mut self, output: &mut PartialBuffer<&mut [u8]>) -> Result<()> {
+        loop {
+            self.state = match self.state {
+                State::Encoding => {
+                    // Return what is already available rather than blocking on the reader for
+                    // more, as an asynchronous read would
+                    if !output.written().is_empty() {
+                        return Ok(());
+                    }
+
+                    let input = self.reader.fill_buf()?;
+                    if input.is_empty() {
+                        State::Flushing
+                    } else {
+                        let mut input = PartialBuffer::new(input);
+                        self.encoder.encode(&mut input, output)?;
+                        let len = input.written().len();
+                        self.reader.consume(len);
+                        State::Encoding
+                    }
+                }
+
+                State::Flushing => {
+                    if self.encoder.finish(output)? {
+                        State::Done
+                    } else {
+                        State::Flushing
+                    }
+                }
+
+                State::Done => State::Done,
+            };
+
+            if let State::Done = self.state {
+                return Ok(());
+            }
+            if output.unwritten().is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<R, E: Encode> Encoder<R, E> {
+    /// Acquires a reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec(&self) -> &E {
+        self.encoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut E {
+        self.encoder.get_mut()
+    }
+}
+
+impl<R: BufRead, E: Encode> Read for Encoder<R, E> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Return any output left over from `fill_buf` before producing more
+        if self.pos < self.filled {
+            let len = min(buf.len(), self.filled - self.pos);
+            buf[..len].copy_from_slice(&self.buffer[self.pos..self.pos + len]);
+            self.pos += len;
+            return Ok(len);
+        }
+
+        let mut output = PartialBuffer::new(buf);
+        self.do_read(&mut output)?;
+        Ok(output.written().len())
+    }
+}
+
+impl<R: BufRead, E: Encode> BufRead for Encoder<R, E> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos == self.filled {
+            // The buffer is only allocated once it is needed, so plain `Read` usage doesn't pay
+            // for it
+            let mut buffer = mem::take(&mut self.buffer);
+            buffer.resize(DEFAULT_BUF_SIZE, 0);
+
+            let mut output = PartialBuffer::new(&mut buffer[..]);
+            let result = self.do_read(&mut output);
+            let len = output.written().len();
+
+            self.buffer = buffer;
+            self.pos = 0;
+            self.filled = len;
+            result?;
+        }
+
+        Ok(&self.buffer[self.pos..self.filled])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = min(self.pos + amt, self.filled);
+    }
+}
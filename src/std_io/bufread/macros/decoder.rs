@@ -0,0 +1,221 @@
+macro_rules! decoder {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        ///
+        /// This structure implements a [`Read`](std::io::Read) interface and will read compressed
+        /// data from an underlying stream and emit a stream of uncompressed data. It also
+        /// implements [`BufRead`](std::io::BufRead) so it can be used by other adapters expecting
+        /// a buffered reader.
+        pub struct $name<$inner> {
+            inner: crate::std_io::bufread::Decoder<$inner, crate::codec::$name>,
+        }
+
+        impl<$inner: std::io::BufRead> $name<$inner> {
+            /// Creates a new decoder which will read compressed data from the given stream and
+            /// emit a uncompressed stream.
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
+            $(
+                /// Creates a new decoder which will read compressed data from the given stream and
+                /// emit a uncompressed stream.
+                ///
+                $($constructor)*
+            )*
+
+            /// Configure multi-member/frame decoding, if enabled this will reset the decoder state
+            /// when reaching the end of a compressed member/frame and expect either EOF or another
+            /// compressed member/frame to follow it in the stream.
+            pub fn multiple_members(&mut self, enabled: bool) {
+                self.inner.multiple_members(enabled);
+            }
+
+            /// Configure what happens to data following the end of the compressed stream when
+            /// multi-member/frame decoding is disabled, see
+            /// [`TrailingData`](crate::TrailingData) for the options.
+            ///
+            /// Defaults to [`TrailingData::Stop`](crate::TrailingData::Stop), leaving the data in
+            /// the underlying reader.
+            pub fn trailing_data(&mut self, policy: crate::TrailingData) {
+                self.inner.trailing_data(policy);
+            }
+
+            /// Acquires a reference to the underlying reader that this decoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying reader that this decoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this decoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Returns the total number of compressed bytes this decoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of uncompressed bytes this decoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Returns the number of compressed bytes this decoder has consumed and decoded so
+            /// far, once the end of the stream is reached this is its exact length. Unlike
+            /// [`total_in`](Self::total_in) this excludes any data read ahead but not decoded,
+            /// see [`into_inner_with_leftover`](Self::into_inner_with_leftover).
+            pub fn bytes_consumed(&self) -> u64 {
+                self.inner.bytes_consumed()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this decoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Returns whether this decoder has reached the end of the compressed stream and
+            /// returned all of its output. If reading returns EOF before this the underlying
+            /// reader ended early.
+            pub fn is_done(&self) -> bool {
+                self.inner.is_done()
+            }
+
+            /// Returns whether this decoder is partway through the header of a member/frame,
+            /// having consumed some of it but not all. This is only tracked for the gzip and zlib
+            /// formats and is always `false` for others.
+            pub fn in_header(&self) -> bool {
+                self.inner.in_header()
+            }
+
+            /// Returns the number of members/frames this decoder has completely decoded so far.
+            pub fn member_count(&self) -> u64 {
+                self.inner.member_count()
+            }
+
+            /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once
+            /// more would be produced it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This protects against
+            /// decompression bombs when decoding untrusted data.
+            pub fn with_output_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_output_limit(limit),
+                }
+            }
+
+            /// Limits the number of compressed bytes this decoder will consume to `limit`, once
+            /// more would be needed it fails with a
+            /// [`LimitExceeded`](crate::error::LimitExceeded) error instead. This stops a
+            /// never-ending stream from keeping the decoder busy indefinitely.
+            pub fn with_input_limit(self, limit: u64) -> Self {
+                Self {
+                    inner: self.inner.with_input_limit(limit),
+                }
+            }
+
+            /// Resets this decoder to decode a new stream from the same reader, reusing its
+            /// allocations.
+            ///
+            /// Any output not yet read is discarded and the statistics start over, while
+            /// configuration such as the limits and multi-member/frame decoding is kept.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this decoder like [`reset`](Self::reset) to decode a new stream from
+            /// `reader`, returning the previous reader.
+            pub fn reset_with_reader(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_reader(reader)
+            }
+
+            /// Consumes this decoder returning the underlying reader.
+            ///
+            /// Note that this may discard internal state of this decoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+
+            /// Consumes this decoder returning the underlying reader, along with any compressed
+            /// data the decoder has read from it but not decoded.
+            ///
+            /// This decoder only consumes as much from the reader as it decodes so whatever
+            /// follows the end of the stream is normally left in the reader, but some decoders
+            /// may read ahead of it. Any such data is returned here, and comes before whatever is
+            /// left in the reader.
+            pub fn into_inner_with_leftover(self) -> ($inner, Vec<u8>) {
+                self.inner.into_inner_with_leftover()
+            }
+        }
+
+        impl<$inner: std::io::Read> $name<std::io::BufReader<$inner>> {
+            /// Creates a new decoder which will read compressed data from the given unbuffered
+            /// stream through an internal [`BufReader`](std::io::BufReader) and emit a
+            /// uncompressed stream, taking the same extra arguments as `new`.
+            pub fn from_read(read: $inner $(, $arg: $arg_ty)*) -> Self {
+                Self::new(std::io::BufReader::new(read) $(, $arg)*)
+            }
+
+            /// Creates a new decoder like [`from_read`](Self::from_read), using an internal buffer
+            /// with the specified capacity.
+            pub fn from_read_with_capacity(
+                capacity: usize,
+                read: $inner
+                $(, $arg: $arg_ty)*
+            ) -> Self {
+                Self::new(std::io::BufReader::with_capacity(capacity, read) $(, $arg)*)
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::Read for $name<$inner> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.inner.read(buf)
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::BufRead for $name<$inner> {
+            fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+                self.inner.fill_buf()
+            }
+
+            fn consume(&mut self, amt: usize) {
+                self.inner.consume(amt)
+            }
+        }
+
+        const _: () = {
+            fn _assert() {
+                use crate::util::{_assert_send, _assert_sync};
+                use std::io::BufRead;
+
+                _assert_send::<$name<Box<dyn BufRead + Send>>>();
+                _assert_sync::<$name<Box<dyn BufRead + Sync>>>();
+            }
+        };
+    }
+}
@@ -0,0 +1,155 @@
+macro_rules! encoder {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        ///
+        /// This structure implements a [`Read`](std::io::Read) interface and will read
+        /// uncompressed data from an underlying stream and emit a stream of compressed data. It
+        /// also implements [`BufRead`](std::io::BufRead) so it can be used by other adapters
+        /// expecting a buffered reader.
+        pub struct $name<$inner> {
+            inner: crate::std_io::bufread::Encoder<$inner, crate::codec::$name>,
+        }
+
+        impl<$inner: std::io::BufRead> $name<$inner> {
+            /// Creates a new encoder which will read uncompressed data from the given stream
+            /// and emit a compressed stream.
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
+            $(
+                /// Creates a new encoder which will read uncompressed data from the given stream
+                /// and emit a compressed stream.
+                ///
+                $($constructor)*
+            )*
+
+            /// Acquires a reference to the underlying reader that this encoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying reader that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the reader which
+            /// may otherwise confuse this encoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Returns the total number of uncompressed bytes this encoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of compressed bytes this encoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this encoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Changes the compression level used for the rest of the stream, the data already
+            /// compressed is unaffected.
+            ///
+            /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and
+            /// `gzip` encoders flush before switching which costs some compression ratio. Other
+            /// encoders return an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+            pub fn set_level(&mut self, level: crate::Level) -> std::io::Result<()> {
+                self.inner.set_level(level)
+            }
+
+            /// Resets this encoder to compress a new stream from the same reader, reusing its
+            /// allocations where the codec allows it.
+            ///
+            /// Any output not yet read is discarded and the statistics start over.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this encoder like [`reset`](Self::reset) to compress a new stream from
+            /// `reader`, returning the previous reader.
+            pub fn reset_with_reader(&mut self, reader: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_reader(reader)
+            }
+
+            /// Consumes this encoder returning the underlying reader.
+            ///
+            /// Note that this may discard internal state of this encoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: std::io::Read> $name<std::io::BufReader<$inner>> {
+            /// Creates a new encoder which will read uncompressed data from the given unbuffered
+            /// stream through an internal [`BufReader`](std::io::BufReader) and emit a compressed
+            /// stream, taking the same extra arguments as `new`.
+            pub fn from_read(read: $inner $(, $arg: $arg_ty)*) -> Self {
+                Self::new(std::io::BufReader::new(read) $(, $arg)*)
+            }
+
+            /// Creates a new encoder like [`from_read`](Self::from_read), using an internal buffer
+            /// with the specified capacity.
+            pub fn from_read_with_capacity(
+                capacity: usize,
+                read: $inner
+                $(, $arg: $arg_ty)*
+            ) -> Self {
+                Self::new(std::io::BufReader::with_capacity(capacity, read) $(, $arg)*)
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::Read for $name<$inner> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.inner.read(buf)
+            }
+        }
+
+        impl<$inner: std::io::BufRead> std::io::BufRead for $name<$inner> {
+            fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+                self.inner.fill_buf()
+            }
+
+            fn consume(&mut self, amt: usize) {
+                self.inner.consume(amt)
+            }
+        }
+
+        const _: () = {
+            fn _assert() {
+                use crate::util::{_assert_send, _assert_sync};
+                use std::io::BufRead;
+
+                _assert_send::<$name<Box<dyn BufRead + Send>>>();
+                _assert_sync::<$name<Box<dyn BufRead + Sync>>>();
+            }
+        };
+    }
+}
@@ -0,0 +1,18 @@
+//! Types which operate over [`BufRead`](std::io::BufRead) streams, both encoders and decoders for
+//! various formats.
+//!
+//! Custom formats can be used with the generic [`Encoder`] and [`Decoder`], see
+//! [`codec`](crate::codec).
+//!
+//! Streams which only implement `Read` can be used through the `from_read` constructors, which
+//! wrap them in a [`BufReader`](std::io::BufReader).
+
+#[macro_use]
+mod macros;
+mod generic;
+
+pub use self::generic::{Decoder, Encoder};
+
+algos!(std_io::bufread<R>);
+any!(std_io::bufread<R>);
+backends!(std_io::bufread<R>);
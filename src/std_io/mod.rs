@@ -0,0 +1,11 @@
+//! Implementations for the synchronous [`std::io`] traits.
+//!
+//! These are built on the same codecs as the asynchronous implementations, so they produce the
+//! same output and behave the same way: flushing, multi-member/frame decoding, limits and
+//! trailing data are all handled identically, and fail with the same errors. This lets code
+//! with both synchronous and asynchronous paths share a single compression stack.
+//!
+//! Reads and writes block on the underlying stream as usual for [`std::io`] types.
+
+pub mod bufread;
+pub mod write;
@@ -0,0 +1,101 @@
+use std::io::{Error, ErrorKind, Result, Write};
+
+/// Buffers the output of a codec before writing it to the underlying writer, letting the codec
+/// write straight into the spare space of the buffer.
+#[derive(Debug)]
+pub(crate) struct BufWriter<W> {
+    inner: W,
+    buf: Vec<u8>,
+    capacity: usize,
+    written: usize,
+    buffered: usize,
+}
+
+impl<W: Write> BufWriter<W> {
+    /// Creates a new `BufWriter` with a buffer of the specified capacity, which is only allocated
+    /// once it is needed.
+    pub(crate) fn with_capacity(capacity: usize, inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            capacity,
+            written: 0,
+            buffered: 0,
+        }
+    }
+
+    /// Returns the spare space at the end of the buffer, first writing out the buffered data if
+    /// there is none.
+    pub(crate) fn partial_flush_buf(&mut self) -> Result<&mut [u8]> {
+        if self.buf.len() < self.capacity {
+            self.buf.resize(self.capacity, 0);
+        }
+        if self.buffered == self.buf.len() {
+            self.flush_buf()?;
+        }
+        Ok(&mut self.buf[self.buffered..])
+    }
+
+    /// Marks `amt` more bytes of the spare space as buffered.
+    pub(crate) fn produce(&mut self, amt: usize) {
+        self.buffered += amt;
+    }
+
+    /// Writes out all of the buffered data.
+    pub(crate) fn flush_buf(&mut self) -> Result<()> {
+        let mut ret = Ok(());
+        while self.written < self.buffered {
+            match self.inner.write(&self.buf[self.written..self.buffered]) {
+                Ok(0) => {
+                    ret = Err(Error::new(
+                        ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    ));
+                    break;
+                }
+                Ok(n) => self.written += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    ret = Err(e);
+                    break;
+                }
+            }
+        }
+        self.buf.copy_within(self.written..self.buffered, 0);
+        self.buffered -= self.written;
+        self.written = 0;
+        ret
+    }
+
+    /// Writes out all of the buffered data, then flushes the underlying writer.
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub(crate) fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Discards any buffered data.
+    pub(crate) fn discard_buffer(&mut self) {
+        self.written = 0;
+        self.buffered = 0;
+    }
+
+    /// Replaces the underlying writer, returning the previous one.
+    pub(crate) fn replace_inner(&mut self, inner: W) -> W {
+        std::mem::replace(&mut self.inner, inner)
+    }
+
+    /// Gets the underlying writer, discarding any buffered data.
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+}
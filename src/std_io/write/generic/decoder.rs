@@ -0,0 +1,312 @@
+use std::io::{Error, ErrorKind, Result, Write};
+
+use crate::{
+    codec::Decode,
+    error,
+    stats::{Instrumented, Stats},
+    std_io::write::BufWriter,
+    util::PartialBuffer,
+    TrailingData,
+};
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+#[derive(Debug)]
+enum State {
+    Decoding,
+    Finishing,
+    Done,
+}
+
+/// A generic decoder which decompresses data with any [`Decode`] implementation.
+///
+/// This structure implements a [`Write`](std::io::Write) interface and will decompress data
+/// written to it and write the uncompressed data to an underlying stream.
+///
+/// The end of the input must be signalled with [`try_finish`](Self::try_finish) or
+/// [`finish`](Self::finish), which fail if the compressed stream was incomplete.
+///
+/// The decoders for each algorithm in this module are built on this type, it can be used
+/// directly to wrap a custom format.
+#[derive(Debug)]
+pub struct Decoder<W, D: Decode> {
+    writer: BufWriter<W>,
+    decoder: Instrumented<D>,
+    state: State,
+    trailing_data: TrailingData,
+}
+
+impl<W: Write, D: Decode> Decoder<W, D> {
+    /// Creates a new decoder which will write uncompressed data to the given stream using
+    /// `decoder`.
+    pub fn new(writer: W, decoder: D) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, writer, decoder)
+    }
+
+    /// Creates a new decoder like [`new`](Self::new), with an internal buffer of the specified
+    /// capacity for the uncompressed output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, writer: W, decoder: D) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        Self {
+            writer: BufWriter::with_capacity(capacity, writer),
+            decoder: Instrumented::new(decoder),
+            state: State::Decoding,
+            trailing_data: TrailingData::Error,
+        }
+    }
+
+    /// Acquires a reference to the underlying writer that this decoder is wrapping.
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this decoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this decoder.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    /// Returns the total number of compressed bytes this decoder has consumed so far.
+    pub fn total_in(&self) -> u64 {
+        self.decoder.stats().total_in
+    }
+
+    /// Returns the total number of uncompressed bytes this decoder has produced so far, including
+    /// any still buffered and not yet written to the underlying writer.
+    pub fn total_out(&self) -> u64 {
+        self.decoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.decoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this decoder so far.
+    pub fn stats(&self) -> Stats {
+        self.decoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Decode::checksum`](crate::codec::Decode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.decoder.checksum()
+    }
+
+    /// Returns whether the end of the compressed stream has been written to this decoder and all
+    /// of its output has been decoded.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Returns whether this decoder is partway through the header of a member/frame, having
+    /// consumed some of it but not all. This is only tracked for the gzip and zlib formats and is
+    /// always `false` for others.
+    pub fn in_header(&self) -> bool {
+        self.decoder.in_header()
+    }
+
+    /// Returns the number of members/frames this decoder has completely decoded so far.
+    pub fn member_count(&self) -> u64 {
+        self.decoder.stats().frames
+    }
+
+    /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once more
+    /// would be produced it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error
+    /// instead. This protects against decompression bombs when decoding untrusted data.
+    pub fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    /// Limits the number of compressed bytes this decoder will consume to `limit`, once more would
+    /// be needed it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error instead.
+    /// This stops a never-ending stream from keeping the decoder busy indefinitely.
+    pub fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+
+    /// Configure what happens to data written after the end of the compressed stream, see
+    /// [`TrailingData`] for the options.
+    ///
+    /// Defaults to [`TrailingData::Error`]. With [`TrailingData::Stop`] the write reaching the end
+    /// of the stream is cut short there and any later writes return `Ok(0)`, leaving the trailing
+    /// data with the caller.
+    pub fn trailing_data(&mut self, policy: TrailingData) {
+        self.trailing_data = policy;
+    }
+
+    /// Signals the end of the input and flushes the rest of the output to the underlying writer,
+    /// failing if the compressed stream was incomplete.
+    pub fn try_finish(&mut self) -> Result<()> {
+        if let State::Decoding = self.state {
+            self.state = State::Finishing;
+        }
+
+        self.flush_codec()?;
+
+        if let State::Done = self.state {
+            self.writer.flush()
+        } else {
+            Err(Error::new(
+                ErrorKind::Other,
+                "Attempt to close before finishing input",
+            ))
+        }
+    }
+
+    /// Signals the end of the input like [`try_finish`](Self::try_finish) and returns the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.try_finish()?;
+        Ok(self.into_inner())
+    }
+
+    /// Resets this decoder to decode a new stream to the same writer, reusing its allocations.
+    ///
+    /// Any output not yet written to the underlying writer is discarded and the statistics start
+    /// over, so this should be called once the previous stream has been finished. Configuration
+    /// such as the limits and trailing data policy is kept.
+    pub fn reset(&mut self) -> Result<()> {
+        self.decoder.reset()?;
+        self.state = State::Decoding;
+        self.writer.discard_buffer();
+        Ok(())
+    }
+
+    /// Resets this decoder like [`reset`](Self::reset) to decode a new stream to `writer`,
+    /// returning the previous writer.
+    pub fn reset_with_writer(&mut self, writer: W) -> Result<W> {
+        self.reset()?;
+        Ok(self.writer.replace_inner(writer))
+    }
+
+    /// Consumes this decoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this decoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    /// Consumes this decoder returning the underlying writer and codec, discarding anything that
+    /// is buffered.
+    pub(crate) fn into_parts(self) -> (W, D) {
+        (self.writer.into_inner(), self.decoder.into_inner())
+    }
+
+    fn flush_codec(&mut self) -> Result<()> {
+        loop {
+            let mut output = PartialBuffer::new(self.writer.partial_flush_buf()?);
+
+            let (state, done) = match self.state {
+                State::Decoding => {
+                    let done = self.decoder.flush(&mut output)?;
+                    (State::Decoding, done)
+                }
+
+                State::Finishing => {
+                    if self.decoder.finish(&mut output)? {
+                        (State::Done, false)
+                    } else {
+                        (State::Finishing, false)
+                    }
+                }
+
+                State::Done => (State::Done, true),
+            };
+
+            self.state = state;
+
+            let produced = output.written().len();
+            self.writer.produce(produced);
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<W, D: Decode> Decoder<W, D> {
+    /// Acquires a reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec(&self) -> &D {
+        self.decoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut D {
+        self.decoder.get_mut()
+    }
+}
+
+impl<W: Write, D: Decode> Write for Decoder<W, D> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut input = PartialBuffer::new(buf);
+
+        loop {
+            let mut output = PartialBuffer::new(self.writer.partial_flush_buf()?);
+
+            self.state = match self.state {
+                State::Decoding => {
+                    if self.decoder.decode(&mut input, &mut output)? {
+                        State::Finishing
+                    } else {
+                        State::Decoding
+                    }
+                }
+
+                State::Finishing => {
+                    if self.decoder.finish(&mut output)? {
+                        State::Done
+                    } else {
+                        State::Finishing
+                    }
+                }
+
+                State::Done => {
+                    match self.trailing_data {
+                        TrailingData::Error => {
+                            return Err(error::Error::UnexpectedTrailingData.into());
+                        }
+                        TrailingData::Ignore => input.advance(input.unwritten().len()),
+                        TrailingData::Stop => {}
+                    }
+                    return Ok(input.written().len());
+                }
+            };
+
+            let produced = output.written().len();
+            self.writer.produce(produced);
+
+            if let State::Done = self.state {
+                return Ok(input.written().len());
+            }
+
+            if input.unwritten().is_empty() {
+                return Ok(input.written().len());
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.flush_codec()?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,317 @@
+use std::io::{Result, Write};
+
+use crate::{
+    codec::Encode,
+    stats::{Instrumented, Stats},
+    std_io::write::BufWriter,
+    util::PartialBuffer,
+};
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+#[derive(Debug)]
+enum State {
+    Encoding,
+    Finishing,
+    Done,
+}
+
+/// A generic encoder which compresses data with any [`Encode`] implementation.
+///
+/// This structure implements a [`Write`](std::io::Write) interface and will compress data written
+/// to it and write the compressed data to an underlying stream.
+///
+/// The stream must be finished with [`try_finish`](Self::try_finish) or
+/// [`finish`](Self::finish) once everything has been written, dropping the encoder without doing
+/// so leaves the compressed stream incomplete.
+///
+/// The encoders for each algorithm in this module are built on this type, it can be used
+/// directly to wrap a custom format.
+#[derive(Debug)]
+pub struct Encoder<W, E: Encode> {
+    writer: BufWriter<W>,
+    encoder: Instrumented<E>,
+    state: State,
+    codec_flush: bool,
+    auto_flush: Option<usize>,
+    unflushed: usize,
+    flush_pending: bool,
+}
+
+impl<W: Write, E: Encode> Encoder<W, E> {
+    /// Creates a new encoder which will write compressed data to the given stream using `encoder`.
+    pub fn new(writer: W, encoder: E) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, writer, encoder)
+    }
+
+    /// Creates a new encoder like [`new`](Self::new), with an internal buffer of the specified
+    /// capacity for the compressed output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, writer: W, encoder: E) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        Self {
+            writer: BufWriter::with_capacity(capacity, writer),
+            encoder: Instrumented::new(encoder),
+            state: State::Encoding,
+            codec_flush: true,
+            auto_flush: None,
+            unflushed: 0,
+            flush_pending: false,
+        }
+    }
+
+    /// Acquires a reference to the underlying writer that this encoder is wrapping.
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    /// Returns the total number of uncompressed bytes this encoder has consumed so far.
+    pub fn total_in(&self) -> u64 {
+        self.encoder.stats().total_in
+    }
+
+    /// Returns the total number of compressed bytes this encoder has produced so far, including any
+    /// still buffered and not yet written to the underlying writer.
+    pub fn total_out(&self) -> u64 {
+        self.encoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.encoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this encoder so far.
+    pub fn stats(&self) -> Stats {
+        self.encoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Encode::checksum`](crate::codec::Encode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.encoder.checksum()
+    }
+
+    /// Configure whether flushing this encoder also flushes the codec, enabled by default.
+    ///
+    /// A codec flush makes everything written so far decodable by the reader, but with deflate
+    /// based formats each one emits an extra block that costs some compression ratio. When
+    /// disabled flushing only writes out the output the codec has already produced.
+    pub fn with_codec_flush(mut self, enabled: bool) -> Self {
+        self.codec_flush = enabled;
+        self
+    }
+
+    /// Configure this encoder to flush the codec and the underlying writer by itself once `after`
+    /// uncompressed bytes have been written since the last flush, so interactive streams reach the
+    /// reader without explicit calls to `flush`. An `after` of 1 flushes after every write.
+    ///
+    /// Like explicit flushes each automatic flush costs some compression ratio. If the flush fails
+    /// the write still succeeds, and the flush is retried by the next call on this encoder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `after` is zero.
+    pub fn with_auto_flush(mut self, after: usize) -> Self {
+        assert!(after > 0, "auto flush threshold must be non-zero");
+
+        self.auto_flush = Some(after);
+        self
+    }
+
+    /// Finishes the compressed stream and flushes it to the underlying writer.
+    ///
+    /// Once this has completed nothing more can be written to this encoder.
+    pub fn try_finish(&mut self) -> Result<()> {
+        self.auto_flush()?;
+
+        loop {
+            let mut output = PartialBuffer::new(self.writer.partial_flush_buf()?);
+
+            self.state = match self.state {
+                State::Encoding | State::Finishing => {
+                    if self.encoder.finish(&mut output)? {
+                        State::Done
+                    } else {
+                        State::Finishing
+                    }
+                }
+
+                State::Done => State::Done,
+            };
+
+            let produced = output.written().len();
+            self.writer.produce(produced);
+
+            if let State::Done = self.state {
+                return self.writer.flush();
+            }
+        }
+    }
+
+    /// Finishes the compressed stream like [`try_finish`](Self::try_finish) and returns the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.try_finish()?;
+        Ok(self.into_inner())
+    }
+
+    /// Changes the compression level used for the rest of the stream, the data already compressed
+    /// is unaffected.
+    ///
+    /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and `gzip`
+    /// encoders flush before switching which costs some compression ratio. Other encoders return
+    /// an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+    pub fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.encoder.set_level(level)
+    }
+
+    /// Resets this encoder to compress a new stream to the same writer, reusing its allocations
+    /// where the codec allows it.
+    ///
+    /// Any output not yet written to the underlying writer is discarded and the statistics start
+    /// over, so this should be called once the previous stream has been finished.
+    pub fn reset(&mut self) -> Result<()> {
+        self.encoder.reset()?;
+        self.state = State::Encoding;
+        self.unflushed = 0;
+        self.flush_pending = false;
+        self.writer.discard_buffer();
+        Ok(())
+    }
+
+    /// Resets this encoder like [`reset`](Self::reset) to compress a new stream to `writer`,
+    /// returning the previous writer.
+    pub fn reset_with_writer(&mut self, writer: W) -> Result<W> {
+        self.reset()?;
+        Ok(self.writer.replace_inner(writer))
+    }
+
+    /// Consumes this encoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    /// Consumes this encoder returning the underlying writer and codec, discarding anything that
+    /// is buffered.
+    pub(crate) fn into_parts(self) -> (W, E) {
+        (self.writer.into_inner(), self.encoder.into_inner())
+    }
+
+    fn flush_codec(&mut self) -> Result<()> {
+        loop {
+            let mut output = PartialBuffer::new(self.writer.partial_flush_buf()?);
+
+            let done = match self.state {
+                State::Encoding => self.encoder.flush(&mut output)?,
+
+                State::Finishing | State::Done => panic!("Flush after close"),
+            };
+
+            let produced = output.written().len();
+            self.writer.produce(produced);
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    fn auto_flush(&mut self) -> Result<()> {
+        if self.flush_pending {
+            self.flush_codec()?;
+            self.writer.flush()?;
+            self.flush_pending = false;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W, E: Encode> Encoder<W, E> {
+    /// Acquires a reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec(&self) -> &E {
+        self.encoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut E {
+        self.encoder.get_mut()
+    }
+}
+
+impl<W: Write, E: Encode> Write for Encoder<W, E> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.auto_flush()?;
+
+        let mut input = PartialBuffer::new(buf);
+
+        loop {
+            let mut output = PartialBuffer::new(self.writer.partial_flush_buf()?);
+
+            self.state = match self.state {
+                State::Encoding => {
+                    self.encoder.encode(&mut input, &mut output)?;
+                    State::Encoding
+                }
+
+                State::Finishing | State::Done => panic!("Write after close"),
+            };
+
+            let produced = output.written().len();
+            self.writer.produce(produced);
+
+            if input.unwritten().is_empty() {
+                break;
+            }
+        }
+
+        let written = input.written().len();
+        if let Some(after) = self.auto_flush {
+            self.unflushed += written;
+            if self.unflushed >= after {
+                self.unflushed = 0;
+                self.flush_pending = true;
+            }
+        }
+
+        // The input has already been taken so the write succeeds whatever happens here, an
+        // unfinished flush (or its error) is picked up again by the next call
+        let _ = self.auto_flush();
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.codec_flush || self.flush_pending {
+            self.flush_codec()?;
+        }
+        self.writer.flush()?;
+        self.unflushed = 0;
+        self.flush_pending = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+macro_rules! encoder {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        ///
+        /// This structure implements a [`Write`](std::io::Write) interface and will take in
+        /// uncompressed data and write it compressed to an underlying stream. The stream must be
+        /// finished with [`try_finish`](Self::try_finish) or [`finish`](Self::finish) once
+        /// everything has been written.
+        pub struct $name<$inner> {
+            inner: crate::std_io::write::Encoder<$inner, crate::codec::$name>,
+        }
+
+        impl<$inner: std::io::Write> $name<$inner> {
+            /// Creates a new encoder which will take in uncompressed data and write it
+            /// compressed to the given stream.
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
+            $(
+                /// Creates a new encoder which will take in uncompressed data and write it
+                /// compressed to the given stream.
+                ///
+                $($constructor)*
+            )*
+
+            /// Creates a new encoder like `new`, taking the same extra arguments, with an internal
+            /// buffer of the specified capacity for the compressed output.
+            ///
+            /// Panics if `capacity` is zero.
+            pub fn with_capacity(capacity: usize, inner: $inner $(, $arg: $arg_ty)*) -> Self {
+                let (inner, codec) = Self::new(inner $(, $arg)*).inner.into_parts();
+                Self {
+                    inner: crate::std_io::write::Encoder::with_capacity(capacity, inner, codec),
+                }
+            }
+
+            /// Acquires a reference to the underlying writer that this encoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying writer that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the writer which
+            /// may otherwise confuse this encoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Returns the total number of uncompressed bytes this encoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of compressed bytes this encoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this encoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Configure whether flushing this encoder also flushes the codec, enabled by
+            /// default.
+            ///
+            /// A codec flush makes everything written so far decodable by the reader, but with
+            /// deflate based formats each one emits an extra block that costs some compression
+            /// ratio. When disabled flushing only writes out the output the codec has already
+            /// produced.
+            pub fn with_codec_flush(self, enabled: bool) -> Self {
+                Self {
+                    inner: self.inner.with_codec_flush(enabled),
+                }
+            }
+
+            /// Configure this encoder to flush the codec and the underlying writer by itself once
+            /// `after` uncompressed bytes have been written since the last flush, so interactive
+            /// streams reach the reader without explicit calls to `flush`. An `after` of 1 flushes
+            /// after every write.
+            ///
+            /// Like explicit flushes each automatic flush costs some compression ratio. If the
+            /// flush fails the write still succeeds, and the flush is retried by the next call on
+            /// this encoder.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `after` is zero.
+            pub fn with_auto_flush(self, after: usize) -> Self {
+                Self {
+                    inner: self.inner.with_auto_flush(after),
+                }
+            }
+
+            /// Finishes the compressed stream and flushes it to the underlying writer.
+            ///
+            /// Once this has completed nothing more can be written to this encoder.
+            pub fn try_finish(&mut self) -> std::io::Result<()> {
+                self.inner.try_finish()
+            }
+
+            /// Finishes the compressed stream like [`try_finish`](Self::try_finish) and returns
+            /// the underlying writer.
+            pub fn finish(self) -> std::io::Result<$inner> {
+                self.inner.finish()
+            }
+
+            /// Changes the compression level used for the rest of the stream, the data already
+            /// compressed is unaffected.
+            ///
+            /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and
+            /// `gzip` encoders flush before switching which costs some compression ratio. Other
+            /// encoders return an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+            pub fn set_level(&mut self, level: crate::Level) -> std::io::Result<()> {
+                self.inner.set_level(level)
+            }
+
+            /// Resets this encoder to compress a new stream to the same writer, reusing its
+            /// allocations where the codec allows it.
+            ///
+            /// Any output not yet written to the underlying writer is discarded and the statistics
+            /// start over, so this should be called once the previous stream has been finished.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this encoder like [`reset`](Self::reset) to compress a new stream to
+            /// `writer`, returning the previous writer.
+            pub fn reset_with_writer(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_writer(writer)
+            }
+
+            /// Consumes this encoder returning the underlying writer.
+            ///
+            /// Note that this may discard internal state of this encoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: std::io::Write> std::io::Write for $name<$inner> {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.inner.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.inner.flush()
+            }
+        }
+
+        const _: () = {
+            fn _assert() {
+                use crate::util::{_assert_send, _assert_sync};
+                use std::io::Write;
+
+                _assert_send::<$name<Box<dyn Write + Send>>>();
+                _assert_sync::<$name<Box<dyn Write + Sync>>>();
+            }
+        };
+    }
+}
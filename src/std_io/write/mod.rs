@@ -0,0 +1,18 @@
+//! Types which operate over [`Write`](std::io::Write) streams, both encoders and decoders for
+//! various formats.
+//!
+//! Custom formats can be used with the generic [`Encoder`] and [`Decoder`], see
+//! [`codec`](crate::codec).
+
+#[macro_use]
+mod macros;
+mod buf_writer;
+mod generic;
+
+use self::buf_writer::BufWriter;
+
+pub use self::generic::{Decoder, Encoder};
+
+algos!(std_io::write<W>);
+any!(std_io::write<W>);
+backends!(std_io::write<W>);
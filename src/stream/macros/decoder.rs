@@ -1,28 +1,24 @@
 macro_rules! decoder {
-    ($(#[$attr:meta])* $name:ident) => {
+    ($(#[$attr:meta])* $name:ident<$inner:ident> $({ $($constructor:tt)* })*) => {
         pin_project_lite::pin_project! {
             $(#[$attr])*
             #[derive(Debug)]
             ///
             /// This structure implements a [`Stream`](futures_core::stream::Stream) interface and will read
             /// compressed data from an underlying stream and emit a stream of uncompressed data.
-            pub struct $name<S> {
+            pub struct $name<$inner> {
                 #[pin]
-                inner: crate::stream::generic::Decoder<S, crate::codec::$name>,
+                inner: crate::stream::generic::Decoder<$inner, crate::codec::$name>,
             }
         }
 
-        impl<S: futures_core::stream::Stream<Item = std::io::Result<bytes_05::Bytes>>> $name<S> {
-            /// Creates a new decoder which will read compressed data from the given stream and
-            /// emit an uncompressed stream.
-            pub fn new(stream: S) -> Self {
-                Self {
-                    inner: crate::stream::Decoder::new(
-                        stream,
-                        crate::codec::$name::new(),
-                    ),
-                }
-            }
+        impl<$inner: futures_core::stream::Stream<Item = std::io::Result<bytes_05::Bytes>>> $name<$inner> {
+            $(
+                /// Creates a new decoder which will read compressed data from the given stream and
+                /// emit an uncompressed stream.
+                ///
+                $($constructor)*
+            )*
 
             /// Configure multi-member/frame decoding, if enabled this will reset the decoder state
             /// when reaching the end of a compressed member/frame and expect either the end of the
@@ -32,7 +28,7 @@ macro_rules! decoder {
             }
 
             /// Acquires a reference to the underlying stream that this decoder is wrapping.
-            pub fn get_ref(&self) -> &S {
+            pub fn get_ref(&self) -> &$inner {
                 self.inner.get_ref()
             }
 
@@ -41,7 +37,7 @@ macro_rules! decoder {
             ///
             /// Note that care must be taken to avoid tampering with the state of the stream which
             /// may otherwise confuse this decoder.
-            pub fn get_mut(&mut self) -> &mut S {
+            pub fn get_mut(&mut self) -> &mut $inner {
                 self.inner.get_mut()
             }
 
@@ -50,7 +46,7 @@ macro_rules! decoder {
             ///
             /// Note that care must be taken to avoid tampering with the state of the stream which
             /// may otherwise confuse this decoder.
-            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut S> {
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
                 self.project().inner.get_pin_mut()
             }
 
@@ -58,13 +54,13 @@ macro_rules! decoder {
             ///
             /// Note that this may discard internal state of this decoder, so care should be taken
             /// to avoid losing resources when this is called.
-            pub fn into_inner(self) -> S {
+            pub fn into_inner(self) -> $inner {
                 self.inner.into_inner()
             }
         }
 
-        impl<S: futures_core::stream::Stream<Item = std::io::Result<bytes_05::Bytes>>>
-            futures_core::stream::Stream for $name<S>
+        impl<$inner: futures_core::stream::Stream<Item = std::io::Result<bytes_05::Bytes>>>
+            futures_core::stream::Stream for $name<$inner>
         {
             type Item = std::io::Result<bytes_05::Bytes>;
 
@@ -15,6 +15,9 @@
 //! these solutions and standard IO data streams like `futures::io::AsyncBufRead` /
 //! `tokio::io::AsyncBufRead` should be zero-cost.
 //!
+//! For streams of `bytes` v1.0 chunks the `futures-stream` feature provides the same encoders and
+//! decoders in `async_compression::futures::stream`, otherwise:
+//!
 //! ```rust
 //! use bytes_05::Bytes;
 //! use futures::{stream::Stream, TryStreamExt};
@@ -147,6 +150,6 @@
 mod macros;
 mod generic;
 
-pub(crate) use self::generic::{Decoder, Encoder};
+pub(crate) use self::generic::Encoder;
 
 algos!(stream<S>);
@@ -0,0 +1,300 @@
+//! Adapters over [`AsyncBufRead`] streams running the codec on a blocking thread.
+
+use core::{
+    cmp::min,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::Result;
+
+use super::{decode, encode, finish_decoder, finish_encoder, Job, Worker, CHUNK_SIZE};
+use crate::{codec, Algorithm, Level};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+
+#[derive(Debug)]
+enum State {
+    Coding,
+    EndOfStream,
+    Finishing,
+    Done,
+}
+
+/// Copies as much of the output not yet read into `buf` as fits, returning whether there was any.
+fn read_output(output: &[u8], pos: &mut usize, buf: &mut ReadBuf<'_>) -> bool {
+    if *pos == output.len() {
+        return false;
+    }
+
+    let len = min(buf.remaining(), output.len() - *pos);
+    buf.put_slice(&output[*pos..*pos + len]);
+    *pos += len;
+    true
+}
+
+/// Takes the output, which has all been read, to reuse its allocation for the next job.
+fn take_output(output: &mut Vec<u8>, pos: &mut usize) -> Vec<u8> {
+    *pos = 0;
+    mem::take(output)
+}
+
+pin_project! {
+    /// An encoder for an [`Algorithm`] chosen at runtime, which reads uncompressed data from an
+    /// [`AsyncBufRead`] stream and compresses it on a blocking thread.
+    ///
+    /// Input is gathered from the underlying reader until a chunk is full or no more is available
+    /// yet, then compressed while this encoder waits for it.
+    #[derive(Debug)]
+    pub struct AnyEncoder<R> {
+        #[pin]
+        reader: R,
+        worker: Worker<codec::AnyEncoder>,
+        state: State,
+        input: Vec<u8>,
+        output: Vec<u8>,
+        pos: usize,
+    }
+}
+
+impl<R: AsyncBufRead> AnyEncoder<R> {
+    /// Creates a new encoder which will read uncompressed data from the given stream and emit a
+    /// compressed stream.
+    pub fn new(reader: R, algorithm: Algorithm) -> Self {
+        Self::with_quality(reader, algorithm, Level::Default)
+    }
+
+    /// Creates a new encoder which will read uncompressed data from the given stream and emit a
+    /// compressed stream, with the specified compression level.
+    pub fn with_quality(reader: R, algorithm: Algorithm, level: Level) -> Self {
+        Self {
+            reader,
+            worker: Worker::new(codec::AnyEncoder::new(algorithm, level)),
+            state: State::Coding,
+            input: Vec::new(),
+            output: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Acquires a reference to the underlying reader that this encoder is wrapping.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Acquires a mutable reference to the underlying reader that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this encoder.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Acquires a pinned mutable reference to the underlying reader that this encoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this encoder.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().reader
+    }
+
+    /// Consumes this encoder returning the underlying reader.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncBufRead> AsyncRead for AnyEncoder<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut this = self.project();
+
+        loop {
+            if read_output(this.output, this.pos, buf) {
+                return Poll::Ready(Ok(()));
+            }
+
+            if this.worker.is_running() {
+                let job = ready!(this.worker.poll_job(cx))?;
+                *this.input = job.input;
+                this.input.clear();
+                *this.output = job.output;
+                *this.pos = 0;
+                if let State::Finishing = this.state {
+                    *this.state = State::Done;
+                }
+                continue;
+            }
+
+            match this.state {
+                State::Coding => {
+                    let mut eof = false;
+                    while this.input.len() < CHUNK_SIZE {
+                        match this.reader.as_mut().poll_fill_buf(cx)? {
+                            Poll::Pending => break,
+                            Poll::Ready([]) => {
+                                eof = true;
+                                break;
+                            }
+                            Poll::Ready(input) => {
+                                let len = min(input.len(), CHUNK_SIZE - this.input.len());
+                                this.input.extend_from_slice(&input[..len]);
+                                this.reader.as_mut().consume(len);
+                            }
+                        }
+                    }
+
+                    let output = take_output(this.output, this.pos);
+                    if !this.input.is_empty() {
+                        let job = Job::new(mem::take(this.input), output);
+                        this.worker.spawn(job, encode);
+                    } else if eof {
+                        this.worker
+                            .spawn(Job::new(Vec::new(), output), finish_encoder);
+                        *this.state = State::Finishing;
+                    } else {
+                        *this.output = output;
+                        return Poll::Pending;
+                    }
+                }
+                State::EndOfStream | State::Finishing => unreachable!(),
+                State::Done => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// A decoder for an [`Algorithm`] chosen at runtime, which reads compressed data from an
+    /// [`AsyncBufRead`] stream and decompresses it on a blocking thread.
+    ///
+    /// Decoding stops at the end of the first member/frame, leaving any data following it
+    /// unconsumed in the underlying reader.
+    #[derive(Debug)]
+    pub struct AnyDecoder<R> {
+        #[pin]
+        reader: R,
+        worker: Worker<codec::AnyDecoder>,
+        state: State,
+        input: Vec<u8>,
+        output: Vec<u8>,
+        pos: usize,
+    }
+}
+
+impl<R: AsyncBufRead> AnyDecoder<R> {
+    /// Creates a new decoder which will read compressed data from the given stream and emit an
+    /// uncompressed stream.
+    pub fn new(reader: R, algorithm: Algorithm) -> Self {
+        Self {
+            reader,
+            worker: Worker::new(codec::AnyDecoder::new(algorithm)),
+            state: State::Coding,
+            input: Vec::new(),
+            output: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Acquires a reference to the underlying reader that this decoder is wrapping.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Acquires a mutable reference to the underlying reader that this decoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this decoder.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Acquires a pinned mutable reference to the underlying reader that this decoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this decoder.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().reader
+    }
+
+    /// Consumes this decoder returning the underlying reader.
+    ///
+    /// Note that this may discard internal state of this decoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncBufRead> AsyncRead for AnyDecoder<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut this = self.project();
+
+        loop {
+            if read_output(this.output, this.pos, buf) {
+                return Poll::Ready(Ok(()));
+            }
+
+            if this.worker.is_running() {
+                let job = ready!(this.worker.poll_job(cx))?;
+                // The input was copied from the reader's buffer, so only what was decoded is
+                // consumed from it
+                this.reader.as_mut().consume(job.consumed);
+                *this.state = match this.state {
+                    State::Coding if job.done => State::EndOfStream,
+                    State::Finishing => State::Done,
+                    _ => State::Coding,
+                };
+                *this.input = job.input;
+                *this.output = job.output;
+                *this.pos = 0;
+                continue;
+            }
+
+            match this.state {
+                State::Coding => match ready!(this.reader.as_mut().poll_fill_buf(cx))? {
+                    [] => {
+                        let job = Job::new(Vec::new(), take_output(this.output, this.pos));
+                        this.worker.spawn(job, finish_decoder);
+                        *this.state = State::Finishing;
+                    }
+                    input => {
+                        let mut chunk = mem::take(this.input);
+                        chunk.clear();
+                        chunk.extend_from_slice(&input[..min(input.len(), CHUNK_SIZE)]);
+                        this.worker
+                            .spawn(Job::new(chunk, take_output(this.output, this.pos)), decode);
+                    }
+                },
+                State::EndOfStream => {
+                    let job = Job::new(Vec::new(), take_output(this.output, this.pos));
+                    this.worker.spawn(job, finish_decoder);
+                    *this.state = State::Finishing;
+                }
+                State::Finishing => unreachable!(),
+                State::Done => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
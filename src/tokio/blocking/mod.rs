@@ -0,0 +1,186 @@
+//! Adapters which run the codec on the [`tokio` blocking thread pool](::tokio::task::spawn_blocking)
+//! instead of inside `poll_read`/`poll_write`.
+//!
+//! Compressing at high levels, such as `zstd` at level 19 or `xz` at level 9, can take tens of
+//! milliseconds per chunk, which blocks the runtime thread and starves every other task scheduled
+//! on it. The adapters in this module hand the data to the codec a chunk at a time on a blocking
+//! thread, while still presenting an [`AsyncRead`](::tokio::io::AsyncRead) or
+//! [`AsyncWrite`](::tokio::io::AsyncWrite) interface. This costs a copy of the data and a thread
+//! handoff per chunk, so is only worth it for slow codecs.
+//!
+//! The codec is chosen at runtime with an [`Algorithm`](crate::Algorithm) as for the `AnyEncoder`
+//! and `AnyDecoder` types in the other modules. They must be used from within a `tokio` runtime.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::{Error, ErrorKind, Result};
+
+use crate::{
+    codec::{Decode, Encode},
+    util::PartialBuffer,
+};
+use futures_core::ready;
+use tokio::task::JoinHandle;
+
+pub mod bufread;
+pub mod write;
+
+/// How much input is handed to the codec at once, and how much output a decoder produces at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A chunk of work done by the codec on a blocking thread.
+#[derive(Debug)]
+struct Job {
+    input: Vec<u8>,
+    consumed: usize,
+    output: Vec<u8>,
+    /// Whether the operation completed: the end of the stream was read by `decode`, or the
+    /// codec was flushed or finished.
+    done: bool,
+}
+
+impl Job {
+    /// Creates a job for `input`, reusing the allocation of `output`.
+    fn new(input: Vec<u8>, mut output: Vec<u8>) -> Self {
+        output.clear();
+        Self {
+            input,
+            consumed: 0,
+            output,
+            done: false,
+        }
+    }
+
+    fn remaining(&self) -> &[u8] {
+        &self.input[self.consumed..]
+    }
+}
+
+/// Calls `f` with space for more output at the end of `output`, returning its result along with
+/// how much output it wrote.
+fn with_spare<T>(
+    output: &mut Vec<u8>,
+    f: impl FnOnce(&mut PartialBuffer<&mut [u8]>) -> Result<T>,
+) -> Result<(T, usize)> {
+    let len = output.len();
+    output.resize(len + CHUNK_SIZE, 0);
+
+    let mut spare = PartialBuffer::new(&mut output[len..]);
+    let result = f(&mut spare);
+    let written = spare.written().len();
+    output.truncate(len + written);
+
+    Ok((result?, written))
+}
+
+fn encode(encoder: &mut impl Encode, job: &mut Job) -> Result<()> {
+    let mut input = PartialBuffer::new(&job.input[..]);
+
+    while !input.unwritten().is_empty() {
+        let prior = input.written().len();
+        let ((), written) =
+            with_spare(&mut job.output, |output| encoder.encode(&mut input, output))?;
+        if input.written().len() == prior && written == 0 {
+            return Err(Error::new(ErrorKind::Other, "encoder made no progress"));
+        }
+    }
+
+    job.consumed = input.written().len();
+    Ok(())
+}
+
+fn flush_encoder(encoder: &mut impl Encode, job: &mut Job) -> Result<()> {
+    while !job.done {
+        job.done = with_spare(&mut job.output, |output| encoder.flush(output))?.0;
+    }
+    Ok(())
+}
+
+fn finish_encoder(encoder: &mut impl Encode, job: &mut Job) -> Result<()> {
+    while !job.done {
+        job.done = with_spare(&mut job.output, |output| encoder.finish(output))?.0;
+    }
+    Ok(())
+}
+
+/// Decodes until the input is consumed, the end of the stream is read, or a chunk of output has
+/// been produced, so a small amount of highly compressed input can't produce unbounded output.
+fn decode(decoder: &mut impl Decode, job: &mut Job) -> Result<()> {
+    let mut input = PartialBuffer::new(&job.input[job.consumed..]);
+
+    while !job.done && !input.unwritten().is_empty() && job.output.len() < CHUNK_SIZE {
+        let prior = input.written().len();
+        let (done, written) =
+            with_spare(&mut job.output, |output| decoder.decode(&mut input, output))?;
+        job.done = done;
+        if !done && input.written().len() == prior && written == 0 {
+            return Err(Error::new(ErrorKind::Other, "decoder made no progress"));
+        }
+    }
+
+    job.consumed += input.written().len();
+    Ok(())
+}
+
+fn flush_decoder(decoder: &mut impl Decode, job: &mut Job) -> Result<()> {
+    while !job.done {
+        job.done = with_spare(&mut job.output, |output| decoder.flush(output))?.0;
+    }
+    Ok(())
+}
+
+fn finish_decoder(decoder: &mut impl Decode, job: &mut Job) -> Result<()> {
+    while !job.done {
+        job.done = with_spare(&mut job.output, |output| decoder.finish(output))?.0;
+    }
+    Ok(())
+}
+
+/// Owns the codec, sending it to a blocking thread along with each [`Job`].
+#[derive(Debug)]
+struct Worker<C> {
+    codec: Option<C>,
+    task: Option<JoinHandle<(C, Job, Result<()>)>>,
+}
+
+impl<C: Send + 'static> Worker<C> {
+    fn new(codec: C) -> Self {
+        Self {
+            codec: Some(codec),
+            task: None,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+
+    fn spawn(&mut self, mut job: Job, run: fn(&mut C, &mut Job) -> Result<()>) {
+        let mut codec = self
+            .codec
+            .take()
+            .expect("codec is only taken while a job is running");
+
+        self.task = Some(tokio::task::spawn_blocking(move || {
+            let result = run(&mut codec, &mut job);
+            (codec, job, result)
+        }));
+    }
+
+    fn poll_job(&mut self, cx: &mut Context<'_>) -> Poll<Result<Job>> {
+        let task = self.task.as_mut().expect("no job is running");
+
+        let (codec, job, result) = match ready!(Pin::new(task).poll(cx)) {
+            Ok(output) => output,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => return Poll::Ready(Err(Error::new(ErrorKind::Other, err))),
+        };
+
+        self.task = None;
+        self.codec = Some(codec);
+        Poll::Ready(result.map(|()| job))
+    }
+}
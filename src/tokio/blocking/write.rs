@@ -0,0 +1,410 @@
+//! Adapters over [`AsyncWrite`] streams running the codec on a blocking thread.
+
+use core::{
+    cmp::min,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::{ErrorKind, Result};
+
+use super::{
+    decode, encode, finish_decoder, finish_encoder, flush_decoder, flush_encoder, Job, Worker,
+    CHUNK_SIZE,
+};
+use crate::{codec, Algorithm, Level};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use tokio::io::AsyncWrite;
+
+#[derive(Debug)]
+enum EncoderState {
+    Encoding,
+    Flushing,
+    Finishing,
+    Done,
+}
+
+#[derive(Debug)]
+enum DecoderState {
+    Decoding,
+    Flushing,
+    EndOfStream,
+    Finishing,
+    Done,
+}
+
+/// Writes all of the output not yet written to `writer`.
+fn poll_write_output<W: AsyncWrite>(
+    mut writer: Pin<&mut W>,
+    cx: &mut Context<'_>,
+    output: &[u8],
+    pos: &mut usize,
+) -> Poll<Result<()>> {
+    while *pos < output.len() {
+        let len = ready!(writer.as_mut().poll_write(cx, &output[*pos..]))?;
+        if len == 0 {
+            return Poll::Ready(Err(ErrorKind::WriteZero.into()));
+        }
+        *pos += len;
+    }
+
+    Poll::Ready(Ok(()))
+}
+
+/// Takes the output, which has all been written, to reuse its allocation for the next job.
+fn take_output(output: &mut Vec<u8>, pos: &mut usize) -> Vec<u8> {
+    *pos = 0;
+    mem::take(output)
+}
+
+pin_project! {
+    /// An encoder for an [`Algorithm`] chosen at runtime, which compresses data written to it on
+    /// a blocking thread and writes the compressed data to an underlying [`AsyncWrite`] stream.
+    ///
+    /// Writes are gathered until a chunk is full, then compressed while the next chunk is
+    /// gathered, so writes only wait for the codec when it falls behind.
+    #[derive(Debug)]
+    pub struct AnyEncoder<W> {
+        #[pin]
+        writer: W,
+        worker: Worker<codec::AnyEncoder>,
+        state: EncoderState,
+        input: Vec<u8>,
+        output: Vec<u8>,
+        pos: usize,
+    }
+}
+
+impl<W: AsyncWrite> AnyEncoder<W> {
+    /// Creates a new encoder which will take in uncompressed data and write it compressed to the
+    /// given stream.
+    pub fn new(writer: W, algorithm: Algorithm) -> Self {
+        Self::with_quality(writer, algorithm, Level::Default)
+    }
+
+    /// Creates a new encoder which will take in uncompressed data and write it compressed to the
+    /// given stream, with the specified compression level.
+    pub fn with_quality(writer: W, algorithm: Algorithm, level: Level) -> Self {
+        Self {
+            writer,
+            worker: Worker::new(codec::AnyEncoder::new(algorithm, level)),
+            state: EncoderState::Encoding,
+            input: Vec::new(),
+            output: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Acquires a reference to the underlying writer that this encoder is wrapping.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Acquires a pinned mutable reference to the underlying writer that this encoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().writer
+    }
+
+    /// Consumes this encoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Waits for the running job and writes out all of its output.
+    fn poll_drain(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            ready!(poll_write_output(
+                this.writer.as_mut(),
+                cx,
+                this.output,
+                this.pos
+            ))?;
+
+            if !this.worker.is_running() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let job = ready!(this.worker.poll_job(cx))?;
+            *this.output = job.output;
+            *this.pos = 0;
+        }
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for AnyEncoder<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        match self.state {
+            EncoderState::Encoding => {}
+            // Anything written after starting a flush is not covered by it, so the next flush
+            // starts over
+            EncoderState::Flushing => *self.as_mut().project().state = EncoderState::Encoding,
+            EncoderState::Finishing | EncoderState::Done => panic!("Write after shutdown"),
+        }
+
+        if self.input.len() >= CHUNK_SIZE {
+            ready!(self.as_mut().poll_drain(cx))?;
+            let this = self.as_mut().project();
+            let job = Job::new(mem::take(this.input), take_output(this.output, this.pos));
+            this.worker.spawn(job, encode);
+        }
+
+        let input = self.project().input;
+        let len = min(buf.len(), CHUNK_SIZE - input.len());
+        input.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            ready!(self.as_mut().poll_drain(cx))?;
+            let this = self.as_mut().project();
+            if !this.input.is_empty() {
+                let job = Job::new(mem::take(this.input), take_output(this.output, this.pos));
+                this.worker.spawn(job, encode);
+                continue;
+            }
+
+            match this.state {
+                EncoderState::Encoding => {
+                    let job = Job::new(Vec::new(), take_output(this.output, this.pos));
+                    this.worker.spawn(job, flush_encoder);
+                    *this.state = EncoderState::Flushing;
+                }
+                EncoderState::Flushing => {
+                    *this.state = EncoderState::Encoding;
+                    break;
+                }
+                EncoderState::Finishing | EncoderState::Done => break,
+            }
+        }
+
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            ready!(self.as_mut().poll_drain(cx))?;
+            let this = self.as_mut().project();
+            if !this.input.is_empty() {
+                let job = Job::new(mem::take(this.input), take_output(this.output, this.pos));
+                this.worker.spawn(job, encode);
+                continue;
+            }
+
+            match this.state {
+                EncoderState::Encoding | EncoderState::Flushing => {
+                    let job = Job::new(Vec::new(), take_output(this.output, this.pos));
+                    this.worker.spawn(job, finish_encoder);
+                    *this.state = EncoderState::Finishing;
+                }
+                EncoderState::Finishing => *this.state = EncoderState::Done,
+                EncoderState::Done => break,
+            }
+        }
+
+        self.project().writer.poll_shutdown(cx)
+    }
+}
+
+pin_project! {
+    /// A decoder for an [`Algorithm`] chosen at runtime, which decompresses data written to it on
+    /// a blocking thread and writes the decompressed data to an underlying [`AsyncWrite`] stream.
+    ///
+    /// Each write is decompressed while the caller prepares the next one. Writing anything after
+    /// the end of the compressed stream fails with
+    /// [`Error::UnexpectedTrailingData`](crate::error::Error::UnexpectedTrailingData), which may
+    /// only be reported by the following write.
+    #[derive(Debug)]
+    pub struct AnyDecoder<W> {
+        #[pin]
+        writer: W,
+        worker: Worker<codec::AnyDecoder>,
+        state: DecoderState,
+        // A job which stopped after producing a chunk of output, with more input left to decode
+        pending: Option<Job>,
+        output: Vec<u8>,
+        pos: usize,
+    }
+}
+
+impl<W: AsyncWrite> AnyDecoder<W> {
+    /// Creates a new decoder which will take in compressed data and write it uncompressed to the
+    /// given stream.
+    pub fn new(writer: W, algorithm: Algorithm) -> Self {
+        Self {
+            writer,
+            worker: Worker::new(codec::AnyDecoder::new(algorithm)),
+            state: DecoderState::Decoding,
+            pending: None,
+            output: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Acquires a reference to the underlying writer that this decoder is wrapping.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this decoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this decoder.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Acquires a pinned mutable reference to the underlying writer that this decoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this decoder.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().writer
+    }
+
+    /// Consumes this decoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this decoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Waits for the running job, and any continuing it, and writes out all of their output.
+    fn poll_drain(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            ready!(poll_write_output(
+                this.writer.as_mut(),
+                cx,
+                this.output,
+                this.pos
+            ))?;
+
+            if let Some(mut job) = this.pending.take() {
+                job.output = take_output(this.output, this.pos);
+                job.output.clear();
+                this.worker.spawn(job, decode);
+            }
+
+            if !this.worker.is_running() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut job = ready!(this.worker.poll_job(cx))?;
+            *this.output = mem::take(&mut job.output);
+            *this.pos = 0;
+
+            if let DecoderState::Decoding = this.state {
+                if job.done {
+                    *this.state = DecoderState::EndOfStream;
+                    if !job.remaining().is_empty() {
+                        return Poll::Ready(
+                            Err(crate::error::Error::UnexpectedTrailingData.into()),
+                        );
+                    }
+                } else if !job.remaining().is_empty() {
+                    *this.pending = Some(job);
+                }
+            }
+        }
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for AnyDecoder<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        ready!(self.as_mut().poll_drain(cx))?;
+        let this = self.project();
+
+        match this.state {
+            DecoderState::Decoding => {}
+            DecoderState::Flushing => *this.state = DecoderState::Decoding,
+            DecoderState::EndOfStream => {
+                return Poll::Ready(Err(crate::error::Error::UnexpectedTrailingData.into()))
+            }
+            DecoderState::Finishing | DecoderState::Done => panic!("Write after shutdown"),
+        }
+
+        let len = min(buf.len(), CHUNK_SIZE);
+        let job = Job::new(buf[..len].to_vec(), take_output(this.output, this.pos));
+        this.worker.spawn(job, decode);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            ready!(self.as_mut().poll_drain(cx))?;
+            let this = self.as_mut().project();
+
+            match this.state {
+                DecoderState::Decoding => {
+                    let job = Job::new(Vec::new(), take_output(this.output, this.pos));
+                    this.worker.spawn(job, flush_decoder);
+                    *this.state = DecoderState::Flushing;
+                }
+                DecoderState::Flushing => {
+                    *this.state = DecoderState::Decoding;
+                    break;
+                }
+                DecoderState::EndOfStream | DecoderState::Finishing | DecoderState::Done => break,
+            }
+        }
+
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            ready!(self.as_mut().poll_drain(cx))?;
+            let this = self.as_mut().project();
+
+            match this.state {
+                DecoderState::Decoding | DecoderState::Flushing | DecoderState::EndOfStream => {
+                    let job = Job::new(Vec::new(), take_output(this.output, this.pos));
+                    this.worker.spawn(job, finish_decoder);
+                    *this.state = DecoderState::Finishing;
+                }
+                DecoderState::Finishing => *this.state = DecoderState::Done,
+                DecoderState::Done => break,
+            }
+        }
+
+        self.project().writer.poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,138 @@
+// Originally sourced from `tokio::io::util::buf_reader`, redefined locally so that the encoders
+// and decoders in this module can be constructed from a plain `AsyncRead` without depending on
+// the `io-util` feature of `tokio`.
+
+use crate::pool::{Buffer, BufferPool};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use std::{
+    cmp::min,
+    fmt, io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+pin_project! {
+    /// Adds buffering to any [`AsyncRead`] so that it can be used with the encoders and decoders
+    /// in this module, this is what their `from_read` constructors wrap the reader in.
+    pub struct BufReader<R> {
+        #[pin]
+        inner: R,
+        buf: Buffer,
+        pos: usize,
+        filled: usize,
+    }
+}
+
+impl<R: AsyncRead> BufReader<R> {
+    /// Creates a new `BufReader` with a default buffer capacity. The default is currently 8 KB,
+    /// but may change in the future.
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, inner)
+    }
+
+    /// Creates a new `BufReader` with the specified buffer capacity.
+    pub fn with_capacity(cap: usize, inner: R) -> Self {
+        Self::with_buffer(Buffer::new(cap), inner)
+    }
+
+    /// Creates a new `BufReader` with a buffer taken from `pool` when it is first needed, and
+    /// given back to it when this `BufReader` is dropped.
+    pub fn with_pool(pool: &BufferPool, inner: R) -> Self {
+        Self::with_buffer(Buffer::pooled(pool), inner)
+    }
+
+    fn with_buffer(buf: Buffer, inner: R) -> Self {
+        Self {
+            inner,
+            buf,
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// It is inadvisable to directly read from the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying reader.
+    ///
+    /// It is inadvisable to directly read from the underlying reader.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().inner
+    }
+
+    /// Returns a reference to the internally buffered data.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Consumes this `BufReader`, returning the underlying reader.
+    ///
+    /// Note that any leftover data in the internal buffer is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for BufReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        // Bypass the internal buffer when it is empty and the read is at least as large
+        if self.pos == self.filled && buf.remaining() >= self.buf.len() {
+            return self.project().inner.poll_read(cx, buf);
+        }
+
+        let available = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let len = min(available.len(), buf.remaining());
+        buf.put_slice(&available[..len]);
+        self.consume(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R: AsyncRead> AsyncBufRead for BufReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.project();
+
+        if *this.pos == *this.filled {
+            let mut buf = ReadBuf::new(&mut this.buf[..]);
+            ready!(this.inner.poll_read(cx, &mut buf))?;
+            *this.filled = buf.filled().len();
+            *this.pos = 0;
+        }
+
+        Poll::Ready(Ok(&this.buf[*this.pos..*this.filled]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.project();
+        *this.pos = min(*this.pos + amt, *this.filled);
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for BufReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufReader")
+            .field("reader", &self.inner)
+            .field(
+                "buffer",
+                &format_args!("{}/{}", self.filled - self.pos, self.buf.len()),
+            )
+            .finish()
+    }
+}
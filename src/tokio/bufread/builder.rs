@@ -0,0 +1,251 @@
+use crate::{
+    codec::{Decode, Encode},
+    tokio::bufread::{Decoder, Encoder},
+    Level, TrailingData,
+};
+use tokio::io::AsyncBufRead;
+
+/// A builder for the encoders in this module, returned by their `builder` method, which collects
+/// the compression level and other options before creating the encoder.
+///
+/// Any option not set keeps the default of an encoder created with `new`.
+#[derive(Debug)]
+pub struct EncoderBuilder<T, R, A = ()> {
+    inner: R,
+    args: A,
+    level: Level,
+    record_codec_time: bool,
+    yield_budget: Option<usize>,
+    #[cfg(feature = "tokio-time")]
+    flush_timeout: Option<core::time::Duration>,
+    build: fn(Self) -> T,
+}
+
+impl<T, R, A> EncoderBuilder<T, R, A> {
+    pub(crate) fn new(inner: R, args: A, build: fn(Self) -> T) -> Self {
+        Self {
+            inner,
+            args,
+            level: Level::Default,
+            record_codec_time: false,
+            yield_budget: None,
+            #[cfg(feature = "tokio-time")]
+            flush_timeout: None,
+            build,
+        }
+    }
+
+    /// Sets the compression level, defaults to [`Level::Default`].
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Configures recording the time spent inside the codec, see
+    /// [`Encoder::record_codec_time`].
+    pub fn record_codec_time(mut self, enabled: bool) -> Self {
+        self.record_codec_time = enabled;
+        self
+    }
+
+    /// Configures the encoder to flush when the input has stalled for longer than `timeout`, see
+    /// [`Encoder::with_flush_timeout`].
+    #[cfg(feature = "tokio-time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio-time")))]
+    pub fn flush_timeout(mut self, timeout: core::time::Duration) -> Self {
+        self.flush_timeout = Some(timeout);
+        self
+    }
+
+    /// Limits how much input the encoder consumes in a single poll before yielding, see
+    /// [`Encoder::with_yield_budget`].
+    pub fn yield_budget(mut self, bytes: usize) -> Self {
+        self.yield_budget = Some(bytes);
+        self
+    }
+
+    /// Creates the encoder with the options collected.
+    pub fn build(self) -> T {
+        let build = self.build;
+        build(self)
+    }
+
+    /// Creates the generic encoder with the options collected, `new` creates it for the level and
+    /// extra arguments.
+    pub(crate) fn build_with<E: Encode>(
+        self,
+        new: impl FnOnce(R, A, Level) -> Encoder<R, E>,
+    ) -> Encoder<R, E>
+    where
+        R: AsyncBufRead,
+    {
+        let mut encoder = new(self.inner, self.args, self.level);
+
+        if self.record_codec_time {
+            encoder.record_codec_time(true);
+        }
+        if let Some(bytes) = self.yield_budget {
+            encoder = encoder.with_yield_budget(bytes);
+        }
+
+        #[cfg(feature = "tokio-time")]
+        if let Some(timeout) = self.flush_timeout {
+            encoder = encoder.with_flush_timeout(timeout);
+        }
+
+        encoder
+    }
+}
+
+/// A builder for the decoders in this module, returned by their `builder` method, which collects
+/// the limits and other options before creating the decoder.
+///
+/// Any option not set keeps the default of a decoder created with `new`.
+#[derive(Debug)]
+pub struct DecoderBuilder<T, R, A = ()> {
+    inner: R,
+    args: A,
+    record_codec_time: bool,
+    output_limit: Option<u64>,
+    input_limit: Option<u64>,
+    compressed_size: Option<u64>,
+    uncompressed_size: Option<u64>,
+    #[cfg(feature = "flate2")]
+    crc32: Option<u32>,
+    yield_budget: Option<usize>,
+    multiple_members: bool,
+    trailing_data: Option<TrailingData>,
+    build: fn(Self) -> T,
+}
+
+impl<T, R, A> DecoderBuilder<T, R, A> {
+    pub(crate) fn new(inner: R, args: A, build: fn(Self) -> T) -> Self {
+        Self {
+            inner,
+            args,
+            record_codec_time: false,
+            output_limit: None,
+            input_limit: None,
+            compressed_size: None,
+            uncompressed_size: None,
+            #[cfg(feature = "flate2")]
+            crc32: None,
+            yield_budget: None,
+            multiple_members: false,
+            trailing_data: None,
+            build,
+        }
+    }
+
+    /// Configures recording the time spent inside the codec, see
+    /// [`Decoder::record_codec_time`].
+    pub fn record_codec_time(mut self, enabled: bool) -> Self {
+        self.record_codec_time = enabled;
+        self
+    }
+
+    /// Limits the number of uncompressed bytes the decoder will produce, see
+    /// [`Decoder::with_output_limit`].
+    pub fn output_limit(mut self, limit: u64) -> Self {
+        self.output_limit = Some(limit);
+        self
+    }
+
+    /// Limits the number of compressed bytes the decoder will consume, see
+    /// [`Decoder::with_input_limit`].
+    pub fn input_limit(mut self, limit: u64) -> Self {
+        self.input_limit = Some(limit);
+        self
+    }
+
+    /// Configures the decoder to decode an entry of exactly `size` bytes of compressed data, see
+    /// [`Decoder::with_compressed_size`].
+    pub fn compressed_size(mut self, size: u64) -> Self {
+        self.compressed_size = Some(size);
+        self
+    }
+
+    /// Verifies the length of the decompressed data, see [`Decoder::with_uncompressed_size`].
+    pub fn uncompressed_size(mut self, size: u64) -> Self {
+        self.uncompressed_size = Some(size);
+        self
+    }
+
+    /// Verifies the CRC-32 of the decompressed data, see [`Decoder::with_crc32`].
+    #[cfg(feature = "flate2")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "deflate", feature = "gzip", feature = "zlib")))
+    )]
+    pub fn crc32(mut self, crc: u32) -> Self {
+        self.crc32 = Some(crc);
+        self
+    }
+
+    /// Configures multi-member/frame decoding, see [`Decoder::multiple_members`].
+    pub fn multiple_members(mut self, enabled: bool) -> Self {
+        self.multiple_members = enabled;
+        self
+    }
+
+    /// Configures what happens to data following the end of the compressed stream, see
+    /// [`Decoder::trailing_data`].
+    pub fn trailing_data(mut self, policy: TrailingData) -> Self {
+        self.trailing_data = Some(policy);
+        self
+    }
+
+    /// Limits how much input the decoder consumes in a single poll before yielding, see
+    /// [`Decoder::with_yield_budget`].
+    pub fn yield_budget(mut self, bytes: usize) -> Self {
+        self.yield_budget = Some(bytes);
+        self
+    }
+
+    /// Creates the decoder with the options collected.
+    pub fn build(self) -> T {
+        let build = self.build;
+        build(self)
+    }
+
+    /// Creates the generic decoder with the options collected, `new` creates it for the extra
+    /// arguments.
+    pub(crate) fn build_with<D: Decode>(
+        self,
+        new: impl FnOnce(R, A) -> Decoder<R, D>,
+    ) -> Decoder<R, D>
+    where
+        R: AsyncBufRead,
+    {
+        let mut decoder = new(self.inner, self.args);
+
+        if self.record_codec_time {
+            decoder.record_codec_time(true);
+        }
+        decoder.multiple_members(self.multiple_members);
+        if let Some(policy) = self.trailing_data {
+            decoder.trailing_data(policy);
+        }
+
+        if let Some(limit) = self.output_limit {
+            decoder = decoder.with_output_limit(limit);
+        }
+        if let Some(limit) = self.input_limit {
+            decoder = decoder.with_input_limit(limit);
+        }
+        if let Some(size) = self.compressed_size {
+            decoder = decoder.with_compressed_size(size);
+        }
+        if let Some(size) = self.uncompressed_size {
+            decoder = decoder.with_uncompressed_size(size);
+        }
+        #[cfg(feature = "flate2")]
+        if let Some(crc) = self.crc32 {
+            decoder = decoder.with_crc32(crc);
+        }
+        if let Some(bytes) = self.yield_budget {
+            decoder = decoder.with_yield_budget(bytes);
+        }
+        decoder
+    }
+}
@@ -1,84 +1,351 @@
 use core::{
+    cmp::{max, min},
+    mem,
     pin::Pin,
     task::{Context, Poll},
 };
-use std::io::Result;
+use std::io::{ErrorKind, Result};
 
-use crate::{codec::Decode, util::PartialBuffer};
+use crate::{
+    checkpoint::Checkpoint,
+    codec::Decode,
+    entry::Entry,
+    error,
+    stats::{Instrumented, Stats},
+    util::{yield_output, PartialBuffer, DEFAULT_YIELD_BUDGET},
+    TrailingData,
+};
 use futures_core::ready;
 use pin_project_lite::pin_project;
+
+const DEFAULT_BUF_SIZE: usize = 8192;
 use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
 
 #[derive(Debug)]
 enum State {
     Decoding,
     Flushing,
+    Trailing,
     Done,
     Next,
 }
 
 pin_project! {
+    /// A generic decoder which decompresses data with any [`Decode`] implementation.
+    ///
+    /// This structure implements an [`AsyncRead`](tokio::io::AsyncRead) interface and will read
+    /// compressed data from an underlying stream and emit a stream of uncompressed data. It also
+    /// implements [`AsyncBufRead`](tokio::io::AsyncBufRead) so it can be used by other
+    /// adapters expecting a buffered reader without wrapping it in a `BufReader`.
+    ///
+    /// The decoders for each algorithm in this module are built on this type, it can be used
+    /// directly to wrap a custom format.
     #[derive(Debug)]
     pub struct Decoder<R, D: Decode> {
         #[pin]
         reader: R,
-        decoder: D,
+        decoder: Instrumented<D>,
         state: State,
+        yield_budget: usize,
+        entry: Entry,
+        // The most recent member/frame boundary
+        checkpoint: Option<Checkpoint>,
+        // Output produced by `poll_fill_buf` in `buffer[pos..filled]` that has not been consumed
+        buffer: Vec<u8>,
+        pos: usize,
+        filled: usize,
         multiple_members: bool,
+        trailing_data: TrailingData,
     }
 }
 
 impl<R: AsyncBufRead, D: Decode> Decoder<R, D> {
+    /// Creates a new decoder which will read compressed data from the given stream using `decoder`.
     pub fn new(reader: R, decoder: D) -> Self {
         Self {
             reader,
-            decoder,
+            decoder: Instrumented::new(decoder),
             state: State::Decoding,
+            yield_budget: DEFAULT_YIELD_BUDGET,
+            entry: Entry::default(),
+            checkpoint: None,
+            buffer: Vec::new(),
+            pos: 0,
+            filled: 0,
             multiple_members: false,
+            trailing_data: TrailingData::Stop,
         }
     }
 
+    /// Acquires a reference to the underlying reader that this decoder is wrapping.
     pub fn get_ref(&self) -> &R {
         &self.reader
     }
 
+    /// Acquires a mutable reference to the underlying reader that this decoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this decoder.
     pub fn get_mut(&mut self) -> &mut R {
         &mut self.reader
     }
 
+    /// Acquires a pinned mutable reference to the underlying reader that this decoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this decoder.
     pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
         self.project().reader
     }
 
+    /// Returns the total number of compressed bytes this decoder has consumed from the underlying
+    /// reader so far.
+    pub fn total_in(&self) -> u64 {
+        self.decoder.stats().total_in
+    }
+
+    /// Returns the total number of uncompressed bytes this decoder has produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.decoder.stats().total_out
+    }
+
+    /// Returns the number of compressed bytes this decoder has consumed and decoded so far, once
+    /// the end of the stream is reached this is its exact length. Unlike
+    /// [`total_in`](Self::total_in) this excludes any data read ahead but not decoded, see
+    /// [`into_inner_with_leftover`](Self::into_inner_with_leftover).
+    pub fn bytes_consumed(&self) -> u64 {
+        self.decoder.stats().total_in - self.decoder.unconsumed().len() as u64
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.decoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this decoder so far.
+    pub fn stats(&self) -> Stats {
+        self.decoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Decode::checksum`](crate::codec::Decode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.decoder.checksum()
+    }
+
+    /// Returns whether this decoder has reached the end of the compressed stream and returned
+    /// all of its output. If reading returns EOF before this the underlying reader ended early.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Returns whether this decoder is partway through the header of a member/frame, having
+    /// consumed some of it but not all. This is only tracked for the gzip and zlib formats and is
+    /// always `false` for others.
+    pub fn in_header(&self) -> bool {
+        self.decoder.in_header()
+    }
+
+    /// Returns the number of members/frames this decoder has completely decoded so far.
+    pub fn member_count(&self) -> u64 {
+        self.decoder.stats().frames
+    }
+
+    /// Returns a [`Checkpoint`] at the most recent member/frame boundary this decoder has passed
+    /// and returned all the output before, from which decoding can be resumed, see the
+    /// [`checkpoint`](crate::checkpoint) module. Returns `None` if there is no such boundary.
+    ///
+    /// Boundaries are only passed between the members/frames of a stream when
+    /// [multi-member/frame decoding](Self::multiple_members) is enabled, and at its end.
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        let returned = self.decoder.stats().total_out - (self.filled - self.pos) as u64;
+        self.checkpoint
+            .filter(|checkpoint| checkpoint.total_out() <= returned)
+    }
+
+    /// Resumes decoding from `checkpoint`, returned by [`checkpoint`](Self::checkpoint) on an
+    /// earlier decoder, see the [`checkpoint`](crate::checkpoint) module.
+    ///
+    /// The underlying reader should carry on from
+    /// [`Checkpoint::total_in`](crate::checkpoint::Checkpoint::total_in) of the compressed
+    /// stream, and the output follows the first
+    /// [`Checkpoint::total_out`](crate::checkpoint::Checkpoint::total_out) bytes returned by the
+    /// earlier decoder. The totals, limits and later checkpoints count from the start of the
+    /// stream.
+    pub fn with_checkpoint(mut self, checkpoint: &Checkpoint) -> Self {
+        self.decoder.resume(checkpoint);
+        self.checkpoint = Some(*checkpoint);
+        self
+    }
+
+    /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once more
+    /// would be produced it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error
+    /// instead. This protects against decompression bombs when decoding untrusted data.
+    pub fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    /// Limits the number of compressed bytes this decoder will consume to `limit`, once more would
+    /// be needed it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error instead.
+    /// This stops a never-ending stream from keeping the decoder busy indefinitely.
+    pub fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+
+    /// Configure this decoder to decode an entry embedded in a container format, such as a file
+    /// in a ZIP archive, which is exactly `size` bytes of compressed data.
+    ///
+    /// The decoder consumes exactly that much from the underlying reader and then reports the end
+    /// of the stream, leaving the reader positioned after the entry whatever the trailing data
+    /// policy. It fails with [`UnexpectedTrailingData`](error::Error::UnexpectedTrailingData) if
+    /// the compressed stream ends before the end of the entry, or with
+    /// [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) if the reader does.
+    pub fn with_compressed_size(mut self, size: u64) -> Self {
+        self.entry.set_compressed_size(size);
+        self
+    }
+
+    /// Verify that the stream decompresses to exactly `size` bytes, failing with
+    /// [`ChecksumMismatch`](crate::error::Error::ChecksumMismatch) otherwise. This is checked as
+    /// soon as more is produced, and at the end of the stream.
+    pub fn with_uncompressed_size(mut self, size: u64) -> Self {
+        self.entry.set_uncompressed_size(size);
+        self
+    }
+
+    /// Verify that the CRC-32 of the decompressed data is `crc` at the end of the stream, as
+    /// stored for each file in a ZIP archive, failing with
+    /// [`ChecksumMismatch`](crate::error::Error::ChecksumMismatch) otherwise.
+    #[cfg(feature = "flate2")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "deflate", feature = "gzip", feature = "zlib")))
+    )]
+    pub fn with_crc32(mut self, crc: u32) -> Self {
+        self.entry.set_crc32(crc);
+        self
+    }
+
+    /// Limits how much input this decoder consumes from the underlying reader in a single poll to
+    /// `bytes`, defaults to 1 MiB. Once it is used up the decoder returns the output it has
+    /// produced, or if there is none yields to the executor by waking the task and returning
+    /// `Pending`, so a reader which is always ready such as an in-memory buffer doesn't monopolize
+    /// the task.
+    ///
+    /// Readers which take part in an executor's own budget, such as the `tokio` IO types, may
+    /// return `Pending` sooner than this.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is zero.
+    pub fn with_yield_budget(mut self, bytes: usize) -> Self {
+        assert!(bytes > 0, "yield budget must be non-zero");
+
+        self.yield_budget = bytes;
+        self
+    }
+
+    /// Consumes this decoder returning the underlying reader.
+    ///
+    /// Note that this may discard internal state of this decoder, so care should be taken to
+    /// avoid losing resources when this is called.
     pub fn into_inner(self) -> R {
         self.reader
     }
 
+    /// Consumes this decoder returning the underlying reader, along with any compressed data the
+    /// decoder has read from it but not decoded.
+    ///
+    /// This decoder only consumes as much from the reader as it decodes so whatever follows the
+    /// end of the stream is normally left in the reader, but some decoders such as the
+    /// [`AutoDecoder`](crate::tokio::bufread::AutoDecoder) may read ahead of it. Any such
+    /// data is returned here, and comes before whatever is left in the reader.
+    pub fn into_inner_with_leftover(self) -> (R, Vec<u8>) {
+        let leftover = self.decoder.unconsumed().to_vec();
+        (self.reader, leftover)
+    }
+
+    /// Configure multi-member/frame decoding, if enabled this will reinitialize the decoder
+    /// when reaching the end of a compressed member/frame and expect either EOF or another
+    /// compressed member/frame to follow it in the stream.
     pub fn multiple_members(&mut self, enabled: bool) {
         self.multiple_members = enabled;
     }
 
+    /// Configure what happens to data following the end of the compressed stream when
+    /// multi-member/frame decoding is disabled, see [`TrailingData`] for the options.
+    ///
+    /// Defaults to [`TrailingData::Stop`], leaving the data in the underlying reader.
+    pub fn trailing_data(&mut self, policy: TrailingData) {
+        self.trailing_data = policy;
+    }
+
+    /// Resets this decoder to decode a new stream from the same reader, reusing its allocations.
+    ///
+    /// Any output not yet read is discarded and the statistics start over, while configuration
+    /// such as the limits and multi-member/frame decoding is kept.
+    pub fn reset(&mut self) -> Result<()> {
+        self.decoder.reset()?;
+        self.entry.reset();
+        self.checkpoint = None;
+        self.state = State::Decoding;
+        self.pos = 0;
+        self.filled = 0;
+        Ok(())
+    }
+
+    /// Resets this decoder like [`reset`](Self::reset) to decode a new stream from `reader`,
+    /// returning the previous reader.
+    pub fn reset_with_reader(&mut self, reader: R) -> Result<R> {
+        self.reset()?;
+        Ok(mem::replace(&mut self.reader, reader))
+    }
+
     fn do_poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         output: &mut PartialBuffer<&mut [u8]>,
     ) -> Poll<Result<()>> {
         let mut this = self.project();
+        let mut budget = *this.yield_budget;
 
         loop {
+            let written = output.written().len();
+
             *this.state = match this.state {
                 State::Decoding => {
-                    let input = ready!(this.reader.as_mut().poll_fill_buf(cx))?;
+                    let stats = this.decoder.stats();
+                    let input = if this.entry.input_len(&stats, 1) == 0 {
+                        // The whole entry has been consumed, what follows it is left in the reader
+                        &[][..]
+                    } else {
+                        let input = ready!(this.reader.as_mut().poll_fill_buf(cx))?;
+                        if input.is_empty() {
+                            if this.entry.is_sized() {
+                                return Poll::Ready(Err(ErrorKind::UnexpectedEof.into()));
+                            }
+                            // Avoid attempting to reinitialise the decoder if the reader
+                            // has returned EOF.
+                            *this.multiple_members = false;
+                        }
+                        input
+                    };
                     if input.is_empty() {
-                        // Avoid attempting to reinitialise the decoder if the reader
-                        // has returned EOF.
-                        *this.multiple_members = false;
                         State::Flushing
                     } else {
-                        let mut input = PartialBuffer::new(input);
+                        let len = this.entry.input_len(&stats, input.len()).min(budget);
+                        let mut input = PartialBuffer::new(&input[..len]);
                         let done = this.decoder.decode(&mut input, output)?;
                         let len = input.written().len();
                         this.reader.as_mut().consume(len);
+                        budget = budget.saturating_sub(len);
                         if done {
                             State::Flushing
                         } else {
@@ -89,42 +356,91 @@ impl<R: AsyncBufRead, D: Decode> Decoder<R, D> {
 
                 State::Flushing => {
                     if this.decoder.finish(output)? {
+                        let stats = this.decoder.stats();
+                        *this.checkpoint = Some(Checkpoint::new(
+                            stats.total_in - this.decoder.unconsumed().len() as u64,
+                            stats.total_out,
+                        ));
                         if *this.multiple_members {
                             this.decoder.reinit()?;
                             State::Next
-                        } else {
+                        } else if this.entry.is_sized() {
                             State::Done
+                        } else {
+                            State::Trailing
                         }
                     } else {
                         State::Flushing
                     }
                 }
 
+                State::Trailing => match *this.trailing_data {
+                    TrailingData::Stop => State::Done,
+                    policy => {
+                        let input = ready!(this.reader.as_mut().poll_fill_buf(cx))?;
+                        if input.is_empty() {
+                            State::Done
+                        } else if let TrailingData::Ignore = policy {
+                            let len = input.len().min(budget);
+                            this.reader.as_mut().consume(len);
+                            budget = budget.saturating_sub(len);
+                            State::Trailing
+                        } else {
+                            return Poll::Ready(Err(error::Error::UnexpectedTrailingData.into()));
+                        }
+                    }
+                },
+
                 State::Done => State::Done,
 
                 State::Next => {
-                    let input = ready!(this.reader.as_mut().poll_fill_buf(cx))?;
-                    if input.is_empty() {
+                    if this.entry.input_len(&this.decoder.stats(), 1) == 0 {
                         State::Done
                     } else {
-                        State::Decoding
+                        let input = ready!(this.reader.as_mut().poll_fill_buf(cx))?;
+                        if !input.is_empty() {
+                            State::Decoding
+                        } else if this.entry.is_sized() {
+                            return Poll::Ready(Err(ErrorKind::UnexpectedEof.into()));
+                        } else {
+                            State::Done
+                        }
                     }
                 }
             };
 
+            let stats = this.decoder.stats();
+            this.entry.update(&stats, &output.written()[written..])?;
+
             if let State::Done = *this.state {
+                this.entry.check(&stats, this.decoder.unconsumed().len())?;
                 return Poll::Ready(Ok(()));
             }
             if output.unwritten().is_empty() {
                 return Poll::Ready(Ok(()));
             }
+            if budget == 0 {
+                return yield_output(cx, output);
+            }
         }
     }
 }
 
+impl<R, D: Decode> Decoder<R, D> {
+    /// Acquires a reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec(&self) -> &D {
+        self.decoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut D {
+        self.decoder.get_mut()
+    }
+}
+
 impl<R: AsyncBufRead, D: Decode> AsyncRead for Decoder<R, D> {
     fn poll_read(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<Result<()>> {
@@ -132,14 +448,74 @@ impl<R: AsyncBufRead, D: Decode> AsyncRead for Decoder<R, D> {
             return Poll::Ready(Ok(()));
         }
 
-        let mut output = PartialBuffer::new(buf.initialize_unfilled());
-        match self.do_poll_read(cx, &mut output)? {
-            Poll::Pending if output.written().is_empty() => Poll::Pending,
-            _ => {
-                let len = output.written().len();
-                buf.advance(len);
-                Poll::Ready(Ok(()))
+        // Return any output left over from `poll_fill_buf` before producing more
+        if self.pos < self.filled {
+            let this = self.project();
+            let len = min(buf.remaining(), *this.filled - *this.pos);
+            buf.put_slice(&this.buffer[*this.pos..*this.pos + len]);
+            *this.pos += len;
+            return Poll::Ready(Ok(()));
+        }
+
+        // Only initialize the part of the unfilled buffer being written to, starting small and
+        // doubling it each time it is filled, so a large uninitialized buffer is not zeroed in
+        // full for a small amount of output
+        let start = buf.filled().len();
+        let mut len = DEFAULT_BUF_SIZE;
+
+        loop {
+            let initialized = buf.initialized().len() - buf.filled().len();
+            let unfilled = buf.initialize_unfilled_to(min(buf.remaining(), max(len, initialized)));
+
+            let mut output = PartialBuffer::new(unfilled);
+            let result = self.as_mut().do_poll_read(cx, &mut output)?;
+            let written = output.written().len();
+            let full = output.unwritten().is_empty();
+            buf.advance(written);
+
+            if result.is_pending() {
+                if buf.filled().len() == start {
+                    return Poll::Pending;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            if !full || buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            len *= 2;
+        }
+    }
+}
+
+impl<R: AsyncBufRead, D: Decode> AsyncBufRead for Decoder<R, D> {
+    fn poll_fill_buf(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        if self.pos == self.filled {
+            // The buffer is only allocated once it is needed, so plain `AsyncRead` usage
+            // doesn't pay for it
+            let mut buffer = core::mem::take(self.as_mut().project().buffer);
+            buffer.resize(DEFAULT_BUF_SIZE, 0);
+
+            let mut output = PartialBuffer::new(&mut buffer[..]);
+            let result = self.as_mut().do_poll_read(cx, &mut output);
+            let len = output.written().len();
+
+            let this = self.as_mut().project();
+            *this.buffer = buffer;
+            *this.pos = 0;
+            *this.filled = len;
+
+            if result?.is_pending() && len == 0 {
+                return Poll::Pending;
             }
         }
+
+        let this = self.project();
+        Poll::Ready(Ok(&this.buffer[*this.pos..*this.filled]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.project();
+        *this.pos = min(*this.pos + amt, *this.filled);
     }
 }
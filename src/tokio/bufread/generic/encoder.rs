@@ -1,52 +1,190 @@
 use core::{
+    cmp::{max, min},
+    mem,
     pin::Pin,
     task::{Context, Poll},
 };
 use std::io::Result;
 
-use crate::{codec::Encode, util::PartialBuffer};
+use super::flush_timeout::FlushTimeout;
+use crate::{
+    codec::Encode,
+    stats::{Instrumented, Stats},
+    util::{yield_output, PartialBuffer, DEFAULT_YIELD_BUDGET},
+};
 use futures_core::ready;
 use pin_project_lite::pin_project;
+
+const DEFAULT_BUF_SIZE: usize = 8192;
 use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
 
 #[derive(Debug)]
 enum State {
     Encoding,
+    TimedFlushing,
     Flushing,
     Done,
 }
 
 pin_project! {
+    /// A generic encoder which compresses data with any [`Encode`] implementation.
+    ///
+    /// This structure implements an [`AsyncRead`](tokio::io::AsyncRead) interface and will read
+    /// uncompressed data from an underlying stream and emit a stream of compressed data. It also
+    /// implements [`AsyncBufRead`](tokio::io::AsyncBufRead) so it can be used by other
+    /// adapters expecting a buffered reader without wrapping it in a `BufReader`.
+    ///
+    /// The encoders for each algorithm in this module are built on this type, it can be used
+    /// directly to wrap a custom format.
     #[derive(Debug)]
     pub struct Encoder<R, E: Encode> {
         #[pin]
         reader: R,
-        encoder: E,
+        encoder: Instrumented<E>,
         state: State,
+        yield_budget: usize,
+        flush_timeout: FlushTimeout,
+        // Output produced by `poll_fill_buf` in `buffer[pos..filled]` that has not been consumed
+        buffer: Vec<u8>,
+        pos: usize,
+        filled: usize,
     }
 }
 
 impl<R: AsyncBufRead, E: Encode> Encoder<R, E> {
+    /// Creates a new encoder which will read uncompressed data from the given stream using
+    /// `encoder`.
     pub fn new(reader: R, encoder: E) -> Self {
         Self {
             reader,
-            encoder,
+            encoder: Instrumented::new(encoder),
             state: State::Encoding,
+            yield_budget: DEFAULT_YIELD_BUDGET,
+            flush_timeout: FlushTimeout::default(),
+            buffer: Vec::new(),
+            pos: 0,
+            filled: 0,
         }
     }
 
+    /// Acquires a reference to the underlying reader that this encoder is wrapping.
     pub fn get_ref(&self) -> &R {
         &self.reader
     }
 
+    /// Acquires a mutable reference to the underlying reader that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this encoder.
     pub fn get_mut(&mut self) -> &mut R {
         &mut self.reader
     }
 
+    /// Acquires a pinned mutable reference to the underlying reader that this encoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the reader which may
+    /// otherwise confuse this encoder.
     pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
         self.project().reader
     }
 
+    /// Returns the total number of uncompressed bytes this encoder has consumed from the underlying
+    /// reader so far.
+    pub fn total_in(&self) -> u64 {
+        self.encoder.stats().total_in
+    }
+
+    /// Returns the total number of compressed bytes this encoder has produced so far.
+    pub fn total_out(&self) -> u64 {
+        self.encoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.encoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this encoder so far.
+    pub fn stats(&self) -> Stats {
+        self.encoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Encode::checksum`](crate::codec::Encode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.encoder.checksum()
+    }
+
+    /// Configure this encoder to flush the compressed stream when the underlying reader has no
+    /// more input available and some of the input it has already taken has been waiting for
+    /// longer than `timeout`, so slow streams don't sit in the encoder until more data arrives.
+    ///
+    /// This uses the `tokio` timer, so must be used from within a `tokio` runtime with it enabled.
+    #[cfg(feature = "tokio-time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio-time")))]
+    pub fn with_flush_timeout(mut self, timeout: core::time::Duration) -> Self {
+        self.flush_timeout.set(timeout);
+        self
+    }
+
+    /// Changes the compression level used for the rest of the stream, the data already compressed
+    /// is unaffected.
+    ///
+    /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and `gzip`
+    /// encoders flush before switching which costs some compression ratio. Other encoders return
+    /// an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+    pub fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.encoder.set_level(level)
+    }
+
+    /// Resets this encoder to compress a new stream from the same reader, reusing its
+    /// allocations where the codec allows it.
+    ///
+    /// Any output not yet read is discarded and the statistics start over.
+    pub fn reset(&mut self) -> Result<()> {
+        self.encoder.reset()?;
+        self.state = State::Encoding;
+        self.flush_timeout.stop();
+        self.pos = 0;
+        self.filled = 0;
+        Ok(())
+    }
+
+    /// Resets this encoder like [`reset`](Self::reset) to compress a new stream from `reader`,
+    /// returning the previous reader.
+    pub fn reset_with_reader(&mut self, reader: R) -> Result<R> {
+        self.reset()?;
+        Ok(mem::replace(&mut self.reader, reader))
+    }
+
+    /// Limits how much input this encoder consumes from the underlying reader in a single poll to
+    /// `bytes`, defaults to 1 MiB. Once it is used up the encoder returns the output it has
+    /// produced, or if there is none yields to the executor by waking the task and returning
+    /// `Pending`, so a reader which is always ready such as an in-memory buffer doesn't monopolize
+    /// the task.
+    ///
+    /// Readers which take part in an executor's own budget, such as the `tokio` IO types, may
+    /// return `Pending` sooner than this.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is zero.
+    pub fn with_yield_budget(mut self, bytes: usize) -> Self {
+        assert!(bytes > 0, "yield budget must be non-zero");
+
+        self.yield_budget = bytes;
+        self
+    }
+
+    /// Consumes this encoder returning the underlying reader.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
     pub fn into_inner(self) -> R {
         self.reader
     }
@@ -57,20 +195,42 @@ impl<R: AsyncBufRead, E: Encode> Encoder<R, E> {
         output: &mut PartialBuffer<&mut [u8]>,
     ) -> Poll<Result<()>> {
         let mut this = self.project();
+        let mut budget = *this.yield_budget;
 
         loop {
             *this.state = match this.state {
-                State::Encoding => {
-                    let input = ready!(this.reader.as_mut().poll_fill_buf(cx))?;
-                    if input.is_empty() {
+                State::Encoding => match this.reader.as_mut().poll_fill_buf(cx)? {
+                    Poll::Pending => {
+                        // While the reader is waiting for more input, input already taken is
+                        // flushed once it has waited too long
+                        ready!(this.flush_timeout.poll_expired(cx));
+                        State::TimedFlushing
+                    }
+                    Poll::Ready([]) => {
+                        this.flush_timeout.stop();
                         State::Flushing
-                    } else {
+                    }
+                    Poll::Ready(input) => {
+                        let input = &input[..input.len().min(budget)];
                         let mut input = PartialBuffer::new(input);
                         this.encoder.encode(&mut input, output)?;
                         let len = input.written().len();
                         this.reader.as_mut().consume(len);
+                        budget = budget.saturating_sub(len);
+                        if len > 0 {
+                            this.flush_timeout.start();
+                        }
                         State::Encoding
                     }
+                },
+
+                State::TimedFlushing => {
+                    if this.encoder.flush(output)? {
+                        this.flush_timeout.stop();
+                        State::Encoding
+                    } else {
+                        State::TimedFlushing
+                    }
                 }
 
                 State::Flushing => {
@@ -90,13 +250,28 @@ impl<R: AsyncBufRead, E: Encode> Encoder<R, E> {
             if output.unwritten().is_empty() {
                 return Poll::Ready(Ok(()));
             }
+            if budget == 0 {
+                return yield_output(cx, output);
+            }
         }
     }
 }
 
+impl<R, E: Encode> Encoder<R, E> {
+    /// Acquires a reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec(&self) -> &E {
+        self.encoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut E {
+        self.encoder.get_mut()
+    }
+}
+
 impl<R: AsyncBufRead, E: Encode> AsyncRead for Encoder<R, E> {
     fn poll_read(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<Result<()>> {
@@ -104,14 +279,74 @@ impl<R: AsyncBufRead, E: Encode> AsyncRead for Encoder<R, E> {
             return Poll::Ready(Ok(()));
         }
 
-        let mut output = PartialBuffer::new(buf.initialize_unfilled());
-        match self.do_poll_read(cx, &mut output)? {
-            Poll::Pending if output.written().is_empty() => Poll::Pending,
-            _ => {
-                let len = output.written().len();
-                buf.advance(len);
-                Poll::Ready(Ok(()))
+        // Return any output left over from `poll_fill_buf` before producing more
+        if self.pos < self.filled {
+            let this = self.project();
+            let len = min(buf.remaining(), *this.filled - *this.pos);
+            buf.put_slice(&this.buffer[*this.pos..*this.pos + len]);
+            *this.pos += len;
+            return Poll::Ready(Ok(()));
+        }
+
+        // Only initialize the part of the unfilled buffer being written to, starting small and
+        // doubling it each time it is filled, so a large uninitialized buffer is not zeroed in
+        // full for a small amount of output
+        let start = buf.filled().len();
+        let mut len = DEFAULT_BUF_SIZE;
+
+        loop {
+            let initialized = buf.initialized().len() - buf.filled().len();
+            let unfilled = buf.initialize_unfilled_to(min(buf.remaining(), max(len, initialized)));
+
+            let mut output = PartialBuffer::new(unfilled);
+            let result = self.as_mut().do_poll_read(cx, &mut output)?;
+            let written = output.written().len();
+            let full = output.unwritten().is_empty();
+            buf.advance(written);
+
+            if result.is_pending() {
+                if buf.filled().len() == start {
+                    return Poll::Pending;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            if !full || buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            len *= 2;
+        }
+    }
+}
+
+impl<R: AsyncBufRead, E: Encode> AsyncBufRead for Encoder<R, E> {
+    fn poll_fill_buf(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<&[u8]>> {
+        if self.pos == self.filled {
+            // The buffer is only allocated once it is needed, so plain `AsyncRead` usage
+            // doesn't pay for it
+            let mut buffer = core::mem::take(self.as_mut().project().buffer);
+            buffer.resize(DEFAULT_BUF_SIZE, 0);
+
+            let mut output = PartialBuffer::new(&mut buffer[..]);
+            let result = self.as_mut().do_poll_read(cx, &mut output);
+            let len = output.written().len();
+
+            let this = self.as_mut().project();
+            *this.buffer = buffer;
+            *this.pos = 0;
+            *this.filled = len;
+
+            if result?.is_pending() && len == 0 {
+                return Poll::Pending;
             }
         }
+
+        let this = self.project();
+        Poll::Ready(Ok(&this.buffer[*this.pos..*this.filled]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.project();
+        *this.pos = min(*this.pos + amt, *this.filled);
     }
 }
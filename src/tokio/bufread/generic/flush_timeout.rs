@@ -0,0 +1,45 @@
+use core::task::{Context, Poll};
+
+/// Tracks how long input has been held in an encoder without being flushed, so it can be flushed
+/// once it has waited longer than the configured timeout. Without the `tokio-time` feature it
+/// never expires.
+#[derive(Debug, Default)]
+pub(super) struct FlushTimeout {
+    #[cfg(feature = "tokio-time")]
+    timeout: Option<core::time::Duration>,
+    #[cfg(feature = "tokio-time")]
+    sleep: Option<core::pin::Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl FlushTimeout {
+    #[cfg(feature = "tokio-time")]
+    pub(super) fn set(&mut self, timeout: core::time::Duration) {
+        self.timeout = Some(timeout);
+        self.sleep = None;
+    }
+
+    /// Starts the timer if it is not already running, called whenever the encoder takes input.
+    pub(super) fn start(&mut self) {
+        #[cfg(feature = "tokio-time")]
+        if let (Some(timeout), None) = (self.timeout, &self.sleep) {
+            self.sleep = Some(Box::pin(tokio::time::sleep(timeout)));
+        }
+    }
+
+    /// Stops the timer, called once everything taken so far has been flushed.
+    pub(super) fn stop(&mut self) {
+        #[cfg(feature = "tokio-time")]
+        {
+            self.sleep = None;
+        }
+    }
+
+    pub(super) fn poll_expired(&mut self, _cx: &mut Context<'_>) -> Poll<()> {
+        #[cfg(feature = "tokio-time")]
+        if let Some(sleep) = &mut self.sleep {
+            return core::future::Future::poll(sleep.as_mut(), _cx);
+        }
+
+        Poll::Pending
+    }
+}
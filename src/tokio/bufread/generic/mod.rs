@@ -1,4 +1,5 @@
 mod decoder;
 mod encoder;
+mod flush_timeout;
 
 pub use self::{decoder::Decoder, encoder::Encoder};
@@ -0,0 +1,25 @@
+use core::{future::poll_fn, pin::Pin, task::Poll};
+use std::io::Result;
+
+use crate::Algorithm;
+use futures_core::ready;
+use tokio::io::AsyncBufRead;
+
+/// Detects the compression format of the data at the start of `reader` without consuming any of
+/// it, so the reader can then be passed through unchanged or given to the matching decoder such as
+/// [`AnyDecoder`](super::AnyDecoder).
+///
+/// The formats are recognised the same way as by [`AutoDecoder`](super::AutoDecoder), `None` is
+/// returned if the data isn't in any of them or the reader is empty. Custom formats are not
+/// checked, use `AutoDecoder::with_formats` for those.
+///
+/// Only the data the reader has buffered after a single fill is inspected, if that is too short to
+/// decide it is judged as if it were the whole stream. A buffer of at least 64 bytes is enough for
+/// every format.
+pub async fn probe<R: AsyncBufRead + Unpin + ?Sized>(reader: &mut R) -> Result<Option<Algorithm>> {
+    poll_fn(|cx| {
+        let prefix = ready!(Pin::new(&mut *reader).poll_fill_buf(cx))?;
+        Poll::Ready(Ok(crate::codec::probe(prefix)))
+    })
+    .await
+}
@@ -0,0 +1,421 @@
+use core::{
+    cmp::min,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::{Error, ErrorKind, Result};
+
+use crate::{
+    codec::Decode,
+    error,
+    pool::{Buffer, BufferPool},
+    stats::{Instrumented, Stats},
+    tokio::write::{AsyncBufWrite, BufWriter},
+    util::PartialBuffer,
+    TrailingData,
+};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use tokio::io::AsyncWrite;
+
+const DEFAULT_BUF_SIZE: usize = 8192;
+
+#[derive(Debug)]
+enum State {
+    Decoding,
+    Finishing,
+    Done,
+}
+
+pin_project! {
+    /// A generic decoder which decompresses data with any [`Decode`] implementation.
+    ///
+    /// This structure implements an [`AsyncWrite`](tokio::io::AsyncWrite) interface and will
+    /// decompress data written to it and write the uncompressed data to an underlying stream. It
+    /// also implements [`AsyncBufWrite`] to allow writing compressed data directly into its
+    /// internal buffer.
+    ///
+    /// The decoders for each algorithm in this module are built on this type, it can be used
+    /// directly to wrap a custom format.
+    #[derive(Debug)]
+    pub struct Decoder<W, D: Decode> {
+        #[pin]
+        writer: BufWriter<W>,
+        decoder: Instrumented<D>,
+        // Compressed data in `input[consumed..buffered]` is waiting to be decoded
+        input: Buffer,
+        consumed: usize,
+        buffered: usize,
+        state: State,
+        propagate_shutdown: bool,
+        trailing_data: TrailingData,
+    }
+}
+
+impl<W: AsyncWrite, D: Decode> Decoder<W, D> {
+    /// Creates a new decoder which will write uncompressed data to the given stream using
+    /// `decoder`.
+    pub fn new(writer: W, decoder: D) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, writer, decoder)
+    }
+
+    /// Creates a new decoder like [`new`](Self::new), with internal buffers of the specified
+    /// capacity for both the compressed input and the uncompressed output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, writer: W, decoder: D) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        Self::with_buffers(
+            Buffer::new(capacity),
+            Buffer::new(capacity),
+            writer,
+            decoder,
+        )
+    }
+
+    /// Creates a new decoder like [`new`](Self::new), with its internal buffers taken from
+    /// `pool` when they are first needed and given back to it when this decoder is dropped.
+    pub fn with_pool(pool: &BufferPool, writer: W, decoder: D) -> Self {
+        let (input, output) = (Buffer::pooled(pool), Buffer::pooled(pool));
+        Self::with_buffers(input, output, writer, decoder)
+    }
+
+    fn with_buffers(input: Buffer, output: Buffer, writer: W, decoder: D) -> Self {
+        Self {
+            writer: BufWriter::with_buffer(output, writer),
+            decoder: Instrumented::new(decoder),
+            input,
+            consumed: 0,
+            buffered: 0,
+            state: State::Decoding,
+            propagate_shutdown: true,
+            trailing_data: TrailingData::Error,
+        }
+    }
+
+    /// Acquires a reference to the underlying writer that this decoder is wrapping.
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying writer that this decoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this decoder.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    /// Acquires a pinned mutable reference to the underlying writer that this decoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this decoder.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
+        self.project().writer.get_pin_mut()
+    }
+
+    /// Returns the total number of compressed bytes this decoder has consumed so far, not
+    /// counting any still waiting in its internal buffer.
+    pub fn total_in(&self) -> u64 {
+        self.decoder.stats().total_in
+    }
+
+    /// Returns the total number of uncompressed bytes this decoder has produced so far, including
+    /// any still buffered and not yet written to the underlying writer.
+    pub fn total_out(&self) -> u64 {
+        self.decoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.decoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this decoder so far.
+    pub fn stats(&self) -> Stats {
+        self.decoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Decode::checksum`](crate::codec::Decode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.decoder.checksum()
+    }
+
+    /// Returns whether the end of the compressed stream has been written to this decoder and all
+    /// of its output has been decoded.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Returns whether this decoder is partway through the header of a member/frame, having
+    /// consumed some of it but not all. This is only tracked for the gzip and zlib formats and is
+    /// always `false` for others.
+    pub fn in_header(&self) -> bool {
+        self.decoder.in_header()
+    }
+
+    /// Returns the number of members/frames this decoder has completely decoded so far.
+    pub fn member_count(&self) -> u64 {
+        self.decoder.stats().frames
+    }
+
+    /// Limits the number of uncompressed bytes this decoder will produce to `limit`, once more
+    /// would be produced it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error
+    /// instead. This protects against decompression bombs when decoding untrusted data.
+    pub fn with_output_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_output_limit(limit);
+        self
+    }
+
+    /// Limits the number of compressed bytes this decoder will consume to `limit`, once more would
+    /// be needed it fails with a [`LimitExceeded`](crate::error::LimitExceeded) error instead.
+    /// This stops a never-ending stream from keeping the decoder busy indefinitely.
+    pub fn with_input_limit(mut self, limit: u64) -> Self {
+        self.decoder.set_input_limit(limit);
+        self
+    }
+
+    /// Configure what happens to data written after the end of the compressed stream, see
+    /// [`TrailingData`] for the options.
+    ///
+    /// Defaults to [`TrailingData::Error`]. With [`TrailingData::Stop`] any later writes return
+    /// `Ok(0)`, leaving the trailing data with the caller.
+    pub fn trailing_data(&mut self, policy: TrailingData) {
+        self.trailing_data = policy;
+    }
+
+    /// Configure whether shutting down this decoder also shuts down the underlying writer,
+    /// enabled by default. When disabled it only flushes the underlying writer once the
+    /// compressed stream has been fully decoded, so the writer can be shared with later streams.
+    pub fn with_shutdown_propagation(mut self, enabled: bool) -> Self {
+        self.propagate_shutdown = enabled;
+        self
+    }
+
+    /// Resets this decoder to decode a new stream to the same writer, reusing its allocations.
+    ///
+    /// Any input not yet decoded and output not yet written to the underlying writer is discarded
+    /// and the statistics start over, so this should be called once the previous stream has been
+    /// closed. Configuration such as the limits and trailing data policy is kept.
+    pub fn reset(&mut self) -> Result<()> {
+        self.decoder.reset()?;
+        self.state = State::Decoding;
+        self.writer.discard_buffer();
+        self.consumed = 0;
+        self.buffered = 0;
+        Ok(())
+    }
+
+    /// Resets this decoder like [`reset`](Self::reset) to decode a new stream to `writer`,
+    /// returning the previous writer.
+    pub fn reset_with_writer(&mut self, writer: W) -> Result<W> {
+        self.reset()?;
+        Ok(self.writer.replace_inner(writer))
+    }
+
+    /// Consumes this decoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this decoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    /// Consumes this decoder returning the underlying writer and codec, discarding anything that
+    /// is buffered.
+    pub(crate) fn into_parts(self) -> (W, D) {
+        (self.writer.into_inner(), self.decoder.into_inner())
+    }
+
+    /// Decodes all the buffered input, if the writer is not ready for all of it then whatever
+    /// input is left is moved to the start of the buffer.
+    fn do_poll_decode(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.consumed == *this.buffered {
+                *this.consumed = 0;
+                *this.buffered = 0;
+                return Poll::Ready(Ok(()));
+            }
+
+            if let State::Finishing | State::Done = this.state {
+                match this.trailing_data {
+                    TrailingData::Error => {
+                        return Poll::Ready(Err(error::Error::UnexpectedTrailingData.into()));
+                    }
+                    TrailingData::Ignore => {
+                        *this.consumed = *this.buffered;
+                        continue;
+                    }
+                    // Left buffered, it's not decoded and never reaches the underlying writer
+                    TrailingData::Stop => return Poll::Ready(Ok(())),
+                }
+            }
+
+            let output = match this.writer.as_mut().poll_partial_flush_buf(cx)? {
+                Poll::Ready(output) => output,
+                Poll::Pending => {
+                    this.input.copy_within(*this.consumed..*this.buffered, 0);
+                    *this.buffered -= *this.consumed;
+                    *this.consumed = 0;
+                    return Poll::Pending;
+                }
+            };
+            let mut output = PartialBuffer::new(output);
+            let mut input = PartialBuffer::new(&this.input[*this.consumed..*this.buffered]);
+
+            if this.decoder.decode(&mut input, &mut output)? {
+                *this.state = State::Finishing;
+            }
+
+            *this.consumed += input.written().len();
+            let produced = output.written().len();
+            this.writer.as_mut().produce(produced);
+        }
+    }
+
+    fn do_poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            let output = ready!(this.writer.as_mut().poll_partial_flush_buf(cx))?;
+            let mut output = PartialBuffer::new(output);
+
+            let (state, done) = match this.state {
+                State::Decoding => {
+                    let done = this.decoder.flush(&mut output)?;
+                    (State::Decoding, done)
+                }
+
+                State::Finishing => {
+                    if this.decoder.finish(&mut output)? {
+                        (State::Done, false)
+                    } else {
+                        (State::Finishing, false)
+                    }
+                }
+
+                State::Done => (State::Done, true),
+            };
+
+            *this.state = state;
+
+            let produced = output.written().len();
+            this.writer.as_mut().produce(produced);
+
+            if done {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
+
+impl<W, D: Decode> Decoder<W, D> {
+    /// Acquires a reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec(&self) -> &D {
+        self.decoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this decoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut D {
+        self.decoder.get_mut()
+    }
+}
+
+impl<W: AsyncWrite, D: Decode> AsyncBufWrite for Decoder<W, D> {
+    fn poll_partial_flush_buf(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<&mut [u8]>> {
+        if let State::Finishing | State::Done = self.state {
+            let this = self.project();
+            return match this.trailing_data {
+                TrailingData::Error => {
+                    Poll::Ready(Err(error::Error::UnexpectedTrailingData.into()))
+                }
+                // Anything written is discarded by the next decode
+                TrailingData::Ignore => {
+                    *this.consumed = 0;
+                    *this.buffered = 0;
+                    Poll::Ready(Ok(&mut this.input[..]))
+                }
+                TrailingData::Stop => Poll::Ready(Ok(&mut [])),
+            };
+        }
+
+        if self.buffered == self.input.len() {
+            // If the writer is not ready there may still be space freed by decoding some input
+            if self.as_mut().do_poll_decode(cx)?.is_pending() && self.buffered == self.input.len() {
+                return Poll::Pending;
+            }
+        }
+
+        let this = self.project();
+        Poll::Ready(Ok(&mut this.input[*this.buffered..]))
+    }
+
+    fn produce(self: Pin<&mut Self>, amt: usize) {
+        *self.project().buffered += amt;
+    }
+}
+
+impl<W: AsyncWrite, D: Decode> AsyncWrite for Decoder<W, D> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let space = ready!(self.as_mut().poll_partial_flush_buf(cx))?;
+        let len = min(space.len(), buf.len());
+        space[..len].copy_from_slice(&buf[..len]);
+        self.produce(len);
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().do_poll_decode(cx))?;
+        ready!(self.as_mut().do_poll_flush(cx))?;
+        ready!(self.project().writer.as_mut().poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().do_poll_decode(cx))?;
+
+        if let State::Decoding = self.as_mut().project().state {
+            *self.as_mut().project().state = State::Finishing;
+        }
+
+        ready!(self.as_mut().do_poll_flush(cx))?;
+
+        if let State::Done = self.as_mut().project().state {
+            let this = self.as_mut().project();
+            if *this.propagate_shutdown {
+                ready!(this.writer.poll_shutdown(cx))?;
+            } else {
+                ready!(this.writer.poll_flush(cx))?;
+            }
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(Error::new(
+                ErrorKind::Other,
+                "Attempt to shutdown before finishing input",
+            )))
+        }
+    }
+}
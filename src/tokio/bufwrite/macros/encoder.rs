@@ -0,0 +1,265 @@
+macro_rules! encoder {
+    (
+        $(#[$attr:meta])* $name:ident<$inner:ident> {
+            pub fn new($new_inner:ident: $new_inner_ty:ident $(, $arg:ident: $arg_ty:ty)*) -> Self
+                $new_body:block
+        } $({ $($constructor:tt)* })*
+    ) => {
+        pin_project_lite::pin_project! {
+            $(#[$attr])*
+            #[derive(Debug)]
+            ///
+            /// This structure implements an [`AsyncWrite`](tokio::io::AsyncWrite) interface and will
+            /// take in uncompressed data and write it compressed to an underlying stream. It also
+            /// implements [`AsyncBufWrite`](crate::tokio::bufwrite::AsyncBufWrite) to allow
+            /// writing uncompressed data directly into its internal buffer.
+            pub struct $name<$inner> {
+                #[pin]
+                inner: crate::tokio::bufwrite::Encoder<$inner, crate::codec::$name>,
+            }
+        }
+
+        impl<$inner: tokio::io::AsyncWrite> $name<$inner> {
+            /// Creates a new encoder which will take in uncompressed data and write it
+            /// compressed to the given stream.
+            pub fn new($new_inner: $new_inner_ty $(, $arg: $arg_ty)*) -> Self $new_body
+
+            $(
+                /// Creates a new encoder which will take in uncompressed data and write it
+                /// compressed to the given stream.
+                ///
+                $($constructor)*
+            )*
+
+            /// Returns a builder for this encoder, taking the same extra arguments as `new`, which
+            /// collects the compression level and other options before creating it.
+            pub fn builder(
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> crate::tokio::bufwrite::EncoderBuilder<Self, $inner, ($($arg_ty,)*)> {
+                crate::tokio::bufwrite::EncoderBuilder::new(inner, ($($arg,)*), |builder| Self {
+                    inner: builder.build_with(|inner, ($($arg,)*), level| {
+                        Self::with_quality(inner $(, $arg)*, level).inner
+                    }),
+                })
+            }
+
+            /// Creates a new encoder like `new`, taking the same extra arguments, with internal
+            /// buffers of the specified capacity for both the uncompressed input and the
+            /// compressed output.
+            ///
+            /// Panics if `capacity` is zero.
+            pub fn with_capacity(capacity: usize, inner: $inner $(, $arg: $arg_ty)*) -> Self {
+                let (inner, codec) = Self::new(inner $(, $arg)*).inner.into_parts();
+                Self {
+                    inner: crate::tokio::bufwrite::Encoder::with_capacity(capacity, inner, codec),
+                }
+            }
+
+            /// Creates a new encoder like `new`, taking the same extra arguments, with its internal
+            /// buffers taken from `pool` when they are first needed and given back to it
+            /// when this encoder is dropped.
+            pub fn with_pool(
+                pool: &crate::pool::BufferPool,
+                inner: $inner
+                $(, $arg: $arg_ty)*
+            ) -> Self {
+                let (inner, codec) = Self::new(inner $(, $arg)*).inner.into_parts();
+                Self {
+                    inner: crate::tokio::bufwrite::Encoder::with_pool(pool, inner, codec),
+                }
+            }
+
+            /// Acquires a reference to the underlying writer that this encoder is wrapping.
+            pub fn get_ref(&self) -> &$inner {
+                self.inner.get_ref()
+            }
+
+            /// Acquires a mutable reference to the underlying writer that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the writer which
+            /// may otherwise confuse this encoder.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                self.inner.get_mut()
+            }
+
+            /// Acquires a pinned mutable reference to the underlying writer that this encoder is
+            /// wrapping.
+            ///
+            /// Note that care must be taken to avoid tampering with the state of the writer which
+            /// may otherwise confuse this encoder.
+            pub fn get_pin_mut(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut $inner> {
+                self.project().inner.get_pin_mut()
+            }
+
+            /// Returns the total number of uncompressed bytes this encoder has consumed so far.
+            pub fn total_in(&self) -> u64 {
+                self.inner.total_in()
+            }
+
+            /// Returns the total number of compressed bytes this encoder has produced so far.
+            pub fn total_out(&self) -> u64 {
+                self.inner.total_out()
+            }
+
+            /// Configure recording the time spent inside the codec, reported by
+            /// [`Stats::codec_time`](crate::stats::Stats::codec_time). Disabling it discards the
+            /// time recorded so far.
+            ///
+            /// This uses [`std::time::Instant`], so it will panic on platforms that don't support
+            /// it such as `wasm32-unknown-unknown`.
+            pub fn record_codec_time(&mut self, enabled: bool) {
+                self.inner.record_codec_time(enabled);
+            }
+
+            /// Returns a snapshot of the statistics of this encoder so far.
+            pub fn stats(&self) -> crate::stats::Stats {
+                self.inner.stats()
+            }
+
+            /// Returns the checksum of the uncompressed data in the current member/frame so far,
+            /// for formats which record one in the stream: the CRC-32 for gzip and the Adler-32
+            /// for zlib, `None` for other formats.
+            pub fn checksum(&self) -> Option<u32> {
+                self.inner.checksum()
+            }
+
+            /// Configure whether shutting down this encoder also shuts down the underlying writer,
+            /// enabled by default. When disabled it only flushes the underlying writer once the
+            /// compressed stream has been finished, so the writer can be shared with
+            /// later streams.
+            pub fn with_shutdown_propagation(self, enabled: bool) -> Self {
+                Self {
+                    inner: self.inner.with_shutdown_propagation(enabled),
+                }
+            }
+
+            /// Configure whether flushing this encoder also flushes the codec, enabled by
+            /// default.
+            ///
+            /// A codec flush makes everything written so far decodable by the reader, but with
+            /// deflate based formats each one emits an extra block that costs some compression
+            /// ratio. When disabled flushing only writes out the output the codec has already
+            /// produced.
+            pub fn with_codec_flush(self, enabled: bool) -> Self {
+                Self {
+                    inner: self.inner.with_codec_flush(enabled),
+                }
+            }
+
+            /// Attempts to finish the compressed stream and flush it to the underlying writer,
+            /// without shutting down the underlying writer.
+            ///
+            /// Once this has completed nothing more can be written to this encoder.
+            pub fn poll_finish(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                self.project().inner.poll_finish(cx)
+            }
+
+            /// Finishes the compressed stream and flushes it to the underlying writer like
+            /// [`poll_finish`](Self::poll_finish), leaving the underlying writer open.
+            pub async fn try_finish(&mut self) -> std::io::Result<()>
+            where
+                $inner: Unpin,
+            {
+                self.inner.try_finish().await
+            }
+
+            /// Finishes the compressed stream like [`try_finish`](Self::try_finish) and returns
+            /// the underlying writer, which is left open.
+            pub async fn finish(self) -> std::io::Result<$inner>
+            where
+                $inner: Unpin,
+            {
+                self.inner.finish().await
+            }
+
+            /// Changes the compression level used for the rest of the stream, the data already
+            /// compressed is unaffected.
+            ///
+            /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and
+            /// `gzip` encoders flush before switching which costs some compression ratio. Other
+            /// encoders return an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+            pub fn set_level(&mut self, level: crate::Level) -> std::io::Result<()> {
+                self.inner.set_level(level)
+            }
+
+            /// Resets this encoder to compress a new stream to the same writer, reusing its
+            /// allocations where the codec allows it.
+            ///
+            /// Any input not yet encoded and output not yet written to the underlying writer is
+            /// discarded and the statistics start over, so this should be called once the previous
+            /// stream has been closed.
+            pub fn reset(&mut self) -> std::io::Result<()> {
+                self.inner.reset()
+            }
+
+            /// Resets this encoder like [`reset`](Self::reset) to compress a new stream to
+            /// `writer`, returning the previous writer.
+            pub fn reset_with_writer(&mut self, writer: $inner) -> std::io::Result<$inner> {
+                self.inner.reset_with_writer(writer)
+            }
+
+            /// Consumes this encoder returning the underlying writer.
+            ///
+            /// Note that this may discard internal state of this encoder, so care should be taken
+            /// to avoid losing resources when this is called.
+            pub fn into_inner(self) -> $inner {
+                self.inner.into_inner()
+            }
+        }
+
+        impl<$inner: tokio::io::AsyncWrite> tokio::io::AsyncWrite for $name<$inner> {
+            fn poll_write(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &[u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.project().inner.poll_write(cx, buf)
+            }
+
+            fn poll_flush(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                self.project().inner.poll_flush(cx)
+            }
+
+            fn poll_shutdown(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<()>> {
+                self.project().inner.poll_shutdown(cx)
+            }
+        }
+
+        impl<$inner: tokio::io::AsyncWrite> crate::tokio::bufwrite::AsyncBufWrite
+            for $name<$inner>
+        {
+            fn poll_partial_flush_buf(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<std::io::Result<&mut [u8]>> {
+                self.project().inner.poll_partial_flush_buf(cx)
+            }
+
+            fn produce(self: std::pin::Pin<&mut Self>, amt: usize) {
+                self.project().inner.produce(amt)
+            }
+        }
+
+        const _: () = {
+            fn _assert() {
+                use crate::util::{_assert_send, _assert_sync};
+                use core::pin::Pin;
+                use tokio::io::AsyncWrite;
+
+                _assert_send::<$name<Pin<Box<dyn AsyncWrite + Send>>>>();
+                _assert_sync::<$name<Pin<Box<dyn AsyncWrite + Sync>>>>();
+            }
+        };
+    }
+}
@@ -0,0 +1,154 @@
+//! A [`tokio_util::codec`](tokio_util_07::codec) implementation for length-prefixed frames which
+//! are each compressed independently.
+
+use crate::{
+    codec::{AnyDecoder, AnyEncoder, Decode, Encode},
+    util::{write_into, PartialBuffer},
+    Algorithm, Level,
+};
+use bytes::{Bytes, BytesMut};
+use std::io::{Error, ErrorKind, Result};
+use tokio_util_07::codec::{Decoder, Encoder, LengthDelimitedCodec};
+
+/// A codec for frames which are each compressed independently and then prefixed with their
+/// compressed length.
+///
+/// Frames are delimited with a [`LengthDelimitedCodec`] using its default configuration, a 4
+/// byte big-endian length prefix. Each outbound frame is compressed with a new encoder for the
+/// [`Algorithm`], and each inbound frame must contain exactly one complete compressed stream,
+/// otherwise decoding fails with an error of kind [`InvalidData`](ErrorKind::InvalidData).
+///
+/// ```rust
+/// # #[cfg(feature = "gzip")]
+/// # fn main() -> std::io::Result<()> {
+/// use async_compression::{tokio::codec::CompressedCodec, Algorithm};
+/// use bytes::{Bytes, BytesMut};
+/// use tokio_util::codec::{Decoder, Encoder};
+/// # use tokio_util_07 as tokio_util;
+///
+/// let mut codec = CompressedCodec::new(Algorithm::Gzip);
+///
+/// let mut buffer = BytesMut::new();
+/// codec.encode(Bytes::from_static(b"hello world"), &mut buffer)?;
+///
+/// assert_eq!(codec.decode(&mut buffer)?.unwrap(), &b"hello world"[..]);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "gzip"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct CompressedCodec {
+    inner: LengthDelimitedCodec,
+    algorithm: Algorithm,
+    level: Level,
+}
+
+impl CompressedCodec {
+    /// Creates a new codec which compresses frames with the given algorithm.
+    pub fn new(algorithm: Algorithm) -> Self {
+        Self::with_quality(algorithm, Level::Default)
+    }
+
+    /// Creates a new codec which compresses frames with the given algorithm and compression
+    /// level.
+    pub fn with_quality(algorithm: Algorithm, level: Level) -> Self {
+        Self {
+            inner: LengthDelimitedCodec::new(),
+            algorithm,
+            level,
+        }
+    }
+
+    /// Returns the maximum length of a frame, see
+    /// [`set_max_frame_length`](Self::set_max_frame_length).
+    pub fn max_frame_length(&self) -> usize {
+        self.inner.max_frame_length()
+    }
+
+    /// Sets the maximum length of a frame, defaults to 8 MiB.
+    ///
+    /// This limits both the compressed length of a frame and the length it decompresses to, so
+    /// that a small compressed frame can't expand into an unbounded amount of memory. Decoding a
+    /// frame which exceeds it fails with an error of kind [`InvalidData`](ErrorKind::InvalidData),
+    /// encoding one fails with an error of kind [`InvalidInput`](ErrorKind::InvalidInput).
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.inner.set_max_frame_length(val);
+    }
+}
+
+impl Decoder for CompressedCodec {
+    type Item = BytesMut;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>> {
+        let frame = match self.inner.decode(src)? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        let max_frame_length = self.max_frame_length();
+        let check_length = |output: &BytesMut| {
+            if output.len() > max_frame_length {
+                Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "decompressed frame exceeds max frame length",
+                ))
+            } else {
+                Ok(())
+            }
+        };
+
+        let mut decoder = AnyDecoder::new(self.algorithm);
+        let mut input = PartialBuffer::new(&frame[..]);
+        let mut output = BytesMut::new();
+
+        while !input.unwritten().is_empty() {
+            let done = write_into(&mut output, |output| decoder.decode(&mut input, output))?;
+            check_length(&output)?;
+            if done {
+                break;
+            }
+        }
+
+        while !write_into(&mut output, |output| decoder.finish(output))? {
+            check_length(&output)?;
+        }
+        check_length(&output)?;
+
+        if !input.unwritten().is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "frame contains data after the end of the compressed stream",
+            ));
+        }
+
+        Ok(Some(output))
+    }
+}
+
+impl Encoder<Bytes> for CompressedCodec {
+    type Error = Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<()> {
+        let mut encoder = AnyEncoder::new(self.algorithm, self.level);
+        let mut input = PartialBuffer::new(&item[..]);
+        let mut output = BytesMut::new();
+
+        while !input.unwritten().is_empty() {
+            write_into(&mut output, |output| encoder.encode(&mut input, output))?;
+        }
+        while !write_into(&mut output, |output| encoder.finish(output))? {}
+
+        self.inner.encode(output.freeze(), dst)
+    }
+}
+
+const _: () = {
+    fn _assert() {
+        use crate::util::{_assert_send, _assert_sync};
+
+        _assert_send::<CompressedCodec>();
+        _assert_sync::<CompressedCodec>();
+    }
+};
@@ -0,0 +1,143 @@
+//! Functions compressing and decompressing whole files with [`tokio::fs`].
+//!
+//! The output is written to a temporary file next to the destination, synced to disk, then renamed
+//! over the destination, so the destination never holds a partially written file, even if the
+//! process crashes.
+
+use std::{
+    convert::TryFrom,
+    io::Result,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::UNIX_EPOCH,
+};
+
+use super::oneshot::copy_buf;
+use crate::{
+    codec::AnyEncoder,
+    tokio::{
+        bufread::{AnyDecoder, BufReader},
+        write::Encoder,
+    },
+    Algorithm, Level,
+};
+use tokio::fs::{self, File};
+
+#[cfg(all(feature = "tokio-uring", target_os = "linux"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "tokio-uring", target_os = "linux"))))]
+pub mod uring;
+
+/// Returns a unique path in the same directory as `dst` to write the output to before renaming
+/// it over `dst`.
+pub(super) fn temp_path(dst: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = dst.file_name().map(Path::new).unwrap_or(dst);
+    dst.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name.display(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+    ))
+}
+
+/// Returns the name of `src` to record in the header, if it can be.
+pub(super) fn file_name(src: &Path) -> Option<&[u8]> {
+    src.file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.contains('\0'))
+        .map(str::as_bytes)
+}
+
+/// Writes the output to a temporary file with `write`, then syncs it and renames it over `dst`,
+/// removing the temporary file if anything fails.
+async fn write_atomic<F, Fut>(dst: &Path, write: F) -> Result<u64>
+where
+    F: FnOnce(File) -> Fut,
+    Fut: core::future::Future<Output = Result<(File, u64)>>,
+{
+    let temp = temp_path(dst);
+
+    let result = async {
+        let (file, written) = write(File::create(&temp).await?).await?;
+        file.sync_all().await?;
+        drop(file);
+        fs::rename(&temp, dst).await?;
+
+        // Sync the directory so the rename itself is durable
+        #[cfg(unix)]
+        if let Some(parent) = dst.parent() {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            File::open(parent).await?.sync_all().await?;
+        }
+
+        Ok(written)
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp).await;
+    }
+
+    result
+}
+
+/// Compresses the file at `src` with `algorithm` at `level` into a file at `dst`, replacing it
+/// atomically if it exists, and returns the size of the compressed file.
+///
+/// For gzip the modification time and name of `src` are recorded in the header, as the `gzip`
+/// tool does. The name is left out if it isn't valid UTF-8.
+pub async fn compress_file(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    algorithm: Algorithm,
+    level: Level,
+) -> Result<u64> {
+    let src = src.as_ref();
+    let input = File::open(src).await?;
+
+    let mtime = input
+        .metadata()
+        .await?
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .and_then(|mtime| u32::try_from(mtime.as_secs()).ok())
+        .unwrap_or(0);
+
+    let mut codec = AnyEncoder::new(algorithm, level);
+    codec.set_file_info(mtime, file_name(src));
+
+    write_atomic(dst.as_ref(), |output| async move {
+        let mut encoder = Encoder::new(output, codec).with_codec_flush(false);
+        copy_buf(BufReader::new(input), &mut encoder).await?;
+        encoder.try_finish().await?;
+        let written = encoder.total_out();
+        Ok((encoder.into_inner(), written))
+    })
+    .await
+}
+
+/// Decompresses the file at `src` with `algorithm` into a file at `dst`, replacing it atomically
+/// if it exists, and returns the size of the decompressed file.
+///
+/// Concatenated members/frames are all decompressed, as the command line tools do.
+pub async fn decompress_file(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    algorithm: Algorithm,
+) -> Result<u64> {
+    let input = File::open(src.as_ref()).await?;
+
+    write_atomic(dst.as_ref(), |mut output| async move {
+        let mut decoder = AnyDecoder::new(BufReader::new(input), algorithm);
+        decoder.multiple_members(true);
+        let written = copy_buf(decoder, &mut output).await?;
+        Ok((output, written))
+    })
+    .await
+}
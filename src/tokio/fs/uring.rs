@@ -0,0 +1,218 @@
+//! The file helpers of the parent module, doing the file IO with `io_uring` through
+//! [`tokio-uring`](tokio_uring_05).
+//!
+//! Reads and writes are submitted straight to the kernel from buffers owned by the helper, instead
+//! of going through the blocking thread pool for each operation as `tokio::fs` does. The codec
+//! runs on the calling task between them. These functions must be called from within a
+//! `tokio-uring` runtime, such as one started with [`tokio_uring::start`](tokio_uring_05::start).
+
+use std::{convert::TryFrom, io::Result, path::Path};
+
+use super::{file_name, temp_path};
+use crate::{
+    codec::{AnyDecoder, AnyEncoder, Decode, Encode},
+    util::PartialBuffer,
+    Algorithm, Level,
+};
+use tokio_uring_05::{
+    buf::BoundedBuf,
+    fs::{self, File},
+};
+
+/// How much is read from or written to the files at once.
+const CHUNK_SIZE: usize = 128 * 1024;
+
+/// Writes output to a file at increasing offsets, reusing one buffer.
+struct Output {
+    file: File,
+    buffer: Vec<u8>,
+    pos: u64,
+}
+
+impl Output {
+    fn new(file: File) -> Self {
+        Self {
+            file,
+            buffer: vec![0; CHUNK_SIZE],
+            pos: 0,
+        }
+    }
+
+    /// Calls `f` with the buffer as output, then writes whatever it wrote to the file.
+    async fn write_with<T>(
+        &mut self,
+        f: impl FnOnce(&mut PartialBuffer<&mut [u8]>) -> Result<T>,
+    ) -> Result<T> {
+        let mut output = PartialBuffer::new(&mut self.buffer[..]);
+        let result = f(&mut output)?;
+        let len = output.written().len();
+
+        if len > 0 {
+            let buffer = std::mem::take(&mut self.buffer);
+            let (written, buffer) = self.file.write_all_at(buffer.slice(..len), self.pos).await;
+            self.buffer = buffer.into_inner();
+            written?;
+            self.pos += len as u64;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Reads a file from the start a chunk at a time, reusing one buffer.
+struct Input {
+    file: File,
+    buffer: Vec<u8>,
+    pos: u64,
+}
+
+impl Input {
+    fn new(file: File) -> Self {
+        Self {
+            file,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            pos: 0,
+        }
+    }
+
+    /// Reads the next chunk of the file, which is empty at the end of the file.
+    async fn read(&mut self) -> Result<&[u8]> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        let (read, buffer) = self.file.read_at(buffer, self.pos).await;
+        self.buffer = buffer;
+        read?;
+        self.pos += self.buffer.len() as u64;
+        Ok(&self.buffer)
+    }
+}
+
+/// Writes the output to a temporary file with `write`, then syncs it and renames it over `dst`,
+/// removing the temporary file if anything fails.
+async fn write_atomic<F, Fut>(dst: &Path, write: F) -> Result<u64>
+where
+    F: FnOnce(Output) -> Fut,
+    Fut: core::future::Future<Output = Result<Output>>,
+{
+    let temp = temp_path(dst);
+
+    let result = async {
+        let output = write(Output::new(File::create(&temp).await?)).await?;
+        output.file.sync_all().await?;
+        output.file.close().await?;
+        fs::rename(&temp, dst).await?;
+
+        // Sync the directory so the rename itself is durable
+        if let Some(parent) = dst.parent() {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            let dir = File::open(parent).await?;
+            dir.sync_all().await?;
+            dir.close().await?;
+        }
+
+        Ok(output.pos)
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp).await;
+    }
+
+    result
+}
+
+/// Compresses the file at `src` with `algorithm` at `level` into a file at `dst` as
+/// [`compress_file`](super::compress_file) does, using `io_uring` for the file IO.
+pub async fn compress_file(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    algorithm: Algorithm,
+    level: Level,
+) -> Result<u64> {
+    let src = src.as_ref();
+    let mut input = Input::new(File::open(src).await?);
+
+    let mtime = input
+        .file
+        .statx()
+        .await
+        .ok()
+        .and_then(|statx| u32::try_from(statx.stx_mtime.tv_sec).ok())
+        .unwrap_or(0);
+
+    let mut codec = AnyEncoder::new(algorithm, level);
+    codec.set_file_info(mtime, file_name(src));
+
+    write_atomic(dst.as_ref(), |mut output| async move {
+        loop {
+            let chunk = input.read().await?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            let mut chunk = PartialBuffer::new(chunk);
+            while !chunk.unwritten().is_empty() {
+                output
+                    .write_with(|buffer| codec.encode(&mut chunk, buffer))
+                    .await?;
+            }
+        }
+
+        while !output.write_with(|buffer| codec.finish(buffer)).await? {}
+
+        input.file.close().await?;
+        Ok(output)
+    })
+    .await
+}
+
+/// Decompresses the file at `src` with `algorithm` into a file at `dst` as
+/// [`decompress_file`](super::decompress_file) does, using `io_uring` for the file IO.
+pub async fn decompress_file(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    algorithm: Algorithm,
+) -> Result<u64> {
+    let mut input = Input::new(File::open(src.as_ref()).await?);
+    let mut codec = AnyDecoder::new(algorithm);
+
+    write_atomic(dst.as_ref(), |mut output| async move {
+        // Whether the last member/frame has been decoded in full, so the input may end
+        let mut ended = false;
+
+        loop {
+            let chunk = input.read().await?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            let mut chunk = PartialBuffer::new(chunk);
+            while !chunk.unwritten().is_empty() {
+                if ended {
+                    codec.reinit()?;
+                    ended = false;
+                }
+
+                if output
+                    .write_with(|buffer| codec.decode(&mut chunk, buffer))
+                    .await?
+                {
+                    while !output.write_with(|buffer| codec.finish(buffer)).await? {}
+                    ended = true;
+                }
+            }
+        }
+
+        if !ended {
+            while !output.write_with(|buffer| codec.finish(buffer)).await? {}
+        }
+
+        input.file.close().await?;
+        Ok(output)
+    })
+    .await
+}
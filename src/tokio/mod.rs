@@ -1,4 +1,22 @@
 //! Implementations for IO traits exported by [`tokio` v1.0](::tokio).
+//!
+//! The [`compress_to_vec`] and [`decompress_to_vec`] functions, and their [`compress_copy`] and
+//! [`decompress_copy`] streaming counterparts, cover compressing or decompressing everything in
+//! one call.
 
+#[cfg(feature = "tokio-blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-blocking")))]
+pub mod blocking;
 pub mod bufread;
+pub mod bufwrite;
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+pub mod codec;
+#[cfg(feature = "tokio-fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-fs")))]
+pub mod fs;
 pub mod write;
+
+mod oneshot;
+
+pub use self::oneshot::{compress_copy, compress_to_vec, decompress_copy, decompress_to_vec};
@@ -0,0 +1,291 @@
+use crate::{
+    codec::{Decode, Encode},
+    pool::BufferPool,
+    tokio::write::{Decoder, Encoder},
+    Level, TrailingData,
+};
+use tokio::io::AsyncWrite;
+
+#[derive(Debug)]
+enum Buffer {
+    Default,
+    Capacity(usize),
+    Pool(BufferPool),
+}
+
+/// A builder for the encoders in this module, returned by their `builder` method, which collects
+/// the compression level and other options before creating the encoder.
+///
+/// Any option not set keeps the default of an encoder created with `new`.
+#[derive(Debug)]
+pub struct EncoderBuilder<T, W, A = ()> {
+    inner: W,
+    args: A,
+    level: Level,
+    buffer: Buffer,
+    record_codec_time: bool,
+    propagate_shutdown: bool,
+    codec_flush: bool,
+    auto_flush: Option<usize>,
+    input_watermarks: Option<(usize, usize)>,
+    build: fn(Self) -> T,
+}
+
+impl<T, W, A> EncoderBuilder<T, W, A> {
+    pub(crate) fn new(inner: W, args: A, build: fn(Self) -> T) -> Self {
+        Self {
+            inner,
+            args,
+            level: Level::Default,
+            buffer: Buffer::Default,
+            record_codec_time: false,
+            propagate_shutdown: true,
+            codec_flush: true,
+            auto_flush: None,
+            input_watermarks: None,
+            build,
+        }
+    }
+
+    /// Sets the compression level, defaults to [`Level::Default`].
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the capacity of the internal buffer for the compressed output,
+    /// see [`Encoder::with_capacity`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        self.buffer = Buffer::Capacity(capacity);
+        self
+    }
+
+    /// Takes the internal buffer from `pool`, see [`Encoder::with_pool`].
+    pub fn buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.buffer = Buffer::Pool(pool.clone());
+        self
+    }
+
+    /// Configures recording the time spent inside the codec, see
+    /// [`Encoder::record_codec_time`].
+    pub fn record_codec_time(mut self, enabled: bool) -> Self {
+        self.record_codec_time = enabled;
+        self
+    }
+
+    /// Configures whether shutting down the encoder also shuts down the underlying writer, see
+    /// [`Encoder::with_shutdown_propagation`].
+    pub fn shutdown_propagation(mut self, enabled: bool) -> Self {
+        self.propagate_shutdown = enabled;
+        self
+    }
+
+    /// Configures whether flushing the encoder also flushes the codec, see
+    /// [`Encoder::with_codec_flush`].
+    pub fn codec_flush(mut self, enabled: bool) -> Self {
+        self.codec_flush = enabled;
+        self
+    }
+
+    /// Configures the encoder to flush by itself once `after` uncompressed bytes have been written
+    /// since the last flush, see [`Encoder::with_auto_flush`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `after` is zero.
+    pub fn auto_flush(mut self, after: usize) -> Self {
+        assert!(after > 0, "auto flush threshold must be non-zero");
+
+        self.auto_flush = Some(after);
+        self
+    }
+
+    /// Configures the encoder to accumulate the data written to it between the `low` and `high`
+    /// watermarks before handing it to the codec, see [`Encoder::with_input_watermarks`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low` is not less than `high`.
+    pub fn input_watermarks(mut self, low: usize, high: usize) -> Self {
+        assert!(
+            low < high,
+            "low watermark must be less than the high watermark"
+        );
+
+        self.input_watermarks = Some((low, high));
+        self
+    }
+
+    /// Creates the encoder with the options collected.
+    pub fn build(self) -> T {
+        let build = self.build;
+        build(self)
+    }
+
+    /// Creates the generic encoder with the options collected, `new` creates it for the level and
+    /// extra arguments and is then rebuilt with the chosen buffers.
+    pub(crate) fn build_with<E: Encode>(
+        self,
+        new: impl FnOnce(W, A, Level) -> Encoder<W, E>,
+    ) -> Encoder<W, E>
+    where
+        W: AsyncWrite,
+    {
+        let (inner, codec) = new(self.inner, self.args, self.level).into_parts();
+        let mut encoder = match self.buffer {
+            Buffer::Default => Encoder::new(inner, codec),
+            Buffer::Capacity(capacity) => Encoder::with_capacity(capacity, inner, codec),
+            Buffer::Pool(pool) => Encoder::with_pool(&pool, inner, codec),
+        };
+
+        if self.record_codec_time {
+            encoder.record_codec_time(true);
+        }
+
+        let encoder = encoder
+            .with_shutdown_propagation(self.propagate_shutdown)
+            .with_codec_flush(self.codec_flush);
+
+        let encoder = match self.auto_flush {
+            Some(after) => encoder.with_auto_flush(after),
+            None => encoder,
+        };
+
+        match self.input_watermarks {
+            Some((low, high)) => encoder.with_input_watermarks(low, high),
+            None => encoder,
+        }
+    }
+}
+
+/// A builder for the decoders in this module, returned by their `builder` method, which collects
+/// the limits and other options before creating the decoder.
+///
+/// Any option not set keeps the default of a decoder created with `new`.
+#[derive(Debug)]
+pub struct DecoderBuilder<T, W, A = ()> {
+    inner: W,
+    args: A,
+    buffer: Buffer,
+    record_codec_time: bool,
+    output_limit: Option<u64>,
+    input_limit: Option<u64>,
+    trailing_data: Option<TrailingData>,
+    propagate_shutdown: bool,
+    build: fn(Self) -> T,
+}
+
+impl<T, W, A> DecoderBuilder<T, W, A> {
+    pub(crate) fn new(inner: W, args: A, build: fn(Self) -> T) -> Self {
+        Self {
+            inner,
+            args,
+            buffer: Buffer::Default,
+            record_codec_time: false,
+            output_limit: None,
+            input_limit: None,
+            trailing_data: None,
+            propagate_shutdown: true,
+            build,
+        }
+    }
+
+    /// Sets the capacity of the internal buffer for the uncompressed output,
+    /// see [`Decoder::with_capacity`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        self.buffer = Buffer::Capacity(capacity);
+        self
+    }
+
+    /// Takes the internal buffer from `pool`, see [`Decoder::with_pool`].
+    pub fn buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.buffer = Buffer::Pool(pool.clone());
+        self
+    }
+
+    /// Configures recording the time spent inside the codec, see
+    /// [`Decoder::record_codec_time`].
+    pub fn record_codec_time(mut self, enabled: bool) -> Self {
+        self.record_codec_time = enabled;
+        self
+    }
+
+    /// Limits the number of uncompressed bytes the decoder will produce, see
+    /// [`Decoder::with_output_limit`].
+    pub fn output_limit(mut self, limit: u64) -> Self {
+        self.output_limit = Some(limit);
+        self
+    }
+
+    /// Limits the number of compressed bytes the decoder will consume, see
+    /// [`Decoder::with_input_limit`].
+    pub fn input_limit(mut self, limit: u64) -> Self {
+        self.input_limit = Some(limit);
+        self
+    }
+
+    /// Configures what happens to data written after the end of the compressed stream, see
+    /// [`Decoder::trailing_data`].
+    pub fn trailing_data(mut self, policy: TrailingData) -> Self {
+        self.trailing_data = Some(policy);
+        self
+    }
+
+    /// Configures whether shutting down the decoder also shuts down the underlying writer, see
+    /// [`Decoder::with_shutdown_propagation`].
+    pub fn shutdown_propagation(mut self, enabled: bool) -> Self {
+        self.propagate_shutdown = enabled;
+        self
+    }
+
+    /// Creates the decoder with the options collected.
+    pub fn build(self) -> T {
+        let build = self.build;
+        build(self)
+    }
+
+    /// Creates the generic decoder with the options collected, `new` creates it for the extra
+    /// arguments and is then rebuilt with the chosen buffers.
+    pub(crate) fn build_with<D: Decode>(
+        self,
+        new: impl FnOnce(W, A) -> Decoder<W, D>,
+    ) -> Decoder<W, D>
+    where
+        W: AsyncWrite,
+    {
+        let (inner, codec) = new(self.inner, self.args).into_parts();
+        let mut decoder = match self.buffer {
+            Buffer::Default => Decoder::new(inner, codec),
+            Buffer::Capacity(capacity) => Decoder::with_capacity(capacity, inner, codec),
+            Buffer::Pool(pool) => Decoder::with_pool(&pool, inner, codec),
+        };
+
+        if self.record_codec_time {
+            decoder.record_codec_time(true);
+        }
+        if let Some(policy) = self.trailing_data {
+            decoder.trailing_data(policy);
+        }
+
+        let mut decoder = decoder.with_shutdown_propagation(self.propagate_shutdown);
+        if let Some(limit) = self.output_limit {
+            decoder = decoder.with_output_limit(limit);
+        }
+        if let Some(limit) = self.input_limit {
+            decoder = decoder.with_input_limit(limit);
+        }
+        decoder
+    }
+}
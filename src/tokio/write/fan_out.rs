@@ -0,0 +1,223 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::{ErrorKind, Result};
+
+use crate::{codec::AnyEncoder, tokio::write::Encoder, Algorithm, Level};
+use futures_core::ready;
+use tokio::io::AsyncWrite;
+
+/// The most data accepted by a single write, which is held until every encoder has taken it.
+const CAPACITY: usize = 64 * 1024;
+
+#[derive(Debug)]
+struct Output<W> {
+    encoder: Encoder<W, AnyEncoder>,
+    // How much of the buffered data this encoder has taken
+    pos: usize,
+    // Whether the ongoing flush or close has completed for this encoder
+    done: bool,
+}
+
+/// An encoder which compresses the data written to it with several algorithms at once, writing
+/// each compressed stream to its own underlying writer, such as to pre-compress a file as gzip,
+/// brotli and zstd while reading it only once.
+///
+/// The data written is buffered once and handed to each encoder in turn, so writes proceed at
+/// the pace of the slowest writer.
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// use async_compression::{tokio::write::FanOutEncoder, Algorithm, Level};
+/// use tokio::io::AsyncWriteExt;
+///
+/// let mut encoder = FanOutEncoder::new()
+///     .with_output(Vec::new(), Algorithm::Gzip, Level::Default)
+///     .with_output(Vec::new(), Algorithm::Zstd, Level::Default);
+/// encoder.write_all(b"example").await?;
+/// encoder.shutdown().await?;
+/// let outputs = encoder.into_inner();
+/// # Ok::<_, std::io::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FanOutEncoder<W> {
+    outputs: Vec<Output<W>>,
+    // Data accepted by `poll_write` which not every encoder has taken yet
+    buffer: Vec<u8>,
+    total_in: u64,
+}
+
+impl<W: AsyncWrite + Unpin> FanOutEncoder<W> {
+    /// Creates a new encoder with no outputs, which are added with
+    /// [`with_output`](Self::with_output).
+    pub fn new() -> Self {
+        Self {
+            outputs: Vec::new(),
+            buffer: Vec::new(),
+            total_in: 0,
+        }
+    }
+
+    /// Adds an output which will take in the uncompressed data and write it compressed with
+    /// `algorithm` at `level` to `writer`.
+    pub fn with_output(mut self, writer: W, algorithm: Algorithm, level: Level) -> Self {
+        self.outputs.push(Output {
+            encoder: Encoder::new(writer, AnyEncoder::new(algorithm, level)),
+            pos: 0,
+            done: false,
+        });
+        self
+    }
+
+    /// Returns the number of outputs.
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Returns whether there are no outputs.
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// Acquires a reference to the underlying writer of the output at `index`, in the order they
+    /// were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get_ref(&self, index: usize) -> &W {
+        self.outputs[index].encoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the underlying writer of the output at `index`, in the
+    /// order they were added.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> &mut W {
+        self.outputs[index].encoder.get_mut()
+    }
+
+    /// Returns the total number of uncompressed bytes written to this encoder so far.
+    pub fn total_in(&self) -> u64 {
+        self.total_in
+    }
+
+    /// Returns the total number of compressed bytes written to the underlying writer of the
+    /// output at `index` so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn total_out(&self, index: usize) -> u64 {
+        self.outputs[index].encoder.total_out()
+    }
+
+    /// Consumes this encoder returning the underlying writers, in the order they were added.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
+    pub fn into_inner(self) -> Vec<W> {
+        self.outputs
+            .into_iter()
+            .map(|output| output.encoder.into_inner())
+            .collect()
+    }
+
+    /// Hands the buffered data to every encoder, clearing the buffer once they have all taken it.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut pending = false;
+
+        for output in &mut self.outputs {
+            while output.pos < self.buffer.len() {
+                match Pin::new(&mut output.encoder).poll_write(cx, &self.buffer[output.pos..])? {
+                    Poll::Ready(0) => return Poll::Ready(Err(ErrorKind::WriteZero.into())),
+                    Poll::Ready(len) => output.pos += len,
+                    Poll::Pending => {
+                        pending = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if pending {
+            return Poll::Pending;
+        }
+
+        self.buffer.clear();
+        for output in &mut self.outputs {
+            output.pos = 0;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Runs `f` on every encoder that hasn't completed yet, until they all have.
+    fn poll_all(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut f: impl FnMut(Pin<&mut Encoder<W, AnyEncoder>>, &mut Context<'_>) -> Poll<Result<()>>,
+    ) -> Poll<Result<()>> {
+        ready!(self.poll_drain(cx))?;
+
+        let mut pending = false;
+        for output in self.outputs.iter_mut().filter(|output| !output.done) {
+            match f(Pin::new(&mut output.encoder), cx)? {
+                Poll::Ready(()) => output.done = true,
+                Poll::Pending => pending = true,
+            }
+        }
+
+        if pending {
+            return Poll::Pending;
+        }
+
+        for output in &mut self.outputs {
+            output.done = false;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> Default for FanOutEncoder<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for FanOutEncoder<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.get_mut();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        ready!(this.poll_drain(cx))?;
+
+        let len = buf.len().min(CAPACITY);
+        this.buffer.extend_from_slice(&buf[..len]);
+        this.total_in += len as u64;
+
+        // Start handing the data over straight away, whatever is left is taken on the next call
+        if let Poll::Ready(Err(err)) = this.poll_drain(cx) {
+            return Poll::Ready(Err(err));
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut()
+            .poll_all(cx, |encoder, cx| encoder.poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut()
+            .poll_all(cx, |encoder, cx| encoder.poll_shutdown(cx))
+    }
+}
@@ -0,0 +1,153 @@
+use core::{
+    fmt,
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io::{IoSlice, Result};
+
+use tokio::io::AsyncWrite;
+
+/// The task shutting down a writer which was dropped before it was shut down.
+pub type CloseTask = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+/// Consumes a writer which was dropped before it was shut down.
+type Cleanup<W> = Box<dyn FnOnce(W) + Send>;
+
+/// A guard around a writer, usually an encoder, which makes sure the compressed stream isn't
+/// silently left unfinished when the writer is dropped before it has been shut down.
+///
+/// Encoders only write the end of the compressed stream, such as the gzip trailer, when they are
+/// shut down, so forgetting to call [`shutdown`](tokio::io::AsyncWrite::poll_shutdown) leaves a
+/// truncated stream behind. When this guard is dropped without having been shut down:
+///
+/// * if a spawner was given with [`with_spawner`](Self::with_spawner), a task shutting down the
+///   writer is handed to it;
+/// * otherwise it panics in debug builds, or does nothing in release builds.
+///
+/// [`into_inner`](Self::into_inner) takes the writer back out without any of this, such as to
+/// give up on the stream after an error.
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// use async_compression::tokio::write::{FinishOnDrop, GzipEncoder};
+/// use tokio::io::AsyncWriteExt;
+///
+/// let mut encoder = FinishOnDrop::new(GzipEncoder::new(Vec::new())).with_spawner(|task| {
+///     tokio::spawn(task);
+/// });
+/// encoder.write_all(b"example").await?;
+/// // The stream is finished by a task on the runtime
+/// drop(encoder);
+/// # Ok::<_, std::io::Error>(())
+/// # }).unwrap();
+/// ```
+pub struct FinishOnDrop<W> {
+    writer: Option<W>,
+    shut_down: bool,
+    cleanup: Option<Cleanup<W>>,
+}
+
+impl<W: AsyncWrite + Unpin> FinishOnDrop<W> {
+    /// Creates a new guard around `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Some(writer),
+            shut_down: false,
+            cleanup: None,
+        }
+    }
+
+    /// Hands a task shutting down the writer to `spawn` when this guard is dropped without having been
+    /// shut down, instead of panicking in debug builds.
+    ///
+    /// The task resolves to the result of shutting down the writer, which it is up to `spawn` to report.
+    pub fn with_spawner(mut self, spawn: impl FnOnce(CloseTask) + Send + 'static) -> Self
+    where
+        W: Send + 'static,
+    {
+        self.cleanup = Some(Box::new(move |mut writer: W| {
+            spawn(Box::pin(async move {
+                poll_fn(|cx| Pin::new(&mut writer).poll_shutdown(cx)).await
+            }))
+        }));
+        self
+    }
+
+    /// Returns whether the writer has been shut down.
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down
+    }
+
+    /// Acquires a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.writer.as_ref().unwrap()
+    }
+
+    /// Acquires a mutable reference to the underlying writer.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this guard.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.as_mut().unwrap()
+    }
+
+    /// Consumes this guard returning the underlying writer, which is then not shut down when
+    /// dropped.
+    pub fn into_inner(mut self) -> W {
+        self.writer.take().unwrap()
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for FinishOnDrop<W> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Pin::new(self.get_mut().get_mut()).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        Pin::new(self.get_mut().get_mut()).poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(self.get_mut().get_mut()).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        let result = Pin::new(this.get_mut()).poll_shutdown(cx);
+        if let Poll::Ready(Ok(())) = result {
+            this.shut_down = true;
+        }
+        result
+    }
+}
+
+impl<W> Drop for FinishOnDrop<W> {
+    fn drop(&mut self) {
+        let writer = match self.writer.take() {
+            Some(writer) if !self.shut_down => writer,
+            _ => return,
+        };
+
+        if let Some(cleanup) = self.cleanup.take() {
+            cleanup(writer);
+        } else if cfg!(debug_assertions) && !std::thread::panicking() {
+            panic!(
+                "FinishOnDrop dropped without being shut down, the compressed stream is truncated"
+            );
+        }
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for FinishOnDrop<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FinishOnDrop")
+            .field("writer", &self.writer)
+            .field("shut_down", &self.shut_down)
+            .finish()
+    }
+}
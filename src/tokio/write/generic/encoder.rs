@@ -1,16 +1,24 @@
 use core::{
+    cmp::min,
+    future::poll_fn,
+    mem,
     pin::Pin,
     task::{Context, Poll},
 };
-use std::io::Result;
+use std::io::{IoSlice, Result};
 
 use crate::{
+    checkpoint::Checkpoint,
     codec::Encode,
+    pool::{Buffer, BufferPool},
+    stats::{Instrumented, Stats},
     tokio::write::{AsyncBufWrite, BufWriter},
     util::PartialBuffer,
 };
 use futures_core::ready;
 use pin_project_lite::pin_project;
+
+const DEFAULT_BUF_SIZE: usize = 8192;
 use tokio::io::AsyncWrite;
 
 #[derive(Debug)]
@@ -21,40 +29,307 @@ enum State {
 }
 
 pin_project! {
+    /// A generic encoder which compresses data with any [`Encode`] implementation.
+    ///
+    /// This structure implements an [`AsyncWrite`](tokio::io::AsyncWrite) interface and will
+    /// compress data written to it and write the compressed data to an underlying stream.
+    ///
+    /// The encoders for each algorithm in this module are built on this type, it can be used
+    /// directly to wrap a custom format.
     #[derive(Debug)]
     pub struct Encoder<W, E: Encode> {
         #[pin]
         writer: BufWriter<W>,
-        encoder: E,
+        encoder: Instrumented<E>,
         state: State,
+        propagate_shutdown: bool,
+        codec_flush: bool,
+        auto_flush: Option<usize>,
+        unflushed: usize,
+        flush_pending: bool,
+        // Input accumulated before it is handed to the codec, in `input[input_pos..]`
+        input: Vec<u8>,
+        input_pos: usize,
+        watermarks: Option<(usize, usize)>,
+        // Whether the buffered input is being handed to the codec down to the low watermark
+        draining: bool,
     }
 }
 
 impl<W: AsyncWrite, E: Encode> Encoder<W, E> {
+    /// Creates a new encoder which will write compressed data to the given stream using `encoder`.
     pub fn new(writer: W, encoder: E) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, writer, encoder)
+    }
+
+    /// Creates a new encoder like [`new`](Self::new), with an internal buffer of the specified
+    /// capacity for the compressed output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, writer: W, encoder: E) -> Self {
+        assert!(capacity > 0, "buffer capacity must be non-zero");
+
+        Self::with_buffer(Buffer::new(capacity), writer, encoder)
+    }
+
+    /// Creates a new encoder like [`new`](Self::new), with its internal buffer taken from `pool`
+    /// when it is first needed and given back to it when this encoder is dropped.
+    pub fn with_pool(pool: &BufferPool, writer: W, encoder: E) -> Self {
+        Self::with_buffer(Buffer::pooled(pool), writer, encoder)
+    }
+
+    fn with_buffer(buffer: Buffer, writer: W, encoder: E) -> Self {
         Self {
-            writer: BufWriter::new(writer),
-            encoder,
+            writer: BufWriter::with_buffer(buffer, writer),
+            encoder: Instrumented::new(encoder),
             state: State::Encoding,
+            propagate_shutdown: true,
+            codec_flush: true,
+            auto_flush: None,
+            unflushed: 0,
+            flush_pending: false,
+            input: Vec::new(),
+            input_pos: 0,
+            watermarks: None,
+            draining: false,
         }
     }
 
+    /// Acquires a reference to the underlying writer that this encoder is wrapping.
     pub fn get_ref(&self) -> &W {
         self.writer.get_ref()
     }
 
+    /// Acquires a mutable reference to the underlying writer that this encoder is wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
     pub fn get_mut(&mut self) -> &mut W {
         self.writer.get_mut()
     }
 
+    /// Acquires a pinned mutable reference to the underlying writer that this encoder is
+    /// wrapping.
+    ///
+    /// Note that care must be taken to avoid tampering with the state of the writer which may
+    /// otherwise confuse this encoder.
     pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut W> {
         self.project().writer.get_pin_mut()
     }
 
+    /// Returns the total number of uncompressed bytes this encoder has consumed so far.
+    pub fn total_in(&self) -> u64 {
+        self.encoder.stats().total_in
+    }
+
+    /// Returns the total number of compressed bytes this encoder has produced so far, including any
+    /// still buffered and not yet written to the underlying writer.
+    pub fn total_out(&self) -> u64 {
+        self.encoder.stats().total_out
+    }
+
+    /// Configure recording the time spent inside the codec, reported by [`Stats::codec_time`].
+    /// Disabling it discards the time recorded so far.
+    ///
+    /// This uses [`std::time::Instant`], so it will panic on platforms that don't support it
+    /// such as `wasm32-unknown-unknown`.
+    pub fn record_codec_time(&mut self, enabled: bool) {
+        self.encoder.record_codec_time(enabled);
+    }
+
+    /// Returns a snapshot of the statistics of this encoder so far.
+    pub fn stats(&self) -> Stats {
+        self.encoder.stats()
+    }
+
+    /// Returns the checksum of the uncompressed data in the current member/frame so far, see
+    /// [`Encode::checksum`](crate::codec::Encode::checksum).
+    pub fn checksum(&self) -> Option<u32> {
+        self.encoder.checksum()
+    }
+
+    /// Configure whether shutting down this encoder also shuts down the underlying writer,
+    /// enabled by default. When disabled it only flushes the underlying writer once the
+    /// compressed stream has been finished, so the writer can be shared with later streams.
+    pub fn with_shutdown_propagation(mut self, enabled: bool) -> Self {
+        self.propagate_shutdown = enabled;
+        self
+    }
+
+    /// Configure whether flushing this encoder also flushes the codec, enabled by default.
+    ///
+    /// A codec flush makes everything written so far decodable by the reader, but with deflate
+    /// based formats each one emits an extra block that costs some compression ratio. When
+    /// disabled flushing only writes out the output the codec has already produced.
+    pub fn with_codec_flush(mut self, enabled: bool) -> Self {
+        self.codec_flush = enabled;
+        self
+    }
+
+    /// Configure this encoder to flush the codec and the underlying writer by itself once `after`
+    /// uncompressed bytes have been written since the last flush, so interactive streams reach the
+    /// reader without explicit calls to `flush`. An `after` of 1 flushes after every write.
+    ///
+    /// Like explicit flushes each automatic flush costs some compression ratio. If the flush can't
+    /// complete straight away the write still succeeds, and the flush is completed by the next
+    /// call on this encoder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `after` is zero.
+    pub fn with_auto_flush(mut self, after: usize) -> Self {
+        assert!(after > 0, "auto flush threshold must be non-zero");
+
+        self.auto_flush = Some(after);
+        self
+    }
+
+    /// Configure this encoder to accumulate the data written to it until `high` bytes are
+    /// buffered before handing any of it to the codec, which then takes the buffered data until
+    /// no more than `low` bytes are left. When the data is written in many small chunks this gives
+    /// the codec larger pieces to work with, which improves the compression ratio of some codecs
+    /// and cuts the overhead of calling them.
+    ///
+    /// Flushing, closing and checkpoints hand all the buffered data to the codec first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low` is not less than `high`.
+    pub fn with_input_watermarks(mut self, low: usize, high: usize) -> Self {
+        assert!(
+            low < high,
+            "low watermark must be less than the high watermark"
+        );
+
+        self.watermarks = Some((low, high));
+        self
+    }
+
+    /// Resumes the stream from `checkpoint`, taken by [`poll_checkpoint`](Self::poll_checkpoint)
+    /// on an earlier encoder, see the [`checkpoint`](crate::checkpoint) module.
+    ///
+    /// The data written to this encoder should carry on from
+    /// [`Checkpoint::total_in`](crate::checkpoint::Checkpoint::total_in), and the compressed
+    /// output follow the first
+    /// [`Checkpoint::total_out`](crate::checkpoint::Checkpoint::total_out) bytes written by the
+    /// earlier encoder. The totals and later checkpoints count from the start of the stream.
+    pub fn with_checkpoint(mut self, checkpoint: &Checkpoint) -> Self {
+        self.encoder.resume(checkpoint);
+        self
+    }
+
+    /// Attempts to end the current member/frame and flush it to the underlying writer, returning
+    /// a [`Checkpoint`] from which the stream can be resumed, see the
+    /// [`checkpoint`](crate::checkpoint) module.
+    ///
+    /// Anything written after this starts a new member/frame.
+    pub fn poll_checkpoint(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Checkpoint>> {
+        ready!(self.as_mut().poll_auto_flush(cx))?;
+        ready!(self.as_mut().do_poll_shutdown(cx))?;
+        ready!(self.as_mut().project().writer.as_mut().poll_flush(cx))?;
+
+        let this = self.project();
+        this.encoder.restart()?;
+        *this.state = State::Encoding;
+        *this.unflushed = 0;
+
+        let stats = this.encoder.stats();
+        Poll::Ready(Ok(Checkpoint::new(stats.total_in, stats.total_out)))
+    }
+
+    /// Ends the current member/frame and flushes it to the underlying writer like
+    /// [`poll_checkpoint`](Self::poll_checkpoint), returning a [`Checkpoint`] from which the
+    /// stream can be resumed.
+    pub async fn checkpoint(&mut self) -> Result<Checkpoint>
+    where
+        W: Unpin,
+    {
+        poll_fn(|cx| Pin::new(&mut *self).poll_checkpoint(cx)).await
+    }
+
+    /// Attempts to finish the compressed stream and flush it to the underlying writer, without
+    /// shutting down the underlying writer.
+    ///
+    /// Once this has completed nothing more can be written to this encoder.
+    pub fn poll_finish(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().poll_auto_flush(cx))?;
+        ready!(self.as_mut().do_poll_shutdown(cx))?;
+        ready!(self.project().writer.as_mut().poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Finishes the compressed stream and flushes it to the underlying writer like
+    /// [`poll_finish`](Self::poll_finish), leaving the underlying writer open.
+    pub async fn try_finish(&mut self) -> Result<()>
+    where
+        W: Unpin,
+    {
+        poll_fn(|cx| Pin::new(&mut *self).poll_finish(cx)).await
+    }
+
+    /// Finishes the compressed stream like [`try_finish`](Self::try_finish) and returns the
+    /// underlying writer, which is left open.
+    pub async fn finish(mut self) -> Result<W>
+    where
+        W: Unpin,
+    {
+        self.try_finish().await?;
+        Ok(self.into_inner())
+    }
+
+    /// Changes the compression level used for the rest of the stream, the data already compressed
+    /// is unaffected.
+    ///
+    /// This is supported by the `deflate`, `gzip` and `zstd` encoders, the `deflate` and `gzip`
+    /// encoders flush before switching which costs some compression ratio. Other encoders return
+    /// an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported).
+    pub fn set_level(&mut self, level: crate::Level) -> Result<()> {
+        self.encoder.set_level(level)
+    }
+
+    /// Resets this encoder to compress a new stream to the same writer, reusing its allocations
+    /// where the codec allows it.
+    ///
+    /// Any output not yet written to the underlying writer is discarded and the statistics start
+    /// over, so this should be called once the previous stream has been closed.
+    pub fn reset(&mut self) -> Result<()> {
+        self.encoder.reset()?;
+        self.state = State::Encoding;
+        self.unflushed = 0;
+        self.flush_pending = false;
+        self.input.clear();
+        self.input_pos = 0;
+        self.draining = false;
+        self.writer.discard_buffer();
+        Ok(())
+    }
+
+    /// Resets this encoder like [`reset`](Self::reset) to compress a new stream to `writer`,
+    /// returning the previous writer.
+    pub fn reset_with_writer(&mut self, writer: W) -> Result<W> {
+        self.reset()?;
+        Ok(self.writer.replace_inner(writer))
+    }
+
+    /// Consumes this encoder returning the underlying writer.
+    ///
+    /// Note that this may discard internal state of this encoder, so care should be taken to
+    /// avoid losing resources when this is called.
     pub fn into_inner(self) -> W {
         self.writer.into_inner()
     }
 
+    /// Consumes this encoder returning the underlying writer and codec, discarding anything that
+    /// is buffered.
+    pub(crate) fn into_parts(self) -> (W, E) {
+        (self.writer.into_inner(), self.encoder.into_inner())
+    }
+
     fn do_poll_write(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -84,7 +359,56 @@ impl<W: AsyncWrite, E: Encode> Encoder<W, E> {
         }
     }
 
-    fn do_poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+    /// Hands the buffered input to the codec until no more than `target` bytes are left.
+    fn poll_drain_input(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        target: usize,
+    ) -> Poll<Result<()>> {
+        loop {
+            let this = self.as_mut().project();
+            if this.input.len() - *this.input_pos <= target {
+                this.input.drain(..*this.input_pos);
+                *this.input_pos = 0;
+                return Poll::Ready(Ok(()));
+            }
+
+            let buffered = mem::take(this.input);
+            let mut input = PartialBuffer::new(&buffered[*this.input_pos..buffered.len() - target]);
+            let result = self.as_mut().do_poll_write(cx, &mut input);
+            let consumed = input.written().len();
+
+            let this = self.as_mut().project();
+            *this.input = buffered;
+            *this.input_pos += consumed;
+            ready!(result)?;
+        }
+    }
+
+    /// Adds as much of `buf` to the buffered input as fits under the high watermark, first
+    /// handing the buffered input to the codec down to the low watermark if it has reached the
+    /// high one.
+    fn poll_buffer_input(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        (low, high): (usize, usize),
+    ) -> Poll<Result<usize>> {
+        let this = self.as_mut().project();
+        if *this.draining || this.input.len() - *this.input_pos >= high {
+            *this.draining = true;
+            ready!(self.as_mut().poll_drain_input(cx, low))?;
+            *self.as_mut().project().draining = false;
+        }
+
+        let this = self.project();
+        let len = min(buf.len(), high - (this.input.len() - *this.input_pos));
+        this.input.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn do_poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().poll_drain_input(cx, 0))?;
         let mut this = self.project();
 
         loop {
@@ -106,7 +430,33 @@ impl<W: AsyncWrite, E: Encode> Encoder<W, E> {
         }
     }
 
-    fn do_poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+    fn poll_auto_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if *self.as_mut().project().flush_pending {
+            ready!(self.as_mut().do_poll_flush(cx))?;
+            ready!(self.as_mut().project().writer.as_mut().poll_flush(cx))?;
+            *self.project().flush_pending = false;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn record_written(mut self: Pin<&mut Self>, cx: &mut Context<'_>, written: usize) {
+        let this = self.as_mut().project();
+        if let Some(after) = *this.auto_flush {
+            *this.unflushed += written;
+            if *this.unflushed >= after {
+                *this.unflushed = 0;
+                *this.flush_pending = true;
+            }
+        }
+
+        // The input has already been taken so the write completes whatever happens here, an
+        // unfinished flush (or its error) is picked up again by the next call
+        let _ = self.poll_auto_flush(cx);
+    }
+
+    fn do_poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().poll_drain_input(cx, 0))?;
         let mut this = self.project();
 
         loop {
@@ -135,29 +485,116 @@ impl<W: AsyncWrite, E: Encode> Encoder<W, E> {
     }
 }
 
+impl<W, E: Encode> Encoder<W, E> {
+    /// Acquires a reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec(&self) -> &E {
+        self.encoder.get_ref()
+    }
+
+    /// Acquires a mutable reference to the codec that this encoder is wrapping.
+    pub(crate) fn codec_mut(&mut self) -> &mut E {
+        self.encoder.get_mut()
+    }
+}
+
 impl<W: AsyncWrite, E: Encode> AsyncWrite for Encoder<W, E> {
-    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize>> {
         if buf.is_empty() {
             return Poll::Ready(Ok(0));
         }
 
+        ready!(self.as_mut().poll_auto_flush(cx))?;
+
+        if let Some(watermarks) = self.watermarks {
+            let written = ready!(self.as_mut().poll_buffer_input(cx, buf, watermarks))?;
+            self.record_written(cx, written);
+            return Poll::Ready(Ok(written));
+        }
+
         let mut input = PartialBuffer::new(buf);
+        let result = self.as_mut().do_poll_write(cx, &mut input)?;
+        let written = input.written().len();
 
-        match self.do_poll_write(cx, &mut input)? {
-            Poll::Pending if input.written().is_empty() => Poll::Pending,
-            _ => Poll::Ready(Ok(input.written().len())),
+        if result.is_pending() && written == 0 {
+            return Poll::Pending;
         }
+
+        self.record_written(cx, written);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize>> {
+        ready!(self.as_mut().poll_auto_flush(cx))?;
+
+        let mut written = 0;
+
+        // Each slice is fed through in turn until the output can't take any more of them
+        for buf in bufs.iter().filter(|buf| !buf.is_empty()) {
+            if let Some(watermarks) = self.watermarks {
+                match self.as_mut().poll_buffer_input(cx, buf, watermarks)? {
+                    Poll::Ready(len) if len == buf.len() => written += len,
+                    Poll::Ready(len) => {
+                        written += len;
+                        break;
+                    }
+                    Poll::Pending if written == 0 => return Poll::Pending,
+                    Poll::Pending => break,
+                }
+                continue;
+            }
+
+            let mut input = PartialBuffer::new(&**buf);
+            let result = self.as_mut().do_poll_write(cx, &mut input)?;
+            written += input.written().len();
+
+            if result.is_pending() {
+                if written == 0 {
+                    return Poll::Pending;
+                }
+                break;
+            }
+        }
+
+        self.record_written(cx, written);
+        Poll::Ready(Ok(written))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        true
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        ready!(self.as_mut().do_poll_flush(cx))?;
-        ready!(self.project().writer.as_mut().poll_flush(cx))?;
+        ready!(self.as_mut().poll_drain_input(cx, 0))?;
+
+        let this = self.as_mut().project();
+        if *this.codec_flush || *this.flush_pending {
+            ready!(self.as_mut().do_poll_flush(cx))?;
+        }
+        ready!(self.as_mut().project().writer.as_mut().poll_flush(cx))?;
+
+        let this = self.project();
+        *this.unflushed = 0;
+        *this.flush_pending = false;
         Poll::Ready(Ok(()))
     }
 
     fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.as_mut().poll_auto_flush(cx))?;
         ready!(self.as_mut().do_poll_shutdown(cx))?;
-        ready!(self.project().writer.as_mut().poll_shutdown(cx))?;
+        let this = self.project();
+        if *this.propagate_shutdown {
+            ready!(this.writer.poll_shutdown(cx))?;
+        } else {
+            ready!(this.writer.poll_flush(cx))?;
+        }
         Poll::Ready(Ok(()))
     }
 }
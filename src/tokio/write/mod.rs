@@ -1,17 +1,35 @@
 //! Types which operate over [`AsyncWrite`](tokio::io::AsyncWrite) streams, both encoders and
 //! decoders for various formats.
+//!
+//! Custom formats can be used with the generic [`Encoder`] and [`Decoder`], see
+//! [`codec`](crate::codec).
+//!
+//! The same data can be compressed with several algorithms at once with a [`FanOutEncoder`].
+//!
+//! Wrapping an encoder in a [`FinishOnDrop`] guards against dropping it before it has been
+//! shut down, which would leave the compressed stream truncated.
 
 #[macro_use]
 mod macros;
+mod builder;
+mod fan_out;
+mod finish_on_drop;
 mod generic;
+mod parallel;
 
-mod buf_write;
+pub(crate) mod buf_write;
 mod buf_writer;
 
-use self::{
-    buf_write::AsyncBufWrite,
-    buf_writer::BufWriter,
+pub(crate) use self::{buf_write::AsyncBufWrite, buf_writer::BufWriter};
+
+pub use self::{
+    builder::{DecoderBuilder, EncoderBuilder},
+    fan_out::FanOutEncoder,
+    finish_on_drop::{CloseTask, FinishOnDrop},
     generic::{Decoder, Encoder},
+    parallel::ParallelEncoder,
 };
 
 algos!(tokio::write<W>);
+any!(tokio::write<W>);
+backends!(tokio::write<W>);
@@ -1,6 +1,53 @@
 pub fn _assert_send<T: Send>() {}
 pub fn _assert_sync<T: Sync>() {}
 
+/// Runs `f` with space to write at the end of `output`, keeping whatever it writes.
+#[cfg(any(
+    feature = "futures-sink",
+    feature = "http-body",
+    feature = "tokio-util"
+))]
+pub(crate) fn write_into<T>(
+    output: &mut bytes::BytesMut,
+    f: impl FnOnce(&mut PartialBuffer<&mut [u8]>) -> std::io::Result<T>,
+) -> std::io::Result<T> {
+    const CHUNK_SIZE: usize = 8_000;
+
+    let len = output.len();
+    output.resize(len + CHUNK_SIZE, 0);
+
+    let mut buffer = PartialBuffer::new(&mut output[len..]);
+    let result = f(&mut buffer);
+    let written = buffer.written().len();
+
+    output.truncate(len + written);
+    result
+}
+
+/// The default number of input bytes a reading adapter consumes in one poll before yielding.
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) const DEFAULT_YIELD_BUDGET: usize = 1024 * 1024;
+
+/// Ends a poll whose input budget has run out, returning the output produced so far or, if there
+/// is none, waking the task straight away so other tasks get to run before it is polled again.
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) fn yield_output(
+    cx: &mut core::task::Context<'_>,
+    output: &PartialBuffer<&mut [u8]>,
+) -> core::task::Poll<std::io::Result<()>> {
+    if output.written().is_empty() {
+        cx.waker().wake_by_ref();
+        core::task::Poll::Pending
+    } else {
+        core::task::Poll::Ready(Ok(()))
+    }
+}
+
+/// A buffer which tracks how much of it has been written to or read from.
+///
+/// The buffer is split into a written part at the start, and an unwritten part at the end which
+/// [`advance`](Self::advance) moves data from. For an input buffer the written part is the data
+/// that has been consumed, for an output buffer it is the data that has been produced.
 #[derive(Debug, Default)]
 pub struct PartialBuffer<B: AsRef<[u8]>> {
     buffer: B,
@@ -8,37 +55,46 @@ pub struct PartialBuffer<B: AsRef<[u8]>> {
 }
 
 impl<B: AsRef<[u8]>> PartialBuffer<B> {
-    pub(crate) fn new(buffer: B) -> Self {
+    /// Creates a buffer with nothing written yet.
+    pub fn new(buffer: B) -> Self {
         Self { buffer, index: 0 }
     }
 
-    pub(crate) fn written(&self) -> &[u8] {
+    /// Returns the part of the buffer that has been written.
+    pub fn written(&self) -> &[u8] {
         &self.buffer.as_ref()[..self.index]
     }
 
-    pub(crate) fn unwritten(&self) -> &[u8] {
+    /// Returns the part of the buffer that has not been written yet.
+    pub fn unwritten(&self) -> &[u8] {
         &self.buffer.as_ref()[self.index..]
     }
 
-    pub(crate) fn advance(&mut self, amount: usize) {
+    /// Moves `amount` bytes from the unwritten part to the written part.
+    pub fn advance(&mut self, amount: usize) {
         self.index += amount;
     }
 
-    pub(crate) fn get_mut(&mut self) -> &mut B {
+    /// Returns a mutable reference to the underlying buffer.
+    pub fn get_mut(&mut self) -> &mut B {
         &mut self.buffer
     }
 
-    pub(crate) fn into_inner(self) -> B {
+    /// Returns the underlying buffer.
+    pub fn into_inner(self) -> B {
         self.buffer
     }
 }
 
 impl<B: AsRef<[u8]> + AsMut<[u8]>> PartialBuffer<B> {
-    pub(crate) fn unwritten_mut(&mut self) -> &mut [u8] {
+    /// Returns the part of the buffer that has not been written yet, mutably.
+    pub fn unwritten_mut(&mut self) -> &mut [u8] {
         &mut self.buffer.as_mut()[self.index..]
     }
 
-    pub(crate) fn copy_unwritten_from<C: AsRef<[u8]>>(&mut self, other: &mut PartialBuffer<C>) {
+    /// Copies as much as fits from the unwritten part of `other` into the unwritten part of this
+    /// buffer, advancing both.
+    pub fn copy_unwritten_from<C: AsRef<[u8]>>(&mut self, other: &mut PartialBuffer<C>) {
         let len = std::cmp::min(self.unwritten().len(), other.unwritten().len());
 
         self.unwritten_mut()[..len].copy_from_slice(&other.unwritten()[..len]);
@@ -0,0 +1,46 @@
+//! Types specific to the zstd format.
+
+use std::{borrow::Cow, sync::Arc};
+
+/// Looks up the dictionaries needed to decode zstd frames by the dictionary ID in their headers,
+/// for decoders created with `ZstdDecoder::with_dictionary_provider`.
+///
+/// The decoder asks for a dictionary at the start of each frame which references a different
+/// dictionary than the one it has loaded, so one decoder can read frames compressed with several
+/// dictionaries, such as when they are rotated. If the provider doesn't have the dictionary, the
+/// decoder fails with an [`Error::DictionaryRequired`](crate::error::Error::DictionaryRequired)
+/// holding the ID, so the caller can fetch it and retry.
+///
+/// This is implemented for closures returning the dictionary as a `Vec<u8>`:
+///
+/// ```
+/// # #[cfg(feature = "futures-io")]
+/// # {
+/// use async_compression::futures::bufread::ZstdDecoder;
+/// use std::collections::HashMap;
+///
+/// let dictionaries: HashMap<u32, Vec<u8>> = HashMap::new();
+/// let decoder = ZstdDecoder::with_dictionary_provider(&[][..], move |id| {
+///     dictionaries.get(&id).cloned()
+/// });
+/// # }
+/// ```
+pub trait DictionaryProvider: Send + Sync {
+    /// Returns the dictionary with the ID `id`, or `None` if it isn't known.
+    fn dictionary(&self, id: u32) -> Option<Cow<'_, [u8]>>;
+}
+
+impl<F> DictionaryProvider for F
+where
+    F: Fn(u32) -> Option<Vec<u8>> + Send + Sync,
+{
+    fn dictionary(&self, id: u32) -> Option<Cow<'_, [u8]>> {
+        self(id).map(Cow::Owned)
+    }
+}
+
+impl<P: DictionaryProvider + ?Sized> DictionaryProvider for Arc<P> {
+    fn dictionary(&self, id: u32) -> Option<Cow<'_, [u8]>> {
+        (**self).dictionary(id)
+    }
+}
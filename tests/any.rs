@@ -0,0 +1,777 @@
+#[macro_use]
+mod utils;
+
+macro_rules! round_trips {
+    ($($variant:ident($feat:literal, $algorithm:ident))*) => {
+        $(
+            #[cfg(feature = $feat)]
+            mod $variant {
+                use async_compression::{
+                    futures::{bufread, compress_to_vec, decompress_to_vec, write},
+                    Algorithm, Level,
+                };
+                use crate::utils::{
+                    algos::$variant::sync,
+                    impls::futures::{bufread::from, read, write::to_vec},
+                    block_on, one_to_six, InputStream,
+                };
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn bufread_decode() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let input = InputStream::from(compressed.chunks(2));
+                    let output = read::to_vec(bufread::AnyDecoder::new(
+                        from(&input),
+                        Algorithm::$algorithm,
+                    ));
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn bufread_decode_from_read() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let output = read::to_vec(bufread::AnyDecoder::from_read_with_capacity(
+                        2,
+                        &compressed[..],
+                        Algorithm::$algorithm,
+                    ));
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn bufread_encode() {
+                    let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+                    let compressed = read::to_vec(bufread::AnyEncoder::with_quality(
+                        from(&input),
+                        Algorithm::$algorithm,
+                        Level::Fastest,
+                    ));
+
+                    assert_eq!(sync::decompress(&compressed), one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn write_decode() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let output = to_vec(
+                        &[compressed],
+                        |output| Box::pin(write::AnyDecoder::new(output, Algorithm::$algorithm)),
+                        2,
+                    );
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn write_decode_with_capacity() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let output = to_vec(
+                        &[compressed],
+                        |output| {
+                            Box::pin(write::AnyDecoder::with_capacity(
+                                16,
+                                output,
+                                Algorithm::$algorithm,
+                            ))
+                        },
+                        2,
+                    );
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn write_encode() {
+                    let compressed = to_vec(
+                        &[vec![1, 2, 3], vec![4, 5, 6]],
+                        |output| Box::pin(write::AnyEncoder::new(output, Algorithm::$algorithm)),
+                        2,
+                    );
+
+                    assert_eq!(sync::decompress(&compressed), one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn write_encode_with_capacity() {
+                    let compressed = to_vec(
+                        &[vec![1, 2, 3], vec![4, 5, 6]],
+                        |output| {
+                            Box::pin(write::AnyEncoder::with_capacity(
+                                16,
+                                output,
+                                Algorithm::$algorithm,
+                            ))
+                        },
+                        2,
+                    );
+
+                    assert_eq!(sync::decompress(&compressed), one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn write_encode_builder() {
+                    let compressed = to_vec(
+                        &[vec![1, 2, 3], vec![4, 5, 6]],
+                        |output| {
+                            Box::pin(
+                                write::AnyEncoder::builder(output, Algorithm::$algorithm)
+                                    .level(Level::Best)
+                                    .buffer_capacity(16)
+                                    .build(),
+                            )
+                        },
+                        2,
+                    );
+
+                    assert_eq!(sync::decompress(&compressed), one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn one_shot() {
+                    let compressed = block_on(compress_to_vec(
+                        one_to_six(),
+                        Algorithm::$algorithm,
+                        Level::Default,
+                    ))
+                    .unwrap();
+                    assert_eq!(sync::decompress(&compressed), one_to_six());
+
+                    let output =
+                        block_on(decompress_to_vec(&compressed, Algorithm::$algorithm, 6)).unwrap();
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(5000)]
+                #[cfg(feature = "tokio-blocking")]
+                fn blocking_round_trip() {
+                    use async_compression::tokio::blocking;
+                    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+                    // Spans several chunks
+                    let bytes: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+
+                    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+                    runtime.block_on(async {
+                        let mut encoder =
+                            blocking::write::AnyEncoder::new(Vec::new(), Algorithm::$algorithm);
+                        for chunk in bytes.chunks(10_000) {
+                            encoder.write_all(chunk).await.unwrap();
+                        }
+                        encoder.shutdown().await.unwrap();
+                        let compressed = encoder.into_inner();
+                        assert_eq!(sync::decompress(&compressed), bytes);
+
+                        let mut output = Vec::new();
+                        blocking::bufread::AnyDecoder::new(&compressed[..], Algorithm::$algorithm)
+                            .read_to_end(&mut output)
+                            .await
+                            .unwrap();
+                        assert_eq!(output, bytes);
+
+                        let mut compressed = Vec::new();
+                        blocking::bufread::AnyEncoder::new(&bytes[..], Algorithm::$algorithm)
+                            .read_to_end(&mut compressed)
+                            .await
+                            .unwrap();
+                        assert_eq!(sync::decompress(&compressed), bytes);
+
+                        let mut decoder =
+                            blocking::write::AnyDecoder::new(Vec::new(), Algorithm::$algorithm);
+                        for chunk in compressed.chunks(1000) {
+                            decoder.write_all(chunk).await.unwrap();
+                        }
+                        decoder.shutdown().await.unwrap();
+                        assert_eq!(decoder.into_inner(), bytes);
+                    });
+                }
+
+                #[test]
+                #[ntest::timeout(5000)]
+                #[cfg(feature = "std-io")]
+                fn std_io_round_trip() {
+                    use async_compression::std_io;
+                    use std::io::{Read as _, Write as _};
+
+                    let bytes: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+
+                    let mut encoder =
+                        std_io::write::AnyEncoder::new(Vec::new(), Algorithm::$algorithm);
+                    for chunk in bytes.chunks(10_000) {
+                        encoder.write_all(chunk).unwrap();
+                    }
+                    let compressed = encoder.finish().unwrap();
+                    assert_eq!(sync::decompress(&compressed), bytes);
+
+                    // The same codecs are used, so the output matches the async encoders
+                    let expected = read::to_vec(bufread::AnyEncoder::new(
+                        &bytes[..],
+                        Algorithm::$algorithm,
+                    ));
+                    let mut output = Vec::new();
+                    std_io::bufread::AnyEncoder::new(&bytes[..], Algorithm::$algorithm)
+                        .read_to_end(&mut output)
+                        .unwrap();
+                    assert_eq!(output, expected);
+
+                    let mut output = Vec::new();
+                    std_io::bufread::AnyDecoder::from_read_with_capacity(
+                        100,
+                        &compressed[..],
+                        Algorithm::$algorithm,
+                    )
+                    .read_to_end(&mut output)
+                    .unwrap();
+                    assert_eq!(output, bytes);
+
+                    let mut decoder =
+                        std_io::write::AnyDecoder::new(Vec::new(), Algorithm::$algorithm);
+                    for chunk in compressed.chunks(1000) {
+                        decoder.write_all(chunk).unwrap();
+                    }
+                    assert_eq!(decoder.finish().unwrap(), bytes);
+                }
+
+                #[test]
+                fn levels() {
+                    let algorithm = Algorithm::$algorithm;
+                    let range = algorithm.level_range();
+
+                    for level in [Level::Fastest, Level::Best, Level::Default] {
+                        assert!(range.contains(&algorithm.resolve_level(level)));
+                        assert_eq!(
+                            algorithm.check_level(level).unwrap(),
+                            algorithm.resolve_level(level),
+                        );
+                    }
+
+                    let quality = *range.end() + 1;
+                    assert_eq!(algorithm.resolve_level(Level::Precise(quality)), *range.end());
+                    let err = algorithm.check_level(Level::Precise(quality)).unwrap_err();
+                    assert_eq!(err.algorithm(), algorithm);
+                    assert_eq!(err.quality(), quality);
+                    assert_eq!(err.range(), range);
+                }
+            }
+        )*
+    };
+}
+
+round_trips! {
+    brotli("brotli", Brotli)
+    bzip2("bzip2", Bzip2)
+    deflate("deflate", Deflate)
+    gzip("gzip", Gzip)
+    lz4("lz4", Lz4)
+    lzma("lzma", Lzma)
+    snappy("snappy", Snappy)
+    xz("xz", Xz)
+    zlib("zlib", Zlib)
+    zstd("zstd", Zstd)
+}
+
+#[test]
+#[cfg(all(feature = "brotli", feature = "gzip"))]
+fn level_out_of_range() {
+    use async_compression::{Algorithm, Level};
+    use std::io::{Error, ErrorKind};
+
+    assert_eq!(Algorithm::Gzip.check_level(Level::Precise(9)).unwrap(), 9);
+    assert_eq!(Algorithm::Gzip.resolve_level(Level::Precise(12)), 10);
+
+    let err = Algorithm::Gzip.check_level(Level::Precise(12)).unwrap_err();
+    assert_eq!(err.range(), 0..=10);
+    assert_eq!(Error::from(err).kind(), ErrorKind::InvalidInput);
+
+    let err = Algorithm::Brotli
+        .check_level(Level::Precise(15))
+        .unwrap_err();
+    assert_eq!(err.quality(), 15);
+    assert_eq!(err.range(), 0..=11);
+}
+
+mod content_encoding {
+    use async_compression::futures::bufread::AnyDecoder;
+    use std::io::ErrorKind;
+
+    #[allow(unused)]
+    use crate::utils::{
+        impls::futures::{bufread::from, read},
+        one_to_six, InputStream,
+    };
+
+    #[allow(unused)]
+    fn decompress(input: &InputStream, encoding: &str) -> Vec<u8> {
+        read::to_vec(AnyDecoder::from_content_encoding(from(input), encoding).unwrap())
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn identity() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+
+        assert_eq!(decompress(&input, "identity"), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn unsupported() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+
+        let err = AnyDecoder::from_content_encoding(from(&input), "compress")
+            .err()
+            .unwrap();
+
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "brotli")]
+    fn br() {
+        let compressed = crate::utils::algos::brotli::sync::compress(one_to_six());
+        let input = InputStream::new(vec![compressed]);
+
+        assert_eq!(decompress(&input, "br"), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "gzip")]
+    fn gzip() {
+        let compressed = crate::utils::algos::gzip::sync::compress(one_to_six());
+        let input = InputStream::new(vec![compressed]);
+
+        assert_eq!(decompress(&input, "gzip"), one_to_six());
+        assert_eq!(decompress(&input, "x-gzip"), one_to_six());
+        assert_eq!(decompress(&input, " GZip "), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "zstd")]
+    fn zstd() {
+        let compressed = crate::utils::algos::zstd::sync::compress(one_to_six());
+        let input = InputStream::new(vec![compressed]);
+
+        assert_eq!(decompress(&input, "zstd"), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "zlib")]
+    fn deflate_zlib() {
+        let compressed = crate::utils::algos::zlib::sync::compress(one_to_six());
+        let input = InputStream::from(compressed.chunks(1));
+
+        assert_eq!(decompress(&input, "deflate"), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "deflate")]
+    fn deflate_raw() {
+        let compressed = crate::utils::algos::deflate::sync::compress(one_to_six());
+        let input = InputStream::from(compressed.chunks(1));
+
+        assert_eq!(decompress(&input, "deflate"), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "deflate")]
+    fn deflate_raw_long() {
+        let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+        let compressed = crate::utils::algos::deflate::sync::compress(&bytes);
+        let input = InputStream::from(compressed.chunks(1024));
+
+        assert_eq!(decompress(&input, "deflate"), bytes);
+    }
+}
+
+mod accept_encoding {
+    use async_compression::futures::bufread::AnyEncoder;
+
+    #[allow(unused)]
+    use crate::utils::{
+        impls::futures::{bufread::from, read},
+        one_to_six, InputStream,
+    };
+
+    fn negotiate(accept_encoding: &str) -> Option<&'static str> {
+        let input = InputStream::new(vec![]);
+        AnyEncoder::from_accept_encoding(from(&input), accept_encoding).map(|(token, _)| token)
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn identity() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+        let (token, encoder) = AnyEncoder::from_accept_encoding(from(&input), "").unwrap();
+
+        assert_eq!(token, "identity");
+        assert_eq!(read::to_vec(encoder), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn unacceptable() {
+        assert_eq!(negotiate("identity;q=0"), None);
+        assert_eq!(negotiate("*;q=0"), None);
+        assert_eq!(negotiate("compress, identity;q=0"), None);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn unsupported() {
+        assert_eq!(negotiate("compress"), Some("identity"));
+        assert_eq!(negotiate("compress;q=1, identity;q=0.5"), Some("identity"));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "gzip")]
+    fn gzip() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+        let (token, encoder) = AnyEncoder::from_accept_encoding(from(&input), "gzip").unwrap();
+        let compressed = read::to_vec(encoder);
+
+        assert_eq!(token, "gzip");
+        assert_eq!(
+            crate::utils::algos::gzip::sync::decompress(&compressed),
+            one_to_six()
+        );
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "gzip")]
+    fn qvalues() {
+        assert_eq!(negotiate("gzip;q=0.5"), Some("gzip"));
+        assert_eq!(negotiate("x-gzip"), Some("gzip"));
+        assert_eq!(negotiate("GZIP ; Q=0.5"), Some("gzip"));
+        assert_eq!(negotiate("gzip;q=0"), Some("identity"));
+        assert_eq!(negotiate("gzip;q=0.5, identity"), Some("identity"));
+        assert_eq!(negotiate("gzip;q=0.5, identity;q=0.5"), Some("gzip"));
+        assert_eq!(negotiate("gzip;q=0.5, *;q=0"), Some("gzip"));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "gzip")]
+    fn malformed() {
+        assert_eq!(negotiate("gzip;q=2"), Some("identity"));
+        assert_eq!(negotiate("gzip;q=0.5000"), Some("identity"));
+        assert_eq!(negotiate("gzip;q"), Some("identity"));
+        assert_eq!(negotiate(",, gzip ,"), Some("gzip"));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(all(feature = "brotli", feature = "gzip", feature = "zstd"))]
+    fn preference() {
+        assert_eq!(negotiate("gzip, br, zstd"), Some("br"));
+        assert_eq!(negotiate("gzip, zstd"), Some("zstd"));
+        assert_eq!(negotiate("*"), Some("br"));
+        assert_eq!(negotiate("gzip, br;q=0.9"), Some("gzip"));
+        assert_eq!(negotiate("br;q=0, *;q=0.5"), Some("zstd"));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "zlib")]
+    fn deflate() {
+        assert_eq!(negotiate("deflate"), Some("deflate"));
+    }
+}
+
+#[cfg(all(feature = "brotli", feature = "gzip"))]
+mod chained {
+    use async_compression::{
+        futures::{bufread, write},
+        Algorithm,
+    };
+
+    use crate::utils::{
+        algos::{brotli, gzip},
+        impls::futures::{bufread::from, read, write::to_vec},
+        one_to_six, InputStream,
+    };
+
+    fn compress(bytes: &[u8]) -> Vec<u8> {
+        brotli::sync::compress(&gzip::sync::compress(bytes))
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_short() {
+        let input = InputStream::from(compress(one_to_six()).chunks(1));
+        let decoder =
+            bufread::AnyDecoder::chained(from(&input), &[Algorithm::Gzip, Algorithm::Brotli]);
+
+        assert_eq!(read::to_vec(decoder), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_long() {
+        let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+        let input = InputStream::from(compress(&bytes).chunks(1024));
+        let decoder =
+            bufread::AnyDecoder::chained(from(&input), &[Algorithm::Gzip, Algorithm::Brotli]);
+
+        assert_eq!(read::to_vec(decoder), bytes);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_long() {
+        let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+        let compressed = compress(&bytes);
+
+        let output = to_vec(
+            &compressed.chunks(1024).map(Vec::from).collect::<Vec<_>>(),
+            |output| {
+                Box::pin(write::AnyDecoder::chained(
+                    output,
+                    &[Algorithm::Gzip, Algorithm::Brotli],
+                ))
+            },
+            512,
+        );
+
+        assert_eq!(output, bytes);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn content_encoding() {
+        let input = InputStream::from(compress(one_to_six()).chunks(2));
+        let decoder =
+            bufread::AnyDecoder::from_content_encoding(from(&input), "gzip, identity, br").unwrap();
+
+        assert_eq!(read::to_vec(decoder), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn wrong_order() {
+        let input = InputStream::new(vec![compress(one_to_six())]);
+        let decoder = bufread::AnyDecoder::from_content_encoding(from(&input), "br, gzip").unwrap();
+
+        let mut output = [0; 64];
+        assert!(read::poll_read(decoder, &mut output).is_err());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn empty() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+
+        assert_eq!(
+            read::to_vec(bufread::AnyDecoder::chained(from(&input), &[])),
+            one_to_six()
+        );
+    }
+}
+
+#[test]
+#[cfg(all(feature = "futures-io", feature = "deflate"))]
+fn parallel_unsupported() {
+    use async_compression::{
+        futures::write::ParallelEncoder, parallel::Executor, Algorithm, Level,
+    };
+
+    struct Threads;
+
+    impl Executor for Threads {
+        fn execute(&self, task: Box<dyn FnOnce() + Send>) {
+            std::thread::spawn(task);
+        }
+    }
+
+    let err =
+        ParallelEncoder::new(Vec::new(), Algorithm::Deflate, Level::Default, Threads).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(all(feature = "gzip", feature = "zstd"))]
+mod transcoder {
+    use async_compression::{futures::bufread::Transcoder, Algorithm};
+    use std::io::ErrorKind;
+
+    use crate::utils::{
+        algos::{gzip, zstd},
+        impls::futures::{bufread::from, read},
+        InputStream,
+    };
+
+    fn data() -> Vec<u8> {
+        (0..100_000u32)
+            .flat_map(|i| (i % 251).to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn gzip_to_zstd() {
+        let compressed = gzip::sync::compress(&data());
+
+        let input = InputStream::from(compressed.chunks(1024));
+        let output = read::to_vec(Transcoder::new(
+            from(&input),
+            Algorithm::Gzip,
+            Algorithm::Zstd,
+        ));
+
+        assert_eq!(zstd::sync::decompress(&output), data());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn multiple_members() {
+        let mut compressed = gzip::sync::compress(&[1, 2, 3]);
+        compressed.extend(gzip::sync::compress(&[4, 5, 6]));
+
+        let input = InputStream::from(compressed.chunks(2));
+        let mut transcoder = Transcoder::new(from(&input), Algorithm::Gzip, Algorithm::Zstd);
+        transcoder.multiple_members(true);
+        let output = read::to_vec(transcoder);
+
+        let frame = zstd_safe::find_frame_compressed_size(&output).unwrap();
+        assert!(frame < output.len());
+        assert_eq!(zstd::sync::decompress(&output), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn truncated() {
+        let compressed = gzip::sync::compress(&data());
+
+        let input = InputStream::from(compressed[..compressed.len() / 2].chunks(1024));
+        let mut transcoder = Transcoder::new(from(&input), Algorithm::Gzip, Algorithm::Zstd);
+        let err = crate::utils::block_on(futures::io::AsyncReadExt::read_to_end(
+            &mut transcoder,
+            &mut Vec::new(),
+        ))
+        .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "tokio")]
+    fn tokio() {
+        use crate::utils::impls::tokio::{bufread::from, read};
+        use async_compression::tokio::bufread::Transcoder;
+
+        let compressed = zstd::sync::compress(&data());
+
+        let input = InputStream::from(compressed.chunks(1024));
+        let output = read::to_vec(Transcoder::new(
+            from(&input),
+            Algorithm::Zstd,
+            Algorithm::Gzip,
+        ));
+
+        assert_eq!(gzip::sync::decompress(&output), data());
+    }
+}
+
+#[cfg(all(feature = "gzip", feature = "zstd"))]
+mod fan_out {
+    use async_compression::{futures::write::FanOutEncoder, Algorithm, Level};
+    use futures::io::AsyncWriteExt;
+    use futures_test::io::AsyncWriteTestExt;
+
+    use crate::utils::{
+        algos::{gzip, zstd},
+        block_on,
+    };
+
+    fn data() -> Vec<u8> {
+        (0..100_000u32)
+            .flat_map(|i| (i % 251).to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn gzip_and_zstd() {
+        let (mut gzipped, mut zstded) = (Vec::new(), Vec::new());
+
+        {
+            let mut encoder = FanOutEncoder::new()
+                .with_output(
+                    (&mut gzipped).limited_write(2).interleave_pending_write(),
+                    Algorithm::Gzip,
+                    Level::Default,
+                )
+                .with_output(
+                    (&mut zstded).limited_write(1000).interleave_pending_write(),
+                    Algorithm::Zstd,
+                    Level::Default,
+                );
+            for chunk in data().chunks(1000) {
+                block_on(encoder.write_all(chunk)).unwrap();
+            }
+            block_on(encoder.flush()).unwrap();
+            block_on(encoder.close()).unwrap();
+
+            assert_eq!(encoder.len(), 2);
+            assert_eq!(encoder.total_in(), data().len() as u64);
+            assert_eq!(
+                encoder.total_out(0),
+                encoder.get_ref(0).get_ref().get_ref().len() as u64
+            );
+        }
+
+        assert_eq!(gzip::sync::decompress(&gzipped), data());
+        assert_eq!(zstd::sync::decompress(&zstded), data());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn no_outputs() {
+        let mut encoder = FanOutEncoder::<Vec<u8>>::new();
+        block_on(encoder.write_all(&data())).unwrap();
+        block_on(encoder.close()).unwrap();
+
+        assert!(encoder.is_empty());
+        assert!(encoder.into_inner().is_empty());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    #[cfg(feature = "tokio")]
+    fn tokio() {
+        use async_compression::tokio::write::FanOutEncoder;
+        use tokio::io::AsyncWriteExt;
+
+        let mut encoder = FanOutEncoder::new()
+            .with_output(Vec::new(), Algorithm::Zstd, Level::Default)
+            .with_output(Vec::new(), Algorithm::Gzip, Level::Fastest);
+        block_on(encoder.write_all(&data())).unwrap();
+        block_on(encoder.shutdown()).unwrap();
+
+        let outputs = encoder.into_inner();
+        assert_eq!(zstd::sync::decompress(&outputs[0]), data());
+        assert_eq!(gzip::sync::decompress(&outputs[1]), data());
+    }
+}
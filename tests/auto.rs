@@ -0,0 +1,221 @@
+#[macro_use]
+mod utils;
+
+use async_compression::futures::bufread::AutoDecoder;
+use utils::{
+    impls::futures::{bufread, read},
+    one_to_six, InputStream,
+};
+
+fn decompress(input: &InputStream) -> Vec<u8> {
+    read::to_vec(AutoDecoder::new(bufread::from(input)))
+}
+
+macro_rules! detects {
+    ($($variant:ident($feat:literal, $algorithm:ident))*) => {
+        $(
+            #[cfg(feature = $feat)]
+            mod $variant {
+                use crate::utils::{algos::$variant::sync, block_on, one_to_six, InputStream};
+                use async_compression::Algorithm;
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let input = InputStream::new(vec![compressed]);
+
+                    assert_eq!(super::decompress(&input), one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short_chunks() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let input = InputStream::from(compressed.chunks(1));
+
+                    assert_eq!(super::decompress(&input), one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn long() {
+                    let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+                    let compressed = sync::compress(&bytes);
+
+                    let input = InputStream::from(compressed.chunks(1024));
+
+                    assert_eq!(super::decompress(&input), bytes);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn probe() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let mut reader = &compressed[..];
+                    let algorithm =
+                        block_on(async_compression::futures::bufread::probe(&mut reader)).unwrap();
+
+                    assert_eq!(algorithm, Some(Algorithm::$algorithm));
+                    assert_eq!(reader, &compressed[..]);
+                }
+            }
+        )*
+    };
+}
+
+detects! {
+    brotli("brotli", Brotli)
+    bzip2("bzip2", Bzip2)
+    gzip("gzip", Gzip)
+    lz4("lz4", Lz4)
+    snappy("snappy", Snappy)
+    xz("xz", Xz)
+    zlib("zlib", Zlib)
+    zstd("zstd", Zstd)
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn passthrough() {
+    let input = InputStream::new(vec![b"hello world, this is not compressed".to_vec()]);
+
+    assert_eq!(decompress(&input), input.bytes());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn passthrough_short() {
+    let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+
+    assert_eq!(decompress(&input), one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn passthrough_long() {
+    let bytes: Vec<u8> = (0..65_536).map(|i| b"abcdefgh"[i % 8]).collect();
+    let input = InputStream::from(bytes.chunks(1024));
+
+    assert_eq!(decompress(&input), bytes);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn probe_passthrough() {
+    use async_compression::futures::bufread::probe;
+
+    let mut reader = &b"hello world, this is not compressed"[..];
+    assert_eq!(utils::block_on(probe(&mut reader)).unwrap(), None);
+    assert_eq!(reader, b"hello world, this is not compressed");
+
+    let mut reader = &[][..];
+    assert_eq!(utils::block_on(probe(&mut reader)).unwrap(), None);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(all(feature = "gzip", feature = "tokio"))]
+fn probe_tokio() {
+    use async_compression::{
+        tokio::bufread::{probe, AnyDecoder},
+        Algorithm,
+    };
+    use tokio::io::AsyncReadExt as _;
+
+    let compressed = utils::algos::gzip::sync::compress(one_to_six());
+    let mut reader = tokio::io::BufReader::new(&compressed[..]);
+
+    let algorithm = utils::block_on(probe(&mut reader)).unwrap();
+    assert_eq!(algorithm, Some(Algorithm::Gzip));
+
+    let mut output = Vec::new();
+    let mut decoder = AnyDecoder::new(reader, algorithm.unwrap());
+    utils::block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn empty() {
+    let input = InputStream::new(vec![]);
+
+    assert_eq!(decompress(&input), &[][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(all(feature = "gzip", feature = "zstd"))]
+fn multiple_members_mixed() {
+    let compressed = [
+        utils::algos::gzip::sync::compress(&[1, 2, 3, 4, 5, 6]),
+        utils::algos::zstd::sync::compress(&[6, 5, 4, 3, 2, 1]),
+    ]
+    .join(&[][..]);
+
+    let input = InputStream::new(vec![compressed]);
+
+    let mut decoder = AutoDecoder::new(bufread::from(&input));
+    decoder.multiple_members(true);
+    let output = read::to_vec(decoder);
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6, 6, 5, 4, 3, 2, 1][..]);
+}
+
+/// A format that stores its payload as-is after a 4 byte magic number.
+struct Stored {
+    header: usize,
+}
+
+impl async_compression::auto::FormatDecoder for Stored {
+    fn decode(&mut self, input: &[u8], output: &mut [u8]) -> std::io::Result<(usize, usize, bool)> {
+        let skip = input.len().min(4 - self.header);
+        self.header += skip;
+        let len = (input.len() - skip).min(output.len());
+        output[..len].copy_from_slice(&input[skip..skip + len]);
+        Ok((skip + len, len, false))
+    }
+}
+
+fn stored_formats() -> async_compression::auto::Formats {
+    let mut formats = async_compression::auto::Formats::new();
+    formats.register_magic(b"STOR", || Box::new(Stored { header: 0 }));
+    formats
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn custom_format() {
+    let input = InputStream::from(b"STOR\x01\x02\x03\x04\x05\x06".chunks(1));
+    let decoder = AutoDecoder::with_formats(bufread::from(&input), stored_formats());
+
+    assert_eq!(read::to_vec(decoder), one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn custom_format_not_matched() {
+    let input = InputStream::from(b"STAR\x01\x02".chunks(1));
+    let decoder = AutoDecoder::with_formats(bufread::from(&input), stored_formats());
+
+    assert_eq!(read::to_vec(decoder), b"STAR\x01\x02");
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "gzip")]
+fn custom_format_takes_priority() {
+    let mut formats = async_compression::auto::Formats::new();
+    formats.register(
+        |prefix| prefix.first().map(|&byte| byte == 0x1f),
+        || Box::new(Stored { header: 0 }),
+    );
+
+    let input = InputStream::from([[0x1f, 0x8b, 0, 0, 1, 2, 3, 4, 5, 6]]);
+    let decoder = AutoDecoder::with_formats(bufread::from(&input), formats);
+
+    assert_eq!(read::to_vec(decoder), one_to_six());
+}
@@ -2,3 +2,24 @@
 mod utils;
 
 test_cases!(bzip2);
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "futures-io")]
+fn bzip2_bufread_decompress_small_memory() {
+    use async_compression::futures::bufread::BzDecoder;
+    use futures::io::AsyncReadExt as _;
+    use utils::{algos::bzip2::sync, block_on, InputStream};
+
+    let input: Vec<u8> = (0..300_000).map(|_| rand::random()).collect();
+    let compressed = [sync::compress(&input), sync::compress(&input)].concat();
+
+    let stream = InputStream::from(compressed.chunks(1000));
+    let mut decoder = BzDecoder::with_small_memory(utils::impls::futures::bufread::from(&stream));
+    decoder.multiple_members(true);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, [&input[..], &input[..]].concat());
+    assert_eq!(decoder.member_count(), 2);
+}
@@ -0,0 +1,408 @@
+#[macro_use]
+mod utils;
+
+use async_compression::codec::{Decode, Encode, PartialBuffer};
+use std::io::Result;
+use utils::one_to_six;
+
+/// Stores each byte as the difference from the previous byte.
+#[derive(Debug, Default)]
+struct DeltaEncoder {
+    last: u8,
+}
+
+/// Reverses the [`DeltaEncoder`].
+#[derive(Debug, Default)]
+struct DeltaDecoder {
+    last: u8,
+}
+
+fn delta(
+    input: &mut PartialBuffer<impl AsRef<[u8]>>,
+    output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    mut f: impl FnMut(u8) -> u8,
+) {
+    let len = input.unwritten().len().min(output.unwritten().len());
+    for (src, dst) in input.unwritten()[..len].iter().zip(output.unwritten_mut()) {
+        *dst = f(*src);
+    }
+    input.advance(len);
+    output.advance(len);
+}
+
+impl Encode for DeltaEncoder {
+    fn encode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<()> {
+        let last = &mut self.last;
+        delta(input, output, |byte| {
+            let encoded = byte.wrapping_sub(*last);
+            *last = byte;
+            encoded
+        });
+        Ok(())
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+impl Decode for DeltaDecoder {
+    fn reinit(&mut self) -> Result<()> {
+        self.last = 0;
+        Ok(())
+    }
+
+    fn decode(
+        &mut self,
+        input: &mut PartialBuffer<impl AsRef<[u8]>>,
+        output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        let last = &mut self.last;
+        delta(input, output, |byte| {
+            *last = last.wrapping_add(byte);
+            *last
+        });
+        Ok(false)
+    }
+
+    fn flush(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn finish(
+        &mut self,
+        _output: &mut PartialBuffer<impl AsRef<[u8]> + AsMut<[u8]>>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+const ENCODED: &[u8] = &[1, 1, 1, 1, 1, 1];
+
+#[test]
+fn partial_buffer() {
+    let mut input = PartialBuffer::new(&[1, 2, 3][..]);
+    let mut output = PartialBuffer::new([0; 2]);
+
+    output.copy_unwritten_from(&mut input);
+
+    assert_eq!(input.written(), [1, 2]);
+    assert_eq!(input.unwritten(), [3]);
+    assert_eq!(output.written(), [1, 2]);
+    assert!(output.unwritten().is_empty());
+    assert_eq!(output.into_inner(), [1, 2]);
+}
+
+#[test]
+fn round_trip() {
+    let mut encoder = DeltaEncoder::default();
+    let mut decoder = DeltaDecoder::default();
+
+    let mut input = PartialBuffer::new(one_to_six());
+    let mut encoded = PartialBuffer::new([0; 6]);
+    encoder.encode(&mut input, &mut encoded).unwrap();
+    assert!(encoder.finish(&mut encoded).unwrap());
+    assert_eq!(encoded.written(), ENCODED);
+
+    let mut encoded = PartialBuffer::new(encoded.into_inner());
+    let mut decoded = PartialBuffer::new([0; 6]);
+    assert!(!decoder.decode(&mut encoded, &mut decoded).unwrap());
+    assert!(decoder.finish(&mut decoded).unwrap());
+    assert_eq!(decoded.written(), one_to_six());
+}
+
+#[cfg(feature = "futures-io")]
+mod futures {
+    use super::{DeltaDecoder, DeltaEncoder, ENCODED};
+    use crate::utils::{
+        block_on,
+        impls::futures::{bufread::from, read, write::to_vec},
+        one_to_six, InputStream,
+    };
+    use async_compression::futures::{bufread, write};
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_encode() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+        let encoder = bufread::Encoder::new(from(&input), DeltaEncoder::default());
+
+        assert_eq!(read::to_vec(encoder), ENCODED);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_decode() {
+        let input = InputStream::from([[1, 1, 1], [1, 1, 1]]);
+        let decoder = bufread::Decoder::new(from(&input), DeltaDecoder::default());
+
+        assert_eq!(read::to_vec(decoder), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_chain() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+        let encoder = bufread::Encoder::new(from(&input), DeltaEncoder::default());
+        let encoder = bufread::Encoder::new(encoder, DeltaEncoder::default());
+        let decoder = bufread::Decoder::new(encoder, DeltaDecoder::default());
+        let decoder = bufread::Decoder::new(decoder, DeltaDecoder::default());
+
+        assert_eq!(read::to_vec(decoder), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_encode() {
+        let output = to_vec(
+            &[vec![1, 2, 3], vec![4, 5, 6]],
+            |output| Box::pin(write::Encoder::new(output, DeltaEncoder::default())),
+            2,
+        );
+
+        assert_eq!(output, ENCODED);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_encode_vectored() {
+        use futures::io::AsyncWriteExt as _;
+        use std::io::IoSlice;
+
+        let mut encoder = write::Encoder::new(Vec::new(), DeltaEncoder::default());
+
+        let bufs = [
+            IoSlice::new(&[1, 2, 3]),
+            IoSlice::new(&[]),
+            IoSlice::new(&[4, 5, 6]),
+        ];
+        assert_eq!(block_on(encoder.write_vectored(&bufs)).unwrap(), 6);
+        block_on(encoder.close()).unwrap();
+
+        assert_eq!(encoder.into_inner(), ENCODED);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_encode_vectored_partial() {
+        use futures::io::AsyncWriteExt as _;
+        use futures_test::io::AsyncWriteTestExt as _;
+        use std::io::IoSlice;
+
+        let input = [vec![1; 6000], vec![2; 6000]];
+        let output = Vec::new().interleave_pending_write();
+        let mut encoder = write::Encoder::new(output, DeltaEncoder::default());
+
+        // The writer is not ready by the time the second slice is reached
+        let bufs = [IoSlice::new(&input[0]), IoSlice::new(&input[1])];
+        let written = block_on(encoder.write_vectored(&bufs)).unwrap();
+        assert_eq!(written, input[0].len());
+
+        block_on(encoder.write_all(&input[1])).unwrap();
+        block_on(encoder.close()).unwrap();
+
+        let decoded: Vec<u8> = encoder
+            .into_inner()
+            .into_inner()
+            .iter()
+            .scan(0u8, |last, &byte| {
+                *last = last.wrapping_add(byte);
+                Some(*last)
+            })
+            .collect();
+        assert_eq!(decoded, input.concat());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_decode() {
+        let output = to_vec(
+            &[vec![1, 1, 1], vec![1, 1, 1]],
+            |output| Box::pin(write::Decoder::new(output, DeltaDecoder::default())),
+            2,
+        );
+
+        assert_eq!(output, one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_decode_vectored() {
+        use futures::io::AsyncWriteExt as _;
+        use std::io::IoSlice;
+
+        let mut decoder = write::Decoder::new(Vec::new(), DeltaDecoder::default());
+
+        let bufs = [
+            IoSlice::new(&[1, 1]),
+            IoSlice::new(&[]),
+            IoSlice::new(&[1, 1, 1, 1]),
+        ];
+        assert_eq!(block_on(decoder.write_vectored(&bufs)).unwrap(), 6);
+        block_on(decoder.close()).unwrap();
+
+        assert_eq!(decoder.into_inner(), one_to_six());
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio {
+    use super::{DeltaDecoder, DeltaEncoder, ENCODED};
+    use crate::utils::{
+        block_on,
+        impls::tokio::{bufread::from, read, write::to_vec},
+        one_to_six, pin_mut, InputStream,
+    };
+    use async_compression::tokio::{bufread, write};
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_encode() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+        let encoder = bufread::Encoder::new(from(&input), DeltaEncoder::default());
+
+        assert_eq!(read::to_vec(encoder), ENCODED);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_decode() {
+        let input = InputStream::from([[1, 1, 1], [1, 1, 1]]);
+        let decoder = bufread::Decoder::new(from(&input), DeltaDecoder::default());
+
+        assert_eq!(read::to_vec(decoder), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_decode_uninit() {
+        use futures::future::poll_fn;
+        use std::mem::MaybeUninit;
+        use tokio::io::{AsyncRead as _, ReadBuf};
+
+        let input = InputStream::from([[1, 1, 1, 1, 1, 1]]);
+        let decoder = bufread::Decoder::new(from(&input), DeltaDecoder::default());
+        pin_mut!(decoder);
+
+        let mut storage = vec![MaybeUninit::uninit(); 1 << 20];
+        let mut buf = ReadBuf::uninit(&mut storage);
+        block_on(poll_fn(|cx| decoder.as_mut().poll_read(cx, &mut buf))).unwrap();
+
+        // Only a small part of the buffer had to be initialized for the output
+        assert_eq!(buf.filled(), one_to_six());
+        assert!(buf.initialized().len() < 1 << 16);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_decode_uninit_long() {
+        use futures::future::poll_fn;
+        use std::mem::MaybeUninit;
+        use tokio::io::{AsyncRead as _, ReadBuf};
+
+        let input = InputStream::new(vec![vec![1; 100_000]]);
+        let decoder = bufread::Decoder::new(from(&input), DeltaDecoder::default());
+        pin_mut!(decoder);
+
+        let mut storage = vec![MaybeUninit::uninit(); 1 << 20];
+        let mut buf = ReadBuf::uninit(&mut storage);
+        block_on(poll_fn(|cx| decoder.as_mut().poll_read(cx, &mut buf))).unwrap();
+
+        // The initialized part grows to fit all the output available at once
+        assert_eq!(buf.filled().len(), 100_000);
+        assert!(buf.initialized().len() < 1 << 18);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufread_chain() {
+        let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+        let encoder = bufread::Encoder::new(from(&input), DeltaEncoder::default());
+        let encoder = bufread::Encoder::new(encoder, DeltaEncoder::default());
+        let decoder = bufread::Decoder::new(encoder, DeltaDecoder::default());
+        let decoder = bufread::Decoder::new(decoder, DeltaDecoder::default());
+
+        assert_eq!(read::to_vec(decoder), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_encode() {
+        let output = to_vec(
+            &[vec![1, 2, 3], vec![4, 5, 6]],
+            |output| Box::pin(write::Encoder::new(output, DeltaEncoder::default())),
+            2,
+        );
+
+        assert_eq!(output, ENCODED);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_encode_vectored() {
+        use std::io::IoSlice;
+        use tokio::io::{AsyncWrite as _, AsyncWriteExt as _};
+
+        let mut encoder = write::Encoder::new(Vec::new(), DeltaEncoder::default());
+        assert!(encoder.is_write_vectored());
+
+        let bufs = [
+            IoSlice::new(&[1, 2, 3]),
+            IoSlice::new(&[]),
+            IoSlice::new(&[4, 5, 6]),
+        ];
+        assert_eq!(block_on(encoder.write_vectored(&bufs)).unwrap(), 6);
+        block_on(encoder.shutdown()).unwrap();
+
+        assert_eq!(encoder.into_inner(), ENCODED);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_decode() {
+        let output = to_vec(
+            &[vec![1, 1, 1], vec![1, 1, 1]],
+            |output| Box::pin(write::Decoder::new(output, DeltaDecoder::default())),
+            2,
+        );
+
+        assert_eq!(output, one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_decode_vectored() {
+        use std::io::IoSlice;
+        use tokio::io::{AsyncWrite as _, AsyncWriteExt as _};
+
+        let mut decoder = write::Decoder::new(Vec::new(), DeltaDecoder::default());
+        assert!(decoder.is_write_vectored());
+
+        let bufs = [
+            IoSlice::new(&[1, 1]),
+            IoSlice::new(&[]),
+            IoSlice::new(&[1, 1, 1, 1]),
+        ];
+        assert_eq!(block_on(decoder.write_vectored(&bufs)).unwrap(), 6);
+        block_on(decoder.shutdown()).unwrap();
+
+        assert_eq!(decoder.into_inner(), one_to_six());
+    }
+}
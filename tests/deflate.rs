@@ -2,3 +2,92 @@
 mod utils;
 
 test_cases!(deflate);
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn deflate_bufread_decompress_entries() {
+    use futures::io::AsyncReadExt as _;
+    use utils::{
+        algos::deflate::{futures::bufread, sync},
+        block_on,
+    };
+
+    let first: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+    let second = b"the second entry".to_vec();
+
+    let crc = |data: &[u8]| {
+        let mut crc = flate2::Crc::new();
+        crc.update(data);
+        crc.sum()
+    };
+
+    // Two entries back to back followed by something else, as in a ZIP archive
+    let entries = [sync::compress(&first), sync::compress(&second)];
+    let archive = [&entries[0][..], &entries[1][..], b"PK\x01\x02"].concat();
+
+    let mut reader = &archive[..];
+    for (entry, data) in entries.iter().zip([&first, &second]) {
+        let mut decoder = bufread::Decoder::new(&mut reader)
+            .with_compressed_size(entry.len() as u64)
+            .with_uncompressed_size(data.len() as u64)
+            .with_crc32(crc(data));
+
+        let mut output = Vec::new();
+        block_on(decoder.read_to_end(&mut output)).unwrap();
+        assert_eq!(&output, data);
+        assert!(decoder.is_done());
+    }
+    assert_eq!(reader, b"PK\x01\x02");
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn deflate_bufread_decompress_entry_mismatch() {
+    use async_compression::error::Error;
+    use futures::io::AsyncReadExt as _;
+    use std::io::ErrorKind;
+    use utils::{
+        algos::deflate::{futures::bufread, sync},
+        block_on, one_to_six,
+    };
+
+    let compressed = sync::compress(one_to_six());
+    let read = |decoder: bufread::Decoder<&[u8]>| {
+        let mut decoder = decoder;
+        block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err()
+    };
+
+    let decoder = bufread::Decoder::new(&compressed[..])
+        .with_compressed_size(compressed.len() as u64)
+        .with_crc32(0);
+    let err = read(decoder);
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::ChecksumMismatch)
+    ));
+
+    let decoder = bufread::Decoder::new(&compressed[..])
+        .with_compressed_size(compressed.len() as u64)
+        .with_uncompressed_size(5);
+    let err = read(decoder);
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::ChecksumMismatch)
+    ));
+
+    // The entry is longer than the compressed stream in it
+    let padded = [&compressed[..], &[0; 4]].concat();
+    let decoder = bufread::Decoder::new(&padded[..]).with_compressed_size(padded.len() as u64);
+    let err = read(decoder);
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::UnexpectedTrailingData)
+    ));
+
+    // The reader ends before the entry does
+    let decoder =
+        bufread::Decoder::new(&compressed[..3]).with_compressed_size(compressed.len() as u64);
+    assert_eq!(read(decoder).kind(), ErrorKind::UnexpectedEof);
+}
@@ -9,6 +9,9 @@ use utils::{algos::gzip::sync, InputStream};
 #[cfg(feature = "stream")]
 use utils::algos::gzip::stream;
 
+#[cfg(feature = "futures-stream")]
+use utils::algos::gzip::futures_stream;
+
 #[cfg(feature = "futures-io")]
 use utils::algos::gzip::futures::bufread;
 
@@ -164,3 +167,1879 @@ fn gzip_bufread_chunks_decompress_with_extra_header() {
 
     assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
 }
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_futures_stream_compress_flushes_when_pending() {
+    use futures::{channel::mpsc, FutureExt as _, StreamExt as _};
+
+    let (sender, receiver) = mpsc::unbounded();
+    let mut encoder = futures_stream::Encoder::new(receiver);
+
+    // While the input stream is pending the data so far is flushed so the receiver can
+    // decompress it without waiting for the rest
+    sender.unbounded_send(Ok(vec![1, 2, 3].into())).unwrap();
+    let mut compressed = encoder
+        .next()
+        .now_or_never()
+        .unwrap()
+        .unwrap()
+        .unwrap()
+        .to_vec();
+    assert!(!compressed.is_empty());
+    assert!(encoder.next().now_or_never().is_none());
+
+    sender.unbounded_send(Ok(vec![4, 5, 6].into())).unwrap();
+    drop(sender);
+    compressed.extend(futures_stream::to_vec(encoder));
+
+    assert_eq!(sync::decompress(&compressed), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufwrite_decompress_trailing_data() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::bufwrite, block_on, pin_mut};
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let decoder = bufwrite::Decoder::new(Vec::new());
+    pin_mut!(decoder);
+    bufwrite::write_all(decoder.as_mut(), &compressed);
+
+    let err = block_on(decoder.close()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(matches!(
+        async_compression::error::Error::from_io(&err),
+        Some(async_compression::error::Error::UnexpectedTrailingData)
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_lines() {
+    use futures::{io::AsyncBufReadExt as _, stream::TryStreamExt as _};
+    use utils::block_on;
+
+    let compressed = sync::compress(b"one\ntwo\nthree\n");
+
+    let input = InputStream::from(compressed.chunks(2));
+    let lines = bufread::Decoder::new(bufread::from(&input)).lines();
+    let lines: Vec<String> = block_on(lines.try_collect()).unwrap();
+
+    assert_eq!(lines, ["one", "two", "three"]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_compress_chained() {
+    use utils::algos::gzip::futures::read;
+
+    let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+    let encoder = bufread::Encoder::new(bufread::Encoder::new(bufread::from(&input)));
+    let output = read::to_vec(encoder);
+
+    assert_eq!(
+        sync::decompress(&sync::decompress(&output)),
+        &[1, 2, 3, 4, 5, 6][..]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_from_read_compress() {
+    use utils::algos::gzip::futures::read;
+
+    let encoder = bufread::Encoder::from_read(&[1, 2, 3, 4, 5, 6][..]);
+    let output = read::to_vec(encoder);
+
+    assert_eq!(sync::decompress(&output), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_bufread_from_read_decompress() {
+    use utils::algos::gzip::tokio::{bufread, read};
+
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let decoder = bufread::Decoder::from_read_with_capacity(2, &compressed[..]);
+    let output = read::to_vec(decoder);
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_decompress_vectored_trailing_data() {
+    use futures::io::AsyncWriteExt as _;
+    use std::io::IoSlice;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let (head, tail) = compressed.split_at(compressed.len() / 2);
+
+    let mut decoder = write::Decoder::new(Vec::new());
+    let bufs = [
+        IoSlice::new(head),
+        IoSlice::new(tail),
+        IoSlice::new(&[0; 8]),
+    ];
+
+    // Writing stops at the end of the gzip member
+    let written = block_on(decoder.write_vectored(&bufs)).unwrap();
+    assert_eq!(written, compressed.len());
+
+    block_on(decoder.close()).unwrap();
+    assert_eq!(decoder.into_inner(), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufwrite_compress_with_capacity() {
+    use utils::algos::gzip::futures::bufwrite;
+
+    let input = [vec![1, 2, 3], vec![4, 5, 6]];
+    let output = bufwrite::to_vec(
+        &input,
+        |output| Box::pin(bufwrite::Encoder::with_capacity(4, output)),
+        2,
+    );
+
+    assert_eq!(sync::decompress(&output), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_totals() {
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(decoder.total_in(), compressed.len() as u64);
+    assert_eq!(decoder.total_out(), 6);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_compress_totals() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let mut encoder = write::Encoder::new(Vec::new());
+    block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    assert_eq!(encoder.total_in(), 6);
+
+    block_on(encoder.close()).unwrap();
+    assert_eq!(encoder.total_out(), encoder.get_ref().len() as u64);
+    assert_eq!(sync::decompress(encoder.get_ref()), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_bufwrite_decompress_totals() {
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::bufwrite, block_on};
+
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let mut decoder = bufwrite::Decoder::new(Vec::new());
+    block_on(decoder.write_all(&compressed)).unwrap();
+    block_on(decoder.shutdown()).unwrap();
+
+    assert_eq!(decoder.get_ref(), &[1, 2, 3, 4, 5, 6]);
+    assert_eq!(decoder.total_in(), compressed.len() as u64);
+    assert_eq!(decoder.total_out(), 6);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_compress_stats() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let mut encoder = write::Encoder::new(Vec::new());
+    assert_eq!(encoder.stats().ratio(), None);
+
+    block_on(encoder.write_all(&[1, 2, 3])).unwrap();
+    block_on(encoder.flush()).unwrap();
+    block_on(encoder.write_all(&[4, 5, 6])).unwrap();
+    assert_eq!(encoder.stats().frames(), 0);
+
+    block_on(encoder.close()).unwrap();
+
+    let stats = encoder.stats();
+    assert_eq!(stats.total_in(), 6);
+    assert_eq!(stats.total_out(), encoder.get_ref().len() as u64);
+    assert_eq!(stats.ratio(), Some(encoder.get_ref().len() as f64 / 6.0));
+    assert_eq!(stats.flushes(), 1);
+    assert_eq!(stats.frames(), 1);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_multiple_members_stats() {
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3]);
+    compressed.extend(sync::compress(&[4, 5, 6]));
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    let stats = decoder.stats();
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(stats.total_in(), compressed.len() as u64);
+    assert_eq!(stats.total_out(), 6);
+    assert_eq!(stats.flushes(), 0);
+    assert_eq!(stats.frames(), 2);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_bufread_compress_codec_time() {
+    use utils::algos::gzip::tokio::{bufread, read};
+
+    let mut encoder = bufread::Encoder::new(&[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(encoder.stats().codec_time(), None);
+
+    encoder.record_codec_time(true);
+    let output = read::to_vec(&mut encoder);
+
+    assert_eq!(sync::decompress(&output), &[1, 2, 3, 4, 5, 6][..]);
+    assert!(encoder.stats().codec_time().is_some());
+
+    encoder.record_codec_time(false);
+    assert_eq!(encoder.stats().codec_time(), None);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_output_limit() {
+    use async_compression::error::{Error, Limit};
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let compressed = sync::compress(&[0; 1024]);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]).with_output_limit(1000);
+    let mut output = Vec::new();
+    let err = block_on(decoder.read_to_end(&mut output)).unwrap_err();
+    let exceeded = match Error::from_io(&err) {
+        Some(Error::LimitExceeded(exceeded)) => exceeded,
+        _ => panic!("unexpected error: {}", err),
+    };
+
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!((exceeded.limit(), exceeded.max()), (Limit::Output, 1000));
+    assert!(output.len() <= 1000);
+    assert_eq!(decoder.total_out(), 1000);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]).with_output_limit(1024);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, [0; 1024]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_decompress_output_limit() {
+    use async_compression::error::Error;
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::write, block_on};
+
+    let compressed = sync::compress(&[0; 1024]);
+
+    let mut decoder = write::Decoder::new(Vec::new()).with_output_limit(100);
+    let err = block_on(async {
+        decoder.write_all(&compressed).await?;
+        decoder.shutdown().await
+    })
+    .unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::LimitExceeded(_))
+    ));
+    assert!(decoder.get_ref().len() <= 100);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_input_limit() {
+    use async_compression::error::{Error, Limit};
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let limit = compressed.len() as u64;
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]).with_input_limit(limit - 1);
+    let err = block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err();
+    let exceeded = match Error::from_io(&err) {
+        Some(Error::LimitExceeded(exceeded)) => exceeded,
+        _ => panic!("unexpected error: {}", err),
+    };
+
+    assert_eq!(
+        (exceeded.limit(), exceeded.max()),
+        (Limit::Input, limit - 1)
+    );
+    assert_eq!(decoder.total_in(), limit - 1);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]).with_input_limit(limit);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_bufwrite_decompress_input_limit() {
+    use async_compression::error::Error;
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::bufwrite, block_on};
+
+    let compressed = sync::compress(&[0; 1024]);
+
+    let mut decoder = bufwrite::Decoder::new(Vec::new()).with_input_limit(10);
+    let err = block_on(async {
+        decoder.write_all(&compressed).await?;
+        decoder.shutdown().await
+    })
+    .unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::LimitExceeded(_))
+    ));
+    assert_eq!(decoder.total_in(), 10);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_checksum_mismatch() {
+    use async_compression::error::Error;
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let crc = compressed.len() - 8;
+    compressed[crc] ^= 0xff;
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    let err = block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::ChecksumMismatch)
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_corrupt_data() {
+    use async_compression::error::Error;
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed[0] = 0;
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    let err = block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err();
+
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(matches!(Error::from_io(&err), Some(Error::CorruptData(_))));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_decompress_trailing_data() {
+    use async_compression::error::Error;
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let mut decoder = write::Decoder::new(Vec::new());
+    let err = block_on(decoder.write_all(&compressed)).unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::UnexpectedTrailingData)
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_trailing_data_error() {
+    use async_compression::{error::Error, TrailingData};
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    decoder.trailing_data(TrailingData::Error);
+    let err = block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::UnexpectedTrailingData)
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_trailing_data_ignore() {
+    use async_compression::TrailingData;
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let input = InputStream::from(compressed.chunks(4));
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    decoder.trailing_data(TrailingData::Ignore);
+
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(decoder.total_in() as usize, compressed.len() - 8);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_trailing_data_stop() {
+    use async_compression::TrailingData;
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[7; 8]);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    decoder.trailing_data(TrailingData::Stop);
+
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(decoder.into_inner(), &[7; 8][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_decompress_trailing_data_ignore() {
+    use async_compression::TrailingData;
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let mut decoder = write::Decoder::new(Vec::new());
+    decoder.trailing_data(TrailingData::Ignore);
+    block_on(decoder.write_all(&compressed)).unwrap();
+    block_on(decoder.write_all(&[0; 8])).unwrap();
+    block_on(decoder.close()).unwrap();
+
+    assert_eq!(decoder.into_inner(), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_decompress_trailing_data_stop() {
+    use async_compression::TrailingData;
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let len = compressed.len();
+    compressed.extend_from_slice(&[0; 8]);
+
+    let mut decoder = write::Decoder::new(Vec::new());
+    decoder.trailing_data(TrailingData::Stop);
+    assert_eq!(block_on(decoder.write(&compressed)).unwrap(), len);
+    assert_eq!(block_on(decoder.write(&compressed[len..])).unwrap(), 0);
+    block_on(decoder.close()).unwrap();
+
+    assert_eq!(decoder.into_inner(), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_bufwrite_decompress_trailing_data_ignore() {
+    use async_compression::TrailingData;
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::bufwrite, block_on};
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let mut decoder = bufwrite::Decoder::new(Vec::new());
+    decoder.trailing_data(TrailingData::Ignore);
+    block_on(decoder.write_all(&compressed)).unwrap();
+    block_on(decoder.write_all(&[0; 8])).unwrap();
+    block_on(decoder.shutdown()).unwrap();
+
+    assert_eq!(decoder.into_inner(), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_futures_stream_decompress_trailing_data_error() {
+    use async_compression::{error::Error, TrailingData};
+    use futures::stream::TryStreamExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let input = InputStream::from(compressed.chunks(4));
+    let mut decoder = futures_stream::Decoder::new(input.bytes_stream());
+    decoder.trailing_data(TrailingData::Error);
+    let err = block_on(decoder.try_collect::<Vec<_>>()).unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::UnexpectedTrailingData)
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_futures_stream_decompress_into_inner_with_leftover() {
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[7; 8]);
+
+    let input = InputStream::from([compressed, vec![8; 4]]);
+    let mut decoder = futures_stream::Decoder::new(Box::pin(input.bytes_stream()));
+    let output = futures_stream::to_vec(&mut decoder);
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+
+    let (stream, leftover) = decoder.into_inner_with_leftover();
+    assert_eq!(leftover, &[7; 8][..]);
+    assert_eq!(futures_stream::to_vec(stream), &[8; 4][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_into_inner_with_leftover() {
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[7; 8]);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+
+    let (reader, leftover) = decoder.into_inner_with_leftover();
+    assert!(leftover.is_empty());
+    assert_eq!(reader, &[7; 8][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_futures_stream_decompress_bytes_consumed() {
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let len = compressed.len() as u64;
+
+    let mut input = compressed;
+    input.extend_from_slice(&[7; 8]);
+
+    let input = InputStream::from(input.chunks(5));
+    let mut decoder = futures_stream::Decoder::new(Box::pin(input.bytes_stream()));
+    let output = futures_stream::to_vec(&mut decoder);
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(decoder.bytes_consumed(), len);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_decompress_state() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let mut decoder = write::Decoder::new(Vec::new());
+    assert!(!decoder.in_header());
+
+    block_on(decoder.write_all(&compressed[..5])).unwrap();
+    assert!(decoder.in_header());
+    assert!(!decoder.is_done());
+    assert_eq!(decoder.member_count(), 0);
+
+    block_on(decoder.write_all(&compressed[5..])).unwrap();
+    block_on(decoder.close()).unwrap();
+    assert!(!decoder.in_header());
+    assert!(decoder.is_done());
+    assert_eq!(decoder.member_count(), 1);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_futures_stream_decompress_truncated_state() {
+    use futures::stream::StreamExt as _;
+    use utils::block_on;
+
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let input = InputStream::from([compressed[..compressed.len() - 4].to_vec()]);
+    let mut decoder = futures_stream::Decoder::new(Box::pin(input.bytes_stream()));
+    let results: Vec<_> = block_on(decoder.by_ref().collect());
+
+    assert!(results.last().unwrap().is_err());
+    assert!(!decoder.is_done());
+    assert_eq!(decoder.member_count(), 0);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_reset_with_reader() {
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let first = sync::compress(&[1, 2, 3]);
+    let second = sync::compress(&[4, 5, 6]);
+
+    let mut decoder = bufread::Decoder::new(&first[..]);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, &[1, 2, 3][..]);
+
+    decoder.reset_with_reader(&second[..]).unwrap();
+    assert!(!decoder.is_done());
+    assert_eq!(decoder.total_in(), 0);
+
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, &[4, 5, 6][..]);
+    assert_eq!(decoder.total_in(), second.len() as u64);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_decompress_reset_with_writer() {
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::write, block_on};
+
+    let mut decoder = write::Decoder::new(Vec::new());
+    block_on(decoder.write_all(&sync::compress(&[1, 2, 3]))).unwrap();
+    block_on(decoder.shutdown()).unwrap();
+
+    let first = decoder.reset_with_writer(Vec::new()).unwrap();
+    assert_eq!(first, &[1, 2, 3][..]);
+
+    block_on(decoder.write_all(&sync::compress(&[4, 5, 6]))).unwrap();
+    block_on(decoder.shutdown()).unwrap();
+    assert_eq!(decoder.into_inner(), &[4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_compress_finish() {
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::write, block_on, TrackClosed};
+
+    let mut encoder = write::Encoder::new(TrackClosed::new(Vec::new()));
+    block_on(encoder.write_all(&[1, 2, 3])).unwrap();
+    let writer = block_on(encoder.finish()).unwrap();
+
+    assert!(!writer.is_closed());
+    assert_eq!(sync::decompress(&writer.into_inner()), &[1, 2, 3][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_futures_bufwrite_compress_try_finish() {
+    use utils::{algos::gzip::futures::bufwrite, block_on, TrackClosed};
+
+    let mut encoder = bufwrite::Encoder::new(TrackClosed::new(Vec::new()));
+    block_on(futures::io::AsyncWriteExt::write_all(
+        &mut encoder,
+        &[1, 2, 3],
+    ))
+    .unwrap();
+    block_on(encoder.try_finish()).unwrap();
+
+    assert!(!encoder.get_ref().is_closed());
+    assert_eq!(
+        sync::decompress(&encoder.into_inner().into_inner()),
+        &[1, 2, 3][..]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_compress_without_shutdown_propagation() {
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::write, block_on, TrackClosed};
+
+    let mut encoder =
+        write::Encoder::new(TrackClosed::new(Vec::new())).with_shutdown_propagation(false);
+    block_on(encoder.write_all(&[1, 2, 3])).unwrap();
+    block_on(encoder.shutdown()).unwrap();
+
+    assert!(!encoder.get_ref().is_closed());
+    assert_eq!(
+        sync::decompress(&encoder.into_inner().into_inner()),
+        &[1, 2, 3][..]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_futures_write_decompress_without_close_propagation() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on, TrackClosed};
+
+    let mut decoder =
+        write::Decoder::new(TrackClosed::new(Vec::new())).with_close_propagation(false);
+    block_on(decoder.write_all(&sync::compress(&[1, 2, 3]))).unwrap();
+    block_on(decoder.close()).unwrap();
+
+    assert!(!decoder.get_ref().is_closed());
+    assert_eq!(decoder.into_inner().into_inner(), &[1, 2, 3][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_compress_without_codec_flush() {
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::write, block_on};
+
+    let mut flushed = write::Encoder::new(Vec::new()).with_codec_flush(false);
+    for chunk in [[1, 2, 3], [4, 5, 6]] {
+        block_on(flushed.write_all(&chunk)).unwrap();
+        block_on(flushed.flush()).unwrap();
+    }
+    block_on(flushed.shutdown()).unwrap();
+
+    let mut unflushed = write::Encoder::new(Vec::new());
+    block_on(unflushed.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    block_on(unflushed.shutdown()).unwrap();
+
+    assert_eq!(flushed.into_inner(), unflushed.into_inner());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_compress_auto_flush() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let mut encoder = write::Encoder::new(Vec::new()).with_auto_flush(4);
+
+    block_on(encoder.write_all(&[1, 2, 3])).unwrap();
+    assert_eq!(encoder.stats().flushes(), 0);
+
+    // Crossing the threshold flushes everything so far out to the underlying writer
+    block_on(encoder.write_all(&[4, 5, 6])).unwrap();
+    assert_eq!(encoder.stats().flushes(), 1);
+    assert_eq!(encoder.get_ref().len() as u64, encoder.total_out());
+
+    block_on(encoder.write_all(&[7])).unwrap();
+    block_on(encoder.close()).unwrap();
+
+    assert_eq!(encoder.stats().flushes(), 1);
+    assert_eq!(
+        sync::decompress(encoder.get_ref()),
+        &[1, 2, 3, 4, 5, 6, 7][..]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_compress_input_watermarks() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on};
+
+    let input: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+
+    let mut encoder = write::Encoder::with_capacity(1, Vec::new()).with_input_watermarks(100, 1000);
+    for chunk in input.chunks(50) {
+        block_on(encoder.write_all(chunk)).unwrap();
+        // The codec is handed the input from the high watermark down to the low one at a time
+        assert_eq!(encoder.total_in() % 900, 0);
+    }
+    assert!(encoder.total_in() < input.len() as u64);
+
+    block_on(encoder.close()).unwrap();
+    assert_eq!(encoder.total_in(), input.len() as u64);
+    assert_eq!(sync::decompress(encoder.get_ref()), input);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_compress_input_watermarks() {
+    use std::io::IoSlice;
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::write, block_on};
+
+    let mut encoder = write::Encoder::builder(Vec::new())
+        .input_watermarks(0, 4)
+        .build();
+
+    let len = block_on(encoder.write_vectored(&[IoSlice::new(&[1, 2]), IoSlice::new(&[3, 4, 5])]))
+        .unwrap();
+    assert_eq!(len, 4);
+    assert_eq!(encoder.total_in(), 0);
+
+    block_on(encoder.write_all(&[5, 6])).unwrap();
+    assert_eq!(encoder.total_in(), 4);
+
+    block_on(encoder.flush()).unwrap();
+    assert_eq!(encoder.total_in(), 6);
+    assert_eq!(encoder.get_ref().len() as u64, encoder.total_out());
+
+    block_on(encoder.shutdown()).unwrap();
+    assert_eq!(sync::decompress(encoder.get_ref()), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio-time")]
+fn gzip_tokio_bufread_compress_flush_timeout() {
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _, BufReader};
+    use utils::algos::gzip::tokio::bufread;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let (mut sender, receiver) = tokio::io::duplex(64);
+        let mut encoder = bufread::Encoder::new(BufReader::new(receiver))
+            .with_flush_timeout(Duration::from_secs(1));
+        sender.write_all(&[1, 2, 3]).await.unwrap();
+
+        // The input stalls after the first write, so it is flushed once the timeout has passed
+        let start = tokio::time::Instant::now();
+        let mut compressed = Vec::new();
+        while encoder.stats().flushes() == 0 {
+            let mut buf = [0; 1024];
+            let len = encoder.read(&mut buf).await.unwrap();
+            compressed.extend_from_slice(&buf[..len]);
+        }
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        drop(sender);
+        encoder.read_to_end(&mut compressed).await.unwrap();
+        assert_eq!(sync::decompress(&compressed), &[1, 2, 3][..]);
+    });
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_compress_flush_timeout() {
+    use futures::{
+        channel::mpsc,
+        io::AsyncReadExt as _,
+        sink::SinkExt as _,
+        stream::{StreamExt as _, TryStreamExt as _},
+    };
+    use std::time::Duration;
+    use utils::algos::gzip::futures::bufread;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let (mut sender, receiver) = mpsc::channel::<Vec<u8>>(1);
+        let reader = receiver.map(Ok::<_, std::io::Error>).into_async_read();
+        let mut encoder = bufread::Encoder::new(reader)
+            .with_flush_timeout(Duration::from_secs(1), tokio::time::sleep);
+        sender.send(vec![1, 2, 3]).await.unwrap();
+
+        // The input stalls after the first write, so it is flushed once the timeout has passed
+        let start = tokio::time::Instant::now();
+        let mut compressed = Vec::new();
+        while encoder.stats().flushes() == 0 {
+            let mut buf = [0; 1024];
+            let len = encoder.read(&mut buf).await.unwrap();
+            compressed.extend_from_slice(&buf[..len]);
+        }
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        drop(sender);
+        encoder.read_to_end(&mut compressed).await.unwrap();
+        assert_eq!(sync::decompress(&compressed), &[1, 2, 3][..]);
+    });
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_compress_set_level() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::futures::write, block_on, Level};
+
+    let input: Vec<u8> = (0..20_000u32)
+        .map(|i| (i % 251) as u8 ^ (i / 7) as u8)
+        .collect();
+
+    // A tiny buffer makes the switch happen over many calls with the output full
+    let mut encoder = write::Encoder::with_capacity(1, Vec::new());
+    block_on(encoder.write_all(&input[..10_000])).unwrap();
+    encoder.set_level(Level::Best).unwrap();
+    block_on(encoder.write_all(&input[10_000..])).unwrap();
+    encoder.set_level(Level::Fastest).unwrap();
+    block_on(encoder.close()).unwrap();
+
+    assert_eq!(sync::decompress(encoder.get_ref()), input);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_compress_builder() {
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{algos::gzip::tokio::write, block_on, Level, TrackClosed};
+
+    let mut encoder = write::Encoder::builder(TrackClosed::new(Vec::new()))
+        .level(Level::Best)
+        .buffer_capacity(1)
+        .auto_flush(3)
+        .shutdown_propagation(false)
+        .build();
+    block_on(encoder.write_all(&[1, 2, 3])).unwrap();
+    assert_eq!(encoder.stats().flushes(), 1);
+    block_on(encoder.shutdown()).unwrap();
+
+    assert!(!encoder.get_ref().is_closed());
+    let compressed = encoder.into_inner().into_inner();
+    // The level is recorded in the header
+    assert_eq!(compressed[8], 0x02);
+    assert_eq!(sync::decompress(&compressed), &[1, 2, 3][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_builder() {
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3]);
+    compressed.extend(sync::compress(&[4, 5, 6]));
+
+    let mut decoder = bufread::Decoder::builder(&compressed[..])
+        .multiple_members(true)
+        .output_limit(4)
+        .build();
+    let err = block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err();
+
+    assert!(matches!(
+        async_compression::error::Error::from_io(&err),
+        Some(async_compression::error::Error::LimitExceeded(_))
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_compress_copy() {
+    use async_compression::{
+        tokio::{compress_copy, decompress_copy},
+        Algorithm,
+    };
+    use utils::{block_on, Level, TrackClosed};
+
+    let mut compressed = TrackClosed::new(Vec::new());
+    let written = block_on(compress_copy(
+        &[1, 2, 3, 4, 5, 6][..],
+        &mut compressed,
+        Algorithm::Gzip,
+        Level::Fastest,
+    ))
+    .unwrap();
+
+    assert!(!compressed.is_closed());
+    let compressed = compressed.into_inner();
+    assert_eq!(written, compressed.len() as u64);
+    assert_eq!(sync::decompress(&compressed), &[1, 2, 3, 4, 5, 6][..]);
+
+    let mut output = Vec::new();
+    let written = block_on(decompress_copy(
+        &compressed[..],
+        &mut output,
+        Algorithm::Gzip,
+    ))
+    .unwrap();
+    assert_eq!(written, 6);
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_decompress_to_vec_errors() {
+    use async_compression::{error::Error, tokio::decompress_to_vec, Algorithm};
+    use utils::block_on;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+    let err = block_on(decompress_to_vec(&compressed, Algorithm::Gzip, 5)).unwrap_err();
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::LimitExceeded(_))
+    ));
+
+    compressed.push(0);
+    let err = block_on(decompress_to_vec(&compressed, Algorithm::Gzip, 6)).unwrap_err();
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::UnexpectedTrailingData)
+    ));
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "tokio-fs")]
+fn gzip_tokio_compress_file() {
+    use async_compression::{
+        tokio::fs::{compress_file, decompress_file},
+        Algorithm,
+    };
+    use flate2::read::GzDecoder;
+    use std::{fs, io::Read, time::UNIX_EPOCH};
+    use utils::Level;
+
+    let dir = std::env::temp_dir().join(format!("async-compression-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let src = dir.join("hello.txt");
+    let compressed = dir.join("hello.txt.gz");
+    let decompressed = dir.join("hello.out");
+    fs::write(&src, b"hello world").unwrap();
+    // An existing destination is replaced
+    fs::write(&compressed, b"stale").unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let written = runtime
+        .block_on(compress_file(
+            &src,
+            &compressed,
+            Algorithm::Gzip,
+            Level::Best,
+        ))
+        .unwrap();
+
+    let bytes = fs::read(&compressed).unwrap();
+    assert_eq!(written, bytes.len() as u64);
+
+    let mut decoder = GzDecoder::new(&bytes[..]);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).unwrap();
+    assert_eq!(output, b"hello world");
+    let header = decoder.header().unwrap();
+    assert_eq!(header.filename(), Some(&b"hello.txt"[..]));
+    let mtime = fs::metadata(&src).unwrap().modified().unwrap();
+    assert_eq!(
+        u64::from(header.mtime()),
+        mtime.duration_since(UNIX_EPOCH).unwrap().as_secs()
+    );
+
+    let written = runtime
+        .block_on(decompress_file(&compressed, &decompressed, Algorithm::Gzip))
+        .unwrap();
+    assert_eq!(written, 11);
+    assert_eq!(fs::read(&decompressed).unwrap(), b"hello world");
+
+    // Only the source and the two outputs are left, no temporary files
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(all(feature = "tokio", feature = "rayon"))]
+fn gzip_tokio_parallel_compress() {
+    use async_compression::{tokio::write::ParallelEncoder, Algorithm};
+    use flate2::read::MultiGzDecoder;
+    use std::io::Read;
+    use tokio::io::AsyncWriteExt as _;
+    use utils::{block_on, Level};
+
+    let bytes: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+
+    let mut encoder = ParallelEncoder::new(Vec::new(), Algorithm::Gzip, Level::Default, pool)
+        .unwrap()
+        .with_block_size(10_000)
+        .with_max_in_flight(3);
+    for chunk in bytes.chunks(3_000) {
+        block_on(encoder.write_all(chunk)).unwrap();
+    }
+    block_on(encoder.shutdown()).unwrap();
+    assert_eq!(encoder.total_in(), bytes.len() as u64);
+
+    let compressed = encoder.into_inner();
+    // Each block is its own member
+    assert_eq!(
+        compressed
+            .windows(3)
+            .filter(|w| w == &[0x1f, 0x8b, 0x08])
+            .count(),
+        10
+    );
+
+    let mut output = Vec::new();
+    MultiGzDecoder::new(&compressed[..])
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, bytes);
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(all(feature = "tokio-uring", target_os = "linux"))]
+fn gzip_tokio_uring_compress_file() {
+    use async_compression::{
+        tokio::fs::uring::{compress_file, decompress_file},
+        Algorithm,
+    };
+    use flate2::read::GzDecoder;
+    use std::{fs, io::Read};
+    use utils::Level;
+
+    let dir = std::env::temp_dir().join(format!("async-compression-uring-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let src = dir.join("hello.txt");
+    let compressed = dir.join("hello.txt.gz");
+    let decompressed = dir.join("hello.out");
+    let bytes: Vec<u8> = (0..300_000).map(|i| (i % 251) as u8).collect();
+    fs::write(&src, &bytes).unwrap();
+
+    tokio_uring_05::start(async {
+        let written = compress_file(&src, &compressed, Algorithm::Gzip, Level::Default)
+            .await
+            .unwrap();
+
+        let mut gzip = fs::read(&compressed).unwrap();
+        assert_eq!(written, gzip.len() as u64);
+        let mut decoder = GzDecoder::new(&gzip[..]);
+        let mut output = Vec::new();
+        decoder.read_to_end(&mut output).unwrap();
+        assert_eq!(output, bytes);
+        assert_eq!(
+            decoder.header().unwrap().filename(),
+            Some(&b"hello.txt"[..])
+        );
+
+        // Concatenated members are all decompressed
+        gzip.extend(sync::compress(&[1, 2, 3]));
+        fs::write(&compressed, &gzip).unwrap();
+        let written = decompress_file(&compressed, &decompressed, Algorithm::Gzip)
+            .await
+            .unwrap();
+        assert_eq!(written, bytes.len() as u64 + 3);
+        let output = fs::read(&decompressed).unwrap();
+        assert_eq!(&output[..bytes.len()], &bytes[..]);
+        assert_eq!(&output[bytes.len()..], &[1, 2, 3][..]);
+
+        // A truncated file fails, leaving no output behind
+        fs::write(&compressed, &gzip[..100]).unwrap();
+        fs::remove_file(&decompressed).unwrap();
+        assert!(decompress_file(&compressed, &decompressed, Algorithm::Gzip)
+            .await
+            .is_err());
+    });
+
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "std-io")]
+fn gzip_std_io_bufread_decompress_multiple_members() {
+    use async_compression::{error::Error, std_io::bufread, TrailingData};
+    use std::io::Read as _;
+
+    let mut compressed = sync::compress(&[1, 2, 3]);
+    compressed.extend(sync::compress(&[4, 5, 6]));
+
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output).unwrap();
+
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(decoder.member_count(), 2);
+    assert_eq!(decoder.total_in(), compressed.len() as u64);
+    assert!(decoder.is_done());
+
+    // Without multi-member decoding the second member is trailing data
+    let mut decoder = bufread::GzipDecoder::new(&compressed[..]);
+    decoder.trailing_data(TrailingData::Error);
+    let err = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::UnexpectedTrailingData)
+    ));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "std-io")]
+fn gzip_std_io_write_compress_flush() {
+    use async_compression::std_io::write;
+    use std::io::Write as _;
+
+    let mut encoder = write::GzipEncoder::new(Vec::new());
+    encoder.write_all(&[1, 2, 3]).unwrap();
+    encoder.flush().unwrap();
+
+    // Everything written before the flush can be decoded from what reached the writer
+    let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+    decoder.write_all(encoder.get_ref()).unwrap();
+    decoder.flush().unwrap();
+    assert_eq!(decoder.get_ref(), &[1, 2, 3][..]);
+
+    encoder.write_all(&[4, 5, 6]).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(sync::decompress(&compressed), &[1, 2, 3, 4, 5, 6][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "std-io")]
+fn gzip_std_io_write_decompress_trailing_data() {
+    use async_compression::{error::Error, std_io::write};
+    use std::io::Write as _;
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[0; 8]);
+
+    let mut decoder = write::GzipDecoder::new(Vec::new());
+    let err = decoder.write_all(&compressed).unwrap_err();
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::UnexpectedTrailingData)
+    ));
+
+    // An incomplete stream is an error once the input ends
+    let mut decoder = write::GzipDecoder::new(Vec::new());
+    decoder.write_all(&compressed[..10]).unwrap();
+    assert!(decoder.try_finish().is_err());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_compress_yield_budget() {
+    use core::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use futures::io::AsyncRead as _;
+    use futures_test::task::new_count_waker;
+
+    // Zeros compress to almost nothing, so without a budget the whole input would be consumed by
+    // a single poll of an always ready reader
+    let input = vec![0; 1024 * 1024];
+    let mut encoder = bufread::Encoder::new(&input[..]).with_yield_budget(16 * 1024);
+
+    let (waker, count) = new_count_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut buf = [0; 1024];
+    let mut output = Vec::new();
+    let mut pending = 0;
+
+    loop {
+        match Pin::new(&mut encoder).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(0)) => break,
+            Poll::Ready(Ok(len)) => output.extend_from_slice(&buf[..len]),
+            Poll::Ready(Err(err)) => panic!("unexpected error: {}", err),
+            Poll::Pending => pending += 1,
+        }
+    }
+
+    // Each time it yielded with no output the task was woken to be polled again
+    assert!(pending > 0);
+    assert_eq!(count.get(), pending);
+    assert_eq!(sync::decompress(&output), input);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_bufread_decompress_yield_budget() {
+    use core::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use futures_test::task::new_count_waker;
+    use tokio::io::{AsyncRead as _, ReadBuf};
+    use utils::algos::gzip::tokio::bufread;
+
+    let input = vec![0; 1024 * 1024];
+    let mut compressed = sync::compress(&input);
+    // Trailing data which is ignored produces no output, however much of it there is
+    compressed.extend(vec![0; 1024 * 1024]);
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]).with_yield_budget(16 * 1024);
+    decoder.trailing_data(async_compression::TrailingData::Ignore);
+
+    let (waker, count) = new_count_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut buf = [0; 1024];
+    let mut output = Vec::new();
+    let mut pending = 0;
+
+    loop {
+        let mut read_buf = ReadBuf::new(&mut buf);
+        match Pin::new(&mut decoder).poll_read(&mut cx, &mut read_buf) {
+            Poll::Ready(Ok(())) if read_buf.filled().is_empty() => break,
+            Poll::Ready(Ok(())) => output.extend_from_slice(read_buf.filled()),
+            Poll::Ready(Err(err)) => panic!("unexpected error: {}", err),
+            Poll::Pending => pending += 1,
+        }
+    }
+
+    assert!(pending >= 1024 / 16);
+    assert_eq!(count.get(), pending);
+    assert_eq!(output, input);
+}
+
+#[allow(unused)]
+fn members() -> Vec<u8> {
+    use flate2::{Compression, GzBuilder};
+    use std::io::Write;
+
+    let mut bytes = Vec::new();
+    for (filename, mtime, data) in [("a.log", 1_600_000_000, &b"first"[..]), ("b.log", 0, b"")] {
+        let mut gz = GzBuilder::new()
+            .filename(filename)
+            .mtime(mtime)
+            .extra(vec![1, 2, 3])
+            .write(&mut bytes, Compression::fast());
+        gz.write_all(data).unwrap();
+        gz.finish().unwrap();
+    }
+    bytes.extend(compress_with_header(&[1, 2, 3, 4, 5, 6]));
+
+    bytes
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_members() {
+    use async_compression::futures::bufread::GzipMembers;
+    use futures::{io::AsyncReadExt as _, stream::TryStreamExt as _};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let compressed = members();
+    let input = InputStream::from(compressed.chunks(7));
+    let mut members = GzipMembers::new(utils::impls::futures::bufread::from(&input));
+
+    let mut output = Vec::new();
+    while let Some(mut member) = utils::block_on(members.try_next()).unwrap() {
+        let mut data = Vec::new();
+        utils::block_on(member.read_to_end(&mut data)).unwrap();
+        output.push((member.header().clone(), data));
+    }
+
+    assert_eq!(output.len(), 3);
+
+    let (header, data) = &output[0];
+    assert_eq!(header.filename(), Some(&b"a.log"[..]));
+    assert_eq!(header.extra(), Some(&[1, 2, 3][..]));
+    assert_eq!(
+        header.mtime_as_system_time(),
+        Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+    );
+    assert_eq!(data, b"first");
+
+    let (header, data) = &output[1];
+    assert_eq!(header.filename(), Some(&b"b.log"[..]));
+    assert_eq!(header.mtime_as_system_time(), None);
+    assert_eq!(data, b"");
+
+    let (header, data) = &output[2];
+    assert_eq!(header.filename(), Some(&b"hello_world.txt"[..]));
+    assert_eq!(header.comment(), Some(&b"test file, please delete"[..]));
+    assert_eq!(header.extra(), None);
+    assert_eq!(data, &[1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_members_skipped() {
+    use async_compression::futures::bufread::GzipMembers;
+    use futures::{io::AsyncReadExt as _, stream::TryStreamExt as _};
+
+    let compressed = members();
+    let mut members = GzipMembers::new(&compressed[..]);
+
+    // The first member is skipped without being read, and can't be read after that
+    let mut first = utils::block_on(members.try_next()).unwrap().unwrap();
+    let mut second = utils::block_on(members.try_next()).unwrap().unwrap();
+    assert_eq!(second.header().filename(), Some(&b"b.log"[..]));
+    assert!(utils::block_on(first.read_to_end(&mut Vec::new())).is_err());
+
+    let mut data = Vec::new();
+    utils::block_on(second.read_to_end(&mut data)).unwrap();
+    assert_eq!(data, b"");
+
+    let third = utils::block_on(members.try_next()).unwrap().unwrap();
+    assert_eq!(third.header().filename(), Some(&b"hello_world.txt"[..]));
+    assert!(utils::block_on(members.try_next()).unwrap().is_none());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_bufread_members() {
+    use async_compression::tokio::bufread::GzipMembers;
+    use futures::stream::TryStreamExt as _;
+    use tokio::io::AsyncReadExt as _;
+
+    let compressed = members();
+    let mut members = GzipMembers::new(&compressed[..]);
+
+    let mut output = Vec::new();
+    while let Some(mut member) = utils::block_on(members.try_next()).unwrap() {
+        let mut data = Vec::new();
+        utils::block_on(member.read_to_end(&mut data)).unwrap();
+        output.push((member.header().filename().unwrap().to_vec(), data));
+    }
+
+    assert_eq!(
+        output,
+        [
+            (b"a.log".to_vec(), b"first".to_vec()),
+            (b"b.log".to_vec(), vec![]),
+            (b"hello_world.txt".to_vec(), vec![1, 2, 3, 4, 5, 6]),
+        ]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_stream_chunks() {
+    use async_compression::futures::stream::Chunks;
+    use futures::stream::TryStreamExt as _;
+
+    let input: Vec<u8> = (0..100_000).map(|_| rand::random()).collect();
+    let stream = InputStream::from(input.chunks(1000));
+    let chunks = Chunks::new(
+        bufread::Encoder::new(utils::impls::futures::bufread::from(&stream)),
+        4096,
+    );
+    let chunks: Vec<_> = utils::block_on(chunks.try_collect()).unwrap();
+
+    let (last, rest) = chunks.split_last().unwrap();
+    assert!(rest.iter().all(|chunk| chunk.len() == 4096));
+    assert!(!last.is_empty() && last.len() <= 4096);
+    assert_eq!(sync::decompress(&chunks.concat()), input);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_stream_chunks_exact() {
+    use async_compression::futures::stream::Chunks;
+    use futures::stream::TryStreamExt as _;
+
+    let chunks = Chunks::new(&[1, 2, 3, 4, 5, 6][..], 3);
+    let chunks: Vec<_> = utils::block_on(chunks.try_collect()).unwrap();
+    assert_eq!(chunks, [&[1, 2, 3][..], &[4, 5, 6][..]]);
+
+    let chunks = Chunks::new(&[][..], 3);
+    let chunks: Vec<bytes::Bytes> = utils::block_on(chunks.try_collect()).unwrap();
+    assert!(chunks.is_empty());
+}
+
+#[allow(unused)]
+fn decompress_members(compressed: &[u8]) -> Vec<u8> {
+    use std::io::Read;
+
+    let mut output = Vec::new();
+    flate2::read::MultiGzDecoder::new(compressed)
+        .read_to_end(&mut output)
+        .unwrap();
+    output
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_write_compress_checkpoint() {
+    use async_compression::{checkpoint::Checkpoint, futures::write::GzipEncoder};
+    use futures::io::AsyncWriteExt as _;
+
+    let input: Vec<u8> = (0..100_000).map(|_| rand::random()).collect();
+
+    let mut encoder = GzipEncoder::new(Vec::new());
+    utils::block_on(encoder.write_all(&input[..30_000])).unwrap();
+    let checkpoint = utils::block_on(encoder.checkpoint()).unwrap();
+    assert_eq!(checkpoint.total_in(), 30_000);
+    assert_eq!(checkpoint.total_out(), encoder.get_ref().len() as u64);
+    assert_eq!(encoder.stats().frames(), 1);
+
+    // Whatever is written after the checkpoint is lost
+    utils::block_on(encoder.write_all(&input[30_000..50_000])).unwrap();
+    utils::block_on(encoder.flush()).unwrap();
+    let mut output = encoder.into_inner();
+
+    let checkpoint = Checkpoint::from_bytes(&checkpoint.to_bytes()).unwrap();
+    output.truncate(checkpoint.total_out() as usize);
+    let mut encoder = GzipEncoder::new(output).with_checkpoint(&checkpoint);
+    utils::block_on(encoder.write_all(&input[checkpoint.total_in() as usize..])).unwrap();
+    utils::block_on(encoder.close()).unwrap();
+    assert_eq!(encoder.total_in(), input.len() as u64);
+    assert_eq!(encoder.total_out(), encoder.get_ref().len() as u64);
+
+    assert_eq!(decompress_members(encoder.get_ref()), input);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_bufread_decompress_checkpoint() {
+    use async_compression::futures::bufread::GzipDecoder;
+    use futures::io::AsyncReadExt as _;
+
+    let (first, second) = (&[1, 2, 3][..], &[4, 5, 6][..]);
+    let mut compressed = sync::compress(first);
+    let len = compressed.len();
+    compressed.extend(sync::compress(second));
+
+    let mut decoder = GzipDecoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    assert_eq!(decoder.checkpoint(), None);
+
+    let mut output = vec![0; 3];
+    utils::block_on(decoder.read_exact(&mut output)).unwrap();
+    // The end of the first member is only reached once more is read
+    assert_eq!(decoder.checkpoint(), None);
+    utils::block_on(decoder.read_exact(&mut [0])).unwrap();
+    let checkpoint = decoder.checkpoint().unwrap();
+    assert_eq!(checkpoint.total_in(), len as u64);
+    assert_eq!(checkpoint.total_out(), 3);
+
+    let mut decoder = GzipDecoder::new(&compressed[len..]).with_checkpoint(&checkpoint);
+    decoder.multiple_members(true);
+    utils::block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(decoder.total_in(), compressed.len() as u64);
+    assert_eq!(
+        decoder.checkpoint().unwrap().total_in(),
+        compressed.len() as u64
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn checkpoint_from_bytes_invalid() {
+    use async_compression::checkpoint::Checkpoint;
+    use std::io::ErrorKind;
+
+    let err = Checkpoint::from_bytes(&[0; 17]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = Checkpoint::from_bytes(&Checkpoint::default().to_bytes()[..16]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_futures_write_finish_on_drop_spawns() {
+    use async_compression::futures::write::{FinishOnDrop, GzipEncoder};
+    use futures::io::{AsyncWrite, AsyncWriteExt as _};
+    use std::{
+        pin::Pin,
+        sync::{mpsc, Arc, Mutex},
+        task::{Context, Poll},
+    };
+
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl AsyncWrite for Shared {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let (tasks, spawned) = mpsc::channel();
+    let mut encoder = FinishOnDrop::new(GzipEncoder::new(Shared(output.clone())))
+        .with_spawner(move |task| tasks.send(task).unwrap());
+    utils::block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    drop(encoder);
+
+    utils::block_on(spawned.recv().unwrap()).unwrap();
+    let output = output.lock().unwrap();
+    assert_eq!(
+        utils::algos::gzip::sync::decompress(&output),
+        [1, 2, 3, 4, 5, 6]
+    );
+}
+
+#[test]
+#[cfg(all(feature = "futures-io", debug_assertions))]
+#[should_panic(expected = "dropped without being closed")]
+fn gzip_futures_write_finish_on_drop_panics() {
+    use async_compression::futures::write::{FinishOnDrop, GzipEncoder};
+    use futures::io::AsyncWriteExt as _;
+
+    let mut encoder = FinishOnDrop::new(GzipEncoder::new(Vec::new()));
+    utils::block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    drop(encoder);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_futures_write_finish_on_drop_closed() {
+    use async_compression::futures::write::{FinishOnDrop, GzipEncoder};
+    use futures::io::AsyncWriteExt as _;
+
+    let mut encoder = FinishOnDrop::new(GzipEncoder::new(Vec::new()));
+    utils::block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    utils::block_on(encoder.close()).unwrap();
+    assert!(encoder.is_closed());
+    let output = encoder.get_ref().get_ref().clone();
+    drop(encoder);
+    assert_eq!(
+        utils::algos::gzip::sync::decompress(&output),
+        [1, 2, 3, 4, 5, 6]
+    );
+
+    // Taking the writer back out disarms the guard
+    let encoder = FinishOnDrop::new(GzipEncoder::new(Vec::new()));
+    drop(encoder.into_inner());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "tokio")]
+fn gzip_tokio_write_finish_on_drop() {
+    use async_compression::tokio::write::{FinishOnDrop, GzipEncoder};
+    use std::sync::mpsc;
+    use tokio::io::AsyncWriteExt as _;
+
+    let (tasks, spawned) = mpsc::channel();
+    let mut encoder = FinishOnDrop::new(GzipEncoder::new(Vec::new()))
+        .with_spawner(move |task| tasks.send(task).unwrap());
+    utils::block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    assert!(!encoder.is_shut_down());
+    drop(encoder);
+    utils::block_on(spawned.recv().unwrap()).unwrap();
+
+    let mut encoder = FinishOnDrop::new(GzipEncoder::new(Vec::new()));
+    utils::block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    utils::block_on(encoder.shutdown()).unwrap();
+    assert!(encoder.is_shut_down());
+    let output = encoder.into_inner().into_inner();
+    assert_eq!(
+        utils::algos::gzip::sync::decompress(&output),
+        [1, 2, 3, 4, 5, 6]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_checksum() {
+    use futures::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use std::convert::TryInto;
+    use utils::{
+        algos::gzip::futures::{bufread, write},
+        block_on, InputStream,
+    };
+
+    let input: Vec<u8> = (0..10_000).map(|_| rand::random()).collect();
+
+    let mut encoder = write::Encoder::new(Vec::new());
+    assert_eq!(encoder.checksum(), Some(0));
+    block_on(encoder.write_all(&input)).unwrap();
+    block_on(encoder.close()).unwrap();
+    let compressed = encoder.get_ref();
+
+    // The checksum is the CRC-32 stored in the footer
+    let crc = u32::from_le_bytes(compressed[compressed.len() - 8..][..4].try_into().unwrap());
+    assert_eq!(encoder.checksum(), Some(crc));
+
+    let input = InputStream::from(compressed.chunks(100));
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = vec![0; 5000];
+    block_on(decoder.read_exact(&mut output)).unwrap();
+    assert_ne!(decoder.checksum(), Some(crc));
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(decoder.checksum(), Some(crc));
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_stream_frames() {
+    use async_compression::{futures::stream::Frames, Algorithm};
+    use futures::stream::TryStreamExt as _;
+
+    let records: Vec<Vec<u8>> = vec![
+        (0..100_000).map(|_| rand::random()).collect(),
+        Vec::new(),
+        b"record".to_vec(),
+    ];
+    let compressed: Vec<u8> = records
+        .iter()
+        .flat_map(|record| sync::compress(record))
+        .collect();
+
+    let stream = InputStream::from(compressed.chunks(1000));
+    let frames = Frames::new(
+        utils::impls::futures::bufread::from(&stream),
+        Algorithm::Gzip,
+    );
+    let frames: Vec<_> = utils::block_on(frames.try_collect()).unwrap();
+    assert_eq!(frames, records);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-stream")]
+fn gzip_stream_frames_max_frame_size() {
+    use async_compression::{error::Error, futures::stream::Frames, Algorithm};
+    use futures::stream::TryStreamExt as _;
+
+    let compressed = [sync::compress(&[0; 100]), sync::compress(&[0; 10_000])].concat();
+    let mut frames = Frames::new(&compressed[..], Algorithm::Gzip).with_max_frame_size(1000);
+
+    assert_eq!(
+        utils::block_on(frames.try_next()).unwrap().unwrap().len(),
+        100
+    );
+    let err = utils::block_on(frames.try_next()).unwrap_err();
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::LimitExceeded(_))
+    ));
+    assert!(utils::block_on(frames.try_next()).unwrap().is_none());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn gzip_backend() {
+    use futures::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use utils::{
+        algos::gzip::futures::{bufread, write},
+        block_on,
+    };
+
+    let input: Vec<u8> = (0..10_000).map(|_| rand::random()).collect();
+
+    let mut encoder = write::Encoder::new(Vec::new());
+    block_on(encoder.write_all(&input)).unwrap();
+    block_on(encoder.close()).unwrap();
+    assert_eq!(encoder.backend().total_in(), input.len() as u64);
+
+    let compressed = encoder.into_inner();
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(decoder.backend().total_out(), input.len() as u64);
+    // The backend only sees the deflate data between the gzip header and footer
+    assert_eq!(
+        decoder.backend_mut().total_in(),
+        compressed.len() as u64 - 18
+    );
+}
@@ -0,0 +1,246 @@
+#[macro_use]
+mod utils;
+
+use async_compression::http_body::{CompressedBody, DecompressedBody};
+use bytes::Bytes;
+use futures::stream;
+use http::HeaderMap;
+use http_body_1::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use std::{convert::Infallible, io::Result};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+fn body(
+    chunks: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    trailers: Option<HeaderMap>,
+) -> StreamBody<impl futures::Stream<Item = std::result::Result<Frame<Bytes>, Infallible>>> {
+    let frames = chunks
+        .into_iter()
+        .map(|chunk| Frame::data(Bytes::copy_from_slice(chunk.as_ref())))
+        .chain(trailers.map(Frame::trailers))
+        .map(Ok);
+    StreamBody::new(stream::iter(frames.collect::<Vec<_>>()))
+}
+
+fn trailers() -> HeaderMap {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("x-checksum", "abc".parse().unwrap());
+    trailers
+}
+
+fn collect<B>(body: B) -> (Vec<u8>, Option<HeaderMap>)
+where
+    B: http_body_1::Body<Data = Bytes, Error = BoxError>,
+{
+    let collected = utils::block_on(body.collect()).unwrap();
+    let trailers = collected.trailers().cloned();
+    (collected.to_bytes().to_vec(), trailers)
+}
+
+macro_rules! round_trips {
+    ($($variant:ident($feat:literal, $algorithm:ident))*) => {
+        $(
+            #[cfg(feature = $feat)]
+            mod $variant {
+                use async_compression::{
+                    http_body::{CompressedBody, DecompressedBody},
+                    Algorithm, Level,
+                };
+                use crate::utils::{algos::$variant::sync, one_to_six};
+                use super::{body, collect};
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn compress() {
+                    let (compressed, trailers) = collect(CompressedBody::with_quality(
+                        body([[1, 2, 3], [4, 5, 6]], None),
+                        Algorithm::$algorithm,
+                        Level::Fastest,
+                    ));
+
+                    assert_eq!(sync::decompress(&compressed), one_to_six());
+                    assert_eq!(trailers, None);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn decompress() {
+                    let compressed = sync::compress(one_to_six());
+
+                    let (output, trailers) = collect(DecompressedBody::new(
+                        body(compressed.chunks(2), None),
+                        Algorithm::$algorithm,
+                    ));
+
+                    assert_eq!(output, one_to_six());
+                    assert_eq!(trailers, None);
+                }
+            }
+        )*
+    }
+}
+
+round_trips! {
+    brotli("brotli", Brotli)
+    bzip2("bzip2", Bzip2)
+    deflate("deflate", Deflate)
+    gzip("gzip", Gzip)
+    lz4("lz4", Lz4)
+    lzma("lzma", Lzma)
+    snappy("snappy", Snappy)
+    xz("xz", Xz)
+    zlib("zlib", Zlib)
+    zstd("zstd", Zstd)
+}
+
+#[cfg(feature = "gzip")]
+mod gzip_body {
+    use super::{body, collect, trailers};
+    use crate::utils::{algos::gzip::sync, one_to_six};
+    use async_compression::{
+        http_body::{CompressedBody, DecompressedBody},
+        Algorithm,
+    };
+    use bytes::Bytes;
+    use futures::{channel::mpsc, FutureExt};
+    use http_body_1::Frame;
+    use http_body_util::{BodyExt, StreamBody};
+    use std::convert::Infallible;
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn compress_trailers() {
+        let (compressed, output_trailers) = collect(CompressedBody::new(
+            body([[1, 2, 3], [4, 5, 6]], Some(trailers())),
+            Algorithm::Gzip,
+        ));
+
+        assert_eq!(sync::decompress(&compressed), one_to_six());
+        assert_eq!(output_trailers, Some(trailers()));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn decompress_trailers() {
+        let compressed = sync::compress(one_to_six());
+
+        let (output, output_trailers) = collect(DecompressedBody::new(
+            body(compressed.chunks(2), Some(trailers())),
+            Algorithm::Gzip,
+        ));
+
+        assert_eq!(output, one_to_six());
+        assert_eq!(output_trailers, Some(trailers()));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn decompress_trailing_data() {
+        let mut compressed = sync::compress(one_to_six());
+        compressed.extend_from_slice(&[7, 8, 9]);
+
+        let (output, output_trailers) = collect(DecompressedBody::new(
+            body(compressed.chunks(2), Some(trailers())),
+            Algorithm::Gzip,
+        ));
+
+        assert_eq!(output, one_to_six());
+        assert_eq!(output_trailers, Some(trailers()));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn decompress_truncated() {
+        let compressed = sync::compress(one_to_six());
+
+        let body = DecompressedBody::new(
+            body(compressed[..compressed.len() - 2].chunks(2), None),
+            Algorithm::Gzip,
+        );
+
+        assert!(crate::utils::block_on(body.collect()).is_err());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn decompress_output_limit() {
+        use async_compression::error::Error;
+
+        let compressed = sync::compress(&[0; 1024]);
+
+        let body = DecompressedBody::new(body(compressed.chunks(2), None), Algorithm::Gzip)
+            .with_output_limit(512);
+        let err = crate::utils::block_on(body.collect()).unwrap_err();
+        let err = err.downcast_ref::<std::io::Error>().unwrap();
+
+        assert!(matches!(Error::from_io(err), Some(Error::LimitExceeded(_))));
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn compress_flushes_when_pending() {
+        let (sender, receiver) = mpsc::unbounded::<Result<Frame<Bytes>, Infallible>>();
+        let mut body = CompressedBody::new(StreamBody::new(receiver), Algorithm::Gzip);
+
+        sender
+            .unbounded_send(Ok(Frame::data(Bytes::from_static(&[1, 2, 3]))))
+            .unwrap();
+        let frame = body.frame().now_or_never().unwrap().unwrap().unwrap();
+        let mut compressed = frame.into_data().unwrap().to_vec();
+        assert!(!compressed.is_empty());
+
+        sender
+            .unbounded_send(Ok(Frame::data(Bytes::from_static(&[4, 5, 6]))))
+            .unwrap();
+        drop(sender);
+        let (rest, _) = collect(body);
+        compressed.extend_from_slice(&rest);
+
+        assert_eq!(sync::decompress(&compressed), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn accept_encoding() {
+        let (encoding, body) =
+            CompressedBody::from_accept_encoding(body([[1, 2, 3], [4, 5, 6]], None), "gzip")
+                .unwrap();
+        let (compressed, _) = collect(body);
+
+        assert_eq!(encoding, "gzip");
+        assert_eq!(sync::decompress(&compressed), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn content_encoding() {
+        let compressed = sync::compress(one_to_six());
+
+        let body =
+            DecompressedBody::from_content_encoding(body(compressed.chunks(2), None), "x-gzip")
+                .unwrap();
+
+        assert_eq!(collect(body).0, one_to_six());
+    }
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn accept_encoding_identity() {
+    let (encoding, body) =
+        CompressedBody::from_accept_encoding(body([[1, 2, 3], [4, 5, 6]], None), "identity")
+            .unwrap();
+
+    assert_eq!(encoding, "identity");
+    assert_eq!(collect(body).0, utils::one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn content_encoding_unsupported() {
+    let result: Result<_> =
+        DecompressedBody::from_content_encoding(body([[1, 2, 3]], None), "unknown").map(|_| ());
+
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+}
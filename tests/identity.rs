@@ -0,0 +1,74 @@
+#[macro_use]
+mod utils;
+
+use async_compression::futures::{bufread, write};
+use utils::{
+    impls::futures::{bufread::from, read, write::to_vec},
+    one_to_six, InputStream,
+};
+
+#[test]
+#[ntest::timeout(1000)]
+fn bufread_encode() {
+    let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+
+    assert_eq!(
+        read::to_vec(bufread::IdentityEncoder::new(from(&input))),
+        one_to_six()
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn bufread_decode() {
+    let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+
+    assert_eq!(
+        read::to_vec(bufread::IdentityDecoder::new(from(&input))),
+        one_to_six()
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn bufread_decode_empty() {
+    let input = InputStream::new(vec![]);
+
+    assert!(read::to_vec(bufread::IdentityDecoder::new(from(&input))).is_empty());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn bufread_decode_long() {
+    let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+    let input = InputStream::from(bytes.chunks(1024));
+
+    assert_eq!(
+        read::to_vec(bufread::IdentityDecoder::new(from(&input))),
+        bytes
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn write_encode() {
+    let output = to_vec(
+        &[vec![1, 2, 3], vec![4, 5, 6]],
+        |output| Box::pin(write::IdentityEncoder::new(output)),
+        2,
+    );
+
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn write_decode() {
+    let output = to_vec(
+        &[vec![1, 2, 3], vec![4, 5, 6]],
+        |output| Box::pin(write::IdentityDecoder::new(output)),
+        2,
+    );
+
+    assert_eq!(output, one_to_six());
+}
@@ -0,0 +1,92 @@
+#[macro_use]
+mod utils;
+
+test_cases!(isal_deflate, reads_ahead);
+test_cases!(isal_gzip, reads_ahead);
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn isal_gzip_bufread_compatible_with_flate2() {
+    use std::io::Read as _;
+    use utils::{
+        algos::isal_gzip::futures::{bufread, read},
+        Level,
+    };
+
+    let input: Vec<u8> = (0..100_000u32).map(|i| (i % 251 * i % 7) as u8).collect();
+
+    for level in [
+        Level::Fastest,
+        Level::Default,
+        Level::Best,
+        Level::Precise(2),
+    ] {
+        let compressed = read::to_vec(bufread::Encoder::with_quality(&input[..], level));
+        let mut output = Vec::new();
+        flate2::read::GzDecoder::new(&compressed[..])
+            .read_to_end(&mut output)
+            .unwrap();
+        assert_eq!(output, input);
+    }
+
+    let mut compressed = Vec::new();
+    flate2::read::GzEncoder::new(&input[..], flate2::Compression::best())
+        .read_to_end(&mut compressed)
+        .unwrap();
+    assert_eq!(read::to_vec(bufread::Decoder::new(&compressed[..])), input);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn isal_deflate_write_compress_flush() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::isal_deflate::futures::write, block_on, Level};
+
+    let mut encoder = write::Encoder::with_quality(Vec::new(), Level::Best);
+    block_on(encoder.write_all(&[1, 2, 3, 4, 5, 6])).unwrap();
+    block_on(encoder.flush()).unwrap();
+
+    // Everything written so far can be decoded without the end of the stream
+    let mut decompress = flate2::Decompress::new(false);
+    let mut output = [0; 16];
+    decompress
+        .decompress(
+            encoder.get_ref(),
+            &mut output,
+            flate2::FlushDecompress::Sync,
+        )
+        .unwrap();
+    assert_eq!(
+        &output[..decompress.total_out() as usize],
+        &[1, 2, 3, 4, 5, 6]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn isal_deflate_bufread_decompress_into_inner_with_leftover() {
+    use futures::io::AsyncReadExt as _;
+    use utils::{
+        algos::isal_deflate::{futures::bufread, sync},
+        block_on,
+    };
+
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    compressed.extend_from_slice(&[7, 8, 9, 10]);
+
+    // Reading a byte at a time leaves the decoder with the output still to write after it has
+    // read all of the input
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    let mut byte = [0];
+    while block_on(decoder.read(&mut byte)).unwrap() == 1 {
+        output.push(byte[0]);
+    }
+    assert_eq!(output, &[1, 2, 3, 4, 5, 6][..]);
+
+    let (reader, leftover) = decoder.into_inner_with_leftover();
+    assert_eq!([&leftover[..], reader].concat(), &[7, 8, 9, 10][..]);
+}
@@ -0,0 +1,95 @@
+#[macro_use]
+mod utils;
+
+use async_compression::{
+    error::{Error, Limit},
+    libdeflate::{compress, decompress, Format},
+    Level,
+};
+use utils::algos::{deflate, gzip, zlib};
+
+const LIMIT: usize = 1024 * 1024;
+
+fn random(len: usize) -> Vec<u8> {
+    (0..len).map(|_| rand::random()).collect()
+}
+
+#[test]
+fn roundtrip() {
+    for format in [Format::Deflate, Format::Gzip, Format::Zlib] {
+        for input in [Vec::new(), vec![1, 2, 3, 4, 5, 6], random(100_000)] {
+            for level in [
+                Level::Fastest,
+                Level::Default,
+                Level::Best,
+                Level::Precise(0),
+            ] {
+                let compressed = compress(format, level, &input).unwrap();
+                assert_eq!(decompress(format, &compressed, LIMIT).unwrap(), input);
+            }
+        }
+    }
+}
+
+#[test]
+fn compatible_with_flate2() {
+    let input = random(100_000);
+
+    let compressed = compress(Format::Deflate, Level::Default, &input).unwrap();
+    assert_eq!(deflate::sync::decompress(&compressed), input);
+    let compressed = deflate::sync::compress(&input);
+    assert_eq!(
+        decompress(Format::Deflate, &compressed, LIMIT).unwrap(),
+        input
+    );
+
+    let compressed = compress(Format::Gzip, Level::Default, &input).unwrap();
+    assert_eq!(gzip::sync::decompress(&compressed), input);
+    let compressed = gzip::sync::compress(&input);
+    assert_eq!(decompress(Format::Gzip, &compressed, LIMIT).unwrap(), input);
+
+    let compressed = compress(Format::Zlib, Level::Default, &input).unwrap();
+    assert_eq!(zlib::sync::decompress(&compressed), input);
+    let compressed = zlib::sync::compress(&input);
+    assert_eq!(decompress(Format::Zlib, &compressed, LIMIT).unwrap(), input);
+}
+
+#[test]
+fn decompress_grows_output() {
+    // Compresses far better than the initial guess of the output size
+    let input = vec![0; 500_000];
+
+    for format in [Format::Deflate, Format::Zlib] {
+        let compressed = compress(format, Level::Default, &input).unwrap();
+        assert_eq!(decompress(format, &compressed, LIMIT).unwrap(), input);
+    }
+}
+
+#[test]
+fn decompress_limit() {
+    let input = vec![0; 100_000];
+
+    for format in [Format::Deflate, Format::Gzip, Format::Zlib] {
+        let compressed = compress(format, Level::Default, &input).unwrap();
+        let err = decompress(format, &compressed, 99_999).unwrap_err();
+        match Error::from_io(&err) {
+            Some(Error::LimitExceeded(exceeded)) => {
+                assert_eq!(exceeded.limit(), Limit::Output);
+                assert_eq!(exceeded.max(), 99_999);
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+
+        assert_eq!(decompress(format, &compressed, 100_000).unwrap(), input);
+    }
+}
+
+#[test]
+fn decompress_corrupt() {
+    for format in [Format::Deflate, Format::Gzip, Format::Zlib] {
+        let mut compressed = compress(format, Level::Default, &random(1000)).unwrap();
+        compressed.truncate(compressed.len() / 2);
+        let err = decompress(format, &compressed, LIMIT).unwrap_err();
+        assert!(matches!(Error::from_io(&err), Some(Error::CorruptData(_))));
+    }
+}
@@ -0,0 +1,76 @@
+#[macro_use]
+mod utils;
+
+test_cases!(lz4);
+
+#[allow(unused)]
+use utils::{one_to_six, InputStream};
+
+#[cfg(feature = "futures-io")]
+use utils::algos::lz4::futures::{bufread, read};
+
+#[cfg(feature = "futures-io")]
+use async_compression::futures::bufread::{Lz4BlockDecoder, Lz4BlockEncoder};
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn lz4_block_compress() {
+    let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+    let compressed = read::to_vec(Lz4BlockEncoder::new(bufread::from(&input)));
+
+    let output = liblz4::block::decompress(&compressed, Some(6)).unwrap();
+
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn lz4_block_decompress() {
+    let compressed = liblz4::block::compress(one_to_six(), None, false).unwrap();
+    let input = InputStream::from(compressed.chunks(2));
+    let output = read::to_vec(Lz4BlockDecoder::new(bufread::from(&input)));
+
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn lz4_block_compress_exceeds_block_size() {
+    use futures::io::AsyncReadExt as _;
+
+    let input = InputStream::from([vec![0; 16]]);
+    let mut encoder = Lz4BlockEncoder::with_quality_and_block_size(
+        bufread::from(&input),
+        async_compression::Level::Default,
+        8,
+    );
+
+    let mut output = Vec::new();
+    let result = futures::executor::block_on(encoder.read_to_end(&mut output));
+
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn lz4_block_with_block_size() {
+    let data = vec![7; 100_000];
+    let input = InputStream::from(data.chunks(1024));
+    let compressed = read::to_vec(Lz4BlockEncoder::with_quality_and_block_size(
+        bufread::from(&input),
+        async_compression::Level::Best,
+        data.len(),
+    ));
+
+    let input = InputStream::from(compressed.chunks(1024));
+    let output = read::to_vec(Lz4BlockDecoder::with_block_size(
+        bufread::from(&input),
+        data.len(),
+    ));
+
+    assert_eq!(output, data);
+}
@@ -0,0 +1,88 @@
+#[macro_use]
+mod utils;
+
+use async_compression::{
+    owned::{OwnedBufDecoder, OwnedBufEncoder},
+    Algorithm, Level,
+};
+use utils::algos::{gzip, zstd};
+
+fn random(len: usize) -> Vec<u8> {
+    (0..len).map(|_| rand::random()).collect()
+}
+
+#[test]
+fn compress() {
+    let input = random(100_000);
+
+    let mut encoder = OwnedBufEncoder::new(Algorithm::Gzip, Level::Default);
+    let mut compressed = Vec::new();
+    let mut output = Vec::with_capacity(1024);
+    for chunk in input.chunks(1000).map(<[u8]>::to_vec) {
+        let (result, chunk) = encoder.encode(chunk, output);
+        assert_eq!(chunk.len(), 1000);
+        output = result.unwrap();
+        compressed.extend_from_slice(&output);
+        output.clear();
+    }
+    compressed.extend_from_slice(&encoder.finish(output).unwrap());
+
+    assert_eq!(encoder.total_in(), input.len() as u64);
+    assert_eq!(encoder.total_out(), compressed.len() as u64);
+    assert_eq!(gzip::sync::decompress(&compressed), input);
+
+    let (result, _) = encoder.encode(vec![1], Vec::new());
+    assert!(result.is_err());
+}
+
+#[test]
+fn compress_flush() {
+    let mut encoder = OwnedBufEncoder::new(Algorithm::Zstd, Level::Default);
+    let (result, _) = encoder.encode(&[1, 2, 3][..], Vec::new());
+    let output = encoder.flush(result.unwrap()).unwrap();
+
+    // The flushed data decodes on its own
+    let mut decoder = OwnedBufDecoder::new(Algorithm::Zstd);
+    let (result, _) = decoder.decode(output, Vec::new());
+    assert_eq!(result.unwrap(), [1, 2, 3]);
+    assert!(!decoder.is_done());
+}
+
+#[test]
+fn decompress() {
+    let input = random(100_000);
+    let compressed = zstd::sync::compress(&input);
+
+    let mut decoder = OwnedBufDecoder::new(Algorithm::Zstd);
+    let mut output = Vec::new();
+    for chunk in compressed.chunks(1000).map(<[u8]>::to_vec) {
+        let (result, _) = decoder.decode(chunk, output);
+        output = result.unwrap();
+    }
+    let output = decoder.finish(output).unwrap();
+
+    assert!(decoder.is_done());
+    assert_eq!(decoder.total_in(), compressed.len() as u64);
+    assert_eq!(output, input);
+}
+
+#[test]
+fn decompress_trailing_data() {
+    let compressed = [&gzip::sync::compress(&[1, 2, 3])[..], b"trailing"].concat();
+
+    let mut decoder = OwnedBufDecoder::new(Algorithm::Gzip);
+    let (result, compressed) = decoder.decode(compressed, Vec::new());
+    assert_eq!(result.unwrap(), [1, 2, 3]);
+    assert!(decoder.is_done());
+    assert_eq!(&compressed[decoder.total_in() as usize..], b"trailing");
+}
+
+#[test]
+fn decompress_truncated() {
+    let compressed = gzip::sync::compress(&random(1000));
+
+    let mut decoder = OwnedBufDecoder::new(Algorithm::Gzip);
+    let (result, _) = decoder.decode(&compressed[..compressed.len() - 4], Vec::new());
+    let output = result.unwrap();
+    assert!(decoder.finish(output).is_err());
+}
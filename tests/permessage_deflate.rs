@@ -0,0 +1,234 @@
+#[macro_use]
+mod utils;
+
+use async_compression::{
+    permessage_deflate::{Params, PerMessageDeflate, Role},
+    Level,
+};
+use std::io::ErrorKind;
+use utils::one_to_six;
+
+// Examples from section 7.2.3 of RFC 7692
+const HELLO: &[u8] = &[0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00];
+const HELLO_AGAIN: &[u8] = &[0xf2, 0x00, 0x11, 0x00, 0x00];
+const HELLO_STORED: &[u8] = &[
+    0x00, 0x05, 0x00, 0xfa, 0xff, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x00,
+];
+const HELLO_FINAL: &[u8] = &[0xf3, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x00];
+
+fn pair(params: &Params) -> (PerMessageDeflate, PerMessageDeflate) {
+    (
+        PerMessageDeflate::new(Role::Server, params).unwrap(),
+        PerMessageDeflate::new(Role::Client, params).unwrap(),
+    )
+}
+
+#[test]
+fn compress_hello() {
+    let mut server = PerMessageDeflate::new(Role::Server, &Params::default()).unwrap();
+
+    assert_eq!(server.compress(b"Hello").unwrap(), HELLO);
+}
+
+#[test]
+fn decompress_hello() {
+    let mut client = PerMessageDeflate::new(Role::Client, &Params::default()).unwrap();
+
+    assert_eq!(client.decompress(HELLO).unwrap(), b"Hello");
+}
+
+#[test]
+fn decompress_hello_with_context_takeover() {
+    let mut client = PerMessageDeflate::new(Role::Client, &Params::default()).unwrap();
+
+    assert_eq!(client.decompress(HELLO).unwrap(), b"Hello");
+    assert_eq!(client.decompress(HELLO_AGAIN).unwrap(), b"Hello");
+}
+
+#[test]
+fn decompress_stored_block() {
+    let mut client = PerMessageDeflate::new(Role::Client, &Params::default()).unwrap();
+
+    assert_eq!(client.decompress(HELLO_STORED).unwrap(), b"Hello");
+}
+
+#[test]
+fn decompress_final_block() {
+    let mut client = PerMessageDeflate::new(Role::Client, &Params::default()).unwrap();
+
+    assert_eq!(client.decompress(HELLO_FINAL).unwrap(), b"Hello");
+    // The final block ended the context, so the next message starts afresh
+    assert_eq!(client.decompress(HELLO).unwrap(), b"Hello");
+}
+
+#[test]
+fn decompress_data_after_final_block() {
+    let mut client = PerMessageDeflate::new(Role::Client, &Params::default()).unwrap();
+
+    let mut message = HELLO_FINAL.to_vec();
+    message.extend_from_slice(HELLO);
+
+    assert_eq!(client.decompress(&message).unwrap(), b"Hello");
+    assert_eq!(client.decompress(HELLO).unwrap(), b"Hello");
+}
+
+#[test]
+fn compress_empty() {
+    let (mut server, mut client) = pair(&Params::default());
+
+    assert_eq!(server.compress(&[]).unwrap(), [0x00]);
+    assert_eq!(client.decompress(&[0x00]).unwrap(), []);
+}
+
+#[test]
+fn decompress_invalid_data() {
+    let mut client = PerMessageDeflate::new(Role::Client, &Params::default()).unwrap();
+
+    assert!(client.decompress(&[0xff; 8]).is_err());
+}
+
+#[test]
+fn round_trip() {
+    let (mut server, mut client) = pair(&Params::default());
+    let input = one_to_six();
+
+    for message in [input, &[], b"Hello", input] {
+        let compressed = server.compress(message).unwrap();
+        assert_eq!(client.decompress(&compressed).unwrap(), message);
+
+        let compressed = client.compress(message).unwrap();
+        assert_eq!(server.decompress(&compressed).unwrap(), message);
+    }
+}
+
+#[test]
+fn round_trip_long() {
+    let (mut server, mut client) = pair(&Params::default());
+    let input = vec![7; 100_000];
+
+    let compressed = server.compress(&input).unwrap();
+    assert_eq!(client.decompress(&compressed).unwrap(), input);
+}
+
+#[test]
+fn round_trip_with_quality() {
+    let params = Params::default();
+    let mut server = PerMessageDeflate::with_quality(Role::Server, &params, Level::Best).unwrap();
+    let mut client = PerMessageDeflate::new(Role::Client, &params).unwrap();
+    let input = one_to_six();
+
+    let compressed = server.compress(input).unwrap();
+    assert_eq!(client.decompress(&compressed).unwrap(), input);
+}
+
+#[test]
+fn context_takeover() {
+    let (mut server, mut client) = pair(&Params::default());
+    let input = one_to_six();
+
+    let first = server.compress(input).unwrap();
+    let second = server.compress(input).unwrap();
+    assert!(second.len() < first.len());
+
+    assert_eq!(client.decompress(&first).unwrap(), input);
+    assert_eq!(client.decompress(&second).unwrap(), input);
+}
+
+#[test]
+fn no_context_takeover() {
+    let params = Params {
+        server_no_context_takeover: true,
+        ..Params::default()
+    };
+    let (mut server, mut client) = pair(&params);
+    let input = one_to_six();
+
+    let first = server.compress(input).unwrap();
+    let second = server.compress(input).unwrap();
+    assert_eq!(first, second);
+
+    assert_eq!(client.decompress(&first).unwrap(), input);
+    assert_eq!(client.decompress(&second).unwrap(), input);
+
+    // Only the server direction was negotiated without context takeover
+    let first = client.compress(input).unwrap();
+    let second = client.compress(input).unwrap();
+    assert!(second.len() < first.len());
+}
+
+#[test]
+fn parse_params() {
+    let params: Params = "permessage-deflate; server_no_context_takeover; \
+                          client_max_window_bits; server_max_window_bits=\"10\""
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        params,
+        Params {
+            server_no_context_takeover: true,
+            client_no_context_takeover: false,
+            server_max_window_bits: Some(10),
+            client_max_window_bits: Some(15),
+        }
+    );
+}
+
+#[test]
+fn parse_params_empty() {
+    let params: Params = "Permessage-Deflate".parse().unwrap();
+
+    assert_eq!(params, Params::default());
+}
+
+#[test]
+fn parse_params_invalid() {
+    for extension in [
+        "",
+        "x-webkit-deflate-frame",
+        "permessage-deflate; unknown",
+        "permessage-deflate; server_no_context_takeover=1",
+        "permessage-deflate; client_no_context_takeover; client_no_context_takeover",
+        "permessage-deflate; server_max_window_bits",
+        "permessage-deflate; server_max_window_bits=7",
+        "permessage-deflate; client_max_window_bits=16",
+        "permessage-deflate; client_max_window_bits=ten",
+        "permessage-deflate; client_max_window_bits=9; client_max_window_bits=10",
+    ] {
+        let err = extension.parse::<Params>().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{}", extension);
+    }
+}
+
+#[test]
+fn display_params() {
+    let params = Params {
+        server_no_context_takeover: true,
+        client_no_context_takeover: true,
+        server_max_window_bits: Some(15),
+        client_max_window_bits: Some(12),
+    };
+
+    let formatted = params.to_string();
+    assert_eq!(
+        formatted,
+        "permessage-deflate; server_no_context_takeover; client_no_context_takeover; \
+         server_max_window_bits=15; client_max_window_bits=12"
+    );
+    assert_eq!(formatted.parse::<Params>().unwrap(), params);
+}
+
+#[test]
+fn small_window_bits() {
+    let params = Params {
+        server_max_window_bits: Some(10),
+        ..Params::default()
+    };
+
+    let err = PerMessageDeflate::new(Role::Server, &params).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // The client only decompresses with the server's window
+    let mut client = PerMessageDeflate::new(Role::Client, &params).unwrap();
+    assert_eq!(client.decompress(HELLO).unwrap(), b"Hello");
+}
@@ -0,0 +1,124 @@
+#[macro_use]
+mod utils;
+
+use async_compression::{
+    futures::{bufread, bufwrite, write},
+    pool::BufferPool,
+};
+use utils::{impls::futures as impls, one_to_six};
+
+#[test]
+#[ntest::timeout(1000)]
+fn write_reuses_buffer() {
+    let pool = BufferPool::new(16, 4);
+
+    for _ in 0..3 {
+        let output = impls::write::to_vec(
+            &[vec![1, 2, 3], vec![4, 5, 6]],
+            |output| Box::pin(write::IdentityEncoder::with_pool(&pool, output)),
+            2,
+        );
+
+        assert_eq!(output, one_to_six());
+        assert_eq!(pool.idle(), 1);
+    }
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn bufwrite_reuses_buffers() {
+    let pool = BufferPool::new(16, 4);
+
+    for _ in 0..3 {
+        let output = impls::bufwrite::to_vec(
+            &[vec![1, 2, 3], vec![4, 5, 6]],
+            |output| Box::pin(bufwrite::IdentityDecoder::with_pool(&pool, output)),
+            2,
+        );
+
+        assert_eq!(output, one_to_six());
+        // One for the input and one for the output
+        assert_eq!(pool.idle(), 2);
+    }
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn bufread_reuses_buffer() {
+    let pool = BufferPool::new(16, 4);
+
+    for _ in 0..3 {
+        let reader = bufread::BufReader::with_pool(&pool, one_to_six());
+        let output = impls::read::to_vec(bufread::IdentityEncoder::new(reader));
+
+        assert_eq!(output, one_to_six());
+        assert_eq!(pool.idle(), 1);
+    }
+}
+
+#[test]
+fn unused_buffer_is_not_taken() {
+    let pool = BufferPool::new(16, 4);
+
+    drop(write::IdentityEncoder::with_pool(&pool, Vec::<u8>::new()));
+
+    assert_eq!(pool.idle(), 0);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn max_idle() {
+    let pool = BufferPool::new(16, 1);
+
+    let output = impls::bufwrite::to_vec(
+        &[one_to_six().to_vec()],
+        |output| Box::pin(bufwrite::IdentityEncoder::with_pool(&pool, output)),
+        2,
+    );
+
+    assert_eq!(output, one_to_six());
+    assert_eq!(pool.idle(), 1);
+}
+
+#[cfg(feature = "tokio")]
+mod tokio {
+    use crate::utils::{impls::tokio as impls, one_to_six};
+    use async_compression::{
+        pool::BufferPool,
+        tokio::{bufwrite, write},
+    };
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn write_reuses_buffer() {
+        let pool = BufferPool::new(16, 4);
+
+        for _ in 0..3 {
+            let output = impls::write::to_vec(
+                &[vec![1, 2, 3], vec![4, 5, 6]],
+                |output| Box::pin(write::IdentityDecoder::with_pool(&pool, output)),
+                2,
+            );
+
+            assert_eq!(output, one_to_six());
+            assert_eq!(pool.idle(), 1);
+        }
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn bufwrite_reuses_buffers() {
+        let pool = BufferPool::new(16, 4);
+
+        for _ in 0..3 {
+            let output = impls::bufwrite::to_vec(
+                &[vec![1, 2, 3], vec![4, 5, 6]],
+                |output| Box::pin(bufwrite::IdentityEncoder::with_pool(&pool, output)),
+                2,
+            );
+
+            assert_eq!(output, one_to_six());
+            assert_eq!(pool.idle(), 2);
+        }
+    }
+}
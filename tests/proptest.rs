@@ -177,9 +177,15 @@ mod proptest {
     #[cfg(feature = "gzip")]
     tests!(gzip);
 
+    #[cfg(feature = "lz4")]
+    tests!(lz4);
+
     #[cfg(feature = "lzma")]
     tests!(lzma);
 
+    #[cfg(feature = "snappy")]
+    tests!(snappy);
+
     #[cfg(feature = "xz")]
     tests!(xz);
 
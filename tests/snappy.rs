@@ -0,0 +1,117 @@
+#[macro_use]
+mod utils;
+
+// The framing format has no end of stream marker, so any trailing data is treated as more chunks.
+test_cases!(snappy, unterminated);
+
+#[allow(unused)]
+use utils::{algos::snappy::sync, one_to_six, InputStream};
+
+#[cfg(feature = "futures-io")]
+use utils::algos::snappy::futures::{bufread, read};
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn snappy_decompress_missing_identifier() {
+    // A valid uncompressed chunk, but without the stream identifier chunk before it
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]).split_off(10);
+
+    let input = InputStream::new(vec![compressed]);
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = [0; 6];
+    let result = read::poll_read(&mut decoder, &mut output);
+
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn snappy_decompress_checksum_mismatch() {
+    let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let last = compressed.len() - 1;
+    compressed[last] ^= 0xff;
+
+    let input = InputStream::new(vec![compressed]);
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = [0; 6];
+    let result = read::poll_read(&mut decoder, &mut output);
+
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn snappy_decompress_skippable_chunk() {
+    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+    let (identifier, chunks) = compressed.split_at(10);
+    let padding = [0xfe, 3, 0, 0, 0, 0, 0];
+
+    let input = InputStream::new(vec![identifier.to_vec(), padding.to_vec(), chunks.to_vec()]);
+    let output = bufread::decompress(bufread::from(&input));
+
+    assert_eq!(output, one_to_six());
+}
+
+#[cfg(feature = "futures-io")]
+use async_compression::futures::bufread::{SnappyBlockDecoder, SnappyBlockEncoder};
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn snappy_block_compress() {
+    let input = InputStream::from([[1, 2, 3], [4, 5, 6]]);
+    let compressed = read::to_vec(SnappyBlockEncoder::new(bufread::from(&input)));
+
+    let output = snap::raw::Decoder::new()
+        .decompress_vec(&compressed)
+        .unwrap();
+
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn snappy_block_decompress() {
+    let compressed = snap::raw::Encoder::new()
+        .compress_vec(one_to_six())
+        .unwrap();
+    let input = InputStream::from(compressed.chunks(2));
+    let output = read::to_vec(SnappyBlockDecoder::new(bufread::from(&input)));
+
+    assert_eq!(output, one_to_six());
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn snappy_block_long() {
+    let bytes: Vec<u8> = (0..100_000).map(|_| rand::random()).collect();
+    let input = InputStream::from(bytes.chunks(1024));
+    let compressed = read::to_vec(SnappyBlockEncoder::new(bufread::from(&input)));
+
+    let input = InputStream::from(compressed.chunks(1024));
+    let output = read::to_vec(SnappyBlockDecoder::new(bufread::from(&input)));
+
+    assert_eq!(output, bytes);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn snappy_block_decompress_exceeds_length_prefix() {
+    let mut compressed = snap::raw::Encoder::new()
+        .compress_vec(one_to_six())
+        .unwrap();
+    compressed.extend_from_slice(&[0; 64]);
+
+    let input = InputStream::new(vec![compressed]);
+    let mut decoder = SnappyBlockDecoder::new(bufread::from(&input));
+    let mut output = [0; 6];
+    let result = read::poll_read(&mut decoder, &mut output);
+
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
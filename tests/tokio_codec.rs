@@ -0,0 +1,166 @@
+#[macro_use]
+mod utils;
+
+use async_compression::{tokio::codec::CompressedCodec, Algorithm};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::io::ErrorKind;
+use tokio_util_07::codec::{Decoder, Encoder};
+
+/// Prefixes `frame` with its length as the codec expects.
+fn length_prefixed(frame: &[u8]) -> BytesMut {
+    let mut buffer = BytesMut::new();
+    buffer.put_u32(frame.len() as u32);
+    buffer.put_slice(frame);
+    buffer
+}
+
+macro_rules! round_trips {
+    ($($variant:ident($feat:literal, $algorithm:ident))*) => {
+        $(
+            #[cfg(feature = $feat)]
+            mod $variant {
+                use async_compression::{tokio::codec::CompressedCodec, Algorithm, Level};
+                use bytes::{Bytes, BytesMut};
+                use crate::{length_prefixed, utils::{algos::$variant::sync, one_to_six}};
+                use tokio_util_07::codec::{Decoder, Encoder};
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn encode() {
+                    let mut codec = CompressedCodec::with_quality(
+                        Algorithm::$algorithm,
+                        Level::Fastest,
+                    );
+                    let mut buffer = BytesMut::new();
+                    codec.encode(Bytes::from_static(one_to_six()), &mut buffer).unwrap();
+
+                    let len = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+                    assert_eq!(len as usize, buffer.len() - 4);
+                    assert_eq!(sync::decompress(&buffer[4..]), one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn decode() {
+                    let mut codec = CompressedCodec::new(Algorithm::$algorithm);
+                    let mut buffer = length_prefixed(&sync::compress(one_to_six()));
+
+                    assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), one_to_six());
+                    assert!(buffer.is_empty());
+                }
+            }
+        )*
+    }
+}
+
+round_trips! {
+    brotli("brotli", Brotli)
+    bzip2("bzip2", Bzip2)
+    deflate("deflate", Deflate)
+    gzip("gzip", Gzip)
+    lz4("lz4", Lz4)
+    lzma("lzma", Lzma)
+    snappy("snappy", Snappy)
+    xz("xz", Xz)
+    zlib("zlib", Zlib)
+    zstd("zstd", Zstd)
+}
+
+#[cfg(feature = "gzip")]
+mod gzip_frames {
+    use super::*;
+    use crate::utils::{algos::gzip::sync, block_on, one_to_six};
+    use futures::{SinkExt as _, StreamExt as _};
+    use tokio_util_07::codec::{FramedRead, FramedWrite};
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn multiple_frames() {
+        let mut codec = CompressedCodec::new(Algorithm::Gzip);
+        let mut buffer = BytesMut::new();
+        codec
+            .encode(Bytes::from_static(&[1, 2, 3]), &mut buffer)
+            .unwrap();
+        codec.encode(Bytes::new(), &mut buffer).unwrap();
+        codec
+            .encode(Bytes::from_static(&[4, 5, 6]), &mut buffer)
+            .unwrap();
+
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[1, 2, 3][..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[][..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[4, 5, 6][..]);
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn partial_frame() {
+        let mut codec = CompressedCodec::new(Algorithm::Gzip);
+        let frame = length_prefixed(&sync::compress(one_to_six()));
+
+        let mut buffer = BytesMut::from(&frame[..frame.len() - 1]);
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+
+        buffer.extend_from_slice(&frame[frame.len() - 1..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), one_to_six());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn trailing_data() {
+        let mut codec = CompressedCodec::new(Algorithm::Gzip);
+        let mut compressed = sync::compress(one_to_six());
+        compressed.extend_from_slice(&[7, 8, 9]);
+
+        let err = codec
+            .decode(&mut length_prefixed(&compressed))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn truncated() {
+        let mut codec = CompressedCodec::new(Algorithm::Gzip);
+        let compressed = sync::compress(one_to_six());
+
+        let frame = &compressed[..compressed.len() - 4];
+        assert!(codec.decode(&mut length_prefixed(frame)).is_err());
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn max_frame_length() {
+        let mut codec = CompressedCodec::new(Algorithm::Gzip);
+        codec.set_max_frame_length(1024);
+        assert_eq!(codec.max_frame_length(), 1024);
+
+        // Compresses to far less than the limit, but decompresses to more
+        let compressed = sync::compress(&[0; 4096]);
+        assert!(compressed.len() < 1024);
+
+        let err = codec
+            .decode(&mut length_prefixed(&compressed))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[ntest::timeout(1000)]
+    fn framed() {
+        let mut write = FramedWrite::new(Vec::new(), CompressedCodec::new(Algorithm::Gzip));
+        block_on(write.send(Bytes::from_static(&[1, 2, 3]))).unwrap();
+        block_on(write.send(Bytes::from_static(&[4, 5, 6]))).unwrap();
+        let written = write.into_inner();
+
+        let read = FramedRead::new(&written[..], CompressedCodec::new(Algorithm::Gzip));
+        let frames: Vec<_> = block_on(read.collect::<Vec<_>>())
+            .into_iter()
+            .map(|frame| frame.unwrap().to_vec())
+            .collect();
+
+        assert_eq!(frames, [vec![1, 2, 3], vec![4, 5, 6]]);
+    }
+}
@@ -0,0 +1,130 @@
+#[macro_use]
+mod utils;
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+use utils::{algos::gzip::sync, block_on};
+
+/// Collects the message and counts of every event, formatted like `frame finished (21 -> 3)`.
+#[derive(Clone, Default)]
+struct Collector {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+#[derive(Default)]
+struct Fields {
+    message: String,
+    total_in: u64,
+    total_out: u64,
+}
+
+impl Visit for Fields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "total_in" => self.total_in = value,
+            "total_out" => self.total_out = value,
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.events.lock().unwrap().push(format!(
+            "{} ({} -> {})",
+            fields.message, fields.total_in, fields.total_out
+        ));
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+fn collect(f: impl FnOnce()) -> Vec<String> {
+    let collector = Collector::default();
+    tracing::subscriber::with_default(collector.clone(), f);
+    let events = collector.events.lock().unwrap().clone();
+    events
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn bufread_decode_members() {
+    use futures::io::AsyncReadExt as _;
+    use utils::algos::gzip::futures::bufread;
+
+    let first = sync::compress(&[1, 2, 3]);
+    let second = sync::compress(&[4, 5, 6]);
+    let compressed = [&first[..], &second[..]].concat();
+
+    let events = collect(|| {
+        let mut decoder = bufread::Decoder::new(&compressed[..]);
+        decoder.multiple_members(true);
+        let mut output = Vec::new();
+        block_on(decoder.read_to_end(&mut output)).unwrap();
+        assert_eq!(output, [1, 2, 3, 4, 5, 6]);
+    });
+
+    let (first, total) = (first.len(), compressed.len());
+    assert_eq!(
+        events,
+        [
+            format!("frame finished ({} -> 3)", first),
+            format!("reinitialized for next member ({} -> 3)", first),
+            format!("frame finished ({} -> 6)", total),
+            format!("reinitialized for next member ({} -> 6)", total),
+        ]
+    );
+}
+
+#[test]
+#[ntest::timeout(1000)]
+fn write_encode_flush_and_error() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::algos::gzip::futures::write;
+
+    let events = collect(|| {
+        let mut encoder = write::Encoder::new(Vec::new());
+        block_on(encoder.write_all(&[1, 2, 3])).unwrap();
+        block_on(encoder.flush()).unwrap();
+    });
+
+    assert_eq!(events.len(), 1);
+    assert!(events[0].starts_with("flush completed (3 -> "));
+
+    let events = collect(|| {
+        let mut decoder = write::Decoder::new(Vec::new());
+        block_on(decoder.write_all(&[0; 16])).unwrap_err();
+    });
+
+    assert_eq!(events.len(), 1);
+    assert!(events[0].starts_with("codec failed (0 -> 0)"));
+}
@@ -1,6 +1,33 @@
-macro_rules! io_algo {
+macro_rules! bufwrite_algo {
     ($impl:ident, $algo:ident($encoder:ident, $decoder:ident)) => {
+        pub mod bufwrite {
+            pub use crate::utils::impls::$impl::bufwrite::{close, to_vec, write_all, BufWriter};
+            pub use async_compression::$impl::bufwrite::{
+                $decoder as Decoder, $encoder as Encoder,
+            };
+
+            use crate::utils::Level;
+
+            pub fn compress(input: &[Vec<u8>], limit: usize) -> Vec<u8> {
+                to_vec(
+                    input,
+                    |input| Box::pin(Encoder::with_quality(input, Level::Fastest)),
+                    limit,
+                )
+            }
+
+            pub fn decompress(input: &[Vec<u8>], limit: usize) -> Vec<u8> {
+                to_vec(input, |input| Box::pin(Decoder::new(input)), limit)
+            }
+        }
+    };
+}
+
+macro_rules! io_algo {
+    ($impl:ident, $algo:ident($encoder:ident, $decoder:ident) $(, $extra:ident)*) => {
         pub mod $impl {
+            $($extra!($impl, $algo($encoder, $decoder));)*
+
             pub mod read {
                 pub use crate::utils::impls::$impl::read::{poll_read, to_vec};
             }
@@ -75,8 +102,51 @@ macro_rules! algos {
                     }
                 }
 
+                #[cfg(feature = "futures-sink")]
+                pub mod futures_sink {
+                    pub use async_compression::futures::sink::{
+                        $decoder as Decoder, $encoder as Encoder,
+                    };
+                    pub use crate::utils::impls::futures_sink::{output, received, send_all, to_vec};
+
+                    use crate::utils::Level;
+
+                    pub fn compress(input: &[Vec<u8>]) -> Vec<u8> {
+                        let (sink, receiver) = output();
+                        send_all(input, Encoder::with_quality(sink, Level::Fastest)).unwrap();
+                        to_vec(receiver)
+                    }
+
+                    pub fn decompress(input: &[Vec<u8>]) -> Vec<u8> {
+                        let (sink, receiver) = output();
+                        send_all(input, Decoder::new(sink)).unwrap();
+                        to_vec(receiver)
+                    }
+                }
+
+                #[cfg(feature = "futures-stream")]
+                pub mod futures_stream {
+                    pub use async_compression::futures::stream::{
+                        $decoder as Decoder, $encoder as Encoder,
+                    };
+                    pub use crate::utils::impls::futures_stream::to_vec;
+                    use bytes::Bytes;
+
+                    use crate::utils::{Level, pin_mut, Stream, Result};
+
+                    pub fn compress(input: impl Stream<Item = Result<Bytes>>) -> Vec<u8> {
+                        pin_mut!(input);
+                        to_vec(Encoder::with_quality(input, Level::Fastest))
+                    }
+
+                    pub fn decompress(input: impl Stream<Item = Result<Bytes>>) -> Vec<u8> {
+                        pin_mut!(input);
+                        to_vec(Decoder::new(input))
+                    }
+                }
+
                 #[cfg(feature = "futures-io")]
-                io_algo!(futures, $name($encoder, $decoder));
+                io_algo!(futures, $name($encoder, $decoder), bufwrite_algo);
 
                 #[cfg(feature = "tokio-02")]
                 io_algo!(tokio_02, $name($encoder, $decoder));
@@ -85,7 +155,7 @@ macro_rules! algos {
                 io_algo!(tokio_03, $name($encoder, $decoder));
 
                 #[cfg(feature = "tokio")]
-                io_algo!(tokio, $name($encoder, $decoder));
+                io_algo!(tokio, $name($encoder, $decoder), bufwrite_algo);
             }
         )*
     }
@@ -173,6 +243,38 @@ algos! {
         }
     }
 
+    pub mod isal_deflate("isal", IsalDeflateEncoder, IsalDeflateDecoder) {
+        pub mod sync {
+            pub use crate::utils::impls::sync::to_vec;
+
+            pub fn compress(bytes: &[u8]) -> Vec<u8> {
+                use isal::{read::DeflateEncoder, CompressionLevel};
+                to_vec(DeflateEncoder::new(bytes, CompressionLevel::Zero))
+            }
+
+            pub fn decompress(bytes: &[u8]) -> Vec<u8> {
+                use isal::read::DeflateDecoder;
+                to_vec(DeflateDecoder::new(bytes))
+            }
+        }
+    }
+
+    pub mod isal_gzip("isal", IsalGzipEncoder, IsalGzipDecoder) {
+        pub mod sync {
+            pub use crate::utils::impls::sync::to_vec;
+
+            pub fn compress(bytes: &[u8]) -> Vec<u8> {
+                use isal::{read::GzipEncoder, CompressionLevel};
+                to_vec(GzipEncoder::new(bytes, CompressionLevel::Zero))
+            }
+
+            pub fn decompress(bytes: &[u8]) -> Vec<u8> {
+                use isal::read::GzipDecoder;
+                to_vec(GzipDecoder::new(bytes))
+            }
+        }
+    }
+
     pub mod zstd("zstd", ZstdEncoder, ZstdDecoder) {
         pub mod sync {
             pub use crate::utils::impls::sync::to_vec;
@@ -233,4 +335,40 @@ algos! {
             }
         }
     }
+
+    pub mod lz4("lz4", Lz4Encoder, Lz4Decoder) {
+        pub mod sync {
+            pub use crate::utils::impls::sync::to_vec;
+
+            pub fn compress(bytes: &[u8]) -> Vec<u8> {
+                use std::io::Write;
+
+                let mut encoder = liblz4::EncoderBuilder::new().level(0).build(Vec::new()).unwrap();
+                encoder.write_all(bytes).unwrap();
+                let (output, result) = encoder.finish();
+                result.unwrap();
+                output
+            }
+
+            pub fn decompress(bytes: &[u8]) -> Vec<u8> {
+                to_vec(liblz4::Decoder::new(bytes).unwrap())
+            }
+        }
+    }
+
+    pub mod snappy("snappy", SnappyEncoder, SnappyDecoder) {
+        pub mod sync {
+            pub use crate::utils::impls::sync::to_vec;
+
+            pub fn compress(bytes: &[u8]) -> Vec<u8> {
+                use snap::read::FrameEncoder;
+                to_vec(FrameEncoder::new(bytes))
+            }
+
+            pub fn decompress(bytes: &[u8]) -> Vec<u8> {
+                use snap::read::FrameDecoder;
+                to_vec(FrameDecoder::new(bytes))
+            }
+        }
+    }
 }
@@ -23,6 +23,70 @@ pub mod futures {
         }
     }
 
+    pub mod bufwrite {
+        pub use async_compression::futures::bufwrite::AsyncBufWrite;
+
+        use crate::utils::{block_on, Pin, TrackClosed};
+        use futures::{
+            future::poll_fn,
+            io::{AsyncWrite, AsyncWriteExt as _},
+            ready,
+        };
+        use futures_test::io::AsyncWriteTestExt as _;
+        use std::task::Poll;
+
+        pub trait BufWriter: AsyncBufWrite + AsyncWrite {}
+
+        impl<W: AsyncBufWrite + AsyncWrite + ?Sized> BufWriter for W {}
+
+        /// Writes each chunk directly into the buffer of the created writer.
+        pub fn write_all(mut writer: Pin<&mut (dyn BufWriter + '_)>, mut chunk: &[u8]) {
+            while !chunk.is_empty() {
+                let len = block_on(poll_fn(|cx| {
+                    let buf = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
+                    let len = buf.len().min(chunk.len());
+                    buf[..len].copy_from_slice(&chunk[..len]);
+                    Poll::Ready(Ok::<_, std::io::Error>(len))
+                }))
+                .unwrap();
+                writer.as_mut().produce(len);
+                chunk = &chunk[len..];
+            }
+        }
+
+        /// Closes the writer, finishing the stream.
+        pub fn close(mut writer: Pin<&mut (dyn BufWriter + '_)>) {
+            block_on(writer.close()).unwrap();
+        }
+
+        pub fn to_vec(
+            input: &[Vec<u8>],
+            create_writer: impl for<'a> FnOnce(
+                &'a mut (dyn AsyncWrite + Unpin),
+            ) -> Pin<Box<dyn BufWriter + 'a>>,
+            limit: usize,
+        ) -> Vec<u8> {
+            let mut output = Vec::new();
+            {
+                let mut test_writer = TrackClosed::new(
+                    (&mut output)
+                        .limited_write(limit)
+                        .interleave_pending_write(),
+                );
+                {
+                    let mut writer = create_writer(&mut test_writer);
+                    for chunk in input {
+                        write_all(writer.as_mut(), chunk);
+                        block_on(writer.flush()).unwrap();
+                    }
+                    block_on(writer.close()).unwrap();
+                }
+                assert!(test_writer.is_closed());
+            }
+            output
+        }
+    }
+
     pub mod read {
         use crate::utils::{block_on, pin_mut};
         use futures::io::{copy_buf, AsyncRead, AsyncReadExt, BufReader, Cursor};
@@ -95,6 +159,71 @@ pub mod stream {
     }
 }
 
+#[cfg(feature = "futures-sink")]
+pub mod futures_sink {
+    use crate::utils::{block_on, pin_mut, Result};
+    use bytes::Bytes;
+    use futures::{
+        channel::mpsc,
+        sink::{Sink, SinkExt as _, SinkMapErr},
+        stream::StreamExt as _,
+    };
+
+    pub type Output =
+        SinkMapErr<mpsc::UnboundedSender<Bytes>, fn(mpsc::SendError) -> std::io::Error>;
+
+    /// Creates a sink to wrap, and a receiver for the items sent into it.
+    pub fn output() -> (Output, mpsc::UnboundedReceiver<Bytes>) {
+        let (sender, receiver) = mpsc::unbounded();
+        let map_err: fn(mpsc::SendError) -> std::io::Error =
+            |err| std::io::Error::new(std::io::ErrorKind::Other, err);
+        (sender.sink_map_err(map_err), receiver)
+    }
+
+    /// Sends each chunk of `input` without flushing, then closes the sink.
+    pub fn send_all(
+        input: &[Vec<u8>],
+        sink: impl Sink<Bytes, Error = std::io::Error>,
+    ) -> Result<()> {
+        pin_mut!(sink);
+        block_on(async {
+            for chunk in input {
+                sink.feed(Bytes::copy_from_slice(chunk)).await?;
+            }
+            sink.close().await
+        })
+    }
+
+    /// Takes everything that has been sent so far.
+    pub fn received(receiver: &mut mpsc::UnboundedReceiver<Bytes>) -> Vec<u8> {
+        let mut output = Vec::new();
+        while let Ok(Some(chunk)) = receiver.try_next() {
+            output.extend_from_slice(&chunk);
+        }
+        output
+    }
+
+    pub fn to_vec(receiver: mpsc::UnboundedReceiver<Bytes>) -> Vec<u8> {
+        block_on(receiver.collect::<Vec<_>>()).concat()
+    }
+}
+
+#[cfg(feature = "futures-stream")]
+pub mod futures_stream {
+    use crate::utils::{block_on, pin_mut, Result};
+    use bytes::Bytes;
+    use futures::stream::{Stream, TryStreamExt as _};
+
+    pub fn to_vec(stream: impl Stream<Item = Result<Bytes>>) -> Vec<u8> {
+        pin_mut!(stream);
+        block_on(stream.try_collect::<Vec<_>>())
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
 #[cfg(feature = "tokio-02")]
 pub mod tokio_02 {
     pub mod bufread {
@@ -254,6 +383,68 @@ pub mod tokio {
         }
     }
 
+    pub mod bufwrite {
+        pub use async_compression::tokio::bufwrite::AsyncBufWrite;
+
+        use crate::utils::{
+            block_on, tokio_ext::AsyncWriteTestExt as _, track_closed::TrackClosed, Pin,
+        };
+        use futures::{future::poll_fn, ready};
+        use std::{io::Cursor, task::Poll};
+        use tokio::io::{AsyncWrite, AsyncWriteExt as _};
+
+        pub trait BufWriter: AsyncBufWrite + AsyncWrite {}
+
+        impl<W: AsyncBufWrite + AsyncWrite + ?Sized> BufWriter for W {}
+
+        /// Writes each chunk directly into the buffer of the created writer.
+        pub fn write_all(mut writer: Pin<&mut (dyn BufWriter + '_)>, mut chunk: &[u8]) {
+            while !chunk.is_empty() {
+                let len = block_on(poll_fn(|cx| {
+                    let buf = ready!(writer.as_mut().poll_partial_flush_buf(cx))?;
+                    let len = buf.len().min(chunk.len());
+                    buf[..len].copy_from_slice(&chunk[..len]);
+                    Poll::Ready(Ok::<_, std::io::Error>(len))
+                }))
+                .unwrap();
+                writer.as_mut().produce(len);
+                chunk = &chunk[len..];
+            }
+        }
+
+        /// Shuts down the writer, finishing the stream.
+        pub fn close(mut writer: Pin<&mut (dyn BufWriter + '_)>) {
+            block_on(writer.shutdown()).unwrap();
+        }
+
+        pub fn to_vec(
+            input: &[Vec<u8>],
+            create_writer: impl for<'a> FnOnce(
+                &'a mut (dyn AsyncWrite + Unpin),
+            ) -> Pin<Box<dyn BufWriter + 'a>>,
+            limit: usize,
+        ) -> Vec<u8> {
+            let mut output = Cursor::new(Vec::new());
+            {
+                let mut test_writer = TrackClosed::new(
+                    (&mut output)
+                        .limited_write(limit)
+                        .interleave_pending_write(),
+                );
+                {
+                    let mut writer = create_writer(&mut test_writer);
+                    for chunk in input {
+                        write_all(writer.as_mut(), chunk);
+                        block_on(writer.flush()).unwrap();
+                    }
+                    block_on(writer.shutdown()).unwrap();
+                }
+                assert!(test_writer.is_closed());
+            }
+            output.into_inner()
+        }
+    }
+
     pub mod read {
         use crate::utils::{block_on, pin_mut, tokio_ext::copy_buf};
         use std::io::Cursor;
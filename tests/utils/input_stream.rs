@@ -34,6 +34,12 @@ impl InputStream {
             .map(std::io::Result::Ok)
     }
 
+    pub fn bytes_stream(&self) -> impl Stream<Item = std::io::Result<bytes::Bytes>> {
+        self.stream()
+            .map(bytes::Bytes::from)
+            .map(std::io::Result::Ok)
+    }
+
     pub fn bytes(&self) -> Vec<u8> {
         self.0.iter().flatten().cloned().collect()
     }
@@ -1,5 +1,15 @@
+/// Only emits the given tests for formats that mark the end of their compressed data, otherwise
+/// there is no way to tell where any trailing data begins.
+macro_rules! terminated {
+    (unterminated; $($item:item)*) => {};
+    // Decoders which read ahead past the end of their compressed data keep part of what follows
+    // it, so they are tested with `into_inner_with_leftover` instead
+    (reads_ahead; $($item:item)*) => {};
+    (; $($item:item)*) => { $($item)* };
+}
+
 macro_rules! io_test_cases {
-    ($impl:ident, $variant:ident) => {
+    ($impl:ident, $variant:ident $(, $flag:ident)?) => {
         mod $impl {
             mod bufread {
                 mod compress {
@@ -176,18 +186,40 @@ macro_rules! io_test_cases {
 
                     #[test]
                     #[ntest::timeout(1000)]
-                    fn trailer() {
-                        let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+                    fn truncated() {
+                        let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
 
-                        compressed.extend_from_slice(&[7, 8, 9, 10]);
+                        let truncated = compressed[..compressed.len() - 1].to_vec();
+                        let input = InputStream::new(vec![truncated]);
+                        let mut decoder = Box::pin(bufread::Decoder::new(bufread::from(&input)));
+                        let result = loop {
+                            match read::poll_read(&mut decoder, &mut [0; 16]) {
+                                Ok(0) => break Ok(()),
+                                Ok(_) => {}
+                                Err(err) => break Err(err),
+                            }
+                        };
+
+                        let kind = result.unwrap_err().kind();
+                        assert_eq!(kind, std::io::ErrorKind::UnexpectedEof);
+                    }
 
-                        let input = InputStream::new(vec![compressed]);
-                        let mut reader = bufread::from(&input);
-                        let output = bufread::decompress(&mut reader);
-                        let trailer = read::to_vec(reader);
+                    terminated! { $($flag)?;
+                        #[test]
+                        #[ntest::timeout(1000)]
+                        fn trailer() {
+                            let mut compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
 
-                        assert_eq!(output, one_to_six());
-                        assert_eq!(trailer, &[7, 8, 9, 10][..]);
+                            compressed.extend_from_slice(&[7, 8, 9, 10]);
+
+                            let input = InputStream::new(vec![compressed]);
+                            let mut reader = bufread::from(&input);
+                            let output = bufread::decompress(&mut reader);
+                            let trailer = read::to_vec(reader);
+
+                            assert_eq!(output, one_to_six());
+                            assert_eq!(trailer, &[7, 8, 9, 10][..]);
+                        }
                     }
 
                     #[test]
@@ -444,24 +476,459 @@ macro_rules! io_test_cases {
     };
 }
 
+macro_rules! bufwrite_test_cases {
+    ($name:ident, $impl:ident, $variant:ident) => {
+        mod $name {
+            mod compress {
+                use crate::utils::{
+                    algos::$variant::{
+                        sync,
+                        $impl::{bufwrite, write},
+                    },
+                    one_to_six, one_to_six_stream, InputStream, Pin,
+                };
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn empty() {
+                    let input = InputStream::new(vec![]);
+
+                    let compressed = bufwrite::compress(input.as_ref(), 65_536);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, &[][..]);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn empty_chunk() {
+                    let input = InputStream::new(vec![vec![]]);
+
+                    let compressed = bufwrite::compress(input.as_ref(), 65_536);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, input.bytes());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short() {
+                    let compressed = bufwrite::compress(one_to_six_stream().as_ref(), 65_536);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short_chunk_output() {
+                    let compressed = bufwrite::compress(one_to_six_stream().as_ref(), 2);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn long() {
+                    let input = InputStream::new(vec![
+                        (0..32_768).map(|_| rand::random()).collect(),
+                        (0..32_768).map(|_| rand::random()).collect(),
+                    ]);
+
+                    let compressed = bufwrite::compress(input.as_ref(), 65_536);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, input.bytes());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn long_chunk_output() {
+                    let input = InputStream::new(vec![
+                        (0..32_768).map(|_| rand::random()).collect(),
+                        (0..32_768).map(|_| rand::random()).collect(),
+                    ]);
+
+                    let compressed = bufwrite::compress(input.as_ref(), 20);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, input.bytes());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn async_write() {
+                    let compressed = write::to_vec(
+                        one_to_six_stream().as_ref(),
+                        |input| Box::pin(bufwrite::Encoder::new(input)),
+                        2,
+                    );
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn reset_with_writer() {
+                    let mut encoder = Box::pin(bufwrite::Encoder::new(Vec::new()));
+                    bufwrite::write_all(encoder.as_mut(), &one_to_six());
+                    bufwrite::close(encoder.as_mut());
+
+                    let first = encoder.reset_with_writer(Vec::new()).unwrap();
+                    bufwrite::write_all(encoder.as_mut(), &one_to_six());
+                    bufwrite::close(encoder.as_mut());
+
+                    let second = Pin::into_inner(encoder).into_inner();
+                    assert_eq!(sync::decompress(&first), one_to_six());
+                    assert_eq!(sync::decompress(&second), one_to_six());
+                }
+            }
+
+            mod decompress {
+                use crate::utils::{
+                    algos::$variant::{
+                        sync,
+                        $impl::{bufwrite, write},
+                    },
+                    one_to_six, InputStream,
+                };
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn empty() {
+                    let compressed = sync::compress(&[]);
+
+                    let input = InputStream::new(vec![compressed]);
+                    let output = bufwrite::decompress(input.as_ref(), 65_536);
+
+                    assert_eq!(output, &[][..]);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short() {
+                    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+                    let input = InputStream::new(vec![compressed]);
+                    let output = bufwrite::decompress(input.as_ref(), 65_536);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short_chunks() {
+                    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+                    let input = InputStream::from(compressed.chunks(2));
+                    let output = bufwrite::decompress(input.as_ref(), 65_536);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn long_chunks() {
+                    let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+                    let compressed = sync::compress(&bytes);
+
+                    let input = InputStream::from(compressed.chunks(1024));
+                    let output = bufwrite::decompress(input.as_ref(), 65_536);
+
+                    assert_eq!(output, bytes);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn async_write() {
+                    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+                    let input = InputStream::from(compressed.chunks(2));
+                    let output = write::to_vec(
+                        input.as_ref(),
+                        |output| Box::pin(bufwrite::Decoder::new(output)),
+                        2,
+                    );
+
+                    assert_eq!(output, one_to_six());
+                }
+            }
+        }
+    };
+}
+
+macro_rules! stream_test_cases {
+    ($(#[$attr:meta])* $impl:ident, $bytes_stream:ident, $variant:ident $(, $flag:ident)?) => {
+        $(#[$attr])*
+        mod $impl {
+            mod compress {
+                use crate::utils::{
+                    algos::$variant::{$impl as stream, sync},
+                    block_on, one_to_six, one_to_six_stream, InputStream, Level,
+                };
+                use futures::stream::StreamExt as _;
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn empty() {
+                    // Can't use InputStream for this as it will inject extra empty chunks
+                    let compressed = stream::compress(futures::stream::empty());
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, &[][..]);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn empty_chunk() {
+                    let input = InputStream::new(vec![vec![]]);
+
+                    let compressed = stream::compress(input.$bytes_stream());
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, input.bytes());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short() {
+                    let compressed = stream::compress(one_to_six_stream().$bytes_stream());
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn long() {
+                    let input = InputStream::new(vec![
+                        (0..32_768).map(|_| rand::random()).collect(),
+                        (0..32_768).map(|_| rand::random()).collect(),
+                    ]);
+
+                    let compressed = stream::compress(input.$bytes_stream());
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, input.bytes());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn error() {
+                    let err = std::io::Error::new(std::io::ErrorKind::Other, "failure");
+                    let input = futures::stream::iter(vec![Err(err)]);
+
+                    let mut stream = stream::Encoder::with_quality(input, Level::Fastest);
+
+                    assert!(block_on(stream.next()).unwrap().is_err());
+                    assert!(block_on(stream.next()).is_none());
+                }
+
+                #[test]
+                fn with_level_best() {
+                    let encoder = stream::Encoder::with_quality(
+                        one_to_six_stream().$bytes_stream(),
+                        Level::Best,
+                    );
+                    let compressed = stream::to_vec(encoder);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                fn with_level_default() {
+                    let encoder = stream::Encoder::new(one_to_six_stream().$bytes_stream());
+                    let compressed = stream::to_vec(encoder);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                fn with_level_0() {
+                    let encoder = stream::Encoder::with_quality(
+                        one_to_six_stream().$bytes_stream(),
+                        Level::Precise(0),
+                    );
+                    let compressed = stream::to_vec(encoder);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                fn with_level_max() {
+                    let encoder = stream::Encoder::with_quality(
+                        one_to_six_stream().$bytes_stream(),
+                        Level::Precise(u32::max_value()),
+                    );
+                    let compressed = stream::to_vec(encoder);
+                    let output = sync::decompress(&compressed);
+
+                    assert_eq!(output, one_to_six());
+                }
+            }
+
+            mod decompress {
+                use crate::utils::{
+                    algos::$variant::{$impl as stream, sync},
+                    block_on, one_to_six, one_to_six_stream, InputStream,
+                };
+                use futures::stream::{StreamExt as _, TryStreamExt as _};
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn empty() {
+                    let compressed = sync::compress(&[]);
+
+                    let input = InputStream::new(vec![compressed]);
+                    let output = stream::decompress(input.$bytes_stream());
+
+                    assert_eq!(output, &[][..]);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn short() {
+                    let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+                    let input = InputStream::new(vec![compressed]);
+                    let output = stream::decompress(input.$bytes_stream());
+
+                    assert_eq!(output, one_to_six());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn long() {
+                    let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+                    let compressed = sync::compress(&bytes);
+
+                    let input = InputStream::new(vec![compressed]);
+                    let output = stream::decompress(input.$bytes_stream());
+
+                    assert_eq!(output, bytes);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn long_chunks() {
+                    let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
+                    let compressed = sync::compress(&bytes);
+
+                    let input = InputStream::from(compressed.chunks(1024));
+                    let output = stream::decompress(input.$bytes_stream());
+
+                    assert_eq!(output, bytes);
+                }
+
+                terminated! { $($flag)?;
+                    #[test]
+                    #[ntest::timeout(1000)]
+                    fn trailer() {
+                        // Currently there is no way to get any partially consumed stream item
+                        // from the decoder, for now we just guarantee that if the compressed
+                        // frame exactly matches an item boundary we will not read the next item
+                        // from the stream.
+                        let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
+
+                        let input = InputStream::new(vec![compressed, vec![7, 8, 9, 10]]);
+
+                        let mut stream = input.$bytes_stream();
+                        let output = stream::decompress(&mut stream);
+                        let trailer = stream::to_vec(stream);
+
+                        assert_eq!(output, one_to_six());
+                        assert_eq!(trailer, &[7, 8, 9, 10][..]);
+                    }
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn multiple_members() {
+                    let compressed = [
+                        sync::compress(&[1, 2, 3, 4, 5, 6]),
+                        sync::compress(&[6, 5, 4, 3, 2, 1]),
+                    ]
+                    .join(&[][..]);
+
+                    let input = InputStream::new(vec![compressed]);
+
+                    let mut decoder = stream::Decoder::new(input.$bytes_stream());
+                    decoder.multiple_members(true);
+                    let output = stream::to_vec(decoder);
+
+                    assert_eq!(output, &[1, 2, 3, 4, 5, 6, 6, 5, 4, 3, 2, 1][..]);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn multiple_members_chunked() {
+                    let compressed = [
+                        sync::compress(&[1, 2, 3, 4, 5, 6]),
+                        sync::compress(&[6, 5, 4, 3, 2, 1]),
+                    ]
+                    .join(&[][..]);
+
+                    let input = InputStream::from(compressed.chunks(1));
+
+                    let mut decoder = stream::Decoder::new(input.$bytes_stream());
+                    decoder.multiple_members(true);
+                    let output = stream::to_vec(decoder);
+
+                    assert_eq!(output, &[1, 2, 3, 4, 5, 6, 6, 5, 4, 3, 2, 1][..]);
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn error() {
+                    let err = std::io::Error::new(std::io::ErrorKind::Other, "failure");
+                    let input = futures::stream::iter(vec![Err(err)]);
+
+                    let mut stream = stream::Decoder::new(input);
+
+                    assert!(block_on(stream.by_ref().try_collect::<Vec<_>>()).is_err());
+                    assert!(block_on(stream.next()).is_none());
+                }
+
+                #[test]
+                #[ntest::timeout(1000)]
+                fn invalid_data() {
+                    let mut stream =
+                        stream::Decoder::new(one_to_six_stream().$bytes_stream());
+
+                    assert!(block_on(stream.by_ref().try_collect::<Vec<_>>()).is_err());
+                    assert!(block_on(stream.next()).is_none());
+                }
+            }
+        }
+    };
+}
+
 macro_rules! test_cases {
-    ($variant:ident) => {
+    ($variant:ident $(, $flag:ident)?) => {
         mod $variant {
             #[cfg(feature = "stream")]
-            #[allow(deprecated)]
-            mod stream {
+            stream_test_cases!(
+                #[allow(deprecated)]
+                stream, bytes_05_stream, $variant $(, $flag)?
+            );
+
+            #[cfg(feature = "futures-sink")]
+            mod futures_sink {
                 mod compress {
                     use crate::utils::{
-                        algos::$variant::{stream, sync},
-                        block_on, one_to_six, one_to_six_stream, InputStream, Level,
+                        algos::$variant::{futures_sink, sync},
+                        one_to_six, one_to_six_stream, InputStream, Level,
                     };
-                    use futures::stream::StreamExt as _;
 
                     #[test]
                     #[ntest::timeout(1000)]
                     fn empty() {
-                        // Can't use InputStream for this as it will inject extra empty chunks
-                        let compressed = stream::compress(futures::stream::empty());
+                        let compressed = futures_sink::compress(&[]);
                         let output = sync::decompress(&compressed);
 
                         assert_eq!(output, &[][..]);
@@ -470,18 +937,16 @@ macro_rules! test_cases {
                     #[test]
                     #[ntest::timeout(1000)]
                     fn empty_chunk() {
-                        let input = InputStream::new(vec![vec![]]);
-
-                        let compressed = stream::compress(input.bytes_05_stream());
+                        let compressed = futures_sink::compress(&[vec![]]);
                         let output = sync::decompress(&compressed);
 
-                        assert_eq!(output, input.bytes());
+                        assert_eq!(output, &[][..]);
                     }
 
                     #[test]
                     #[ntest::timeout(1000)]
                     fn short() {
-                        let compressed = stream::compress(one_to_six_stream().bytes_05_stream());
+                        let compressed = futures_sink::compress(one_to_six_stream().as_ref());
                         let output = sync::decompress(&compressed);
 
                         assert_eq!(output, one_to_six());
@@ -495,7 +960,7 @@ macro_rules! test_cases {
                             (0..32_768).map(|_| rand::random()).collect(),
                         ]);
 
-                        let compressed = stream::compress(input.bytes_05_stream());
+                        let compressed = futures_sink::compress(input.as_ref());
                         let output = sync::decompress(&compressed);
 
                         assert_eq!(output, input.bytes());
@@ -503,76 +968,35 @@ macro_rules! test_cases {
 
                     #[test]
                     #[ntest::timeout(1000)]
-                    fn error() {
-                        let err = std::io::Error::new(std::io::ErrorKind::Other, "failure");
-                        let input = futures::stream::iter(vec![Err(err)]);
-
-                        let mut stream = stream::Encoder::with_quality(input, Level::Fastest);
-
-                        assert!(block_on(stream.next()).unwrap().is_err());
-                        assert!(block_on(stream.next()).is_none());
-                    }
-
-                    #[test]
-                    fn with_level_best() {
-                        let encoder = stream::Encoder::with_quality(
-                            one_to_six_stream().bytes_05_stream(),
-                            Level::Best,
-                        );
-                        let compressed = stream::to_vec(encoder);
-                        let output = sync::decompress(&compressed);
-
-                        assert_eq!(output, one_to_six());
-                    }
-
-                    #[test]
-                    fn with_level_default() {
-                        let encoder = stream::Encoder::new(one_to_six_stream().bytes_05_stream());
-                        let compressed = stream::to_vec(encoder);
-                        let output = sync::decompress(&compressed);
-
-                        assert_eq!(output, one_to_six());
-                    }
+                    fn flush() {
+                        use futures::sink::SinkExt as _;
 
-                    #[test]
-                    fn with_level_0() {
-                        let encoder = stream::Encoder::with_quality(
-                            one_to_six_stream().bytes_05_stream(),
-                            Level::Precise(0),
-                        );
-                        let compressed = stream::to_vec(encoder);
-                        let output = sync::decompress(&compressed);
+                        let (sink, mut receiver) = futures_sink::output();
+                        let mut encoder = futures_sink::Encoder::with_quality(sink, Level::Fastest);
 
-                        assert_eq!(output, one_to_six());
-                    }
+                        crate::utils::block_on(encoder.send(vec![1, 2, 3].into())).unwrap();
+                        let mut compressed = futures_sink::received(&mut receiver);
+                        assert!(!compressed.is_empty());
 
-                    #[test]
-                    fn with_level_max() {
-                        let encoder = stream::Encoder::with_quality(
-                            one_to_six_stream().bytes_05_stream(),
-                            Level::Precise(u32::max_value()),
-                        );
-                        let compressed = stream::to_vec(encoder);
-                        let output = sync::decompress(&compressed);
+                        futures_sink::send_all(&[vec![4, 5, 6]], encoder).unwrap();
+                        compressed.extend(futures_sink::to_vec(receiver));
 
-                        assert_eq!(output, one_to_six());
+                        assert_eq!(sync::decompress(&compressed), one_to_six());
                     }
                 }
 
                 mod decompress {
                     use crate::utils::{
-                        algos::$variant::{stream, sync},
-                        block_on, one_to_six, one_to_six_stream, InputStream,
+                        algos::$variant::{futures_sink, sync},
+                        one_to_six, one_to_six_stream, InputStream,
                     };
-                    use futures::stream::{StreamExt as _, TryStreamExt as _};
 
                     #[test]
                     #[ntest::timeout(1000)]
                     fn empty() {
                         let compressed = sync::compress(&[]);
 
-                        let input = InputStream::new(vec![compressed]);
-                        let output = stream::decompress(input.bytes_05_stream());
+                        let output = futures_sink::decompress(&[compressed]);
 
                         assert_eq!(output, &[][..]);
                     }
@@ -582,24 +1006,11 @@ macro_rules! test_cases {
                     fn short() {
                         let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
 
-                        let input = InputStream::new(vec![compressed]);
-                        let output = stream::decompress(input.bytes_05_stream());
+                        let output = futures_sink::decompress(&[compressed]);
 
                         assert_eq!(output, one_to_six());
                     }
 
-                    #[test]
-                    #[ntest::timeout(1000)]
-                    fn long() {
-                        let bytes: Vec<u8> = (0..65_536).map(|_| rand::random()).collect();
-                        let compressed = sync::compress(&bytes);
-
-                        let input = InputStream::new(vec![compressed]);
-                        let output = stream::decompress(input.bytes_05_stream());
-
-                        assert_eq!(output, bytes);
-                    }
-
                     #[test]
                     #[ntest::timeout(1000)]
                     fn long_chunks() {
@@ -607,101 +1018,44 @@ macro_rules! test_cases {
                         let compressed = sync::compress(&bytes);
 
                         let input = InputStream::from(compressed.chunks(1024));
-                        let output = stream::decompress(input.bytes_05_stream());
+                        let output = futures_sink::decompress(input.as_ref());
 
                         assert_eq!(output, bytes);
                     }
 
-                    #[test]
-                    #[ntest::timeout(1000)]
-                    fn trailer() {
-                        // Currently there is no way to get any partially consumed stream item from
-                        // the decoder, for now we just guarantee that if the compressed frame
-                        // exactly matches an item boundary we will not read the next item from the
-                        // stream.
-                        let compressed = sync::compress(&[1, 2, 3, 4, 5, 6]);
-
-                        let input = InputStream::new(vec![compressed, vec![7, 8, 9, 10]]);
-
-                        let mut stream = input.bytes_05_stream();
-                        let output = stream::decompress(&mut stream);
-                        let trailer = stream::to_vec(stream);
-
-                        assert_eq!(output, one_to_six());
-                        assert_eq!(trailer, &[7, 8, 9, 10][..]);
-                    }
-
-                    #[test]
-                    #[ntest::timeout(1000)]
-                    fn multiple_members() {
-                        let compressed = [
-                            sync::compress(&[1, 2, 3, 4, 5, 6]),
-                            sync::compress(&[6, 5, 4, 3, 2, 1]),
-                        ]
-                        .join(&[][..]);
-
-                        let input = InputStream::new(vec![compressed]);
-
-                        let mut decoder = stream::Decoder::new(input.bytes_05_stream());
-                        decoder.multiple_members(true);
-                        let output = stream::to_vec(decoder);
-
-                        assert_eq!(output, &[1, 2, 3, 4, 5, 6, 6, 5, 4, 3, 2, 1][..]);
-                    }
-
-                    #[test]
-                    #[ntest::timeout(1000)]
-                    fn multiple_members_chunked() {
-                        let compressed = [
-                            sync::compress(&[1, 2, 3, 4, 5, 6]),
-                            sync::compress(&[6, 5, 4, 3, 2, 1]),
-                        ]
-                        .join(&[][..]);
-
-                        let input = InputStream::from(compressed.chunks(1));
-
-                        let mut decoder = stream::Decoder::new(input.bytes_05_stream());
-                        decoder.multiple_members(true);
-                        let output = stream::to_vec(decoder);
-
-                        assert_eq!(output, &[1, 2, 3, 4, 5, 6, 6, 5, 4, 3, 2, 1][..]);
-                    }
-
-                    #[test]
-                    #[ntest::timeout(1000)]
-                    fn error() {
-                        let err = std::io::Error::new(std::io::ErrorKind::Other, "failure");
-                        let input = futures::stream::iter(vec![Err(err)]);
-
-                        let mut stream = stream::Decoder::new(input);
-
-                        assert!(block_on(stream.by_ref().try_collect::<Vec<_>>()).is_err());
-                        assert!(block_on(stream.next()).is_none());
-                    }
-
                     #[test]
                     #[ntest::timeout(1000)]
                     fn invalid_data() {
-                        let mut stream =
-                            stream::Decoder::new(one_to_six_stream().bytes_05_stream());
+                        let (sink, _receiver) = futures_sink::output();
+                        let decoder = futures_sink::Decoder::new(sink);
 
-                        assert!(block_on(stream.by_ref().try_collect::<Vec<_>>()).is_err());
-                        assert!(block_on(stream.next()).is_none());
+                        assert!(
+                            futures_sink::send_all(one_to_six_stream().as_ref(), decoder).is_err()
+                        );
                     }
                 }
             }
 
+            #[cfg(feature = "futures-stream")]
+            stream_test_cases!(futures_stream, bytes_stream, $variant $(, $flag)?);
+
             #[cfg(feature = "futures-io")]
-            io_test_cases!(futures, $variant);
+            io_test_cases!(futures, $variant $(, $flag)?);
+
+            #[cfg(feature = "futures-io")]
+            bufwrite_test_cases!(futures_bufwrite, futures, $variant);
 
             #[cfg(feature = "tokio-02")]
-            io_test_cases!(tokio_02, $variant);
+            io_test_cases!(tokio_02, $variant $(, $flag)?);
 
             #[cfg(feature = "tokio-03")]
-            io_test_cases!(tokio_03, $variant);
+            io_test_cases!(tokio_03, $variant $(, $flag)?);
+
+            #[cfg(feature = "tokio")]
+            io_test_cases!(tokio, $variant $(, $flag)?);
 
             #[cfg(feature = "tokio")]
-            io_test_cases!(tokio, $variant);
+            bufwrite_test_cases!(tokio_bufwrite, tokio, $variant);
         }
     };
 }
@@ -21,6 +21,10 @@ impl<W> TrackClosed<W> {
     pub fn is_closed(&self) -> bool {
         self.closed
     }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
 }
 
 #[cfg(feature = "futures-io")]
@@ -2,3 +2,68 @@
 mod utils;
 
 test_cases!(zlib);
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn zlib_bufread_decompress_dictionary_required() {
+    use async_compression::error::Error;
+    use futures::io::AsyncReadExt as _;
+    use utils::{algos::zlib::futures::bufread, block_on};
+
+    // A zlib header with the preset dictionary flag set, followed by the dictionary's checksum
+    let compressed = [0x78, 0xbb, 0x12, 0x34, 0x56, 0x78, 0x03, 0x00];
+
+    let mut decoder = bufread::Decoder::new(&compressed[..]);
+    let err = block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err();
+
+    assert!(matches!(
+        Error::from_io(&err),
+        Some(Error::DictionaryRequired {
+            id: Some(0x1234_5678)
+        })
+    ));
+}
+
+#[test]
+#[cfg(feature = "futures-io")]
+fn zlib_write_compress_set_level_unsupported() {
+    use std::io::ErrorKind;
+    use utils::{algos::zlib::futures::write, Level};
+
+    let mut encoder = write::Encoder::new(Vec::new());
+    let err = encoder.set_level(Level::Best).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn zlib_checksum() {
+    use futures::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use std::convert::TryInto;
+    use utils::{
+        algos::zlib::futures::{bufread, write},
+        block_on, InputStream,
+    };
+
+    let input: Vec<u8> = (0..10_000).map(|_| rand::random()).collect();
+
+    let mut encoder = write::Encoder::new(Vec::new());
+    assert_eq!(encoder.checksum(), Some(1));
+    block_on(encoder.write_all(&input)).unwrap();
+    block_on(encoder.close()).unwrap();
+    let compressed = encoder.get_ref();
+
+    // The checksum is the Adler-32 stored in the trailer
+    let adler = u32::from_be_bytes(compressed[compressed.len() - 4..].try_into().unwrap());
+    assert_eq!(encoder.checksum(), Some(adler));
+
+    let input = InputStream::from(compressed.chunks(100));
+    let mut decoder = bufread::Decoder::new(bufread::from(&input));
+    let mut output = vec![0; 5000];
+    block_on(decoder.read_exact(&mut output)).unwrap();
+    assert_ne!(decoder.checksum(), Some(adler));
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(decoder.checksum(), Some(adler));
+}
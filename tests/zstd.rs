@@ -2,3 +2,259 @@
 mod utils;
 
 test_cases!(zstd);
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn zstd_write_compress_set_level() {
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::zstd::futures::write, algos::zstd::sync, block_on, one_to_six, Level};
+
+    let mut encoder = write::Encoder::new(Vec::new());
+    block_on(encoder.write_all(&one_to_six()[..3])).unwrap();
+    encoder.set_level(Level::Best).unwrap();
+    block_on(encoder.write_all(&one_to_six()[3..])).unwrap();
+    block_on(encoder.close()).unwrap();
+
+    assert_eq!(sync::decompress(encoder.get_ref()), one_to_six());
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "futures-io")]
+fn zstd_parallel_compress_custom_executor() {
+    use async_compression::{futures::write::ParallelEncoder, parallel::Executor, Algorithm};
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::zstd::sync, block_on, Level};
+
+    struct Threads;
+
+    impl Executor for Threads {
+        fn execute(&self, task: Box<dyn FnOnce() + Send>) {
+            std::thread::spawn(task);
+        }
+    }
+
+    let bytes: Vec<u8> = (0..50_000).map(|i| (i % 251) as u8).collect();
+
+    let mut encoder = ParallelEncoder::new(Vec::new(), Algorithm::Zstd, Level::Default, Threads)
+        .unwrap()
+        .with_block_size(4096);
+    for chunk in bytes.chunks(1000) {
+        block_on(encoder.write_all(chunk)).unwrap();
+    }
+    block_on(encoder.close()).unwrap();
+
+    assert_eq!(sync::decompress(&encoder.into_inner()), bytes);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn zstd_parallel_compress_empty() {
+    use async_compression::{futures::write::ParallelEncoder, parallel::Executor, Algorithm};
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::zstd::sync, block_on, Level};
+
+    struct Inline;
+
+    impl Executor for Inline {
+        fn execute(&self, task: Box<dyn FnOnce() + Send>) {
+            task();
+        }
+    }
+
+    let mut encoder =
+        ParallelEncoder::new(Vec::new(), Algorithm::Zstd, Level::Default, Inline).unwrap();
+    block_on(encoder.close()).unwrap();
+
+    let compressed = encoder.into_inner();
+    assert!(!compressed.is_empty());
+    assert_eq!(sync::decompress(&compressed), &[][..]);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn zstd_bufread_compress_max_frame_size() {
+    use async_compression::futures::bufread::{ZstdDecoder, ZstdEncoder};
+    use futures::io::AsyncReadExt as _;
+    use utils::{block_on, InputStream, Level};
+
+    let input: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+    let stream = InputStream::from(input.chunks(1000));
+    let mut encoder = ZstdEncoder::with_max_frame_size(
+        utils::impls::futures::bufread::from(&stream),
+        Level::Default,
+        16 * 1024,
+    );
+    let mut compressed = Vec::new();
+    block_on(encoder.read_to_end(&mut compressed)).unwrap();
+
+    // Each frame decodes on its own
+    let mut decoder = ZstdDecoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, &input[..16 * 1024]);
+
+    let mut decoder = ZstdDecoder::new(&compressed[..]);
+    decoder.multiple_members(true);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, input);
+    assert_eq!(decoder.member_count(), 7);
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn zstd_write_compress_max_frame_size_exact() {
+    use async_compression::futures::write::ZstdEncoder;
+    use futures::io::AsyncWriteExt as _;
+    use utils::{algos::zstd::sync, block_on, Level};
+
+    // The input fills the last frame exactly, no empty frame follows it
+    let input = vec![7; 4096];
+    let mut encoder = ZstdEncoder::with_max_frame_size(Vec::new(), Level::Default, 1024);
+    block_on(encoder.write_all(&input)).unwrap();
+    block_on(encoder.flush()).unwrap();
+    block_on(encoder.close()).unwrap();
+
+    let compressed = encoder.into_inner();
+    assert_eq!(sync::decompress(&compressed), input);
+
+    let mut frames = 0;
+    let mut rest = &compressed[..];
+    while !rest.is_empty() {
+        let len = zstd_safe::find_frame_compressed_size(rest).unwrap();
+        rest = &rest[len..];
+        frames += 1;
+    }
+    assert_eq!(frames, 4);
+}
+
+/// Trains a dictionary on samples of made up JSON records, which differ with `seed`.
+fn train_dictionary(seed: u32) -> Vec<u8> {
+    let mut samples = Vec::new();
+    let mut sizes = Vec::new();
+    for i in 0..1000 {
+        let sample = format!(
+            r#"{{"seed":{},"id":{},"name":"user{}","tags":["alpha","beta","gamma"],"score":{}}}"#,
+            seed,
+            i,
+            i * 7,
+            rand::random::<u16>()
+        );
+        samples.extend_from_slice(sample.as_bytes());
+        sizes.push(sample.len());
+    }
+
+    let mut dictionary = Vec::with_capacity(4096);
+    zstd_safe::train_from_buffer(&mut dictionary, &samples, &sizes).unwrap();
+    dictionary
+}
+
+/// Compresses `input` into a single frame with `dictionary`.
+fn compress_with_dictionary(input: &[u8], dictionary: &[u8]) -> Vec<u8> {
+    let mut cctx = zstd_safe::CCtx::create();
+    cctx.load_dictionary(dictionary).unwrap();
+    let mut output = Vec::with_capacity(zstd_safe::compress_bound(input.len()));
+    cctx.compress2(&mut output, input).unwrap();
+    output
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "futures-io")]
+fn zstd_bufread_decompress_dictionary_provider() {
+    use async_compression::futures::bufread::ZstdDecoder;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+    use utils::{algos::zstd::sync, impls::futures::bufread, impls::futures::read, InputStream};
+
+    let first = train_dictionary(1);
+    let second = train_dictionary(2);
+    let first_id = zstd_safe::get_dict_id_from_dict(&first);
+    let second_id = zstd_safe::get_dict_id_from_dict(&second);
+    assert_ne!(first_id, second_id);
+
+    let record = br#"{"seed":1,"id":5,"name":"user35","tags":["alpha","beta"],"score":3}"#;
+    let mut compressed = compress_with_dictionary(record, &first);
+    compressed.extend(compress_with_dictionary(record, &second));
+    compressed.extend(sync::compress(record));
+    compressed.extend(compress_with_dictionary(record, &first));
+
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let dictionaries: HashMap<_, _> = vec![(first_id, first), (second_id, second)]
+        .into_iter()
+        .collect();
+    let provider = {
+        let lookups = lookups.clone();
+        move |id| {
+            lookups.fetch_add(1, Ordering::SeqCst);
+            dictionaries.get(&id).cloned()
+        }
+    };
+
+    // Split the headers across reads
+    let input = InputStream::from(compressed.chunks(3));
+    let mut decoder = ZstdDecoder::with_dictionary_provider(bufread::from(&input), provider);
+    decoder.multiple_members(true);
+    let output = read::to_vec(decoder);
+
+    assert_eq!(output, record.repeat(4));
+    // The dictionary is only looked up when it changes
+    assert_eq!(lookups.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+#[ntest::timeout(5000)]
+#[cfg(feature = "futures-io")]
+fn zstd_bufread_decompress_dictionary_provider_missing() {
+    use async_compression::{error::Error, futures::bufread::ZstdDecoder};
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+
+    let dictionary = train_dictionary(1);
+    let id = zstd_safe::get_dict_id_from_dict(&dictionary);
+    let compressed = compress_with_dictionary(&[1, 2, 3, 4, 5, 6], &dictionary);
+
+    let mut decoder = ZstdDecoder::with_dictionary_provider(&compressed[..], |_| None);
+    let err = block_on(decoder.read_to_end(&mut Vec::new())).unwrap_err();
+    match Error::from_io(&err) {
+        Some(Error::DictionaryRequired { id: Some(required) }) => assert_eq!(*required, id),
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+#[ntest::timeout(1000)]
+#[cfg(feature = "futures-io")]
+fn zstd_bufread_compress_backend_parameter() {
+    use async_compression::futures::bufread::{ZstdDecoder, ZstdEncoder};
+    use futures::io::AsyncReadExt as _;
+    use utils::block_on;
+    use zstd_safe::CParameter;
+
+    let input = utils::one_to_six();
+    let mut encoder = ZstdEncoder::new(input);
+    encoder
+        .backend_mut()
+        .set_parameter(CParameter::ChecksumFlag(true))
+        .unwrap();
+    let mut compressed = Vec::new();
+    block_on(encoder.read_to_end(&mut compressed)).unwrap();
+
+    // The content checksum flag of the frame header descriptor
+    assert_ne!(compressed[4] & 0x04, 0);
+
+    let mut decoder = ZstdDecoder::new(&compressed[..]);
+    let mut output = Vec::new();
+    block_on(decoder.read_to_end(&mut output)).unwrap();
+    assert_eq!(output, input);
+}